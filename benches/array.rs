@@ -0,0 +1,76 @@
+//! 64 channels in one `TripleBufferArray` versus 64 separate
+//! `TripleBuffer`s, writing every channel and then reading every channel.
+//! The array is driven both per channel, one RMW per channel and side, and
+//! through `publish_all`/`update_all`, one RMW per word of four channels.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use tri_buffer::{SpinNotifier, TripleBuffer, TripleBufferArray};
+
+const CHANNELS: usize = 64;
+
+fn separate_buffers(c: &mut Criterion) {
+    let buffers: Vec<TripleBuffer<f32, SpinNotifier>> = (0..CHANNELS)
+        .map(|_| TripleBuffer::with_notifiers(0.0, 0.0, 0.0, SpinNotifier, SpinNotifier))
+        .collect();
+    let mut writers: Vec<_> = buffers.iter().map(|buffer| buffer.get_writer()).collect();
+    let mut readers: Vec<_> = buffers.iter().map(|buffer| buffer.get_reader()).collect();
+    let mut sample = 0.0;
+    c.bench_function("separate_buffers", |b| {
+        b.iter(|| {
+            sample += 1.0;
+            for writer in &mut writers {
+                writer.write(sample);
+            }
+            let mut sum = 0.0;
+            for reader in &mut readers {
+                sum += *reader.read();
+            }
+            black_box(sum)
+        })
+    });
+}
+
+fn array_per_channel(c: &mut Criterion) {
+    let array = TripleBufferArray::<f32, CHANNELS>::new(|| 0.0);
+    let mut writer = array.get_writer();
+    let mut reader = array.get_reader();
+    let mut sample = 0.0;
+    c.bench_function("array_per_channel", |b| {
+        b.iter(|| {
+            sample += 1.0;
+            for channel in 0..CHANNELS {
+                writer.write(channel, sample);
+            }
+            let mut sum = 0.0;
+            for channel in 0..CHANNELS {
+                sum += *reader.read(channel);
+            }
+            black_box(sum)
+        })
+    });
+}
+
+fn array_bulk(c: &mut Criterion) {
+    let array = TripleBufferArray::<f32, CHANNELS>::new(|| 0.0);
+    let mut writer = array.get_writer();
+    let mut reader = array.get_reader();
+    let mut sample = 0.0;
+    c.bench_function("array_bulk", |b| {
+        b.iter(|| {
+            sample += 1.0;
+            for channel in 0..CHANNELS {
+                *writer.input_buffer(channel) = sample;
+            }
+            writer.publish_all();
+            reader.update_all();
+            let mut sum = 0.0;
+            for channel in 0..CHANNELS {
+                sum += *reader.output_buffer(channel);
+            }
+            black_box(sum)
+        })
+    });
+}
+
+criterion_group!(benches, separate_buffers, array_per_channel, array_bulk);
+criterion_main!(benches);
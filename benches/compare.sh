@@ -0,0 +1,12 @@
+#!/bin/sh
+# Runs a bench without and then with the given features, and reports the
+# change: this crate's side of the suite, e.g. `benches/compare.sh padded`,
+# or the bench named second, e.g. `benches/compare.sh packed-control control`.
+set -e
+bench="${2:-suite}"
+filter=""
+if [ "$bench" = suite ]; then
+    filter=tri-buffer
+fi
+cargo bench --bench "$bench" -- $filter --save-baseline without-features
+cargo bench --bench "$bench" --features "$1" -- $filter --baseline without-features
@@ -0,0 +1,51 @@
+//! The atomic traffic on the handles' own hot paths, with nothing else
+//! touching the buffer: a publish is one swap of the control word, an
+//! update that finds a new frame a load and a compare-exchange of it, and
+//! one that finds none only the load. Compare with an earlier commit, where
+//! each also loaded and stored its slot index in the buffer, by saving a
+//! baseline there:
+//!
+//! ```text
+//! cargo bench --bench control -- --save-baseline before
+//! ```
+//!
+//! Under `packed-control` the word holds the handles' slots as well, and a
+//! publish becomes a load and a compare-exchange; compare the two with
+//!
+//! ```text
+//! benches/compare.sh packed-control control
+//! ```
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use tri_buffer::{SpinNotifier, TripleBuffer};
+
+fn buffer() -> TripleBuffer<u64, SpinNotifier> {
+    TripleBuffer::from_slots_with_notifiers([0; 3], SpinNotifier, SpinNotifier)
+}
+
+fn publish(c: &mut Criterion) {
+    let buffer = buffer();
+    let writer = buffer.get_writer();
+    c.bench_function("publish", |b| b.iter(|| black_box(writer.publish())));
+}
+
+fn update_new(c: &mut Criterion) {
+    let buffer = buffer();
+    let writer = buffer.get_writer();
+    let mut reader = buffer.get_reader();
+    c.bench_function("publish_then_update", |b| {
+        b.iter(|| {
+            writer.publish();
+            black_box(reader.update())
+        })
+    });
+}
+
+fn update_stale(c: &mut Criterion) {
+    let buffer = buffer();
+    let mut reader = buffer.get_reader();
+    c.bench_function("update_stale", |b| b.iter(|| black_box(reader.update())));
+}
+
+criterion_group!(benches, publish, update_new, update_stale);
+criterion_main!(benches);
@@ -0,0 +1,80 @@
+//! The `padded` feature on an SPSC stress test, with one-word frames so
+//! that the control word is all the two threads share. The feature is
+//! fixed at build time, so compare two runs:
+//!
+//! ```text
+//! cargo bench --bench padding -- --save-baseline unpadded
+//! cargo bench --bench padding --features padded -- --baseline unpadded
+//! ```
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tri_buffer::{SpinNotifier, TripleBuffer};
+
+const STOP: u64 = u64::MAX;
+
+fn buffer() -> TripleBuffer<u64, SpinNotifier> {
+    TripleBuffer::from_slots_with_notifiers([0; 3], SpinNotifier, SpinNotifier)
+}
+
+/// Latency: a frame there and back through two buffers, with a thread
+/// echoing every ping as a pong.
+fn ping_pong(c: &mut Criterion) {
+    let (ping, pong) = (buffer(), buffer());
+    std::thread::scope(|scope| {
+        scope.spawn(|| {
+            let mut ping = ping.get_reader();
+            let mut pong = pong.get_writer();
+            loop {
+                if ping.update() {
+                    let frame = *ping.output_buffer();
+                    if frame == STOP {
+                        break;
+                    }
+                    pong.write(frame);
+                }
+            }
+        });
+
+        let mut ping = ping.get_writer();
+        let mut pong = pong.get_reader();
+        let mut i = 0;
+        c.bench_function("ping_pong", |b| {
+            b.iter(|| {
+                i += 1;
+                ping.write(i);
+                while *pong.read() != i {
+                    std::hint::spin_loop();
+                }
+            })
+        });
+        ping.write(STOP);
+    });
+}
+
+/// Throughput: publishes while the reader polls for them.
+fn publish_while_polled(c: &mut Criterion) {
+    let buffer = buffer();
+    let stop = AtomicBool::new(false);
+    std::thread::scope(|scope| {
+        scope.spawn(|| {
+            let mut reader = buffer.get_reader();
+            while !stop.load(Ordering::Relaxed) {
+                black_box(reader.update());
+            }
+        });
+
+        let mut writer = buffer.get_writer();
+        let mut i = 0;
+        c.bench_function("publish_while_polled", |b| {
+            b.iter(|| {
+                i += 1;
+                writer.write(i);
+            })
+        });
+        stop.store(true, Ordering::Relaxed);
+    });
+}
+
+criterion_group!(benches, ping_pong, publish_while_polled);
+criterion_main!(benches);
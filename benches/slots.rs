@@ -0,0 +1,90 @@
+//! Three slots versus four. The fourth slot costs one more frame of memory
+//! and lets the writer rotate through two private slots, so it doesn't
+//! immediately rewrite the slot the reader just let go of; whether that
+//! beats the extra cache footprint depends on the frame size and on how
+//! often both sides touch the buffer.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tri_buffer::{NBuffer, SpinNotifier};
+
+type Frame = [u64; 64];
+
+fn buffer<const SLOTS: usize>() -> NBuffer<Frame, SLOTS, SpinNotifier> {
+    NBuffer::from_slots_with_notifiers([[0; 64]; SLOTS], SpinNotifier, SpinNotifier)
+}
+
+fn round_trip<const SLOTS: usize>(c: &mut Criterion) {
+    let buffer = buffer::<SLOTS>();
+    let mut writer = buffer.get_writer();
+    let mut reader = buffer.get_reader();
+    let mut i = 0;
+    c.bench_with_input(BenchmarkId::new("round_trip", SLOTS), &SLOTS, |b, _| {
+        b.iter(|| {
+            i += 1;
+            writer.write([i; 64]);
+            black_box(reader.read()[0])
+        })
+    });
+}
+
+fn read_while_writing<const SLOTS: usize>(c: &mut Criterion) {
+    let buffer = buffer::<SLOTS>();
+    let stop = AtomicBool::new(false);
+    std::thread::scope(|scope| {
+        scope.spawn(|| {
+            let mut writer = buffer.get_writer();
+            let mut i = 0;
+            while !stop.load(Ordering::Relaxed) {
+                i += 1;
+                writer.write([i; 64]);
+            }
+        });
+
+        let mut reader = buffer.get_reader();
+        c.bench_with_input(
+            BenchmarkId::new("read_while_writing", SLOTS),
+            &SLOTS,
+            |b, _| b.iter(|| black_box(reader.read()[0])),
+        );
+        stop.store(true, Ordering::Relaxed);
+    });
+}
+
+fn write_while_reading<const SLOTS: usize>(c: &mut Criterion) {
+    let buffer = buffer::<SLOTS>();
+    let stop = AtomicBool::new(false);
+    std::thread::scope(|scope| {
+        scope.spawn(|| {
+            let mut reader = buffer.get_reader();
+            while !stop.load(Ordering::Relaxed) {
+                black_box(reader.read()[0]);
+            }
+        });
+
+        let mut writer = buffer.get_writer();
+        let mut i = 0;
+        c.bench_with_input(
+            BenchmarkId::new("write_while_reading", SLOTS),
+            &SLOTS,
+            |b, _| {
+                b.iter(|| {
+                    i += 1;
+                    writer.write([i; 64]);
+                })
+            },
+        );
+        stop.store(true, Ordering::Relaxed);
+    });
+}
+
+criterion_group!(
+    benches,
+    round_trip::<3>,
+    round_trip::<4>,
+    read_while_writing::<3>,
+    read_while_writing::<4>,
+    write_while_reading::<3>,
+    write_while_reading::<4>,
+);
+criterion_main!(benches);
@@ -0,0 +1,251 @@
+//! The scenarios to compare against, each with 16 B, 1 KiB and 64 KiB
+//! frames:
+//! - `publish` and `read`, uncontended;
+//! - `ping_pong`, a frame there and back through two buffers;
+//! - `throughput`, publishes while another thread polls for them.
+//!
+//! `--features baselines` runs them on `triple_buffer`, a `Mutex` and
+//! `tokio::sync::watch` as well. To see what a feature of this crate does
+//! to them, `benches/compare.sh padded` benches without and then with it.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use tri_buffer::{BufferReader, BufferWriter, SpinNotifier, TripleBuffer};
+
+/// A latest-value channel, carrying frames tagged with a `u64` in their
+/// first bytes.
+trait Backend {
+    const NAME: &'static str;
+    type Writer<const N: usize>: Send;
+    type Reader<const N: usize>: Send;
+
+    fn new<const N: usize>() -> (Self::Writer<N>, Self::Reader<N>);
+    fn publish<const N: usize>(writer: &mut Self::Writer<N>, frame: &[u8; N]);
+    /// The latest frame's tag.
+    fn read<const N: usize>(reader: &mut Self::Reader<N>) -> u64;
+}
+
+fn tag<const N: usize>(frame: &[u8; N]) -> u64 {
+    u64::from_le_bytes(frame[..8].try_into().unwrap())
+}
+
+fn retag<const N: usize>(frame: &mut [u8; N], tag: u64) {
+    frame[..8].copy_from_slice(&tag.to_le_bytes());
+}
+
+fn tagged<const N: usize>(tag: u64) -> Box<[u8; N]> {
+    let mut frame = Box::new([0; N]);
+    retag(&mut frame, tag);
+    frame
+}
+
+const STOP: u64 = u64::MAX;
+
+struct TriBuffer;
+
+impl Backend for TriBuffer {
+    const NAME: &'static str = "tri-buffer";
+    type Writer<const N: usize> = BufferWriter<'static, [u8; N], SpinNotifier>;
+    type Reader<const N: usize> = BufferReader<'static, [u8; N], SpinNotifier>;
+
+    fn new<const N: usize>() -> (Self::Writer<N>, Self::Reader<N>) {
+        // Leaked, so the handles can move to other threads; a few per run.
+        let buffer = Box::leak(Box::new(TripleBuffer::from_slots_with_notifiers(
+            [[0; N]; 3],
+            SpinNotifier,
+            SpinNotifier,
+        )));
+        let (reader, writer) = buffer.split();
+        (writer, reader)
+    }
+
+    fn publish<const N: usize>(writer: &mut Self::Writer<N>, frame: &[u8; N]) {
+        writer.input_buffer().copy_from_slice(frame);
+        writer.publish();
+    }
+
+    fn read<const N: usize>(reader: &mut Self::Reader<N>) -> u64 {
+        tag(reader.read())
+    }
+}
+
+#[cfg(feature = "baselines")]
+struct TripleBufferCrate;
+
+#[cfg(feature = "baselines")]
+impl Backend for TripleBufferCrate {
+    const NAME: &'static str = "triple_buffer";
+    type Writer<const N: usize> = triple_buffer::Input<[u8; N]>;
+    type Reader<const N: usize> = triple_buffer::Output<[u8; N]>;
+
+    fn new<const N: usize>() -> (Self::Writer<N>, Self::Reader<N>) {
+        triple_buffer::triple_buffer(&[0; N])
+    }
+
+    fn publish<const N: usize>(writer: &mut Self::Writer<N>, frame: &[u8; N]) {
+        writer.input_buffer_mut().copy_from_slice(frame);
+        writer.publish();
+    }
+
+    fn read<const N: usize>(reader: &mut Self::Reader<N>) -> u64 {
+        tag(reader.read())
+    }
+}
+
+#[cfg(feature = "baselines")]
+struct Mutex;
+
+#[cfg(feature = "baselines")]
+impl Backend for Mutex {
+    const NAME: &'static str = "mutex";
+    type Writer<const N: usize> = std::sync::Arc<std::sync::Mutex<[u8; N]>>;
+    type Reader<const N: usize> = std::sync::Arc<std::sync::Mutex<[u8; N]>>;
+
+    fn new<const N: usize>() -> (Self::Writer<N>, Self::Reader<N>) {
+        let frame = std::sync::Arc::new(std::sync::Mutex::new([0; N]));
+        (frame.clone(), frame)
+    }
+
+    fn publish<const N: usize>(writer: &mut Self::Writer<N>, frame: &[u8; N]) {
+        writer.lock().unwrap().copy_from_slice(frame);
+    }
+
+    fn read<const N: usize>(reader: &mut Self::Reader<N>) -> u64 {
+        tag(&reader.lock().unwrap())
+    }
+}
+
+#[cfg(feature = "baselines")]
+struct Watch;
+
+#[cfg(feature = "baselines")]
+impl Backend for Watch {
+    const NAME: &'static str = "tokio-watch";
+    type Writer<const N: usize> = tokio::sync::watch::Sender<[u8; N]>;
+    type Reader<const N: usize> = tokio::sync::watch::Receiver<[u8; N]>;
+
+    fn new<const N: usize>() -> (Self::Writer<N>, Self::Reader<N>) {
+        tokio::sync::watch::channel([0; N])
+    }
+
+    fn publish<const N: usize>(writer: &mut Self::Writer<N>, frame: &[u8; N]) {
+        writer.send_modify(|latest| latest.copy_from_slice(frame));
+    }
+
+    fn read<const N: usize>(reader: &mut Self::Reader<N>) -> u64 {
+        tag(&reader.borrow_and_update())
+    }
+}
+
+fn label(size: usize) -> String {
+    match size {
+        size if size >= 1024 => format!("{}KiB", size / 1024),
+        size => format!("{size}B"),
+    }
+}
+
+fn publish<B: Backend, const N: usize>(c: &mut Criterion) {
+    let (mut writer, _reader) = B::new::<N>();
+    let frame = tagged::<N>(1);
+    let mut group = c.benchmark_group("publish");
+    group.throughput(Throughput::Bytes(N as u64));
+    group.bench_function(BenchmarkId::new(B::NAME, label(N)), |b| {
+        b.iter(|| B::publish(&mut writer, black_box(&frame)))
+    });
+    group.finish();
+}
+
+fn read<B: Backend, const N: usize>(c: &mut Criterion) {
+    let (mut writer, mut reader) = B::new::<N>();
+    B::publish(&mut writer, &tagged::<N>(1));
+    let mut group = c.benchmark_group("read");
+    group.bench_function(BenchmarkId::new(B::NAME, label(N)), |b| {
+        b.iter(|| black_box(B::read(&mut reader)))
+    });
+    group.finish();
+}
+
+fn ping_pong<B: Backend, const N: usize>(c: &mut Criterion) {
+    let (mut ping, mut ping_reader) = B::new::<N>();
+    let (mut pong_writer, mut pong) = B::new::<N>();
+    std::thread::scope(|scope| {
+        scope.spawn(move || {
+            let mut echo = tagged::<N>(0);
+            loop {
+                let frame = B::read(&mut ping_reader);
+                if frame == STOP {
+                    break;
+                }
+                if frame != tag(&echo) {
+                    retag(&mut echo, frame);
+                    B::publish(&mut pong_writer, &echo);
+                }
+                std::hint::spin_loop();
+            }
+        });
+
+        let mut frame = tagged::<N>(0);
+        let mut i = 0;
+        let mut group = c.benchmark_group("ping_pong");
+        group.bench_function(BenchmarkId::new(B::NAME, label(N)), |b| {
+            b.iter(|| {
+                i += 1;
+                retag(&mut frame, i);
+                B::publish(&mut ping, &frame);
+                while B::read(&mut pong) != i {
+                    std::hint::spin_loop();
+                }
+            })
+        });
+        group.finish();
+        retag(&mut frame, STOP);
+        B::publish(&mut ping, &frame);
+    });
+}
+
+fn throughput<B: Backend, const N: usize>(c: &mut Criterion) {
+    let (mut writer, mut reader) = B::new::<N>();
+    let stop = AtomicBool::new(false);
+    std::thread::scope(|scope| {
+        scope.spawn(|| {
+            while !stop.load(Ordering::Relaxed) {
+                black_box(B::read(&mut reader));
+            }
+        });
+
+        let frame = tagged::<N>(1);
+        let mut group = c.benchmark_group("throughput");
+        group.throughput(Throughput::Bytes(N as u64));
+        group.bench_function(BenchmarkId::new(B::NAME, label(N)), |b| {
+            b.iter(|| B::publish(&mut writer, black_box(&frame)))
+        });
+        group.finish();
+        stop.store(true, Ordering::Relaxed);
+    });
+}
+
+fn scenarios<B: Backend>(c: &mut Criterion) {
+    fn sized<B: Backend, const N: usize>(c: &mut Criterion) {
+        publish::<B, N>(c);
+        read::<B, N>(c);
+        ping_pong::<B, N>(c);
+        throughput::<B, N>(c);
+    }
+    sized::<B, 16>(c);
+    sized::<B, 1024>(c);
+    sized::<B, 65536>(c);
+}
+
+fn backends(c: &mut Criterion) {
+    scenarios::<TriBuffer>(c);
+    #[cfg(feature = "baselines")]
+    {
+        scenarios::<TripleBufferCrate>(c);
+        scenarios::<Mutex>(c);
+        scenarios::<Watch>(c);
+    }
+}
+
+criterion_group!(benches, backends);
+criterion_main!(benches);
@@ -0,0 +1,69 @@
+//! Single-core Cortex-M pattern: the SysTick handler publishes a tick count
+//! and the main loop sleeps in `WFE` until the next frame is available.
+//!
+//! cargo build --example cortex_m_wfe --features cortex-m --target thumbv7m-none-eabi
+//!
+//! A flashable image additionally needs your board's `memory.x` and
+//! `-C link-arg=-Tlink.x`, as usual for `cortex-m-rt`.
+//!
+//! On the host the interrupt is simulated by a thread.
+#![cfg_attr(target_os = "none", no_std, no_main)]
+
+use tri_buffer::{TripleBuffer, WfeNotifier};
+
+static TICKS: TripleBuffer<u32, WfeNotifier> =
+    TripleBuffer::with_notifiers(0, 0, 0, WfeNotifier::new(), WfeNotifier::new());
+
+#[cfg(target_os = "none")]
+mod target {
+    use cortex_m::peripheral::syst::SystClkSource;
+    use cortex_m_rt::{entry, exception};
+    use panic_halt as _;
+
+    use super::TICKS;
+
+    #[exception]
+    fn SysTick() {
+        static mut COUNT: u32 = 0;
+        *COUNT += 1;
+        TICKS.get_writer().write(*COUNT);
+    }
+
+    #[entry]
+    fn main() -> ! {
+        let mut reader = TICKS.get_reader();
+        let mut core = cortex_m::Peripherals::take().unwrap();
+        core.SYST.set_clock_source(SystClkSource::Core);
+        core.SYST.set_reload(8_000_000);
+        core.SYST.clear_current();
+        core.SYST.enable_counter();
+        core.SYST.enable_interrupt();
+
+        let mut last = 0;
+        loop {
+            let tick = *reader.read_blocking();
+            assert!(tick > last);
+            last = tick;
+        }
+    }
+}
+
+#[cfg(not(target_os = "none"))]
+fn main() {
+    let isr = std::thread::spawn(|| {
+        for count in 1..=10 {
+            std::thread::sleep(std::time::Duration::from_millis(10));
+            TICKS.get_writer().write(count);
+        }
+    });
+
+    let mut reader = TICKS.get_reader();
+    let mut last = 0;
+    while last != 10 {
+        let tick = *reader.read_blocking();
+        assert!(tick > last);
+        last = tick;
+        println!("tick {tick}");
+    }
+    isr.join().unwrap();
+}
@@ -0,0 +1,110 @@
+//! The writer's and reader's hot paths as standalone functions, for
+//! `tests/no_panic.rs` to check the optimized build of for panic branches,
+//! along with the `rt-safe` subset's methods.
+
+use std::hint::black_box;
+
+#[cfg(feature = "rt-safe")]
+use tri_buffer::rt::{RtReader, RtWriter};
+use tri_buffer::{BufferReader, BufferWriter, NBuffer, SpinNotifier};
+
+type Frame = [u32; 16];
+type Reader<const SLOTS: usize> = BufferReader<'static, Frame, SpinNotifier, SLOTS>;
+type Writer<const SLOTS: usize> = BufferWriter<'static, Frame, SpinNotifier, SLOTS>;
+
+static TRIPLE: NBuffer<Frame, 3, SpinNotifier> =
+    NBuffer::from_slots_with_notifiers([[0; 16]; 3], SpinNotifier, SpinNotifier);
+static QUAD: NBuffer<Frame, 4, SpinNotifier> =
+    NBuffer::from_slots_with_notifiers([[0; 16]; 4], SpinNotifier, SpinNotifier);
+
+macro_rules! hot_path {
+    ($slots:literal: $read:ident, $update:ident, $publish:ident, $write:ident) => {
+        #[no_mangle]
+        #[inline(never)]
+        pub fn $read(reader: &mut Reader<$slots>) -> u32 {
+            reader.read()[0]
+        }
+
+        #[no_mangle]
+        #[inline(never)]
+        pub fn $update(reader: &mut Reader<$slots>) -> bool {
+            reader.update()
+        }
+
+        #[no_mangle]
+        #[inline(never)]
+        pub fn $publish(writer: &Writer<$slots>) -> bool {
+            writer.publish()
+        }
+
+        #[no_mangle]
+        #[inline(never)]
+        pub fn $write(writer: &mut Writer<$slots>, frame: Frame) {
+            writer.write(frame)
+        }
+    };
+}
+
+hot_path!(3: hot_read_3, hot_update_3, hot_publish_3, hot_write_3);
+hot_path!(4: hot_read_4, hot_update_4, hot_publish_4, hot_write_4);
+
+/// The `rt-safe` subset's own methods.
+#[cfg(feature = "rt-safe")]
+macro_rules! rt_hot_path {
+    ($slots:literal: $try_read:ident, $peek:ident, $write_copy:ident) => {
+        #[no_mangle]
+        #[inline(never)]
+        pub fn $try_read(reader: &mut Reader<$slots>) -> Option<u32> {
+            RtReader::try_read(reader).map(|frame| frame[0])
+        }
+
+        #[no_mangle]
+        #[inline(never)]
+        pub fn $peek(reader: &mut Reader<$slots>) -> u32 {
+            RtReader::peek(reader)[0]
+        }
+
+        #[no_mangle]
+        #[inline(never)]
+        pub fn $write_copy(writer: &mut Writer<$slots>, frame: &Frame) -> bool {
+            RtWriter::write_copy(writer, frame)
+        }
+    };
+}
+
+#[cfg(feature = "rt-safe")]
+rt_hot_path!(3: hot_try_read_3, hot_peek_3, hot_write_copy_3);
+#[cfg(feature = "rt-safe")]
+rt_hot_path!(4: hot_try_read_4, hot_peek_4, hot_write_copy_4);
+
+fn main() {
+    let (Some(mut reader), Some(mut writer)) = (TRIPLE.try_get_reader(), TRIPLE.try_get_writer())
+    else {
+        return;
+    };
+    hot_write_3(&mut writer, black_box([1; 16]));
+    black_box(hot_publish_3(&writer));
+    black_box(hot_update_3(&mut reader));
+    black_box(hot_read_3(&mut reader));
+    #[cfg(feature = "rt-safe")]
+    {
+        black_box(hot_write_copy_3(&mut writer, &black_box([2; 16])));
+        black_box(hot_try_read_3(&mut reader));
+        black_box(hot_peek_3(&mut reader));
+    }
+
+    let (Some(mut reader), Some(mut writer)) = (QUAD.try_get_reader(), QUAD.try_get_writer())
+    else {
+        return;
+    };
+    hot_write_4(&mut writer, black_box([1; 16]));
+    black_box(hot_publish_4(&writer));
+    black_box(hot_update_4(&mut reader));
+    black_box(hot_read_4(&mut reader));
+    #[cfg(feature = "rt-safe")]
+    {
+        black_box(hot_write_copy_4(&mut writer, &black_box([2; 16])));
+        black_box(hot_try_read_4(&mut reader));
+        black_box(hot_peek_4(&mut reader));
+    }
+}
@@ -0,0 +1,120 @@
+//! RTIC pattern: a SysTick hardware task samples a sensor and publishes
+//! frames, the idle task sleeps in `WFE` until the next one arrives.
+//!
+//! cargo build --example rtic_sensor --features rtic --target thumbv7m-none-eabi
+//!
+//! The buffer is an `#[init]` local, so `split` hands out both handles
+//! without a panicking path; they then move into the tasks' `#[local]`
+//! resources, which requires them to be `Send`.
+//!
+//! On the host the hardware task is simulated by a thread.
+#![cfg_attr(target_os = "none", no_std, no_main)]
+
+#[derive(Clone, Copy)]
+pub struct Frame {
+    seq: u32,
+    accel: [i16; 3],
+}
+
+impl Frame {
+    const ZERO: Self = Self {
+        seq: 0,
+        accel: [0; 3],
+    };
+
+    fn sample(seq: u32) -> Self {
+        let v = seq as i16;
+        Self {
+            seq,
+            accel: [v, v.wrapping_neg(), 0],
+        }
+    }
+}
+
+#[cfg(target_os = "none")]
+#[rtic::app(device = lm3s6965)]
+mod app {
+    use cortex_m::peripheral::syst::SystClkSource;
+    use panic_halt as _;
+    use tri_buffer::{BufferReader, BufferWriter, TripleBuffer, WfeNotifier};
+
+    use super::Frame;
+
+    #[shared]
+    struct Shared {}
+
+    #[local]
+    struct Local {
+        writer: BufferWriter<'static, Frame, WfeNotifier>,
+        reader: BufferReader<'static, Frame, WfeNotifier>,
+    }
+
+    #[init(local = [
+        frames: TripleBuffer<Frame, WfeNotifier> = TripleBuffer::with_notifiers(
+            Frame::ZERO,
+            Frame::ZERO,
+            Frame::ZERO,
+            WfeNotifier::new(),
+            WfeNotifier::new(),
+        ),
+    ])]
+    fn init(cx: init::Context) -> (Shared, Local) {
+        let mut syst = cx.core.SYST;
+        syst.set_clock_source(SystClkSource::Core);
+        syst.set_reload(120_000);
+        syst.clear_current();
+        syst.enable_counter();
+        syst.enable_interrupt();
+
+        let (reader, writer) = cx.local.frames.split();
+        (Shared {}, Local { writer, reader })
+    }
+
+    #[task(binds = SysTick, priority = 2, local = [writer, seq: u32 = 0])]
+    fn sample(cx: sample::Context) {
+        *cx.local.seq += 1;
+        cx.local.writer.write(Frame::sample(*cx.local.seq));
+    }
+
+    #[idle(local = [reader])]
+    fn idle(cx: idle::Context) -> ! {
+        let mut last = 0;
+        loop {
+            let frame = *cx.local.reader.read_blocking();
+            assert!(frame.seq > last);
+            assert_eq!(frame.accel[0], frame.seq as i16);
+            last = frame.seq;
+        }
+    }
+}
+
+#[cfg(not(target_os = "none"))]
+fn main() {
+    use tri_buffer::{TripleBuffer, WfeNotifier};
+
+    let frames: &'static mut TripleBuffer<Frame, WfeNotifier> =
+        Box::leak(Box::new(TripleBuffer::with_notifiers(
+            Frame::ZERO,
+            Frame::ZERO,
+            Frame::ZERO,
+            WfeNotifier::new(),
+            WfeNotifier::new(),
+        )));
+    let (mut reader, mut writer) = frames.split();
+
+    let isr = std::thread::spawn(move || {
+        for seq in 1..=10 {
+            std::thread::sleep(std::time::Duration::from_millis(10));
+            writer.write(Frame::sample(seq));
+        }
+    });
+
+    let mut last = 0;
+    while last != 10 {
+        let frame = *reader.read_blocking();
+        assert!(frame.seq > last);
+        last = frame.seq;
+        println!("frame {} {:?}", frame.seq, frame.accel);
+    }
+    isr.join().unwrap();
+}
@@ -0,0 +1,188 @@
+//! Drives a buffer with an arbitrary script of writer and reader operations,
+//! first one at a time against the reference model in `tests/support`, then
+//! on two threads that meet at the script's `Sync` points. The buffer runs
+//! its `paranoid` checks throughout, so a broken invariant panics. Run it
+//! with `cargo +nightly fuzz run operations` from the repository root.
+
+#![no_main]
+
+use std::sync::{Barrier, Mutex};
+use std::thread;
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use tri_buffer::{BufferState, NBuffer};
+
+// The script never needs the model's combined or polling ops.
+#[allow(dead_code)]
+#[path = "../../tests/support/model.rs"]
+mod model;
+
+use model::Op;
+
+const FRAME_LEN: usize = 16;
+
+#[derive(Debug, Arbitrary)]
+enum Slots {
+    Two,
+    Three,
+    Four,
+}
+
+#[derive(Debug, Clone, Copy, Arbitrary)]
+enum WriterOp {
+    Stage { offset: u8, byte: u8 },
+    Publish,
+    Reacquire,
+}
+
+#[derive(Debug, Clone, Copy, Arbitrary)]
+enum ReaderOp {
+    Update,
+    Read,
+    Reacquire,
+}
+
+#[derive(Debug, Clone, Copy, Arbitrary)]
+enum Step {
+    Writer(WriterOp),
+    Reader(ReaderOp),
+    /// Both threads finish their ops so far before either goes on.
+    Sync,
+}
+
+#[derive(Debug, Arbitrary)]
+struct Input {
+    slots: Slots,
+    steps: Vec<Step>,
+}
+
+/// `seq` indexes the frame's entry in the published log; 0 is the initial
+/// frame every slot starts with.
+#[derive(Clone, Copy)]
+struct Frame {
+    seq: usize,
+    bytes: [u8; FRAME_LEN],
+}
+
+fn model_ops(steps: &[Step]) -> Vec<Op> {
+    let mut ops = Vec::new();
+    for step in steps {
+        match *step {
+            Step::Writer(WriterOp::Stage { offset, byte }) => {
+                ops.push(Op::Stage(u32::from_le_bytes([offset, byte, 0, 0])))
+            }
+            Step::Writer(WriterOp::Publish) => ops.push(Op::Publish),
+            Step::Writer(WriterOp::Reacquire) => ops.extend([Op::DropWriter, Op::AcquireWriter]),
+            Step::Reader(ReaderOp::Update) => ops.push(Op::Update),
+            Step::Reader(ReaderOp::Read) => ops.push(Op::Read),
+            Step::Reader(ReaderOp::Reacquire) => ops.extend([Op::DropReader, Op::AcquireReader]),
+            Step::Sync => {}
+        }
+    }
+    ops
+}
+
+fn check_state<const SLOTS: usize>(state: BufferState) {
+    assert!(state.output < SLOTS, "{state:?}");
+    assert!(state.back.is_none_or(|back| back < SLOTS), "{state:?}");
+    assert!(state.input.is_none_or(|input| input < SLOTS), "{state:?}");
+}
+
+fn run_writer<const SLOTS: usize>(
+    buffer: &NBuffer<Frame, SLOTS>,
+    steps: &[Step],
+    log: &Mutex<Vec<[u8; FRAME_LEN]>>,
+    barrier: &Barrier,
+) {
+    let mut writer = buffer.get_writer();
+    for step in steps {
+        match *step {
+            Step::Writer(WriterOp::Stage { offset, byte }) => {
+                writer.input_buffer().bytes[offset as usize % FRAME_LEN] = byte;
+            }
+            Step::Writer(WriterOp::Publish) => {
+                // Logged before the publish makes the frame visible.
+                let frame = writer.input_buffer();
+                let mut log = log.lock().unwrap();
+                frame.seq = log.len();
+                log.push(frame.bytes);
+                drop(log);
+                writer.publish();
+            }
+            Step::Writer(WriterOp::Reacquire) => {
+                drop(writer);
+                writer = buffer.get_writer();
+            }
+            Step::Reader(_) => continue,
+            Step::Sync => {
+                barrier.wait();
+            }
+        }
+        check_state::<SLOTS>(buffer.state());
+    }
+}
+
+fn run_reader<const SLOTS: usize>(
+    buffer: &NBuffer<Frame, SLOTS>,
+    steps: &[Step],
+    log: &Mutex<Vec<[u8; FRAME_LEN]>>,
+    barrier: &Barrier,
+) {
+    let mut reader = buffer.get_reader();
+    let mut last_seq = 0;
+    for step in steps {
+        match *step {
+            Step::Reader(ReaderOp::Update) => {
+                reader.update();
+            }
+            Step::Reader(ReaderOp::Read) => {
+                let frame = *reader.read();
+                let log = log.lock().unwrap();
+                assert_eq!(frame.bytes, log[frame.seq], "read an unpublished frame");
+                assert!(
+                    frame.seq >= last_seq,
+                    "read frame {} after {last_seq}",
+                    frame.seq
+                );
+                last_seq = frame.seq;
+            }
+            Step::Reader(ReaderOp::Reacquire) => {
+                drop(reader);
+                reader = buffer.get_reader();
+            }
+            Step::Writer(_) => continue,
+            Step::Sync => {
+                barrier.wait();
+            }
+        }
+        check_state::<SLOTS>(buffer.state());
+    }
+}
+
+fn run<const SLOTS: usize>(steps: &[Step]) {
+    model::run::<SLOTS>(&model_ops(steps));
+
+    let buffer = NBuffer::<Frame, SLOTS>::new(|| Frame {
+        seq: 0,
+        bytes: [0; FRAME_LEN],
+    });
+    let log = Mutex::new(vec![[0; FRAME_LEN]]);
+    let barrier = Barrier::new(2);
+    thread::scope(|s| {
+        let writer = s.spawn(|| run_writer(&buffer, steps, &log, &barrier));
+        let reader = s.spawn(|| run_reader(&buffer, steps, &log, &barrier));
+        // Joined by hand: the scope alone can return before the threads have
+        // run their thread-local destructors, which LeakSanitizer reports.
+        writer.join().unwrap();
+        reader.join().unwrap();
+    });
+}
+
+fuzz_target!(|input: Input| {
+    match input.slots {
+        Slots::Two => run::<2>(&input.steps),
+        Slots::Three => run::<3>(&input.steps),
+        Slots::Four => run::<4>(&input.steps),
+    }
+});
@@ -0,0 +1,88 @@
+/* The C API of the `tri-buffer` crate's `ffi` feature; see `src/ffi.rs`
+ * for the ownership rules. Written by hand in the layout cbindgen emits
+ * for that module. `tests/ffi.rs` checks that it declares every function
+ * and constant of `src/ffi.rs` with the same signature, and nothing else;
+ * it translates the Rust types itself and does not run cbindgen. */
+
+#ifndef TRI_BUFFER_H
+#define TRI_BUFFER_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+#define TRIBUF_OK 0
+
+/**
+ * A pointer argument was null.
+ */
+#define TRIBUF_NULL -1
+
+/**
+ * A frame or output length differed from the buffer's frame size.
+ */
+#define TRIBUF_LENGTH -2
+
+/**
+ * `tribuf_destroy` found a handle still acquired.
+ */
+#define TRIBUF_BUSY -3
+
+#define TRIBUF_PANIC -4
+
+typedef struct TriBufHandle TriBufHandle;
+
+typedef struct TriBufReader TriBufReader;
+
+typedef struct TriBufWriter TriBufWriter;
+
+/**
+ * Called with its context after every publish.
+ */
+typedef void (*TriBufCallback)(void*);
+
+/**
+ * A buffer of zeroed `size`-byte frames, or null if `size` is 0.
+ */
+TriBufHandle *tribuf_create(uintptr_t size);
+
+/**
+ * Frees `handle`, unless a writer or reader is still acquired on it.
+ */
+int32_t tribuf_destroy(TriBufHandle *handle);
+
+/**
+ * The buffer's writer, or null while another is acquired.
+ */
+TriBufWriter *tribuf_writer_acquire(TriBufHandle *handle);
+
+/**
+ * The buffer's reader, or null while another is acquired.
+ */
+TriBufReader *tribuf_reader_acquire(TriBufHandle *handle);
+
+void tribuf_writer_release(TriBufWriter *writer);
+
+void tribuf_reader_release(TriBufReader *reader);
+
+/**
+ * Copies the `len` bytes at `data` into the next frame and publishes it.
+ */
+int32_t tribuf_write(TriBufWriter *writer, const uint8_t *data, uintptr_t len);
+
+/**
+ * Copies the latest frame into the `len` bytes at `out`, and stores into
+ * `out_fresh`, unless it is null, whether the frame is new since the
+ * previous read.
+ */
+int32_t tribuf_reader_read(TriBufReader *reader, uint8_t *out, uintptr_t len, bool *out_fresh);
+
+/**
+ * Has every later publish on `handle` call `callback` with `ctx`, in place
+ * of the callback set before; a null `callback` removes it. Once this
+ * returns, the callback it replaced is neither running nor called again.
+ */
+int32_t tribuf_set_on_publish(TriBufHandle *handle, TriBufCallback callback, void *ctx);
+
+#endif  /* TRI_BUFFER_H */
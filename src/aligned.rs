@@ -0,0 +1,213 @@
+//! Over-aligned frames. `repr(align)` can't take a const parameter, so
+//! `Aligned` gets its alignment from a zero-sized marker field with one
+//! `repr(align)` type per supported power of two; any other `A` has no
+//! `Alignment` impl and doesn't compile.
+
+use core::fmt;
+use core::ops::{Deref, DerefMut};
+
+/// `T` aligned to at least `A` bytes, a power of two up to 4096, e.g. so a
+/// DMA controller or cache line sees every slot of a
+/// `TripleBuffer<Aligned<32, Frame>>` start on an `A`-byte boundary. The
+/// size is rounded up to a multiple of `A`; the byte and DMA helpers skip
+/// that padding and see through to `T`.
+#[repr(C)]
+pub struct Aligned<const A: usize, T>
+where
+    Align<A>: Alignment,
+{
+    _align: [<Align<A> as Alignment>::Marker; 0],
+    pub value: T,
+}
+
+/// Names an alignment for `Aligned`.
+pub struct Align<const A: usize>;
+
+/// Implemented for the `Align<A>` that `Aligned` supports.
+pub trait Alignment: sealed::Sealed {
+    #[doc(hidden)]
+    type Marker: Copy;
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+macro_rules! alignments {
+    ($($align:literal: $marker:ident),* $(,)?) => {
+        $(
+            #[doc(hidden)]
+            #[derive(Clone, Copy)]
+            #[repr(align($align))]
+            pub struct $marker;
+
+            impl sealed::Sealed for Align<$align> {}
+
+            impl Alignment for Align<$align> {
+                type Marker = $marker;
+            }
+        )*
+    };
+}
+
+alignments!(
+    1: Align1, 2: Align2, 4: Align4, 8: Align8, 16: Align16, 32: Align32, 64: Align64,
+    128: Align128, 256: Align256, 512: Align512, 1024: Align1024, 2048: Align2048,
+    4096: Align4096,
+);
+
+impl<const A: usize, T> Aligned<A, T>
+where
+    Align<A>: Alignment,
+{
+    pub const fn new(value: T) -> Self {
+        Self { _align: [], value }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<const A: usize, T> Deref for Aligned<A, T>
+where
+    Align<A>: Alignment,
+{
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<const A: usize, T> DerefMut for Aligned<A, T>
+where
+    Align<A>: Alignment,
+{
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+impl<const A: usize, T: Default> Default for Aligned<A, T>
+where
+    Align<A>: Alignment,
+{
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<const A: usize, T: Clone> Clone for Aligned<A, T>
+where
+    Align<A>: Alignment,
+{
+    fn clone(&self) -> Self {
+        Self::new(self.value.clone())
+    }
+}
+
+impl<const A: usize, T: Copy> Copy for Aligned<A, T> where Align<A>: Alignment {}
+
+impl<const A: usize, T: PartialEq> PartialEq for Aligned<A, T>
+where
+    Align<A>: Alignment,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<const A: usize, T: Eq> Eq for Aligned<A, T> where Align<A>: Alignment {}
+
+impl<const A: usize, T: fmt::Debug> fmt::Debug for Aligned<A, T>
+where
+    Align<A>: Alignment,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Aligned").field(&self.value).finish()
+    }
+}
+
+#[cfg(feature = "bytemuck")]
+impl<const A: usize, T: bytemuck::Pod> crate::PodFrame for Aligned<A, T>
+where
+    Align<A>: Alignment,
+{
+    type Pod = T;
+
+    fn pod(&self) -> &T {
+        &self.value
+    }
+
+    fn pod_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::mem;
+
+    use super::*;
+    use crate::{NBuffer, TripleBuffer};
+
+    type Frame = [u8; 20];
+
+    #[test]
+    fn layout_rounds_up_to_the_alignment() {
+        assert_eq!(mem::align_of::<Aligned<32, Frame>>(), 32);
+        assert_eq!(mem::size_of::<Aligned<32, Frame>>(), 32);
+        assert_eq!(mem::align_of::<Aligned<4096, u8>>(), 4096);
+        // Never below `T`'s own alignment.
+        assert_eq!(mem::align_of::<Aligned<1, u64>>(), mem::align_of::<u64>());
+        assert_eq!(mem::size_of::<Aligned<1, Frame>>(), 20);
+    }
+
+    #[test]
+    fn every_slot_is_aligned() {
+        fn check<const SLOTS: usize>() {
+            let buffer = NBuffer::<Aligned<32, Frame>, SLOTS>::new(Default::default);
+            for i in 0..SLOTS as u8 {
+                assert_eq!(buffer.slot(i) as usize % 32, 0, "slot {i} of {SLOTS}");
+            }
+        }
+        check::<2>();
+        check::<3>();
+        check::<4>();
+        check::<7>();
+    }
+
+    #[test]
+    fn frames_pass_through_the_wrapper() {
+        const ZERO: Aligned<64, Frame> = Aligned::new([0; 20]);
+        let buffer = TripleBuffer::new_const(ZERO, ZERO, ZERO);
+        let mut writer = buffer.get_writer();
+        let mut reader = buffer.get_reader();
+
+        writer.input_buffer()[3] = 7;
+        writer.publish();
+        let frame = reader.read();
+        assert_eq!(frame[3], 7);
+        assert_eq!(frame.into_inner(), {
+            let mut expected = [0; 20];
+            expected[3] = 7;
+            expected
+        });
+    }
+
+    #[cfg(feature = "bytemuck")]
+    #[test]
+    fn byte_views_skip_the_padding() {
+        let buffer = TripleBuffer::new(Aligned::<32, Frame>::default);
+        let mut writer = buffer.get_writer();
+        let mut reader = buffer.get_reader();
+
+        let bytes = writer.input_as_bytes_mut();
+        assert_eq!(bytes.len(), 20);
+        assert_eq!(bytes.as_ptr() as usize % 32, 0);
+        bytes.copy_from_slice(&[9; 20]);
+        writer.publish();
+        assert_eq!(reader.read_as_bytes(), [9; 20]);
+    }
+}
@@ -0,0 +1,105 @@
+//! `Arc` frames for large states: the writer publishes by moving a new
+//! `Arc` into its slot, and the reader hands out owned clones of the
+//! latest one, so neither copies the state itself. An old state is freed
+//! once its slot is overwritten and no clone of it is left, possibly on
+//! the writer's thread.
+
+use alloc::sync::Arc;
+
+use crate::{BufferReader, BufferWriter, Notifier};
+
+impl<'a, S, N: Notifier, const SLOTS: usize> BufferWriter<'a, Arc<S>, N, SLOTS> {
+    /// Publishes `state`, dropping the `Arc` its slot held before.
+    pub fn publish_arc(&mut self, state: Arc<S>) {
+        self.write(state);
+    }
+}
+
+impl<'a, S, N: Notifier, const SLOTS: usize> BufferReader<'a, Arc<S>, N, SLOTS> {
+    /// Updates and clones the latest state.
+    pub fn latest_arc(&mut self) -> Arc<S> {
+        Arc::clone(self.read())
+    }
+
+    /// Updates and clones the latest state, if it is new.
+    pub fn latest_arc_if_new(&mut self) -> Option<Arc<S>> {
+        self.update().then(|| Arc::clone(self.output_buffer()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::sync::{Arc, Weak};
+    use alloc::vec::Vec;
+
+    use crate::{NBuffer, TripleBuffer};
+
+    #[test]
+    fn old_states_are_released() {
+        let buffer = TripleBuffer::new(|| Arc::new(0u32));
+        let mut writer = buffer.get_writer();
+        let mut reader = buffer.get_reader();
+
+        let first = Arc::new(1);
+        let weak = Arc::downgrade(&first);
+        writer.publish_arc(first);
+        let held = reader.latest_arc();
+        assert_eq!(Arc::strong_count(&held), 2);
+        assert_eq!(reader.latest_arc_if_new(), None);
+
+        // Three publishes push it out of the reader's slot and every other
+        // one, but the clone still holds it.
+        for state in 2..5 {
+            writer.publish_arc(Arc::new(state));
+            assert_eq!(*reader.latest_arc_if_new().unwrap(), state);
+        }
+        assert_eq!(Arc::strong_count(&held), 1);
+        drop(held);
+        assert!(weak.upgrade().is_none());
+    }
+
+    #[test]
+    fn unread_states_are_released_on_overwrite() {
+        let buffer = NBuffer::<Arc<u32>, 4>::new(|| Arc::new(0));
+        let mut writer = buffer.get_writer();
+        let mut reader = buffer.get_reader();
+
+        let weaks: Vec<Weak<u32>> = (1..=8)
+            .map(|state| {
+                let state = Arc::new(state);
+                let weak = Arc::downgrade(&state);
+                writer.publish_arc(state);
+                weak
+            })
+            .collect();
+        // The reader still holds the initial state, so only the last three
+        // are left in the writer's slots.
+        let alive: Vec<bool> = weaks.iter().map(|weak| weak.strong_count() > 0).collect();
+        assert_eq!(alive, [false, false, false, false, false, true, true, true]);
+        assert_eq!(*reader.latest_arc(), 8);
+    }
+
+    #[test]
+    fn readers_see_whole_states() {
+        let states = if cfg!(miri) { 200 } else { 20_000 };
+        let buffer = TripleBuffer::new(|| Arc::new([0u64; 64]));
+        let mut writer = buffer.get_writer();
+        let mut reader = buffer.get_reader();
+
+        std::thread::scope(|s| {
+            s.spawn(move || {
+                for state in 1..=states {
+                    writer.publish_arc(Arc::new([state; 64]));
+                }
+            });
+            let mut previous = 0;
+            while previous != states {
+                if let Some(state) = reader.latest_arc_if_new() {
+                    assert!(state.iter().all(|&word| word == state[0]), "torn state");
+                    assert!(state[0] > previous, "state went back in time");
+                    previous = state[0];
+                }
+            }
+        });
+    }
+}
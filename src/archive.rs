@@ -0,0 +1,102 @@
+//! `rkyv` archiving for the `rkyv` feature: the reader archives the latest
+//! frame straight from the output slot, and a replaying writer publishes
+//! archived frames by deserializing them into the input slot.
+
+use rkyv::ser::Serializer;
+use rkyv::{Archive, Archived, Deserialize, Infallible, Serialize};
+
+use crate::{BufferReader, BufferWriter, Notifier};
+
+impl<'a, T: Archive, N: Notifier, const SLOTS: usize> BufferReader<'a, T, N, SLOTS> {
+    /// Updates and archives the latest frame into `ser`, without copying it
+    /// out first. Returns the position of the archived root, as
+    /// `Serializer::serialize_value` does.
+    pub fn archive_latest<S: Serializer + ?Sized>(&mut self, ser: &mut S) -> Result<usize, S::Error>
+    where
+        T: Serialize<S>,
+    {
+        ser.serialize_value(self.read())
+    }
+}
+
+impl<'a, T: Archive, N: Notifier, const SLOTS: usize> BufferWriter<'a, T, N, SLOTS> {
+    /// Deserializes `archived` into the input slot, dropping the frame it
+    /// held, and publishes it.
+    pub fn write_archived(&mut self, archived: &Archived<T>)
+    where
+        Archived<T>: Deserialize<T, Infallible>,
+    {
+        let Ok(value) = archived.deserialize(&mut Infallible);
+        self.write(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rkyv::ser::serializers::AllocSerializer;
+    use rkyv::{check_archived_root, Archive, Deserialize, Serialize};
+    use std::string::String;
+    use std::vec::Vec;
+
+    use crate::{NBuffer, TripleBuffer};
+
+    #[derive(Archive, Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+    #[archive(check_bytes)]
+    struct Frame {
+        seq: u32,
+        label: String,
+        samples: Vec<i16>,
+    }
+
+    fn frame(seq: u32) -> Frame {
+        Frame {
+            seq,
+            label: std::format!("frame {seq}"),
+            samples: (0..seq as i16).map(|s| -s).collect(),
+        }
+    }
+
+    #[test]
+    fn latest_frame_archives_and_validates() {
+        let buffer = TripleBuffer::new(Frame::default);
+        let mut writer = buffer.get_writer();
+        let mut reader = buffer.get_reader();
+        writer.write(frame(3));
+        writer.write(frame(5));
+
+        let mut ser = AllocSerializer::<256>::default();
+        reader.archive_latest(&mut ser).unwrap();
+        let bytes = ser.into_serializer().into_inner();
+        let archived = check_archived_root::<Frame>(&bytes).unwrap();
+        assert_eq!(archived.seq, 5);
+        assert_eq!(archived.label, "frame 5");
+        assert_eq!(archived.samples.as_slice(), [0, -1, -2, -3, -4]);
+        assert!(!reader.updated(), "archiving left the frame behind");
+    }
+
+    #[test]
+    fn archived_frames_replay_through_the_writer() {
+        let archive = |frame: &Frame| {
+            let mut ser = AllocSerializer::<256>::default();
+            rkyv::ser::Serializer::serialize_value(&mut ser, frame).unwrap();
+            ser.into_serializer().into_inner()
+        };
+        let buffer = NBuffer::<Frame, 4>::new(Frame::default);
+        let mut writer = buffer.get_writer();
+        let mut reader = buffer.get_reader();
+
+        for seq in [1, 7, 2] {
+            let bytes = archive(&frame(seq));
+            writer.write_archived(check_archived_root::<Frame>(&bytes).unwrap());
+            assert_eq!(*reader.read(), frame(seq));
+        }
+
+        // And back: what the reader archives replays as the same frame.
+        let mut ser = AllocSerializer::<256>::default();
+        reader.archive_latest(&mut ser).unwrap();
+        let bytes = ser.into_serializer().into_inner();
+        writer.write_archived(check_archived_root::<Frame>(&bytes).unwrap());
+        assert!(reader.update());
+        assert_eq!(*reader.output_buffer(), frame(2));
+    }
+}
@@ -0,0 +1,401 @@
+use core::cell::UnsafeCell;
+
+use crate::atomic::{AtomicBool, AtomicU32, Ordering};
+use crate::ord;
+
+// Per-channel control byte: the back slot and its dirty bit, which both
+// sides swap, and the writer's and reader's slots, which only their owner
+// changes. Every transition is a single RMW on the word holding the byte.
+const BACK_SHIFT: u8 = 0;
+const DIRTY_BIT: u8 = 0b100;
+const INPUT_SHIFT: u8 = 3;
+const OUTPUT_SHIFT: u8 = 5;
+const SLOT_MASK: u8 = 0b11;
+
+const fn slot(control: u8, shift: u8) -> u8 {
+    (control >> shift) & SLOT_MASK
+}
+
+const fn with_slot(control: u8, shift: u8, slot: u8) -> u8 {
+    (control & !(SLOT_MASK << shift)) | (slot << shift)
+}
+
+const fn publish_transition(control: u8) -> u8 {
+    let back = slot(control, BACK_SHIFT);
+    let control = with_slot(control, BACK_SHIFT, slot(control, INPUT_SHIFT));
+    with_slot(control, INPUT_SHIFT, back) | DIRTY_BIT
+}
+
+const fn update_transition(control: u8) -> u8 {
+    let back = slot(control, BACK_SHIFT);
+    let control = with_slot(control, BACK_SHIFT, slot(control, OUTPUT_SHIFT));
+    with_slot(control, OUTPUT_SHIFT, back) & !DIRTY_BIT
+}
+
+// Back slot 0, clean.
+const INITIAL_CONTROL: u8 = (1 << INPUT_SHIFT) | (2 << OUTPUT_SHIFT);
+
+/// Channels whose control bytes share one atomic word.
+const LANES: usize = 4;
+
+/// The control bytes, padded to whole words. Only ever accessed through
+/// `word`, so every atomic access to them has the same size.
+#[repr(C, align(4))]
+struct Controls<const CHANNELS: usize> {
+    lanes: UnsafeCell<[u8; CHANNELS]>,
+    tail: UnsafeCell<[u8; LANES - 1]>,
+}
+
+impl<const CHANNELS: usize> Controls<CHANNELS> {
+    const WORDS: usize = CHANNELS.div_ceil(LANES);
+
+    const fn new() -> Self {
+        Self {
+            lanes: UnsafeCell::new([INITIAL_CONTROL; CHANNELS]),
+            tail: UnsafeCell::new([0; LANES - 1]),
+        }
+    }
+
+    fn word(&self, word: usize) -> &AtomicU32 {
+        debug_assert!(word < Self::WORDS);
+        // The struct is word-aligned and at least `WORDS` words long, and
+        // all of it sits in `UnsafeCell`s.
+        unsafe { AtomicU32::from_ptr((self as *const Self).cast::<u32>().cast_mut().add(word)) }
+    }
+
+    /// The channel's word and its lane in it.
+    fn lane(&self, channel: usize) -> (&AtomicU32, usize) {
+        assert!(channel < CHANNELS, "Channel out of range");
+        (self.word(channel / LANES), channel % LANES)
+    }
+
+    fn load(&self, channel: usize, order: Ordering) -> u8 {
+        let (word, lane) = self.lane(channel);
+        get_lane(word.load(order), lane)
+    }
+
+    /// Channels of `word` that exist, rather than being padding.
+    const fn active(word: usize) -> usize {
+        let rest = CHANNELS - word * LANES;
+        if rest < LANES {
+            rest
+        } else {
+            LANES
+        }
+    }
+}
+
+/// Bit offset of a channel's control byte within its word, so that the
+/// bytes sit at increasing addresses whatever the endianness.
+const fn shift(lane: usize) -> u32 {
+    let byte = if cfg!(target_endian = "little") {
+        lane
+    } else {
+        LANES - 1 - lane
+    };
+    byte as u32 * 8
+}
+
+const fn get_lane(packed: u32, lane: usize) -> u8 {
+    (packed >> shift(lane)) as u8
+}
+
+const fn set_lane(packed: u32, lane: usize, control: u8) -> u32 {
+    (packed & !(0xff << shift(lane))) | (control as u32) << shift(lane)
+}
+
+/// The dirty bits of the given lanes of `packed`.
+fn dirty_lanes(packed: u32, lanes: core::ops::Range<usize>) -> u32 {
+    lanes.fold(0, |dirty, lane| {
+        dirty | packed & (DIRTY_BIT as u32) << shift(lane)
+    })
+}
+
+/// Runs `transition` on one lane of `word` and returns that lane's former
+/// control byte.
+fn update_lane(word: &AtomicU32, lane: usize, transition: impl Fn(u8) -> Option<u8>) -> Option<u8> {
+    word.fetch_update(ord::acqrel(), ord::acquire(), |packed| {
+        Some(set_lane(packed, lane, transition(get_lane(packed, lane))?))
+    })
+    .ok()
+    .map(|packed| get_lane(packed, lane))
+}
+
+/// Runs `transition` on the given lanes of `word` and returns the former
+/// word.
+fn update_lanes(
+    word: &AtomicU32,
+    lanes: core::ops::Range<usize>,
+    transition: impl Fn(u8) -> Option<u8>,
+) -> Option<u32> {
+    word.fetch_update(ord::acqrel(), ord::acquire(), |packed| {
+        let mut next = packed;
+        for lane in lanes.clone() {
+            if let Some(control) = transition(get_lane(packed, lane)) {
+                next = set_lane(next, lane, control);
+            }
+        }
+        (next != packed).then_some(next)
+    })
+    .ok()
+}
+
+/// `CHANNELS` independent triple buffers sharing one pair of handles, with
+/// the control state of each channel packed into a single byte and four of
+/// those bytes to an atomic word. No notifiers or hooks: reads and writes
+/// never block.
+pub struct TripleBufferArray<T, const CHANNELS: usize> {
+    slots: UnsafeCell<[[T; CHANNELS]; 3]>,
+    control: Controls<CHANNELS>,
+
+    is_reader_exist: AtomicBool,
+    is_writer_exist: AtomicBool,
+}
+
+unsafe impl<T: Send, const CHANNELS: usize> Sync for TripleBufferArray<T, CHANNELS> {}
+
+impl<T, const CHANNELS: usize> TripleBufferArray<T, CHANNELS> {
+    pub fn new(generator: impl Fn() -> T) -> Self {
+        Self::from_slots(core::array::from_fn(|_| {
+            core::array::from_fn(|_| generator())
+        }))
+    }
+
+    pub const fn from_slots(slots: [[T; CHANNELS]; 3]) -> Self {
+        Self {
+            slots: UnsafeCell::new(slots),
+            control: Controls::new(),
+
+            is_reader_exist: AtomicBool::new(false),
+            is_writer_exist: AtomicBool::new(false),
+        }
+    }
+
+    pub fn get_reader(&self) -> ArrayReader<'_, T, CHANNELS> {
+        if self.is_reader_exist.swap(true, ord::acquire()) {
+            panic!("Reader already exists");
+        }
+        ArrayReader { buffer: self }
+    }
+
+    pub fn get_writer(&self) -> ArrayWriter<'_, T, CHANNELS> {
+        if self.is_writer_exist.swap(true, ord::acquire()) {
+            panic!("Writer already exists");
+        }
+        ArrayWriter { buffer: self }
+    }
+
+    fn slot(&self, slot: u8, channel: usize) -> *mut T {
+        assert!(channel < CHANNELS, "Channel out of range");
+        unsafe {
+            self.slots
+                .get()
+                .cast::<[T; CHANNELS]>()
+                .add(slot as usize)
+                .cast::<T>()
+                .add(channel)
+        }
+    }
+}
+
+pub struct ArrayReader<'a, T, const CHANNELS: usize> {
+    buffer: &'a TripleBufferArray<T, CHANNELS>,
+}
+
+impl<'a, T, const CHANNELS: usize> ArrayReader<'a, T, CHANNELS> {
+    pub fn read(&mut self, channel: usize) -> &T {
+        self.update(channel);
+        self.output_buffer(channel)
+    }
+
+    pub fn updated(&self, channel: usize) -> bool {
+        self.buffer.control.load(channel, ord::acquire()) & DIRTY_BIT != 0
+    }
+
+    pub fn output_buffer(&mut self, channel: usize) -> &mut T {
+        let control = self.buffer.control.load(channel, ord::relaxed());
+        unsafe { &mut *self.buffer.slot(slot(control, OUTPUT_SHIFT), channel) }
+    }
+
+    pub fn update(&mut self, channel: usize) -> bool {
+        let (word, lane) = self.buffer.control.lane(channel);
+        update_lane(word, lane, |control| {
+            (control & DIRTY_BIT != 0).then(|| update_transition(control))
+        })
+        .is_some()
+    }
+
+    /// Takes the latest frame of every channel that has one; returns how
+    /// many did. One RMW per word of channels with a new frame, a single
+    /// load per word without.
+    pub fn update_all(&mut self) -> usize {
+        let controls = &self.buffer.control;
+        let mut updated = 0;
+        for index in 0..Controls::<CHANNELS>::WORDS {
+            let word = controls.word(index);
+            let lanes = 0..Controls::<CHANNELS>::active(index);
+            if dirty_lanes(word.load(ord::acquire()), lanes.clone()) == 0 {
+                continue;
+            }
+            let former = update_lanes(word, lanes.clone(), |control| {
+                (control & DIRTY_BIT != 0).then(|| update_transition(control))
+            });
+            updated += former.map_or(0, |former| dirty_lanes(former, lanes).count_ones());
+        }
+        updated as usize
+    }
+}
+
+impl<'a, T, const CHANNELS: usize> Drop for ArrayReader<'a, T, CHANNELS> {
+    fn drop(&mut self) {
+        self.buffer.is_reader_exist.store(false, ord::release());
+    }
+}
+
+pub struct ArrayWriter<'a, T, const CHANNELS: usize> {
+    buffer: &'a TripleBufferArray<T, CHANNELS>,
+}
+
+impl<'a, T, const CHANNELS: usize> ArrayWriter<'a, T, CHANNELS> {
+    pub fn write(&mut self, channel: usize, value: T) {
+        *self.input_buffer(channel) = value;
+        self.publish(channel);
+    }
+
+    pub fn input_buffer(&mut self, channel: usize) -> &mut T {
+        let control = self.buffer.control.load(channel, ord::relaxed());
+        unsafe { &mut *self.buffer.slot(slot(control, INPUT_SHIFT), channel) }
+    }
+
+    pub fn consumed(&self, channel: usize) -> bool {
+        self.buffer.control.load(channel, ord::acquire()) & DIRTY_BIT == 0
+    }
+
+    /// Publishes the channel's input slot; returns whether an unread frame
+    /// was overwritten.
+    pub fn publish(&self, channel: usize) -> bool {
+        let (word, lane) = self.buffer.control.lane(channel);
+        let former = update_lane(word, lane, |control| Some(publish_transition(control))).unwrap();
+        former & DIRTY_BIT != 0
+    }
+
+    /// Publishes every channel's input slot, e.g. after staging all of them
+    /// through `input_buffer`. One RMW per word of channels.
+    pub fn publish_all(&self) {
+        for index in 0..Controls::<CHANNELS>::WORDS {
+            let lanes = 0..Controls::<CHANNELS>::active(index);
+            update_lanes(self.buffer.control.word(index), lanes, |control| {
+                Some(publish_transition(control))
+            });
+        }
+    }
+}
+
+impl<'a, T, const CHANNELS: usize> Drop for ArrayWriter<'a, T, CHANNELS> {
+    fn drop(&mut self) {
+        self.buffer.is_writer_exist.store(false, ord::release());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn channels_are_fresh_independently() {
+        let array = TripleBufferArray::<u32, 4>::new(|| 0);
+        let mut writer = array.get_writer();
+        let mut reader = array.get_reader();
+
+        writer.write(1, 10);
+        assert!(!reader.updated(0));
+        assert!(reader.updated(1));
+        assert_eq!(*reader.read(1), 10);
+        assert!(!reader.update(1));
+
+        assert!(!writer.publish(2));
+        *writer.input_buffer(2) = 20;
+        assert!(writer.publish(2), "unread frame overwritten");
+        assert_eq!(*reader.read(2), 20);
+        assert_eq!(*reader.read(3), 0);
+
+        for channel in 0..4 {
+            *writer.input_buffer(channel) = 100 + channel as u32;
+        }
+        writer.publish_all();
+        assert_eq!(reader.update_all(), 4);
+        assert_eq!(reader.update_all(), 0);
+        for channel in 0..4 {
+            assert_eq!(*reader.output_buffer(channel), 100 + channel as u32);
+            assert!(writer.consumed(channel));
+        }
+    }
+
+    #[test]
+    fn bulk_operations_cover_partial_words() {
+        let array = TripleBufferArray::<u32, 6>::new(|| 0);
+        let mut writer = array.get_writer();
+        let mut reader = array.get_reader();
+
+        writer.write(5, 50);
+        assert_eq!(reader.update_all(), 1);
+        assert_eq!(*reader.output_buffer(5), 50);
+
+        for channel in 0..6 {
+            *writer.input_buffer(channel) = 60 + channel as u32;
+        }
+        writer.publish_all();
+        writer.write(1, 71);
+        assert_eq!(reader.update_all(), 6);
+        for channel in 0..6 {
+            let expected = if channel == 1 { 71 } else { 60 + channel as u32 };
+            assert_eq!(*reader.output_buffer(channel), expected);
+            assert!(writer.consumed(channel));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Channel out of range")]
+    fn channel_past_the_last_lane_panics() {
+        let array = TripleBufferArray::<u32, 6>::new(|| 0);
+        array.get_reader().updated(6);
+    }
+
+    #[test]
+    fn channels_never_mix_under_stress() {
+        static ARRAY: TripleBufferArray<u64, 64> = TripleBufferArray::from_slots([[0; 64]; 3]);
+        let rounds = if cfg!(miri) { 20 } else { 2_000 };
+
+        let jh = std::thread::spawn(move || {
+            let mut writer = ARRAY.get_writer();
+            for round in 1..=rounds {
+                let value = |channel: usize| round * 64 + channel as u64;
+                if round % 2 == 0 {
+                    for channel in 0..64 {
+                        writer.write(channel, value(channel));
+                    }
+                } else {
+                    for channel in 0..64 {
+                        *writer.input_buffer(channel) = value(channel);
+                    }
+                    writer.publish_all();
+                }
+            }
+        });
+
+        let mut reader = ARRAY.get_reader();
+        let mut last = [0; 64];
+        while last.iter().any(|&value| value / 64 != rounds) {
+            reader.update_all();
+            for (channel, last) in last.iter_mut().enumerate() {
+                let value = *reader.output_buffer(channel);
+                if value != 0 {
+                    assert_eq!(value as usize % 64, channel, "frame from another channel");
+                }
+                assert!(value >= *last, "channel went back in time");
+                *last = value;
+            }
+        }
+        jh.join().unwrap();
+    }
+}
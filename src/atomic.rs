@@ -0,0 +1,51 @@
+//! Every atomic type the crate uses, from `portable-atomic` under the
+//! default `portable-atomic` feature, or straight from `core` without it.
+//! Without it the target needs native compare-and-swap up to pointer width,
+//! and `SnapshotRing` and the 64-bit counters of `seq`, `stats`,
+//! `watermarks` and `event-log` need it at 64 bits too; `portable-atomic`
+//! emulates whatever the target lacks.
+
+#[cfg(feature = "portable-atomic")]
+#[allow(unused_imports)]
+pub(crate) use portable_atomic::{
+    fence, AtomicBool, AtomicI32, AtomicPtr, AtomicU16, AtomicU32, AtomicU64, AtomicU8,
+    AtomicUsize, Ordering,
+};
+
+#[cfg(all(not(feature = "portable-atomic"), target_has_atomic = "64"))]
+#[allow(unused_imports)]
+pub(crate) use core::sync::atomic::AtomicU64;
+#[cfg(not(feature = "portable-atomic"))]
+#[allow(unused_imports)]
+pub(crate) use core::sync::atomic::{
+    fence, AtomicBool, AtomicI32, AtomicPtr, AtomicU16, AtomicU32, AtomicU8, AtomicUsize, Ordering,
+};
+
+#[cfg(all(
+    not(feature = "portable-atomic"),
+    not(all(
+        target_has_atomic = "8",
+        target_has_atomic = "16",
+        target_has_atomic = "32",
+        target_has_atomic = "ptr"
+    ))
+))]
+compile_error!(
+    "this target lacks native compare-and-swap atomics; enable the `portable-atomic` feature \
+     (and `critical-section` if it has no atomics at all)"
+);
+
+#[cfg(all(
+    not(feature = "portable-atomic"),
+    not(target_has_atomic = "64"),
+    any(
+        feature = "seq",
+        feature = "stats",
+        feature = "watermarks",
+        feature = "event-log"
+    )
+))]
+compile_error!(
+    "`seq`, `stats`, `watermarks` and `event-log` need 64-bit atomics, which this target \
+     lacks; enable the `portable-atomic` feature"
+);
@@ -0,0 +1,123 @@
+//! Spin → yield escalation for polling loops, modeled on crossbeam's
+//! `Backoff`. Used by the crate's own spin waits and usable around
+//! `updated()`/`consumed()` in hand-written loops.
+
+const SPIN_LIMIT: u32 = 6;
+const YIELD_LIMIT: u32 = 10;
+
+/// Exponential backoff: each `spin`/`snooze` busy-waits twice as long as
+/// the previous one, up to `2^spin_limit` iterations. Past that, `snooze`
+/// yields to the OS scheduler with the `std` feature and keeps spinning at
+/// the cap without it.
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    step: u32,
+    spin_limit: u32,
+}
+
+impl Backoff {
+    pub const fn new() -> Self {
+        Self::with_spin_limit(SPIN_LIMIT)
+    }
+
+    /// `spin_limit` is capped below the yield limit so `is_completed` is
+    /// always reachable.
+    pub const fn with_spin_limit(spin_limit: u32) -> Self {
+        Self {
+            step: 0,
+            spin_limit: if spin_limit < YIELD_LIMIT {
+                spin_limit
+            } else {
+                YIELD_LIMIT
+            },
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.step = 0;
+    }
+
+    /// Backs off in a lock-free retry loop, e.g. after a failed CAS.
+    pub fn spin(&mut self) {
+        for _ in 0..1u32 << self.step.min(self.spin_limit) {
+            core::hint::spin_loop();
+        }
+        if self.step <= self.spin_limit {
+            self.step += 1;
+        }
+    }
+
+    /// Backs off while waiting for another thread to make progress.
+    pub fn snooze(&mut self) {
+        if self.will_yield() {
+            #[cfg(feature = "std")]
+            std::thread::yield_now();
+        } else {
+            for _ in 0..1u32 << self.step.min(self.spin_limit) {
+                core::hint::spin_loop();
+            }
+        }
+        if self.step <= YIELD_LIMIT {
+            self.step += 1;
+        }
+    }
+
+    /// Whether backing off stopped helping and the caller should block on
+    /// something instead.
+    pub fn is_completed(&self) -> bool {
+        self.step > YIELD_LIMIT
+    }
+
+    fn will_yield(&self) -> bool {
+        cfg!(feature = "std") && self.step > self.spin_limit
+    }
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snooze_escalates_to_completion() {
+        let mut backoff = Backoff::new();
+        for _ in 0..=SPIN_LIMIT {
+            assert!(!backoff.will_yield());
+            backoff.snooze();
+        }
+        for _ in SPIN_LIMIT + 1..=YIELD_LIMIT {
+            assert_eq!(backoff.will_yield(), cfg!(feature = "std"));
+            assert!(!backoff.is_completed());
+            backoff.snooze();
+        }
+        assert!(backoff.is_completed());
+
+        backoff.reset();
+        assert!(!backoff.is_completed());
+    }
+
+    #[test]
+    fn spin_never_completes() {
+        let mut backoff = Backoff::with_spin_limit(2);
+        for _ in 0..100 {
+            backoff.spin();
+        }
+        assert_eq!(backoff.step, 3);
+        assert!(!backoff.is_completed());
+    }
+
+    #[test]
+    fn spin_limit_is_capped() {
+        let mut backoff = Backoff::with_spin_limit(u32::MAX);
+        for _ in 0..=YIELD_LIMIT {
+            assert!(!backoff.will_yield());
+            backoff.snooze();
+        }
+        assert!(backoff.is_completed());
+    }
+}
@@ -0,0 +1,119 @@
+//! `heapless::Vec<u8, N>` and `heapless::String<N>` frames, for the
+//! `heapless` feature: refilled in place each frame, reusing the slot's
+//! storage, with the length published along with the bytes.
+
+use heapless::{String, Vec};
+
+use crate::{BufferReader, BufferWriter, FrameTooLong, Notifier};
+
+fn check(len: usize, capacity: usize) -> Result<(), FrameTooLong> {
+    if len > capacity {
+        Err(FrameTooLong { len, capacity })
+    } else {
+        Ok(())
+    }
+}
+
+impl<'a, const B: usize, N: Notifier, const SLOTS: usize> BufferWriter<'a, Vec<u8, B>, N, SLOTS> {
+    /// Replaces the input slot's contents with `data` and publishes it.
+    /// Nothing is published, nor the slot touched, if it doesn't fit.
+    pub fn write_extend(&mut self, data: &[u8]) -> Result<(), FrameTooLong> {
+        check(data.len(), B)?;
+        let input = self.input_buffer();
+        input.clear();
+        // Can't fail: the capacity was checked above.
+        let _ = input.extend_from_slice(data);
+        self.publish();
+        Ok(())
+    }
+}
+
+impl<'a, const B: usize, N: Notifier, const SLOTS: usize> BufferWriter<'a, String<B>, N, SLOTS> {
+    /// Like `write_extend` for a string; `len` in the error is in bytes.
+    pub fn write_str(&mut self, data: &str) -> Result<(), FrameTooLong> {
+        check(data.len(), B)?;
+        let input = self.input_buffer();
+        input.clear();
+        let _ = input.push_str(data);
+        self.publish();
+        Ok(())
+    }
+}
+
+impl<'a, const B: usize, N: Notifier, const SLOTS: usize> BufferReader<'a, Vec<u8, B>, N, SLOTS> {
+    /// Updates, then the latest frame's bytes.
+    pub fn read_as_slice(&mut self) -> &[u8] {
+        self.read().as_slice()
+    }
+}
+
+impl<'a, const B: usize, N: Notifier, const SLOTS: usize> BufferReader<'a, String<B>, N, SLOTS> {
+    /// Updates, then the latest frame's text.
+    pub fn read_as_str(&mut self) -> &str {
+        self.read().as_str()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{NBuffer, TripleBuffer};
+
+    #[test]
+    fn vec_frames_round_trip() {
+        let buffer = TripleBuffer::new(Vec::<u8, 8>::new);
+        let mut writer = buffer.get_writer();
+        let mut reader = buffer.get_reader();
+
+        assert_eq!(reader.read_as_slice(), []);
+        writer.write_extend(b"abcdefgh").unwrap();
+        assert_eq!(reader.read_as_slice(), b"abcdefgh");
+        // A shorter frame doesn't leave the tail of an older one behind.
+        for frame in [&b"xy"[..], b"", b"12345"] {
+            writer.write_extend(frame).unwrap();
+            assert_eq!(reader.read_as_slice(), frame);
+        }
+    }
+
+    #[test]
+    fn overflowing_frames_are_not_published() {
+        let buffer = NBuffer::<Vec<u8, 4>, 2>::new(Vec::new);
+        let mut writer = buffer.get_writer();
+        let mut reader = buffer.get_reader();
+
+        writer.write_extend(b"ok").unwrap();
+        assert_eq!(
+            writer.write_extend(b"toolong"),
+            Err(FrameTooLong {
+                len: 7,
+                capacity: 4
+            })
+        );
+        assert_eq!(
+            reader.read_as_slice(),
+            b"ok",
+            "overflow clobbered the frame"
+        );
+        assert!(!reader.update());
+    }
+
+    #[test]
+    fn string_frames_round_trip() {
+        let buffer = TripleBuffer::new(String::<6>::new);
+        let mut writer = buffer.get_writer();
+        let mut reader = buffer.get_reader();
+
+        writer.write_str("héllo").unwrap();
+        assert_eq!(reader.read_as_str(), "héllo");
+        assert_eq!(
+            writer.write_str("héllo!"),
+            Err(FrameTooLong {
+                len: 7,
+                capacity: 6
+            })
+        );
+        assert!(!reader.update());
+        writer.write_str("bye").unwrap();
+        assert_eq!(reader.read_as_str(), "bye");
+    }
+}
@@ -0,0 +1,146 @@
+//! Frames whose size is only known at runtime.
+//!
+//! `Box<[T]>` slots are allocated once, all with the same length, and then
+//! only written in place through `write_slice`; assigning a new box through
+//! `write`/`input_buffer` would still compile but could change a slot's
+//! length behind the reader's back.
+//!
+//! Trait objects work as payloads as they are: a
+//! `TripleBuffer<Box<dyn Trait + Send>>` publishes whole boxes, so each
+//! frame may be a different concrete type. Every `write` moves a fresh box
+//! in and drops the one it replaces, which allocates on each publish.
+
+use alloc::boxed::Box;
+use alloc::vec;
+use core::fmt;
+
+use crate::{BufferReader, BufferWriter, NBuffer, Notifier};
+
+/// A slice didn't match the length of the buffer's slots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct LengthMismatch {
+    pub expected: usize,
+    pub actual: usize,
+}
+
+impl fmt::Display for LengthMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "slice of length {} for slots of length {}",
+            self.actual, self.expected
+        )
+    }
+}
+
+impl core::error::Error for LengthMismatch {}
+
+fn check(expected: usize, actual: usize) -> Result<(), LengthMismatch> {
+    if expected == actual {
+        Ok(())
+    } else {
+        Err(LengthMismatch { expected, actual })
+    }
+}
+
+impl<T: Clone, const SLOTS: usize> NBuffer<Box<[T]>, SLOTS> {
+    /// Allocates every slot as `len` copies of `fill`.
+    pub fn new_boxed_slices(len: usize, fill: T) -> Self {
+        Self::new(|| vec![fill.clone(); len].into_boxed_slice())
+    }
+}
+
+impl<'a, T: Clone, N: Notifier, const SLOTS: usize> BufferWriter<'a, Box<[T]>, N, SLOTS> {
+    /// Copies `data` into the input slot and publishes it, unless its length
+    /// differs from the slot's.
+    pub fn write_slice(&mut self, data: &[T]) -> Result<(), LengthMismatch> {
+        let input = self.input_buffer();
+        check(input.len(), data.len())?;
+        input.clone_from_slice(data);
+        self.publish();
+        Ok(())
+    }
+}
+
+impl<'a, T: Clone, N: Notifier, const SLOTS: usize> BufferReader<'a, Box<[T]>, N, SLOTS> {
+    /// Copies the latest frame into `out`, unless its length differs from
+    /// the frame's. Returns whether the frame is new, like `update`.
+    pub fn read_slice(&mut self, out: &mut [T]) -> Result<bool, LengthMismatch> {
+        check(self.output_buffer().len(), out.len())?;
+        let updated = self.update();
+        out.clone_from_slice(self.output_buffer());
+        Ok(updated)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TripleBuffer;
+    use alloc::string::String;
+
+    #[test]
+    fn slices_of_the_wrong_length_are_rejected() {
+        let buffer = TripleBuffer::new_boxed_slices(4, 0u8);
+        let mut writer = buffer.get_writer();
+        let mut reader = buffer.get_reader();
+
+        assert_eq!(
+            writer.write_slice(&[1, 2, 3]),
+            Err(LengthMismatch {
+                expected: 4,
+                actual: 3
+            })
+        );
+        assert!(!reader.updated());
+        assert_eq!(writer.write_slice(&[1, 2, 3, 4]), Ok(()));
+
+        let mut out = [0; 5];
+        assert_eq!(
+            reader.read_slice(&mut out),
+            Err(LengthMismatch {
+                expected: 4,
+                actual: 5
+            })
+        );
+        assert!(reader.updated(), "failed read took the frame");
+        let mut out = [0; 4];
+        assert_eq!(reader.read_slice(&mut out), Ok(true));
+        assert_eq!(out, [1, 2, 3, 4]);
+        assert_eq!(reader.read_slice(&mut out), Ok(false));
+    }
+
+    trait Shape {
+        fn describe(&self) -> String;
+    }
+
+    struct Square(u32);
+    struct Circle(u32);
+
+    impl Shape for Square {
+        fn describe(&self) -> String {
+            alloc::format!("square {}", self.0)
+        }
+    }
+
+    impl Shape for Circle {
+        fn describe(&self) -> String {
+            alloc::format!("circle {}", self.0)
+        }
+    }
+
+    #[test]
+    fn trait_object_frames_dispatch_after_read() {
+        let buffer: TripleBuffer<Box<dyn Shape + Send>> =
+            TripleBuffer::new(|| -> Box<dyn Shape + Send> { Box::new(Square(0)) });
+        let mut writer = buffer.get_writer();
+        let mut reader = buffer.get_reader();
+
+        writer.write(Box::new(Circle(3)));
+        assert_eq!(reader.read().describe(), "circle 3");
+        writer.write(Box::new(Square(5)));
+        assert_eq!(reader.read().describe(), "square 5");
+        assert_eq!(reader.read().describe(), "square 5");
+    }
+}
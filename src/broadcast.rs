@@ -0,0 +1,389 @@
+//! Run the loom model with
+//! `RUSTFLAGS="--cfg tri_buffer_loom" cargo test --release --lib broadcast`.
+
+#[cfg(all(tri_buffer_loom, test))]
+use loom::{
+    cell::UnsafeCell,
+    sync::atomic::{fence, AtomicBool, AtomicUsize},
+};
+#[cfg(not(all(tri_buffer_loom, test)))]
+use {
+    crate::atomic::{fence, AtomicBool, AtomicUsize},
+    core::cell::UnsafeCell,
+};
+
+use crate::atomic::Ordering;
+use crate::ord;
+
+const INDEX_BITS: u32 = 8;
+const INDEX_MASK: usize = (1 << INDEX_BITS) - 1;
+const MAX_READERS: usize = INDEX_MASK - 1;
+
+fn pack(slot: usize, sequence: usize) -> usize {
+    slot | sequence << INDEX_BITS
+}
+
+fn slot_of(latest: usize) -> usize {
+    latest & INDEX_MASK
+}
+
+fn sequence_of(latest: usize) -> usize {
+    latest >> INDEX_BITS
+}
+
+struct Slot<T>(UnsafeCell<T>);
+
+#[cfg(not(all(tri_buffer_loom, test)))]
+impl<T> Slot<T> {
+    fn new(value: T) -> Self {
+        Self(UnsafeCell::new(value))
+    }
+
+    unsafe fn get(&self) -> &T {
+        &*self.0.get()
+    }
+
+    #[allow(clippy::mut_from_ref)]
+    unsafe fn get_mut(&self) -> &mut T {
+        &mut *self.0.get()
+    }
+
+    unsafe fn set(&self, value: T) {
+        *self.0.get() = value;
+    }
+}
+
+// loom only tracks accesses made inside `with`/`with_mut`; the reference
+// returning accessors are unchecked under the model.
+#[cfg(all(tri_buffer_loom, test))]
+impl<T> Slot<T> {
+    fn new(value: T) -> Self {
+        Self(UnsafeCell::new(value))
+    }
+
+    unsafe fn get(&self) -> &T {
+        self.0.with(|ptr| &*ptr)
+    }
+
+    #[allow(clippy::mut_from_ref)]
+    unsafe fn get_mut(&self) -> &mut T {
+        self.0.with_mut(|ptr| &mut *ptr)
+    }
+
+    unsafe fn set(&self, value: T) {
+        self.0.with_mut(|ptr| *ptr = value);
+    }
+
+    unsafe fn with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        self.0.with(|ptr| f(&*ptr))
+    }
+}
+
+/// One writer, up to `READERS` readers, each reader with its own output
+/// slot, so `READERS + 2` slots in total.
+///
+/// Readers announce the slot they are about to read in `held` and re-check
+/// that it is still the latest frame; the writer only ever writes into a
+/// slot that is neither the latest nor announced by a reader. Both sides
+/// separate their store from the following load with a `SeqCst` fence, so
+/// either the writer sees the announcement or the reader sees the newer
+/// frame and retries. Publishing samples every reader and picks
+/// a free slot in O(`READERS`²), which is meant for a handful of readers.
+pub struct BroadcastTripleBuffer<T, const READERS: usize> {
+    reader_slots: [Slot<T>; READERS],
+    spare_slots: [Slot<T>; 2],
+
+    // Slot index in the low bits, publish count above. The count wraps
+    // after 2^24 publishes on 32-bit targets; a reader that skipped exactly
+    // that many then misses one frame.
+    latest: AtomicUsize,
+    input: AtomicUsize,
+    held: [AtomicUsize; READERS],
+    seen: [AtomicUsize; READERS],
+
+    is_reader_exist: [AtomicBool; READERS],
+    is_writer_exist: AtomicBool,
+}
+
+unsafe impl<T: Send + Sync, const READERS: usize> Sync for BroadcastTripleBuffer<T, READERS> {}
+
+impl<T, const READERS: usize> BroadcastTripleBuffer<T, READERS> {
+    const SLOTS: usize = READERS + 2;
+
+    pub fn new(generator: impl Fn() -> T) -> Self {
+        const {
+            assert!(READERS >= 1 && READERS <= MAX_READERS);
+        }
+        Self {
+            reader_slots: core::array::from_fn(|_| Slot::new(generator())),
+            spare_slots: [Slot::new(generator()), Slot::new(generator())],
+            latest: AtomicUsize::new(pack(0, 0)),
+            input: AtomicUsize::new(1),
+            held: core::array::from_fn(|_| AtomicUsize::new(0)),
+            seen: core::array::from_fn(|_| AtomicUsize::new(0)),
+            is_reader_exist: core::array::from_fn(|_| AtomicBool::new(false)),
+            is_writer_exist: AtomicBool::new(false),
+        }
+    }
+
+    fn slot(&self, index: usize) -> &Slot<T> {
+        if index < READERS {
+            &self.reader_slots[index]
+        } else {
+            &self.spare_slots[index - READERS]
+        }
+    }
+
+    pub fn get_reader(&self, index: usize) -> BroadcastReader<'_, T, READERS> {
+        assert!(index < READERS, "Reader index out of range");
+        if self.is_reader_exist[index].swap(true, ord::acquire()) {
+            panic!("Reader {index} already exists");
+        }
+        BroadcastReader {
+            buffer: self,
+            index,
+        }
+    }
+
+    /// All readers at once, in index order.
+    pub fn take_readers(&self) -> [BroadcastReader<'_, T, READERS>; READERS] {
+        core::array::from_fn(|index| self.get_reader(index))
+    }
+
+    pub fn get_writer(&self) -> BroadcastWriter<'_, T, READERS> {
+        if self.is_writer_exist.swap(true, ord::acquire()) {
+            panic!("Writer already exists");
+        }
+        BroadcastWriter { buffer: self }
+    }
+}
+
+pub struct BroadcastReader<'a, T, const READERS: usize> {
+    buffer: &'a BroadcastTripleBuffer<T, READERS>,
+    index: usize,
+}
+
+impl<'a, T, const READERS: usize> BroadcastReader<'a, T, READERS> {
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn updated(&self) -> bool {
+        let latest = self.buffer.latest.load(ord::acquire());
+        sequence_of(latest) != self.buffer.seen[self.index].load(ord::relaxed())
+    }
+
+    /// Moves this reader to the latest frame. Lock-free: it retries only
+    /// while the writer publishes in between.
+    pub fn update(&mut self) -> bool {
+        let buffer = self.buffer;
+        let mut latest = buffer.latest.load(ord::acquire());
+        if sequence_of(latest) == buffer.seen[self.index].load(ord::relaxed()) {
+            return false;
+        }
+        loop {
+            buffer.held[self.index].store(slot_of(latest), ord::release());
+            fence(Ordering::SeqCst);
+            let check = buffer.latest.load(ord::acquire());
+            if slot_of(check) == slot_of(latest) {
+                buffer.seen[self.index].store(sequence_of(check), ord::relaxed());
+                return true;
+            }
+            latest = check;
+        }
+    }
+
+    pub fn output_buffer(&self) -> &T {
+        let slot = self.buffer.held[self.index].load(ord::relaxed());
+        unsafe { self.buffer.slot(slot).get() }
+    }
+
+    pub fn read(&mut self) -> &T {
+        self.update();
+        self.output_buffer()
+    }
+
+    #[cfg(all(tri_buffer_loom, test))]
+    fn with_output<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        let slot = self.buffer.held[self.index].load(ord::relaxed());
+        unsafe { self.buffer.slot(slot).with(f) }
+    }
+}
+
+impl<'a, T, const READERS: usize> Drop for BroadcastReader<'a, T, READERS> {
+    fn drop(&mut self) {
+        self.buffer.is_reader_exist[self.index].store(false, ord::release());
+    }
+}
+
+pub struct BroadcastWriter<'a, T, const READERS: usize> {
+    buffer: &'a BroadcastTripleBuffer<T, READERS>,
+}
+
+impl<'a, T, const READERS: usize> BroadcastWriter<'a, T, READERS> {
+    pub fn input_buffer(&mut self) -> &mut T {
+        let input = self.buffer.input.load(ord::relaxed());
+        unsafe { self.buffer.slot(input).get_mut() }
+    }
+
+    pub fn write(&mut self, value: T) {
+        let input = self.buffer.input.load(ord::relaxed());
+        unsafe { self.buffer.slot(input).set(value) };
+        self.publish();
+    }
+
+    /// Makes the input slot the latest frame for every reader.
+    pub fn publish(&mut self) {
+        let buffer = self.buffer;
+        let input = buffer.input.load(ord::relaxed());
+        let sequence = sequence_of(buffer.latest.load(ord::relaxed())) + 1;
+        buffer.latest.store(pack(input, sequence), ord::release());
+        fence(Ordering::SeqCst);
+
+        // Each reader is sampled once: re-reading `held` per candidate could
+        // see readers moving around and count every slot as taken. The
+        // snapshot names at most READERS slots and one is the latest, so one
+        // of the READERS + 2 is always free.
+        let held: [usize; READERS] =
+            core::array::from_fn(|reader| buffer.held[reader].load(ord::acquire()));
+        let free = (0..BroadcastTripleBuffer::<T, READERS>::SLOTS)
+            .find(|slot| *slot != input && !held.contains(slot));
+        buffer.input.store(free.unwrap(), ord::relaxed());
+    }
+}
+
+impl<'a, T, const READERS: usize> Drop for BroadcastWriter<'a, T, READERS> {
+    fn drop(&mut self) {
+        self.buffer.is_writer_exist.store(false, ord::release());
+    }
+}
+
+#[cfg(all(test, not(tri_buffer_loom)))]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn readers_track_latest_independently() {
+        let buffer = BroadcastTripleBuffer::<u32, 3>::new(|| 0);
+        let mut writer = buffer.get_writer();
+        let [mut fast, mut slow, mut idle] = buffer.take_readers();
+
+        writer.write(1);
+        assert_eq!(*fast.read(), 1);
+        assert_eq!(*slow.read(), 1);
+        for i in 2..=100 {
+            writer.write(i);
+            assert_eq!(*fast.read(), i);
+            assert_eq!(*slow.output_buffer(), 1);
+        }
+        assert!(slow.updated());
+        assert_eq!(*slow.read(), 100);
+        assert!(!slow.update());
+
+        assert_eq!(*idle.output_buffer(), 0);
+        assert_eq!(*idle.read(), 100);
+        assert!(!fast.update());
+    }
+
+    #[test]
+    #[should_panic]
+    fn reader_index_out_of_range() {
+        let buffer = BroadcastTripleBuffer::<u32, 2>::new(|| 0);
+        let _reader = buffer.get_reader(2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn take_readers_after_get_reader() {
+        let buffer = BroadcastTripleBuffer::<u32, 2>::new(|| 0);
+        let _reader = buffer.get_reader(1);
+        let _readers = buffer.take_readers();
+    }
+
+    #[test]
+    fn dropped_reader_can_be_reacquired() {
+        let buffer = BroadcastTripleBuffer::<u32, 1>::new(|| 0);
+        let mut writer = buffer.get_writer();
+        writer.write(1);
+        assert_eq!(*buffer.get_reader(0).read(), 1);
+
+        let mut reader = buffer.get_reader(0);
+        assert!(!reader.update());
+        writer.write(2);
+        assert_eq!(*reader.read(), 2);
+    }
+
+    #[test]
+    fn slow_and_fast_readers_stress() {
+        static BUFFER: std::sync::OnceLock<BroadcastTripleBuffer<[u64; 8], 3>> =
+            std::sync::OnceLock::new();
+        let buffer = BUFFER.get_or_init(|| BroadcastTripleBuffer::new(|| [0; 8]));
+        let last = if cfg!(miri) { 200 } else { 100_000 };
+
+        let readers = buffer.take_readers().map(|mut reader| {
+            std::thread::spawn(move || {
+                let pause = Duration::from_micros(reader.index() as u64 * 50);
+                let mut previous = 0;
+                while previous != last {
+                    let frame = *reader.read();
+                    assert!(frame.iter().all(|&v| v == frame[0]), "torn frame");
+                    assert!(frame[0] >= previous, "frame went back in time");
+                    previous = frame[0];
+                    if !pause.is_zero() {
+                        std::thread::sleep(pause);
+                    }
+                }
+            })
+        });
+
+        let mut writer = buffer.get_writer();
+        for i in 1..=last {
+            writer.write([i; 8]);
+        }
+        for reader in readers {
+            reader.join().unwrap();
+        }
+    }
+}
+
+#[cfg(all(test, tri_buffer_loom))]
+mod loom_tests {
+    use super::*;
+    use loom::thread;
+
+    #[test]
+    fn readers_never_see_torn_or_stale_frames() {
+        loom::model(|| {
+            let buffer: &'static BroadcastTripleBuffer<(usize, usize), 2> =
+                Box::leak(Box::new(BroadcastTripleBuffer::new(|| (0, 0))));
+            let [mut first, mut second] = buffer.take_readers();
+            let mut writer = buffer.get_writer();
+
+            let writer = thread::spawn(move || {
+                for i in 1..=3 {
+                    writer.write((i, i));
+                }
+            });
+            let reader = thread::spawn(move || {
+                let mut previous = 0;
+                for _ in 0..2 {
+                    first.update();
+                    let (a, b) = first.with_output(|frame| *frame);
+                    assert_eq!(a, b);
+                    assert!(a >= previous);
+                    previous = a;
+                }
+            });
+
+            second.update();
+            let (a, b) = second.with_output(|frame| *frame);
+            assert_eq!(a, b);
+
+            writer.join().unwrap();
+            reader.join().unwrap();
+            second.update();
+            assert_eq!(second.with_output(|frame| *frame), (3, 3));
+        });
+    }
+}
@@ -0,0 +1,121 @@
+//! Byte views of `Pod` frames, for the `bytemuck` feature: an I/O layer can
+//! send the output frame or fill the input slot in place, without going
+//! through a `T`.
+
+use bytemuck::Pod;
+
+use crate::{BufferReader, BufferWriter, Notifier};
+
+/// A frame whose bytes the byte views and DMA slots expose: a `Pod`, or an
+/// `Aligned` one without its padding.
+pub trait PodFrame {
+    type Pod: Pod;
+
+    fn pod(&self) -> &Self::Pod;
+    fn pod_mut(&mut self) -> &mut Self::Pod;
+}
+
+impl<T: Pod> PodFrame for T {
+    type Pod = T;
+
+    fn pod(&self) -> &T {
+        self
+    }
+
+    fn pod_mut(&mut self) -> &mut T {
+        self
+    }
+}
+
+impl<'a, T: PodFrame, N: Notifier, const SLOTS: usize> BufferReader<'a, T, N, SLOTS> {
+    /// Updates, then views the latest frame's bytes, like `read`.
+    pub fn read_as_bytes(&mut self) -> &[u8] {
+        bytemuck::bytes_of(self.read().pod())
+    }
+
+    /// Views the bytes of the frame in the output slot without updating,
+    /// so it is the frame the previous `update` took.
+    pub fn peek_as_bytes(&mut self) -> &[u8] {
+        bytemuck::bytes_of(self.output_buffer().pod())
+    }
+}
+
+impl<'a, T: PodFrame, N: Notifier, const SLOTS: usize> BufferWriter<'a, T, N, SLOTS> {
+    /// The input slot's bytes, like `input_buffer`: the reader sees them
+    /// only after `publish`, and the slot holds whatever frame it last held
+    /// (not necessarily the last one published), so write all of it.
+    pub fn input_as_bytes_mut(&mut self) -> &mut [u8] {
+        bytemuck::bytes_of_mut(self.input_buffer().pod_mut())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{QuadBuffer, TripleBuffer};
+
+    #[repr(C)]
+    #[derive(Debug, Clone, Copy, PartialEq, Default)]
+    struct Reading {
+        timestamp: u64,
+        value: f32,
+        channel: u16,
+        flags: u16,
+    }
+
+    // `repr(C)` with no padding, and every bit pattern is valid.
+    unsafe impl bytemuck::Zeroable for Reading {}
+    unsafe impl bytemuck::Pod for Reading {}
+
+    fn encode(reading: &Reading) -> [u8; 16] {
+        let mut bytes = [0; 16];
+        bytes[..8].copy_from_slice(&reading.timestamp.to_ne_bytes());
+        bytes[8..12].copy_from_slice(&reading.value.to_ne_bytes());
+        bytes[12..14].copy_from_slice(&reading.channel.to_ne_bytes());
+        bytes[14..].copy_from_slice(&reading.flags.to_ne_bytes());
+        bytes
+    }
+
+    #[test]
+    fn frames_round_trip_through_byte_views() {
+        let buffer = TripleBuffer::new(Reading::default);
+        let mut writer = buffer.get_writer();
+        let mut reader = buffer.get_reader();
+        let reading = Reading {
+            timestamp: 0x0102_0304_0506_0708,
+            value: -1.5,
+            channel: 0x0a0b,
+            flags: 0x8001,
+        };
+
+        writer.write(reading);
+        assert_eq!(reader.peek_as_bytes(), [0; 16], "peek updated");
+        assert_eq!(reader.read_as_bytes(), encode(&reading));
+
+        let next = Reading {
+            timestamp: 9,
+            ..reading
+        };
+        writer.input_as_bytes_mut().copy_from_slice(&encode(&next));
+        assert_eq!(
+            reader.read_as_bytes(),
+            encode(&reading),
+            "seen before publish"
+        );
+        writer.publish();
+        assert_eq!(*reader.read(), next);
+        assert_eq!(reader.peek_as_bytes(), encode(&next));
+    }
+
+    #[test]
+    fn byte_views_cover_the_whole_frame() {
+        let buffer = QuadBuffer::new(|| [0u32; 5]);
+        let mut writer = buffer.get_writer();
+        let mut reader = buffer.get_reader();
+
+        assert_eq!(writer.input_as_bytes_mut().len(), 20);
+        writer.input_as_bytes_mut().fill(0xab);
+        writer.publish();
+        assert_eq!(reader.read(), &[0xabab_abab; 5]);
+        assert_eq!(reader.peek_as_bytes(), [0xab; 20]);
+    }
+}
@@ -0,0 +1,72 @@
+//! The overwrite policy of the `checked-rt` feature: where a dropped frame
+//! means the reader missed its deadline, e.g. in a test rig, `publish` can
+//! fail right when it drops one instead of only counting it.
+
+use core::fmt;
+
+use crate::atomic::AtomicU8;
+use crate::hook::Hook;
+use crate::ord;
+
+/// A frame `publish` replaced before the reader took it, passed to an
+/// `OverwritePolicy::Hook`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub struct Overwrite {
+    /// Sequence number of the lost frame.
+    #[cfg(feature = "seq")]
+    pub seq: u64,
+}
+
+impl fmt::Display for Overwrite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "tri-buffer publish overwrote an unread frame")?;
+        #[cfg(feature = "seq")]
+        write!(f, " (seq {})", self.seq)?;
+        Ok(())
+    }
+}
+
+/// What every buffer's `publish` does when it overwrites an unread frame.
+#[derive(Debug, Clone, Copy)]
+pub enum OverwritePolicy {
+    /// Drop the frame, as without `checked-rt`.
+    Allow,
+    /// Panic once the publish is complete; the buffer stays usable.
+    Panic,
+    /// Call the hook from the writer's thread, then go on.
+    Hook(fn(&Overwrite)),
+}
+
+const ALLOW: u8 = 0;
+const PANIC: u8 = 1;
+const HOOK: u8 = 2;
+
+static POLICY: AtomicU8 = AtomicU8::new(ALLOW);
+static HANDLER: Hook<Overwrite> = Hook::new();
+
+/// Sets the policy for all buffers; `Allow` until first called.
+pub fn set_overwrite_policy(policy: OverwritePolicy) {
+    let mode = match policy {
+        OverwritePolicy::Allow => ALLOW,
+        OverwritePolicy::Panic => PANIC,
+        OverwritePolicy::Hook(hook) => {
+            HANDLER.set(Some(hook));
+            HOOK
+        }
+    };
+    // Releases the hook to publishes that see the mode.
+    POLICY.store(mode, ord::release());
+}
+
+/// Applies the policy to a publish that overwrote `lost()`; `rt-safe`
+/// compiles it out.
+#[cfg_attr(feature = "rt-safe", allow(dead_code))]
+pub(crate) fn overwrote(lost: impl FnOnce() -> Overwrite) {
+    match POLICY.load(ord::acquire()) {
+        ALLOW => {}
+        PANIC => panic!("{}", lost()),
+        _ => HANDLER.fire(lost),
+    }
+}
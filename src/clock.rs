@@ -0,0 +1,185 @@
+use core::ops::Sub;
+
+use crate::{BufferReader, BufferWriter, Notifier};
+
+/// A time source for publish timestamps: `std::time::Instant` through
+/// `StdClock`, or e.g. a cycle counter on embedded targets.
+pub trait Clock {
+    type Instant: Copy + PartialOrd;
+
+    fn now(&self) -> Self::Instant;
+}
+
+/// `std::time::Instant::now`.
+#[cfg(feature = "std")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct StdClock;
+
+#[cfg(feature = "std")]
+impl Clock for StdClock {
+    type Instant = std::time::Instant;
+
+    fn now(&self) -> Self::Instant {
+        std::time::Instant::now()
+    }
+}
+
+/// A frame and the instant it was published, kept in the same slot so the
+/// stamp is swapped with the frame it belongs to. Initial frames have none.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Stamped<T, I> {
+    pub frame: T,
+    pub stamp: Option<I>,
+}
+
+impl<T, I> Stamped<T, I> {
+    pub const fn new(frame: T) -> Self {
+        Self { frame, stamp: None }
+    }
+}
+
+impl<'a, T, I: Copy, N: Notifier, const SLOTS: usize> BufferWriter<'a, Stamped<T, I>, N, SLOTS> {
+    /// Stamps the input slot with `clock.now()` and publishes it.
+    pub fn publish_stamped(&mut self, clock: &impl Clock<Instant = I>) -> bool {
+        self.input_buffer().stamp = Some(clock.now());
+        self.publish()
+    }
+
+    pub fn write_stamped(&mut self, frame: T, clock: &impl Clock<Instant = I>) {
+        self.input_buffer().frame = frame;
+        self.publish_stamped(clock);
+    }
+}
+
+impl<'a, T, I: Copy, N: Notifier, const SLOTS: usize> BufferReader<'a, Stamped<T, I>, N, SLOTS> {
+    /// When the frame in the output slot was published.
+    pub fn timestamp(&mut self) -> Option<I> {
+        self.output_buffer().stamp
+    }
+
+    /// How long ago the frame in the output slot was published.
+    pub fn age<C: Clock<Instant = I>>(&mut self, clock: &C) -> Option<I::Output>
+    where
+        I: Sub,
+    {
+        let stamp = self.timestamp()?;
+        Some(clock.now() - stamp)
+    }
+}
+
+#[cfg(feature = "watermarks")]
+impl<'a, T, N: Notifier, const SLOTS: usize> BufferWriter<'a, Stamped<T, u64>, N, SLOTS> {
+    /// `publish_stamped` with a tick clock, recording the gap since the
+    /// previous timed publish in the buffer's `watermarks()`.
+    pub fn publish_timed(&mut self, clock: &impl Clock<Instant = u64>) -> bool {
+        let now = clock.now();
+        self.input_buffer().stamp = Some(now);
+        self.write_buffer.watermarks.published_at(now);
+        self.publish()
+    }
+
+    pub fn write_timed(&mut self, frame: T, clock: &impl Clock<Instant = u64>) {
+        self.input_buffer().frame = frame;
+        self.publish_timed(clock);
+    }
+}
+
+#[cfg(feature = "watermarks")]
+impl<'a, T, N: Notifier, const SLOTS: usize> BufferReader<'a, Stamped<T, u64>, N, SLOTS> {
+    /// `read` with a tick clock, recording how old the returned frame is in
+    /// the buffer's `watermarks()`. Initial frames have no stamp and don't
+    /// count.
+    pub fn read_timed(&mut self, clock: &impl Clock<Instant = u64>) -> &Stamped<T, u64> {
+        self.update();
+        if let Some(stamp) = self.output_buffer().stamp {
+            self.read_buffer.watermarks.read_at(clock.now(), stamp);
+        }
+        self.output_buffer()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SpinNotifier, TripleBuffer};
+
+    use crate::atomic::{AtomicU64, Ordering};
+
+    /// Ticks once per `now()`.
+    struct ScriptedClock(AtomicU64);
+
+    impl Clock for ScriptedClock {
+        type Instant = u64;
+
+        fn now(&self) -> u64 {
+            self.0.fetch_add(1, Ordering::Relaxed) + 1
+        }
+    }
+
+    #[test]
+    fn stamps_follow_their_frames() {
+        let buffer = TripleBuffer::new(|| Stamped::new('-'));
+        let clock = ScriptedClock(AtomicU64::new(0));
+        let mut writer = buffer.get_writer();
+        let mut reader = buffer.get_reader();
+
+        assert_eq!(reader.timestamp(), None);
+        assert_eq!(reader.age(&clock), None);
+        writer.write_stamped('a', &clock);
+        writer.write_stamped('b', &clock);
+        assert_eq!(reader.read().frame, 'b');
+        assert_eq!(reader.timestamp(), Some(2));
+        assert_eq!(reader.age(&clock), Some(1));
+
+        writer.write_stamped('c', &clock);
+        assert_eq!(reader.timestamp(), Some(2), "stamp moved without its frame");
+        assert_eq!(reader.read().frame, 'c');
+        assert_eq!(reader.age(&clock), Some(1));
+    }
+
+    #[test]
+    fn stamps_match_frames_under_stress() {
+        static FRAMES: TripleBuffer<Stamped<u64, u64>, SpinNotifier> = TripleBuffer::with_notifiers(
+            Stamped::new(0),
+            Stamped::new(0),
+            Stamped::new(0),
+            SpinNotifier,
+            SpinNotifier,
+        );
+        static CLOCK: ScriptedClock = ScriptedClock(AtomicU64::new(0));
+        let count = if cfg!(miri) { 200 } else { 50_000 };
+
+        let jh = std::thread::spawn(move || {
+            let mut writer = FRAMES.get_writer();
+            for i in 1..=count {
+                // The clock only ticks here, so frame `i` is stamped `i`.
+                writer.write_stamped(i, &CLOCK);
+            }
+        });
+
+        let mut reader = FRAMES.get_reader();
+        let mut last = 0;
+        while last != count {
+            let frame = reader.read().frame;
+            if frame != 0 {
+                assert_eq!(reader.timestamp(), Some(frame), "stamp of another frame");
+            }
+            last = frame;
+        }
+        jh.join().unwrap();
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn std_clock_ages_frames() {
+        let buffer = TripleBuffer::new(|| Stamped::new(()));
+        let mut writer = buffer.get_writer();
+        let mut reader = buffer.get_reader();
+
+        writer.publish_stamped(&StdClock);
+        reader.update();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        assert!(reader.age(&StdClock).unwrap() >= std::time::Duration::from_millis(5));
+    }
+}
@@ -0,0 +1,104 @@
+//! `postcard` encoding for the `postcard` feature, into and out of caller
+//! buffers, so it needs no allocator: the reader encodes the latest frame
+//! straight from the output slot, and the writer publishes decoded frames.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::{BufferReader, BufferWriter, Notifier};
+
+impl<'a, T, N: Notifier, const SLOTS: usize> BufferReader<'a, T, N, SLOTS> {
+    /// Updates and encodes the latest frame into `buf`; returns the encoded
+    /// prefix, or `SerializeBufferFull` if it doesn't fit.
+    pub fn encode_latest<'b>(&mut self, buf: &'b mut [u8]) -> Result<&'b [u8], postcard::Error>
+    where
+        T: Serialize,
+    {
+        postcard::to_slice(self.read(), buf).map(|encoded| &*encoded)
+    }
+}
+
+impl<'a, T, N: Notifier, const SLOTS: usize> BufferWriter<'a, T, N, SLOTS> {
+    /// Decodes a frame from `data` and publishes it. Nothing is published,
+    /// nor the input slot touched, if `data` doesn't decode.
+    pub fn write_decoded(&mut self, data: &[u8]) -> Result<(), postcard::Error>
+    where
+        T: DeserializeOwned,
+    {
+        self.write(postcard::from_bytes(data)?);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use crate::{NBuffer, TripleBuffer};
+
+    #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+    struct State {
+        tick: u32,
+        position: [i16; 3],
+        armed: bool,
+    }
+
+    const STATE: State = State {
+        tick: 300,
+        position: [-1, 0, 1024],
+        armed: true,
+    };
+
+    #[test]
+    fn states_round_trip() {
+        let sender = TripleBuffer::new(State::default);
+        let receiver = NBuffer::<State, 2>::new(State::default);
+        let (mut source, mut reader) = (sender.get_writer(), sender.get_reader());
+        let (mut writer, mut sink) = (receiver.get_writer(), receiver.get_reader());
+
+        source.write(STATE);
+        let mut uart = [0; 32];
+        let encoded = reader.encode_latest(&mut uart).unwrap();
+        assert_eq!(encoded, postcard::to_slice(&STATE, &mut [0; 32]).unwrap());
+        writer.write_decoded(encoded).unwrap();
+        assert_eq!(*sink.read(), STATE);
+    }
+
+    #[test]
+    fn encoding_into_a_short_buffer_fails() {
+        let buffer = TripleBuffer::new(State::default);
+        let mut writer = buffer.get_writer();
+        let mut reader = buffer.get_reader();
+        writer.write(STATE);
+
+        assert_eq!(
+            reader.encode_latest(&mut [0; 4]),
+            Err(postcard::Error::SerializeBufferFull)
+        );
+        // The frame was still taken.
+        assert!(!reader.updated());
+        assert!(reader.encode_latest(&mut [0; 16]).is_ok());
+    }
+
+    #[test]
+    fn malformed_input_is_not_published() {
+        let buffer = TripleBuffer::new(State::default);
+        let mut writer = buffer.get_writer();
+        let mut reader = buffer.get_reader();
+        let mut encoded = [0; 32];
+        let len = postcard::to_slice(&STATE, &mut encoded).unwrap().len();
+
+        assert_eq!(
+            writer.write_decoded(&encoded[..len - 1]),
+            Err(postcard::Error::DeserializeUnexpectedEnd)
+        );
+        // `armed` must be 0 or 1.
+        encoded[len - 1] = 2;
+        assert_eq!(
+            writer.write_decoded(&encoded[..len]),
+            Err(postcard::Error::DeserializeBadBool)
+        );
+        assert!(!reader.updated());
+        assert_eq!(*reader.read(), State::default());
+    }
+}
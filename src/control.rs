@@ -0,0 +1,254 @@
+//! The back-slot state machine, free of atomics: `NBuffer` applies these
+//! transitions with a swap or CAS, `UnsyncTripleBuffer` with plain `Cell`
+//! writes, and the Kani proofs step through them directly, so none of them
+//! can drift from the others.
+//!
+//! By default the word holds only the back slot: the input and output slots
+//! live in the handles, as only one side ever uses each, and `publish` is a
+//! single swap. Under `packed-control` it widens to a `u32` holding all
+//! three, a byte each, so the word alone is the whole state: `publish`
+//! becomes a CAS loop, as it has to keep the reader's byte. The low byte is
+//! the same either way.
+
+/// The integer a [`ControlWord`] is stored in.
+#[cfg(not(feature = "packed-control"))]
+pub(crate) type Word = u8;
+#[cfg(feature = "packed-control")]
+pub(crate) type Word = u32;
+
+pub(crate) const BACK_INDEX_MASK: u8 = 0x7f;
+pub(crate) const BACK_DIRTY_BIT: u8 = 0x80;
+// Where `packed-control` keeps the input and output slots.
+#[cfg(feature = "packed-control")]
+const INPUT_SHIFT: u32 = 8;
+#[cfg(feature = "packed-control")]
+const OUTPUT_SHIFT: u32 = 16;
+// Marks the two-slot writer as holding no slot.
+pub(crate) const NO_SLOT: u8 = BACK_INDEX_MASK;
+pub(crate) const MAX_SLOTS: usize = NO_SLOT as usize;
+
+#[cfg(all(feature = "packed-control", feature = "redundant-control"))]
+compile_error!(
+    "`redundant-control` checks the separate index words that `packed-control` folds into \
+     the control word; enable only one of them"
+);
+
+pub(crate) const fn is_dirty(back_info: Word) -> bool {
+    back_info & BACK_DIRTY_BIT as Word != 0
+}
+
+/// The back info after publishing `input_idx`.
+pub(crate) const fn published(input_idx: u8) -> u8 {
+    input_idx | BACK_DIRTY_BIT
+}
+
+/// The back info: the back slot's index, plus the dirty bit while it holds
+/// a frame the reader hasn't taken. Under `packed-control`, also the input
+/// and output slots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ControlWord(pub(crate) Word);
+
+impl ControlWord {
+    /// A word with the given slots; `back_info` may carry the dirty bit.
+    /// Without `packed-control` only `back_info` is kept.
+    #[allow(unused_variables)]
+    pub(crate) const fn new(back_info: u8, input: u8, output: u8) -> Self {
+        #[cfg(not(feature = "packed-control"))]
+        {
+            Self(back_info)
+        }
+        #[cfg(feature = "packed-control")]
+        {
+            Self(
+                back_info as Word
+                    | (input as Word) << INPUT_SHIFT
+                    | (output as Word) << OUTPUT_SHIFT,
+            )
+        }
+    }
+
+    /// The low byte: the back slot and the dirty bit.
+    pub(crate) const fn back_info(self) -> u8 {
+        #[cfg(not(feature = "packed-control"))]
+        {
+            self.0
+        }
+        #[cfg(feature = "packed-control")]
+        {
+            self.0 as u8
+        }
+    }
+
+    pub(crate) const fn back(self) -> u8 {
+        self.back_info() & BACK_INDEX_MASK
+    }
+
+    #[cfg(feature = "packed-control")]
+    pub(crate) const fn input(self) -> u8 {
+        (self.0 >> INPUT_SHIFT) as u8 & BACK_INDEX_MASK
+    }
+
+    #[cfg(feature = "packed-control")]
+    pub(crate) const fn output(self) -> u8 {
+        (self.0 >> OUTPUT_SHIFT) as u8 & BACK_INDEX_MASK
+    }
+
+    /// The word with `input` as the input slot, for a writer parking one
+    /// the spare ring gave it instead of the one `publish` left here.
+    #[cfg(feature = "packed-control")]
+    pub(crate) const fn with_input(self, input: u8) -> Self {
+        Self::new(self.back_info(), input, self.output())
+    }
+
+    /// The slot `update` takes, if the back slot holds an unread frame.
+    pub(crate) const fn taken(self) -> Option<u8> {
+        if is_dirty(self.0) {
+            Some(self.back())
+        } else {
+            None
+        }
+    }
+}
+
+/// `publish` of `input_idx` over `ctrl`: the new control word, the slot that
+/// left the back slot, and whether it held an unread frame. Without
+/// `packed-control` the new word doesn't depend on the old one, so a swap
+/// can apply it; with it, the freed slot becomes the input slot, which past
+/// three slots the writer swaps for a spare and only stores on `park`.
+pub(crate) const fn publish_transition(
+    ctrl: ControlWord,
+    input_idx: u8,
+) -> (ControlWord, u8, bool) {
+    #[cfg(not(feature = "packed-control"))]
+    let next = ControlWord(published(input_idx));
+    #[cfg(feature = "packed-control")]
+    let next = ControlWord::new(published(input_idx), ctrl.back(), ctrl.output());
+    (next, ctrl.back(), is_dirty(ctrl.0))
+}
+
+/// `update` handing back `output_idx`: the new control word and output
+/// slot, or `None` if there is nothing to take.
+pub(crate) const fn update_transition(
+    ctrl: ControlWord,
+    output_idx: u8,
+) -> Option<(ControlWord, u8)> {
+    match ctrl.taken() {
+        #[cfg(not(feature = "packed-control"))]
+        Some(taken) => Some((ControlWord(output_idx), taken)),
+        #[cfg(feature = "packed-control")]
+        Some(taken) => Some((ControlWord::new(output_idx, ctrl.input(), taken), taken)),
+        None => None,
+    }
+}
+
+/// A two-slot writer taking the back slot to write into, frame or not: the
+/// new control word, the writer's new input slot, and whether it took back
+/// an unread frame.
+pub(crate) const fn retract_transition(ctrl: ControlWord) -> (ControlWord, u8, bool) {
+    #[cfg(not(feature = "packed-control"))]
+    let next = ControlWord(NO_SLOT);
+    #[cfg(feature = "packed-control")]
+    let next = ControlWord::new(NO_SLOT, ctrl.back(), ctrl.output());
+    (next, ctrl.back(), is_dirty(ctrl.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words() -> impl Iterator<Item = ControlWord> {
+        (0..=u8::MAX).map(|back_info| ControlWord::new(back_info, 1, 2))
+    }
+
+    #[test]
+    fn publish_over_every_word() {
+        for ctrl in words() {
+            for input_idx in 0..=NO_SLOT {
+                let (next, freed, overwrote) = publish_transition(ctrl, input_idx);
+                assert_eq!(next.taken(), Some(input_idx), "{ctrl:?} {input_idx}");
+                assert_eq!(freed, ctrl.back(), "{ctrl:?} {input_idx}");
+                assert_eq!(overwrote, ctrl.taken().is_some(), "{ctrl:?} {input_idx}");
+                #[cfg(feature = "packed-control")]
+                assert_eq!(
+                    (next.input(), next.output()),
+                    (freed, ctrl.output()),
+                    "{ctrl:?} {input_idx}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn update_over_every_word() {
+        for ctrl in words() {
+            for output_idx in 0..=NO_SLOT {
+                match update_transition(ctrl, output_idx) {
+                    Some((next, taken)) => {
+                        assert_eq!(next.back(), output_idx, "{ctrl:?} {output_idx}");
+                        assert_eq!(next.taken(), None, "{ctrl:?} {output_idx}");
+                        assert_eq!(Some(taken), ctrl.taken(), "{ctrl:?} {output_idx}");
+                        #[cfg(feature = "packed-control")]
+                        assert_eq!(
+                            (next.input(), next.output()),
+                            (ctrl.input(), taken),
+                            "{ctrl:?} {output_idx}"
+                        );
+                    }
+                    None => assert!(!is_dirty(ctrl.0), "{ctrl:?} {output_idx}"),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn retract_over_every_word() {
+        for ctrl in words() {
+            let (next, input_idx, retracted) = retract_transition(ctrl);
+            assert_eq!(next.back(), NO_SLOT, "{ctrl:?}");
+            assert_eq!(next.taken(), None, "{ctrl:?}");
+            assert_eq!(input_idx, ctrl.back(), "{ctrl:?}");
+            assert_eq!(retracted, ctrl.taken().is_some(), "{ctrl:?}");
+            #[cfg(feature = "packed-control")]
+            assert_eq!((next.input(), next.output()), (input_idx, ctrl.output()));
+        }
+    }
+
+    /// Every three-slot state the handles can be in, with each slot held by
+    /// exactly one of the writer, the back and the reader.
+    fn rotations() -> impl Iterator<Item = (ControlWord, u8, u8)> {
+        (0..3u8).flat_map(|back| {
+            (0..3u8)
+                .filter(move |&input| input != back)
+                .flat_map(move |input| {
+                    let output = 3 - back - input;
+                    [back, published(back)].map(|back_info| {
+                        (ControlWord::new(back_info, input, output), input, output)
+                    })
+                })
+        })
+    }
+
+    fn assert_permutation(ctrl: ControlWord, input: u8, output: u8) {
+        let mut slots = [ctrl.back(), input, output];
+        slots.sort();
+        assert_eq!(slots, [0, 1, 2], "{ctrl:?} {input} {output}");
+        #[cfg(feature = "packed-control")]
+        assert_eq!((ctrl.input(), ctrl.output()), (input, output), "{ctrl:?}");
+    }
+
+    #[test]
+    fn rotations_keep_one_owner_per_slot() {
+        for (ctrl, input, output) in rotations() {
+            let (published, input_after, _) = publish_transition(ctrl, input);
+            assert_permutation(published, input_after, output);
+
+            match update_transition(ctrl, output) {
+                Some((updated, output_after)) => {
+                    assert_permutation(updated, input, output_after);
+                    assert_eq!(updated.taken(), None);
+                }
+                None => assert_eq!(ctrl.taken(), None),
+            }
+        }
+    }
+}
@@ -0,0 +1,234 @@
+//! CRC-32 protection of published frames, for the `crc` feature: once a
+//! buffer has a checksum function, `publish` stores each frame's CRC next to
+//! its slot, and `read_verified` recomputes it, catching a frame corrupted
+//! after its publish, e.g. in external RAM.
+
+use core::fmt;
+use core::marker::PhantomData;
+use core::ptr;
+
+use crate::atomic::{AtomicBool, AtomicPtr, AtomicU32};
+use crate::{ord, per_slot, BufferReader, BufferWriter, NBuffer, Notifier};
+
+/// The frame in the output slot doesn't match the CRC published with it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct CrcMismatch {
+    pub expected: u32,
+    pub actual: u32,
+}
+
+impl fmt::Display for CrcMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "frame CRC {:#010x}, published with {:#010x}",
+            self.actual, self.expected
+        )
+    }
+}
+
+impl core::error::Error for CrcMismatch {}
+
+const TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// The CRC-32 of `bytes`, as in zlib and Ethernet.
+pub fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0, |crc, &byte| {
+        (crc >> 8) ^ TABLE[((crc ^ byte as u32) & 0xff) as usize]
+    })
+}
+
+/// The CRC-32 of a `Pod` frame's bytes, for `NBuffer::set_crc`.
+#[cfg(feature = "bytemuck")]
+pub fn crc32_pod<T: crate::PodFrame>(frame: &T) -> u32 {
+    crc32(bytemuck::bytes_of(frame.pod()))
+}
+
+/// The checksum function, stored like the hooks, and the CRC of the frame
+/// in each slot, if it was published with one.
+pub(crate) struct Crcs<T, const SLOTS: usize> {
+    checksum: AtomicPtr<()>,
+    crcs: [AtomicU32; SLOTS],
+    checked: [AtomicBool; SLOTS],
+    next_unchecked: AtomicBool,
+    _frame: PhantomData<fn(&T) -> u32>,
+}
+
+impl<T, const SLOTS: usize> Crcs<T, SLOTS> {
+    pub(crate) const fn new() -> Self {
+        Self {
+            checksum: AtomicPtr::new(ptr::null_mut()),
+            crcs: [const { AtomicU32::new(0) }; SLOTS],
+            checked: [const { AtomicBool::new(false) }; SLOTS],
+            next_unchecked: AtomicBool::new(false),
+            _frame: PhantomData,
+        }
+    }
+
+    fn checksum(&self) -> Option<fn(&T) -> u32> {
+        let checksum = self.checksum.load(ord::acquire());
+        // Only ever set from a `fn(&T) -> u32` in `set_crc`.
+        (!checksum.is_null())
+            .then(|| unsafe { core::mem::transmute::<*mut (), fn(&T) -> u32>(checksum) })
+    }
+
+    /// Stores the CRC of the frame about to be published from `idx`, unless
+    /// there is no checksum or the publish is unchecked.
+    #[inline]
+    pub(crate) fn stamp(&self, idx: u8, frame: *const T) {
+        let checksum = self
+            .checksum()
+            .filter(|_| !self.next_unchecked.swap(false, ord::relaxed()));
+        if let Some(checksum) = checksum {
+            per_slot(&self.crcs, idx).store(checksum(unsafe { &*frame }), ord::relaxed());
+        }
+        per_slot(&self.checked, idx).store(checksum.is_some(), ord::relaxed());
+    }
+}
+
+impl<T, const SLOTS: usize, N: Notifier> NBuffer<T, SLOTS, N> {
+    /// Has every later `publish` store the CRC `checksum` computes over the
+    /// frame, e.g. `crc32_pod`; `None` stops it. Frames already published
+    /// keep theirs.
+    pub fn set_crc(&self, checksum: Option<fn(&T) -> u32>) {
+        let checksum = checksum.map_or(ptr::null_mut(), |checksum| checksum as *mut ());
+        self.crcs.checksum.store(checksum, ord::release());
+    }
+}
+
+impl<'a, T, N: Notifier, const SLOTS: usize> BufferWriter<'a, T, N, SLOTS> {
+    /// Like `publish`, but skips the CRC; `read_verified` then passes the
+    /// frame unchecked.
+    pub fn publish_unchecked(&self) -> bool {
+        self.write_buffer
+            .crcs
+            .next_unchecked
+            .store(true, ord::relaxed());
+        self.publish()
+    }
+}
+
+impl<'a, T, N: Notifier, const SLOTS: usize> BufferReader<'a, T, N, SLOTS> {
+    /// Updates and recomputes the CRC of the latest frame, new or not.
+    /// Frames published without one, unchecked or before `set_crc`, pass.
+    pub fn read_verified(&mut self) -> Result<&T, CrcMismatch> {
+        self.update();
+        let output_idx = self.output();
+        let crcs = &self.read_buffer.crcs;
+        let checksum = crcs
+            .checksum()
+            .filter(|_| per_slot(&crcs.checked, output_idx).load(ord::relaxed()));
+        let frame = self.output_buffer();
+        if let Some(checksum) = checksum {
+            let expected = per_slot(&crcs.crcs, output_idx).load(ord::relaxed());
+            let actual = checksum(frame);
+            if actual != expected {
+                return Err(CrcMismatch { expected, actual });
+            }
+        }
+        Ok(frame)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::ControlWord;
+    use crate::TripleBuffer;
+
+    fn sum(frame: &[u8; 4]) -> u32 {
+        crc32(frame)
+    }
+
+    #[test]
+    fn crc32_matches_the_standard_check_value() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+    }
+
+    #[test]
+    fn corruption_after_publish_is_caught() {
+        let buffer = TripleBuffer::new(|| [0u8; 4]);
+        buffer.set_crc(Some(sum));
+        let mut writer = buffer.get_writer();
+        let mut reader = buffer.get_reader();
+
+        writer.write([1, 2, 3, 4]);
+        // Flip a bit in the published slot, behind both handles' backs.
+        let published = ControlWord(buffer.back_info.load(ord::relaxed())).back();
+        unsafe { (*buffer.slot(published))[2] ^= 0x10 };
+        assert_eq!(
+            reader.read_verified(),
+            Err(CrcMismatch {
+                expected: crc32(&[1, 2, 3, 4]),
+                actual: crc32(&[1, 2, 0x13, 4]),
+            })
+        );
+        // The frame was still taken, and stays corrupt until replaced.
+        assert!(!reader.updated());
+        assert!(reader.read_verified().is_err());
+
+        writer.write([5, 6, 7, 8]);
+        assert_eq!(reader.read_verified(), Ok(&[5, 6, 7, 8]));
+    }
+
+    #[test]
+    fn only_checked_publishes_are_verified() {
+        let buffer = TripleBuffer::new(|| [0u8; 4]);
+        let mut writer = buffer.get_writer();
+        let mut reader = buffer.get_reader();
+
+        // No checksum yet.
+        writer.write([1; 4]);
+        assert_eq!(reader.read_verified(), Ok(&[1; 4]));
+
+        buffer.set_crc(Some(sum));
+        *writer.input_buffer() = [2; 4];
+        writer.publish_unchecked();
+        assert!(reader.update());
+        reader.output_buffer()[0] = 9;
+        assert_eq!(reader.read_verified(), Ok(&[9, 2, 2, 2]));
+
+        // The next plain publish is checked again.
+        writer.write([3; 4]);
+        assert!(reader.update());
+        reader.output_buffer()[0] = 9;
+        assert!(reader.read_verified().is_err());
+    }
+
+    #[cfg(feature = "bytemuck")]
+    #[test]
+    fn pod_frames_checksum_their_bytes() {
+        let buffer = TripleBuffer::new(|| [0u32; 2]);
+        buffer.set_crc(Some(crc32_pod));
+        let mut writer = buffer.get_writer();
+        let mut reader = buffer.get_reader();
+
+        writer.write([0x0403_0201, 0x0807_0605]);
+        assert!(reader.read_verified().is_ok());
+        let pod = crc32_pod(&[0x0403_0201u32, 0x0807_0605]);
+        assert_eq!(
+            pod,
+            crc32(bytemuck::bytes_of(&[0x0403_0201u32, 0x0807_0605]))
+        );
+    }
+}
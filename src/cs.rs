@@ -0,0 +1,125 @@
+use core::cell::Cell;
+use critical_section::Mutex;
+
+use crate::{Backoff, Notifier};
+
+/// Sets a pending flag inside `critical_section::with` and runs an optional
+/// callback there, e.g. to clear the low-power bits an ISR returns into.
+///
+/// Meant for single-core targets whose foreground loop polls `take_pending`
+/// between sleeps. `wait` itself can only poll with a `Backoff`, as there is
+/// no portable sleep primitive to block on.
+pub struct CsNotifier {
+    pending: Mutex<Cell<bool>>,
+    callback: Option<fn()>,
+}
+
+impl CsNotifier {
+    pub const fn new() -> Self {
+        Self {
+            pending: Mutex::new(Cell::new(false)),
+            callback: None,
+        }
+    }
+
+    /// `callback` runs on every notification, inside the critical section.
+    pub const fn with_callback(callback: fn()) -> Self {
+        Self {
+            pending: Mutex::new(Cell::new(false)),
+            callback: Some(callback),
+        }
+    }
+
+    /// Whether a notification arrived since the last call; clears the flag.
+    pub fn take_pending(&self) -> bool {
+        critical_section::with(|cs| self.pending.borrow(cs).replace(false))
+    }
+}
+
+impl Default for CsNotifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Notifier for CsNotifier {
+    #[inline]
+    fn notify(&self) {
+        critical_section::with(|cs| {
+            self.pending.borrow(cs).set(true);
+            if let Some(callback) = self.callback {
+                callback();
+            }
+        });
+    }
+
+    fn wait(&self, until: impl Fn() -> bool) {
+        let mut backoff = Backoff::new();
+        while !until() {
+            if self.take_pending() {
+                backoff.reset();
+            } else {
+                backoff.snooze();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TripleBuffer;
+
+    use crate::atomic::{AtomicU32, Ordering};
+
+    #[test]
+    fn notify_sets_pending_and_runs_callback() {
+        static CALLS: AtomicU32 = AtomicU32::new(0);
+        fn count() {
+            CALLS.fetch_add(1, Ordering::Relaxed);
+        }
+
+        let buffer = TripleBuffer::with_notifiers(
+            0,
+            0,
+            0,
+            CsNotifier::with_callback(count),
+            CsNotifier::new(),
+        );
+        let mut writer = buffer.get_writer();
+        let mut reader = buffer.get_reader();
+        assert!(!buffer.reader_notifier.take_pending());
+
+        writer.write(1);
+        writer.write(2);
+        assert_eq!(CALLS.load(Ordering::Relaxed), 2);
+        assert!(buffer.reader_notifier.take_pending());
+        assert!(!buffer.reader_notifier.take_pending());
+
+        assert_eq!(*reader.read(), 2);
+        assert!(buffer.writer_notifier.take_pending());
+    }
+
+    #[test]
+    fn foreground_loop_receives_frames_from_isr() {
+        static BUFFER: TripleBuffer<u32, CsNotifier> =
+            TripleBuffer::with_notifiers(0, 0, 0, CsNotifier::new(), CsNotifier::new());
+        let isr = std::thread::spawn(|| {
+            let mut writer = BUFFER.get_writer();
+            for i in 1..=100 {
+                writer.write_blocking(i);
+            }
+        });
+
+        let mut reader = BUFFER.get_reader();
+        let mut last = 0;
+        while last != 100 {
+            if BUFFER.reader_notifier.take_pending() && reader.update() {
+                assert_eq!(*reader.output_buffer(), last + 1);
+                last += 1;
+            }
+            std::thread::yield_now();
+        }
+        isr.join().unwrap();
+    }
+}
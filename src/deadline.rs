@@ -0,0 +1,187 @@
+use crate::{is_dirty, ord, BufferReader, BufferWriter, Notifier};
+
+/// A point in time after which a timed blocking call gives up.
+///
+/// Implement it over whatever monotonic clock the platform has, e.g. an
+/// RTIC monotonic or a tick counter incremented from SysTick.
+pub trait Deadline {
+    fn expired(&self) -> bool;
+
+    /// How long, in microseconds, a waiter may sleep before checking
+    /// `expired` again. `None` means the notifier's own wakeups suffice,
+    /// e.g. because the clock's tick interrupt ends a `WFE` anyway.
+    fn wait_hint(&self) -> Option<u32>;
+}
+
+impl<D: Deadline + ?Sized> Deadline for &D {
+    fn expired(&self) -> bool {
+        (**self).expired()
+    }
+
+    fn wait_hint(&self) -> Option<u32> {
+        (**self).wait_hint()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Deadline for std::time::Instant {
+    fn expired(&self) -> bool {
+        std::time::Instant::now() >= *self
+    }
+
+    fn wait_hint(&self) -> Option<u32> {
+        let remaining = self.saturating_duration_since(std::time::Instant::now());
+        Some(u32::try_from(remaining.as_micros()).unwrap_or(u32::MAX))
+    }
+}
+
+fn wait_until<N: Notifier>(notifier: &N, ready: impl Fn() -> bool, deadline: &impl Deadline) {
+    let done = || ready() || deadline.expired();
+    while !done() {
+        match deadline.wait_hint() {
+            Some(timeout_us) => notifier.wait_timeout(done, timeout_us),
+            None => notifier.wait(done),
+        }
+    }
+}
+
+impl<'a, T, N: Notifier, const SLOTS: usize> BufferReader<'a, T, N, SLOTS> {
+    /// Like `read_blocking`, but returns `None` once `deadline` expired
+    /// without a new frame.
+    pub fn read_blocking_until(&mut self, deadline: impl Deadline) -> Option<&T> {
+        let buffer = self.read_buffer;
+        let published = || is_dirty(buffer.back_info.load(ord::acquire()));
+        wait_until(&buffer.reader_notifier, published, &deadline);
+        if published() {
+            Some(self.read())
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "std")]
+    pub fn read_timeout(&mut self, timeout: std::time::Duration) -> Option<&T> {
+        self.read_blocking_until(std::time::Instant::now() + timeout)
+    }
+}
+
+impl<'a, T, N: Notifier, const SLOTS: usize> BufferWriter<'a, T, N, SLOTS> {
+    /// Like `write_blocking`, but gives `value` back once `deadline` expired
+    /// before the previous frame was consumed.
+    pub fn write_blocking_until(&mut self, value: T, deadline: impl Deadline) -> Result<(), T> {
+        let buffer = self.write_buffer;
+        let consumed = || !is_dirty(buffer.back_info.load(ord::acquire()));
+        wait_until(&buffer.writer_notifier, consumed, &deadline);
+        if consumed() {
+            self.write(value);
+            Ok(())
+        } else {
+            Err(value)
+        }
+    }
+
+    #[cfg(feature = "std")]
+    pub fn write_timeout(&mut self, value: T, timeout: std::time::Duration) -> Result<(), T> {
+        self.write_blocking_until(value, std::time::Instant::now() + timeout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SpinNotifier, TripleBuffer};
+    #[cfg(feature = "std")]
+    use std::time::Duration;
+
+    use crate::atomic::Ordering;
+
+    /// Expires after `budget` calls to `expired`, like a counter bumped by a
+    /// tick interrupt between polls.
+    struct TickDeadline {
+        ticks: crate::atomic::AtomicU32,
+        budget: u32,
+    }
+
+    impl TickDeadline {
+        fn new(budget: u32) -> Self {
+            Self {
+                ticks: crate::atomic::AtomicU32::new(0),
+                budget,
+            }
+        }
+    }
+
+    impl Deadline for TickDeadline {
+        fn expired(&self) -> bool {
+            self.ticks.fetch_add(1, Ordering::Relaxed) >= self.budget
+        }
+
+        fn wait_hint(&self) -> Option<u32> {
+            None
+        }
+    }
+
+    #[test]
+    fn tick_deadline_expires() {
+        let buffer =
+            TripleBuffer::with_notifiers(0, 0, 0, SpinNotifier::new(), SpinNotifier::new());
+        let mut reader = buffer.get_reader();
+        let mut writer = buffer.get_writer();
+
+        let ticks = TickDeadline::new(50);
+        assert_eq!(reader.read_blocking_until(&ticks), None);
+        assert!(ticks.ticks.load(Ordering::Relaxed) > 50);
+
+        writer.write(1);
+        assert_eq!(reader.read_blocking_until(TickDeadline::new(0)), Some(&1));
+        assert_eq!(writer.write_blocking_until(2, TickDeadline::new(0)), Ok(()));
+        assert_eq!(writer.write_blocking_until(3, TickDeadline::new(50)), Err(3));
+        reader.update();
+        assert_eq!(writer.write_blocking_until(3, TickDeadline::new(0)), Ok(()));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn instant_deadline_matches_tick_deadline() {
+        use std::time::Instant;
+
+        let buffer =
+            TripleBuffer::with_notifiers(0, 0, 0, SpinNotifier::new(), SpinNotifier::new());
+        let mut reader = buffer.get_reader();
+        let mut writer = buffer.get_writer();
+
+        assert_eq!(reader.read_blocking_until(Instant::now()), None);
+        writer.write(1);
+        assert_eq!(reader.read_blocking_until(Instant::now()), Some(&1));
+        assert_eq!(writer.write_blocking_until(2, Instant::now()), Ok(()));
+        assert_eq!(writer.write_blocking_until(3, Instant::now()), Err(3));
+        reader.update();
+        assert_eq!(writer.write_blocking_until(3, Instant::now()), Ok(()));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn read_timeout_expires_without_publish() {
+        let buffer = TripleBuffer::new(|| 0);
+        let mut reader = buffer.get_reader();
+        let _writer = buffer.get_writer();
+
+        let start = std::time::Instant::now();
+        assert_eq!(reader.read_timeout(Duration::from_millis(50)), None);
+        assert!(start.elapsed() >= Duration::from_millis(50));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn read_timeout_returns_frame_published_while_waiting() {
+        static BUFFER: TripleBuffer<u32> = TripleBuffer::<u32>::new_const(0, 0, 0);
+        let mut reader = BUFFER.get_reader();
+        let jh = std::thread::spawn(|| {
+            std::thread::sleep(Duration::from_millis(20));
+            BUFFER.get_writer().write(1);
+        });
+
+        assert_eq!(reader.read_timeout(Duration::from_secs(5)), Some(&1));
+        jh.join().unwrap();
+    }
+}
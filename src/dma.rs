@@ -0,0 +1,159 @@
+//! `embedded-dma` buffers over the handles' slots, for the `embedded-dma`
+//! feature: a HAL transfer can fill the input slot or send the output
+//! slot, byte for byte, and the guard keeps its handle borrowed so nothing
+//! publishes or updates while the DMA owns the memory.
+//!
+//! The guards are only `'static` when the handle is borrowed for
+//! `'static`, e.g. a writer from a static buffer kept in a
+//! `cortex_m::singleton!`. HALs that require `'static` buffers, so that a
+//! forgotten transfer can't leave the DMA running over freed memory, won't
+//! take them otherwise.
+
+use embedded_dma::{ReadBuffer, WriteBuffer};
+
+use crate::{BufferReader, BufferWriter, Notifier, PodFrame};
+
+/// The writer's input slot as a DMA write target, from
+/// `BufferWriter::dma_write_slot`. Dropping it without `complete` leaves
+/// whatever the DMA wrote unpublished.
+pub struct DmaWriteSlot<'w, 'a, T: PodFrame, N: Notifier, const SLOTS: usize = 3> {
+    writer: &'w mut BufferWriter<'a, T, N, SLOTS>,
+    slot: *mut T::Pod,
+}
+
+/// The reader's output slot as a DMA read source, from
+/// `BufferReader::dma_read_slot`.
+pub struct DmaReadSlot<'r, 'a, T: PodFrame, N: Notifier, const SLOTS: usize = 3> {
+    reader: &'r mut BufferReader<'a, T, N, SLOTS>,
+    slot: *const T::Pod,
+    fresh: bool,
+}
+
+impl<'a, T: PodFrame, N: Notifier, const SLOTS: usize> BufferWriter<'a, T, N, SLOTS> {
+    /// Lends the input slot to a DMA transfer; `complete` publishes it.
+    pub fn dma_write_slot(&mut self) -> DmaWriteSlot<'_, 'a, T, N, SLOTS> {
+        let slot = self.input_buffer().pod_mut() as *mut T::Pod;
+        DmaWriteSlot { writer: self, slot }
+    }
+}
+
+impl<'a, T: PodFrame, N: Notifier, const SLOTS: usize> BufferReader<'a, T, N, SLOTS> {
+    /// Updates, then lends the latest frame to a DMA transfer.
+    pub fn dma_read_slot(&mut self) -> DmaReadSlot<'_, 'a, T, N, SLOTS> {
+        let fresh = self.update();
+        let slot = self.output_buffer().pod() as *const T::Pod;
+        DmaReadSlot {
+            reader: self,
+            slot,
+            fresh,
+        }
+    }
+}
+
+impl<'w, 'a, T: PodFrame, N: Notifier, const SLOTS: usize> DmaWriteSlot<'w, 'a, T, N, SLOTS> {
+    /// Publishes the frame once the transfer is done; returns what
+    /// `publish` does.
+    pub fn complete(self) -> bool {
+        self.writer.publish()
+    }
+}
+
+impl<'r, 'a, T: PodFrame, N: Notifier, const SLOTS: usize> DmaReadSlot<'r, 'a, T, N, SLOTS> {
+    /// Whether the lent frame was new, like `update`.
+    pub fn fresh(&self) -> bool {
+        self.fresh
+    }
+
+    /// Hands the slot back once the transfer is done; returns whether a
+    /// newer frame was published meanwhile.
+    pub fn complete(self) -> bool {
+        self.reader.updated()
+    }
+}
+
+// The slot stays put while the guard lives: the writer can't publish, nor
+// the reader update, through their borrowed handles, and every bit pattern
+// is a valid `T`.
+unsafe impl<'w, 'a, T: PodFrame, N: Notifier, const SLOTS: usize> WriteBuffer
+    for DmaWriteSlot<'w, 'a, T, N, SLOTS>
+{
+    type Word = u8;
+
+    unsafe fn write_buffer(&mut self) -> (*mut u8, usize) {
+        (self.slot.cast(), core::mem::size_of::<T::Pod>())
+    }
+}
+
+unsafe impl<'r, 'a, T: PodFrame, N: Notifier, const SLOTS: usize> ReadBuffer
+    for DmaReadSlot<'r, 'a, T, N, SLOTS>
+{
+    type Word = u8;
+
+    unsafe fn read_buffer(&self) -> (*const u8, usize) {
+        (self.slot.cast(), core::mem::size_of::<T::Pod>())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_dma::{ReadBuffer, WriteBuffer};
+
+    use crate::{NBuffer, TripleBuffer};
+
+    /// A transfer as a HAL would run it: owns the buffer until it is done,
+    /// then hands it back.
+    fn dma_rx<B: WriteBuffer<Word = u8>>(mut buffer: B, data: &[u8]) -> B {
+        let (ptr, len) = unsafe { buffer.write_buffer() };
+        assert_eq!(len, data.len());
+        unsafe { core::ptr::copy_nonoverlapping(data.as_ptr(), ptr, len) };
+        buffer
+    }
+
+    fn dma_tx<B: ReadBuffer<Word = u8>>(buffer: B, out: &mut [u8]) -> B {
+        let (ptr, len) = unsafe { buffer.read_buffer() };
+        assert_eq!(len, out.len());
+        unsafe { core::ptr::copy_nonoverlapping(ptr, out.as_mut_ptr(), len) };
+        buffer
+    }
+
+    #[test]
+    fn transfers_publish_only_on_complete() {
+        let buffer = TripleBuffer::new(|| [0u16; 4]);
+        let mut writer = buffer.get_writer();
+        let mut reader = buffer.get_reader();
+        let frame = [0x0102u16, 0x0304, 0x0506, 0x0708];
+
+        let slot = dma_rx(writer.dma_write_slot(), bytemuck::bytes_of(&frame));
+        assert!(!reader.updated(), "published before complete");
+        assert!(!slot.complete());
+        assert_eq!(*reader.read(), frame);
+
+        // An abandoned transfer publishes nothing.
+        let _ = dma_rx(writer.dma_write_slot(), &[0xff; 8]);
+        assert!(!reader.updated());
+    }
+
+    #[test]
+    fn transfers_send_the_latest_frame() {
+        let buffer = NBuffer::<[u32; 2], 2>::new(Default::default);
+        let mut writer = buffer.get_writer();
+        let mut reader = buffer.get_reader();
+        let mut out = [0; 8];
+
+        writer.write([1, 2]);
+        let slot = dma_tx(reader.dma_read_slot(), &mut out);
+        assert!(slot.fresh());
+        assert!(!slot.complete());
+        assert_eq!(out, *bytemuck::bytes_of(&[1u32, 2]));
+
+        writer.write([3, 4]);
+        let slot = dma_tx(reader.dma_read_slot(), &mut out);
+        writer.write([5, 6]);
+        assert!(slot.complete(), "newer frame not reported");
+        assert_eq!(out, *bytemuck::bytes_of(&[3u32, 4]));
+
+        let slot = dma_tx(reader.dma_read_slot(), &mut out);
+        assert!(slot.fresh());
+        assert_eq!(out, *bytemuck::bytes_of(&[5u32, 6]));
+    }
+}
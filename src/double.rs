@@ -0,0 +1,327 @@
+use core::cell::UnsafeCell;
+use core::fmt;
+use core::marker::PhantomData;
+use core::ops::Deref;
+use core::panic::{RefUnwindSafe, UnwindSafe};
+
+use crate::atomic::{AtomicBool, AtomicU8, Ordering};
+use crate::{ord, DefaultNotifier, Notifier, Unshared};
+
+// Which slot is the front one, readable through a `ReadGuard`.
+const FRONT_BIT: u8 = 0b001;
+// The front slot holds a frame no guard has been taken on yet.
+const DIRTY_BIT: u8 = 0b010;
+// A `ReadGuard` holds the front slot.
+const READING_BIT: u8 = 0b100;
+
+/// Two slots and a writer that waits: `publish` swaps the back slot to the
+/// front only once the reader took the previous frame and dropped its
+/// `ReadGuard`. Saves the third copy of a large frame at the cost of the
+/// writer running in lock step with the reader.
+pub struct DoubleBuffer<T, N = DefaultNotifier> {
+    buffers: [UnsafeCell<T>; 2],
+    state: AtomicU8,
+
+    is_reader_exist: AtomicBool,
+    is_writer_exist: AtomicBool,
+
+    reader_notifier: N,
+    writer_notifier: N,
+}
+
+/// `try_publish` found the previous frame unread or still held.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct WouldBlock;
+
+impl fmt::Display for WouldBlock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "previous frame not released by the reader")
+    }
+}
+
+impl core::error::Error for WouldBlock {}
+
+unsafe impl<T: Send, N: Sync> Sync for DoubleBuffer<T, N> {}
+
+// The handles move between threads as `NBuffer`'s do, and likewise are
+// never `Sync`. A guard isn't `Send`, so it is released on the thread that
+// took it; sharing one only hands out `&T`.
+unsafe impl<'a, T: Send, N: Notifier + Sync> Send for DoubleReader<'a, T, N> {}
+unsafe impl<'a, T: Send, N: Notifier + Sync> Send for DoubleWriter<'a, T, N> {}
+unsafe impl<'r, 'a, T: Sync, N: Notifier> Sync for ReadGuard<'r, 'a, T, N> {}
+
+// As for `NBuffer`: a panic never leaves `state` half updated, and a guard
+// dropped by the unwind releases the front slot.
+impl<T: RefUnwindSafe, N: RefUnwindSafe> RefUnwindSafe for DoubleBuffer<T, N> {}
+impl<T: UnwindSafe, N: UnwindSafe> UnwindSafe for DoubleBuffer<T, N> {}
+
+impl<T> DoubleBuffer<T> {
+    pub fn new(generator: impl Fn() -> T) -> Self {
+        Self::new_const(generator(), generator())
+    }
+
+    pub const fn new_const(s1: T, s2: T) -> Self {
+        Self::with_notifiers(s1, s2, DefaultNotifier::new(), DefaultNotifier::new())
+    }
+}
+
+impl<T, N: Notifier> DoubleBuffer<T, N> {
+    pub const fn with_notifiers(s1: T, s2: T, reader_notifier: N, writer_notifier: N) -> Self {
+        Self {
+            buffers: [UnsafeCell::new(s1), UnsafeCell::new(s2)],
+            state: AtomicU8::new(0),
+
+            is_reader_exist: AtomicBool::new(false),
+            is_writer_exist: AtomicBool::new(false),
+
+            reader_notifier,
+            writer_notifier,
+        }
+    }
+
+    pub fn get_reader(&self) -> DoubleReader<'_, T, N> {
+        if self.is_reader_exist.swap(true, ord::acquire()) {
+            panic!("Reader already exists");
+        }
+        DoubleReader {
+            buffer: self,
+            _unshared: PhantomData,
+        }
+    }
+
+    pub fn get_writer(&self) -> DoubleWriter<'_, T, N> {
+        if self.is_writer_exist.swap(true, ord::acquire()) {
+            panic!("Writer already exists");
+        }
+        DoubleWriter {
+            buffer: self,
+            _unshared: PhantomData,
+        }
+    }
+
+    fn slot(&self, state: u8) -> *mut T {
+        self.buffers[(state & FRONT_BIT) as usize].get()
+    }
+}
+
+pub struct DoubleReader<'a, T, N: Notifier = DefaultNotifier> {
+    buffer: &'a DoubleBuffer<T, N>,
+    _unshared: Unshared,
+}
+
+/// Holds the front slot; the writer can't publish until it is dropped.
+pub struct ReadGuard<'r, 'a, T, N: Notifier> {
+    reader: &'r mut DoubleReader<'a, T, N>,
+    value: &'r T,
+    _unshared: Unshared,
+}
+
+impl<'a, T, N: Notifier> DoubleReader<'a, T, N> {
+    pub fn updated(&self) -> bool {
+        self.buffer.state.load(ord::acquire()) & DIRTY_BIT != 0
+    }
+
+    /// Takes the front frame, new or not, and holds it until the guard is
+    /// dropped.
+    pub fn read(&mut self) -> ReadGuard<'_, 'a, T, N> {
+        let state = self.buffer.state.fetch_or(READING_BIT, Ordering::SeqCst);
+        self.buffer.state.fetch_and(!DIRTY_BIT, Ordering::SeqCst);
+        let value = unsafe { &*self.buffer.slot(state) };
+        ReadGuard {
+            reader: self,
+            value,
+            _unshared: PhantomData,
+        }
+    }
+
+    /// Waits on the reader notifier until a new frame is published, then
+    /// reads it.
+    pub fn read_blocking(&mut self) -> ReadGuard<'_, 'a, T, N> {
+        let buffer = self.buffer;
+        buffer
+            .reader_notifier
+            .wait(|| buffer.state.load(ord::acquire()) & DIRTY_BIT != 0);
+        self.read()
+    }
+}
+
+// The reader is only borrowed mutably so it can't take a second guard; the
+// guard itself reads the frame through `&T`.
+impl<'r, 'a, T: RefUnwindSafe, N: Notifier + RefUnwindSafe> UnwindSafe for ReadGuard<'r, 'a, T, N> {}
+
+impl<'r, 'a, T, N: Notifier> Deref for ReadGuard<'r, 'a, T, N> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value
+    }
+}
+
+impl<'r, 'a, T, N: Notifier> Drop for ReadGuard<'r, 'a, T, N> {
+    fn drop(&mut self) {
+        let buffer = self.reader.buffer;
+        buffer.state.fetch_and(!READING_BIT, Ordering::SeqCst);
+        buffer.writer_notifier.notify();
+    }
+}
+
+impl<'a, T, N: Notifier> Drop for DoubleReader<'a, T, N> {
+    fn drop(&mut self) {
+        self.buffer.is_reader_exist.store(false, Ordering::SeqCst);
+        // Lets a waiting writer notice the disconnect.
+        self.buffer.writer_notifier.notify();
+    }
+}
+
+pub struct DoubleWriter<'a, T, N: Notifier = DefaultNotifier> {
+    buffer: &'a DoubleBuffer<T, N>,
+    _unshared: Unshared,
+}
+
+impl<'a, T, N: Notifier> DoubleWriter<'a, T, N> {
+    /// The back slot, which only the writer ever touches.
+    pub fn input_buffer(&mut self) -> &mut T {
+        let state = self.buffer.state.load(ord::acquire());
+        unsafe { &mut *self.buffer.slot(state ^ FRONT_BIT) }
+    }
+
+    /// Whether `try_publish` would succeed. Without a reader nothing is
+    /// waited for but an outstanding guard.
+    pub fn can_publish(&self) -> bool {
+        Self::releasable(self.buffer, self.buffer.state.load(ord::acquire()))
+    }
+
+    fn releasable(buffer: &DoubleBuffer<T, N>, state: u8) -> bool {
+        state & READING_BIT == 0
+            && (state & DIRTY_BIT == 0 || !buffer.is_reader_exist.load(Ordering::SeqCst))
+    }
+
+    /// Swaps the back slot to the front, unless the reader hasn't taken and
+    /// released the previous frame yet.
+    pub fn try_publish(&mut self) -> Result<(), WouldBlock> {
+        let buffer = self.buffer;
+        let mut state = buffer.state.load(ord::acquire());
+        loop {
+            if !Self::releasable(buffer, state) {
+                return Err(WouldBlock);
+            }
+            match buffer.state.compare_exchange_weak(
+                state,
+                (state ^ FRONT_BIT) | DIRTY_BIT,
+                Ordering::SeqCst,
+                ord::acquire(),
+            ) {
+                Ok(_) => break,
+                Err(current) => state = current,
+            }
+        }
+        buffer.reader_notifier.notify();
+        Ok(())
+    }
+
+    /// Waits on the writer notifier until the previous frame was released,
+    /// then publishes.
+    pub fn publish(&mut self) {
+        let buffer = self.buffer;
+        while self.try_publish().is_err() {
+            buffer
+                .writer_notifier
+                .wait(|| Self::releasable(buffer, buffer.state.load(ord::acquire())));
+        }
+    }
+
+    pub fn write(&mut self, value: T) {
+        *self.input_buffer() = value;
+        self.publish();
+    }
+}
+
+impl<'a, T, N: Notifier> Drop for DoubleWriter<'a, T, N> {
+    fn drop(&mut self) {
+        self.buffer.is_writer_exist.store(false, Ordering::SeqCst);
+        // Lets a waiting reader notice the disconnect.
+        self.buffer.reader_notifier.notify();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SpinNotifier;
+    #[cfg(feature = "std")]
+    use crate::ThreadNotifier;
+
+    #[test]
+    fn publish_waits_for_guard_release() {
+        let buffer = DoubleBuffer::with_notifiers(0, 0, SpinNotifier, SpinNotifier);
+        let mut writer = buffer.get_writer();
+        let mut reader = buffer.get_reader();
+
+        *writer.input_buffer() = 1;
+        assert_eq!(writer.try_publish(), Ok(()));
+        *writer.input_buffer() = 2;
+        assert_eq!(writer.try_publish(), Err(WouldBlock), "unread frame");
+
+        let guard = reader.read();
+        assert_eq!(*guard, 1);
+        assert!(!writer.can_publish(), "held frame");
+        drop(guard);
+        assert_eq!(writer.try_publish(), Ok(()));
+        assert_eq!(*reader.read(), 2);
+
+        // Re-reading an old frame holds up the writer just the same.
+        let guard = reader.read();
+        assert!(!writer.can_publish());
+        drop(guard);
+        drop(reader);
+        assert_eq!(writer.try_publish(), Ok(()));
+        assert_eq!(writer.try_publish(), Ok(()), "no reader to wait for");
+    }
+
+    fn exchange_whole_frames<N: Notifier + Sync>(
+        buffer: &'static DoubleBuffer<[u64; 64], N>,
+        count: u64,
+    ) {
+        use crate::atomic::AtomicU64;
+        let reads = AtomicU64::new(0);
+
+        std::thread::scope(|s| {
+            s.spawn(|| {
+                let mut writer = buffer.get_writer();
+                for i in 1..=count {
+                    writer.write([i; 64]);
+                    // Every publish but the first waited for the frame before.
+                    assert!(reads.load(Ordering::Relaxed) + 1 >= i);
+                }
+            });
+
+            let mut reader = buffer.get_reader();
+            for i in 1..=count {
+                let frame = reader.read_blocking();
+                assert!(frame.iter().all(|&word| word == frame[0]), "torn frame");
+                assert_eq!(frame[0], i, "writer didn't wait for consumption");
+                reads.fetch_add(1, Ordering::Relaxed);
+            }
+        });
+    }
+
+    #[test]
+    fn writer_is_gated_by_reader() {
+        static FRAMES: DoubleBuffer<[u64; 64], SpinNotifier> =
+            DoubleBuffer::with_notifiers([0; 64], [0; 64], SpinNotifier, SpinNotifier);
+        exchange_whole_frames(&FRAMES, 200);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn thread_notifier_writer_is_gated_by_reader() {
+        static FRAMES: DoubleBuffer<[u64; 64], ThreadNotifier> = DoubleBuffer::with_notifiers(
+            [0; 64],
+            [0; 64],
+            ThreadNotifier::new(),
+            ThreadNotifier::new(),
+        );
+        exchange_whole_frames(&FRAMES, if cfg!(miri) { 200 } else { 20_000 });
+    }
+}
@@ -0,0 +1,168 @@
+use crate::{BufferReader, BufferWriter, DefaultNotifier, Notifier, TripleBuffer};
+
+/// Two `TripleBuffer`s for a two-way exchange, e.g. commands down to a
+/// real-time thread and status back up. Side A writes `A` frames and reads
+/// `B` frames, side B the other way round; the directions are independent
+/// and each keeps only its latest frame.
+pub struct Duplex<A, B, N = DefaultNotifier> {
+    a: TripleBuffer<A, N>,
+    b: TripleBuffer<B, N>,
+}
+
+/// The writing half of one direction and the reading half of the other.
+pub struct DuplexEndpoint<'a, Out, In, N: Notifier = DefaultNotifier> {
+    writer: BufferWriter<'a, Out, N>,
+    reader: BufferReader<'a, In, N>,
+}
+
+pub type DuplexEndpointA<'a, A, B, N = DefaultNotifier> = DuplexEndpoint<'a, A, B, N>;
+pub type DuplexEndpointB<'a, A, B, N = DefaultNotifier> = DuplexEndpoint<'a, B, A, N>;
+
+impl<A, B, N: Notifier> Duplex<A, B, N> {
+    /// Pairs two buffers, built with any of their `const` constructors.
+    pub const fn new(a: TripleBuffer<A, N>, b: TripleBuffer<B, N>) -> Self {
+        Self { a, b }
+    }
+
+    /// Hands out both endpoints without the runtime existence checks, like
+    /// `TripleBuffer::split`.
+    pub fn split(&mut self) -> (DuplexEndpointA<'_, A, B, N>, DuplexEndpointB<'_, A, B, N>) {
+        let (a_reader, a_writer) = self.a.split();
+        let (b_reader, b_writer) = self.b.split();
+        (
+            DuplexEndpoint {
+                writer: a_writer,
+                reader: b_reader,
+            },
+            DuplexEndpoint {
+                writer: b_writer,
+                reader: a_reader,
+            },
+        )
+    }
+
+    /// Side A, e.g. from a `static`. Panics if either of its handles exists.
+    pub fn endpoint_a(&self) -> DuplexEndpointA<'_, A, B, N> {
+        DuplexEndpoint {
+            writer: self.a.get_writer(),
+            reader: self.b.get_reader(),
+        }
+    }
+
+    /// Side B. Panics if either of its handles exists.
+    pub fn endpoint_b(&self) -> DuplexEndpointB<'_, A, B, N> {
+        DuplexEndpoint {
+            writer: self.b.get_writer(),
+            reader: self.a.get_reader(),
+        }
+    }
+}
+
+impl<A, B> Duplex<A, B> {
+    pub const fn new_const(a1: A, a2: A, a3: A, b1: B, b2: B, b3: B) -> Self {
+        Self::new(
+            TripleBuffer::new_const(a1, a2, a3),
+            TripleBuffer::new_const(b1, b2, b3),
+        )
+    }
+}
+
+impl<'a, Out, In, N: Notifier> DuplexEndpoint<'a, Out, In, N> {
+    pub fn write(&mut self, value: Out) {
+        self.writer.write(value);
+    }
+
+    pub fn input_buffer(&mut self) -> &mut Out {
+        self.writer.input_buffer()
+    }
+
+    pub fn publish(&self) -> bool {
+        self.writer.publish()
+    }
+
+    pub fn consumed(&self) -> bool {
+        self.writer.consumed()
+    }
+
+    pub fn read(&mut self) -> &In {
+        self.reader.read()
+    }
+
+    pub fn updated(&mut self) -> bool {
+        self.reader.updated()
+    }
+
+    pub fn update(&mut self) -> bool {
+        self.reader.update()
+    }
+
+    pub fn output_buffer(&mut self) -> &mut In {
+        self.reader.output_buffer()
+    }
+
+    /// The outgoing handle, for the rest of the writer API.
+    pub fn writer(&mut self) -> &mut BufferWriter<'a, Out, N> {
+        &mut self.writer
+    }
+
+    /// The incoming handle, for the rest of the reader API.
+    pub fn reader(&mut self) -> &mut BufferReader<'a, In, N> {
+        &mut self.reader
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SpinNotifier;
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    enum Command {
+        Idle,
+        Move(u32),
+    }
+
+    #[test]
+    fn directions_are_independent() {
+        static LINK: Duplex<Command, u64> =
+            Duplex::new_const(Command::Idle, Command::Idle, Command::Idle, 0, 0, 0);
+        let mut control = LINK.endpoint_a();
+        let mut rt = LINK.endpoint_b();
+
+        control.write(Command::Move(1));
+        control.write(Command::Move(2));
+        assert!(!control.updated(), "command echoed back");
+        assert_eq!(*rt.read(), Command::Move(2));
+        assert!(control.consumed());
+
+        rt.write(7);
+        assert!(!rt.updated());
+        assert_eq!(*control.read(), 7);
+        assert_eq!(*rt.read(), Command::Move(2));
+    }
+
+    #[test]
+    fn ping_pong_across_threads() {
+        let mut link = Duplex::new(
+            TripleBuffer::with_notifiers(0u32, 0, 0, SpinNotifier, SpinNotifier),
+            TripleBuffer::with_notifiers(0u32, 0, 0, SpinNotifier, SpinNotifier),
+        );
+        let (mut ping, mut pong) = link.split();
+        let count = 200;
+
+        std::thread::scope(|scope| {
+            scope.spawn(move || {
+                // Echoes every ping back, incremented.
+                for _ in 1..=count {
+                    let value = *pong.reader().read_blocking();
+                    pong.write(value + 1);
+                }
+            });
+
+            for i in 1..=count {
+                ping.write(2 * i - 1);
+                assert_eq!(*ping.reader().read_blocking(), 2 * i);
+            }
+        });
+    }
+}
@@ -0,0 +1,127 @@
+//! Bevy integration for the `bevy` feature: the reader lives in a resource
+//! that a `First` system updates once per frame of the app, so systems see
+//! the same latest frame, and whether it is new, for the rest of the run.
+
+use std::sync::Mutex;
+
+use bevy_app::{App, First, Plugin};
+use bevy_ecs::prelude::{Res, ResMut, Resource};
+use bevy_ecs::system::SystemParam;
+
+use crate::{BufferReader, DefaultNotifier, Notifier};
+
+/// A `Resource` owning the reader of a `'static` buffer, e.g. a `static`
+/// or a leaked one, whose writer publishes from another thread.
+#[derive(Resource)]
+pub struct TripleBufferResource<
+    T: 'static,
+    N: Notifier + 'static = DefaultNotifier,
+    const SLOTS: usize = 3,
+> {
+    reader: BufferReader<'static, T, N, SLOTS>,
+    // The reader's output slot, so `get` can take `&self`; it only moves
+    // in `update`, which takes `&mut self`.
+    latest: *const T,
+    fresh: bool,
+}
+
+// The reader is only touched through `&mut self`; `&self` only reads the
+// output slot, which the writer never does while the reader holds it.
+unsafe impl<T: Send, N: Notifier + Sync, const SLOTS: usize> Send
+    for TripleBufferResource<T, N, SLOTS>
+{
+}
+unsafe impl<T: Send + Sync, N: Notifier + Sync, const SLOTS: usize> Sync
+    for TripleBufferResource<T, N, SLOTS>
+{
+}
+
+impl<T, N: Notifier, const SLOTS: usize> TripleBufferResource<T, N, SLOTS> {
+    pub fn new(mut reader: BufferReader<'static, T, N, SLOTS>) -> Self {
+        let latest = reader.output_buffer() as *const T;
+        Self {
+            reader,
+            latest,
+            fresh: false,
+        }
+    }
+
+    /// Takes the latest frame, if there is a new one; returns whether there
+    /// was. `TripleBufferPlugin` calls this at the start of every frame.
+    pub fn update(&mut self) -> bool {
+        self.fresh = self.reader.update();
+        self.latest = self.reader.output_buffer() as *const T;
+        self.fresh
+    }
+
+    /// The frame taken by the last `update`, or an older one if it found
+    /// none.
+    pub fn get(&self) -> &T {
+        unsafe { &*self.latest }
+    }
+
+    /// Whether the last `update` took a new frame.
+    pub fn is_fresh(&self) -> bool {
+        self.fresh
+    }
+}
+
+/// Inserts a `TripleBufferResource` for `reader` and updates it in `First`.
+pub struct TripleBufferPlugin<
+    T: 'static,
+    N: Notifier + 'static = DefaultNotifier,
+    const SLOTS: usize = 3,
+> {
+    // `Plugin::build` only gets `&self`.
+    reader: Mutex<Option<BufferReader<'static, T, N, SLOTS>>>,
+}
+
+impl<T, N: Notifier, const SLOTS: usize> TripleBufferPlugin<T, N, SLOTS> {
+    pub fn new(reader: BufferReader<'static, T, N, SLOTS>) -> Self {
+        Self {
+            reader: Mutex::new(Some(reader)),
+        }
+    }
+}
+
+impl<T: Send + Sync, N: Notifier + Send + Sync, const SLOTS: usize> Plugin
+    for TripleBufferPlugin<T, N, SLOTS>
+{
+    fn build(&self, app: &mut App) {
+        let reader = self
+            .reader
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .take()
+            .expect("TripleBufferPlugin built twice");
+        app.insert_resource(TripleBufferResource::new(reader))
+            .add_systems(First, update_latest::<T, N, SLOTS>);
+    }
+}
+
+fn update_latest<T: Send + Sync, N: Notifier + Send + Sync, const SLOTS: usize>(
+    mut resource: ResMut<TripleBufferResource<T, N, SLOTS>>,
+) {
+    resource.update();
+}
+
+/// The latest frame, for systems: `get` and `is_fresh` as on the resource.
+#[derive(SystemParam)]
+pub struct LatestChanged<
+    'w,
+    T: Send + Sync + 'static,
+    N: Notifier + Send + Sync + 'static = DefaultNotifier,
+    const SLOTS: usize = 3,
+> {
+    resource: Res<'w, TripleBufferResource<T, N, SLOTS>>,
+}
+
+impl<T: Send + Sync, N: Notifier + Send + Sync, const SLOTS: usize> LatestChanged<'_, T, N, SLOTS> {
+    pub fn get(&self) -> &T {
+        self.resource.get()
+    }
+
+    pub fn is_fresh(&self) -> bool {
+        self.resource.is_fresh()
+    }
+}
@@ -0,0 +1,211 @@
+#[cfg(feature = "event-log")]
+use crate::atomic::{AtomicU32, AtomicU64};
+
+#[cfg(feature = "event-log")]
+use crate::ord;
+
+/// How many of the latest transitions the log keeps.
+#[cfg(feature = "event-log")]
+pub const EVENT_LOG_LEN: usize = 32;
+
+/// What changed the control state, in an `Event`.
+#[cfg(feature = "event-log")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum EventKind {
+    Publish,
+    /// `update()` took the published frame.
+    Consume,
+    /// A two-slot writer took the back slot to write into.
+    Retract,
+    ReaderAcquired,
+    WriterAcquired,
+    ReaderReleased,
+    WriterReleased,
+}
+
+#[cfg(feature = "event-log")]
+impl EventKind {
+    const ALL: [Self; 7] = [
+        Self::Publish,
+        Self::Consume,
+        Self::Retract,
+        Self::ReaderAcquired,
+        Self::WriterAcquired,
+        Self::ReaderReleased,
+        Self::WriterReleased,
+    ];
+}
+
+/// One recorded transition of `back_info`, the slot index plus the dirty
+/// bit in `0x80`. Handle events record it unchanged.
+#[cfg(feature = "event-log")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Event {
+    /// Counts every event of the buffer, wrapping, so gaps show what was
+    /// overwritten before it was dumped.
+    pub index: u32,
+    pub kind: EventKind,
+    pub old: u8,
+    pub new: u8,
+}
+
+/// A ring of the latest `EVENT_LOG_LEN` events with the `event-log`
+/// feature, zero-sized no-ops without. Each entry packs `index + 1`, kind
+/// and both bytes into one word so it can't be torn; 0 is an empty entry.
+pub(crate) struct EventLog {
+    #[cfg(feature = "event-log")]
+    entries: [AtomicU64; EVENT_LOG_LEN],
+    #[cfg(feature = "event-log")]
+    next: AtomicU32,
+    // Index of the first event the next dump reports.
+    #[cfg(feature = "event-log")]
+    dumped: AtomicU32,
+}
+
+impl EventLog {
+    pub(crate) const fn new() -> Self {
+        Self {
+            #[cfg(feature = "event-log")]
+            entries: [const { AtomicU64::new(0) }; EVENT_LOG_LEN],
+            #[cfg(feature = "event-log")]
+            next: AtomicU32::new(0),
+            #[cfg(feature = "event-log")]
+            dumped: AtomicU32::new(0),
+        }
+    }
+
+    /// `kind` indexes `EventKind::ALL`; a plain number so callers needn't
+    /// name the type without the feature.
+    #[inline]
+    pub(crate) fn record(&self, kind: u8, old: u8, new: u8) {
+        #[cfg(feature = "event-log")]
+        {
+            let index = self.next.fetch_add(1, ord::relaxed());
+            let entry = (index.wrapping_add(1) as u64) << 32
+                | (kind as u64) << 16
+                | (old as u64) << 8
+                | new as u64;
+            self.entries[index as usize % EVENT_LOG_LEN].store(entry, ord::release());
+        }
+        #[cfg(not(feature = "event-log"))]
+        let _ = (kind, old, new);
+    }
+
+    /// Reports the events recorded since the previous dump that are still
+    /// in the ring, oldest first.
+    #[cfg(feature = "event-log")]
+    pub(crate) fn dump(&self, f: &mut impl FnMut(Event)) {
+        let next = self.next.load(ord::acquire());
+        let dumped = self.dumped.swap(next, ord::relaxed());
+        let len = next.wrapping_sub(dumped).min(EVENT_LOG_LEN as u32);
+        for index in (0..len).map(|back| next.wrapping_sub(len - back)) {
+            let entry = self.entries[index as usize % EVENT_LOG_LEN].load(ord::acquire());
+            // Skip entries overwritten (or not yet written) since `next`.
+            if (entry >> 32) as u32 != index.wrapping_add(1) {
+                continue;
+            }
+            f(Event {
+                index,
+                kind: EventKind::ALL[(entry >> 16) as u8 as usize],
+                old: (entry >> 8) as u8,
+                new: entry as u8,
+            });
+        }
+    }
+}
+
+pub(crate) const PUBLISH: u8 = 0;
+pub(crate) const CONSUME: u8 = 1;
+pub(crate) const RETRACT: u8 = 2;
+pub(crate) const READER_ACQUIRED: u8 = 3;
+pub(crate) const WRITER_ACQUIRED: u8 = 4;
+pub(crate) const READER_RELEASED: u8 = 5;
+pub(crate) const WRITER_RELEASED: u8 = 6;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(not(feature = "event-log"))]
+    #[test]
+    fn log_vanishes_without_the_feature() {
+        assert_eq!(core::mem::size_of::<EventLog>(), 0);
+        assert_eq!(core::mem::align_of::<EventLog>(), 1);
+    }
+
+    #[cfg(feature = "event-log")]
+    fn dump<T, const SLOTS: usize>(buffer: &crate::NBuffer<T, SLOTS>) -> std::vec::Vec<Event> {
+        let mut events = std::vec::Vec::new();
+        buffer.dump_events(&mut |event| events.push(event));
+        events
+    }
+
+    #[cfg(feature = "event-log")]
+    #[test]
+    fn log_replays_scripted_transitions() {
+        use crate::TripleBuffer;
+        use EventKind::*;
+
+        let event = |index, kind, old, new| Event {
+            index,
+            kind,
+            old,
+            new,
+        };
+        let buffer = TripleBuffer::new(|| 0);
+        let mut writer = buffer.get_writer();
+        let mut reader = buffer.get_reader();
+        writer.write(1);
+        writer.write(2);
+        reader.update();
+        reader.update();
+        drop(reader);
+
+        assert_eq!(
+            dump(&buffer),
+            [
+                event(0, WriterAcquired, 0x00, 0x00),
+                event(1, ReaderAcquired, 0x00, 0x00),
+                event(2, Publish, 0x00, 0x81),
+                event(3, Publish, 0x81, 0x80),
+                event(4, Consume, 0x80, 0x02),
+                event(5, ReaderReleased, 0x02, 0x02),
+            ]
+        );
+        assert_eq!(dump(&buffer), [], "dumped twice");
+        drop(writer);
+        assert_eq!(dump(&buffer), [event(6, WriterReleased, 0x02, 0x02)]);
+    }
+
+    #[cfg(feature = "event-log")]
+    #[test]
+    fn log_records_two_slot_retract() {
+        use crate::NBuffer;
+        use EventKind::*;
+
+        let buffer = NBuffer::<u32, 2>::new(|| 0);
+        let mut writer = buffer.get_writer();
+        writer.write(1);
+        writer.write(2);
+        let kinds: std::vec::Vec<_> = dump(&buffer).iter().map(|event| event.kind).collect();
+        assert_eq!(kinds, [WriterAcquired, Retract, Publish, Retract, Publish]);
+    }
+
+    #[cfg(feature = "event-log")]
+    #[test]
+    fn log_keeps_only_the_latest() {
+        use crate::TripleBuffer;
+
+        let buffer = TripleBuffer::new(|| 0);
+        let mut writer = buffer.get_writer();
+        for i in 0..100 {
+            writer.write(i);
+        }
+        let indices: std::vec::Vec<_> = dump(&buffer).iter().map(|event| event.index).collect();
+        let expected: std::vec::Vec<_> = (101 - EVENT_LOG_LEN as u32..101).collect();
+        assert_eq!(indices, expected);
+    }
+}
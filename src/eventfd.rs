@@ -0,0 +1,257 @@
+use std::io;
+use std::marker::PhantomData;
+use std::os::fd::{AsFd, AsRawFd, BorrowedFd, RawFd};
+
+use crate::atomic::{fence, AtomicBool, AtomicI32, Ordering};
+use crate::{is_dirty, ord, NBuffer};
+
+const NO_FD: RawFd = -1;
+
+/// Lazily created eventfd that is readable while a publish is pending.
+/// `signaled` is set by the first publish after a drain, so later ones skip
+/// the `write` while the fd is already readable.
+pub(crate) struct EventFd {
+    fd: AtomicI32,
+    signaled: AtomicBool,
+    #[cfg(test)]
+    writes: crate::atomic::AtomicU32,
+    #[cfg(test)]
+    reads: crate::atomic::AtomicU32,
+}
+
+impl EventFd {
+    pub(crate) const fn new() -> Self {
+        Self {
+            fd: AtomicI32::new(NO_FD),
+            signaled: AtomicBool::new(false),
+            #[cfg(test)]
+            writes: crate::atomic::AtomicU32::new(0),
+            #[cfg(test)]
+            reads: crate::atomic::AtomicU32::new(0),
+        }
+    }
+
+    fn get_or_create(&self) -> io::Result<RawFd> {
+        let fd = self.fd.load(ord::acquire());
+        if fd != NO_FD {
+            return Ok(fd);
+        }
+
+        let created = unsafe { libc::eventfd(0, libc::EFD_NONBLOCK | libc::EFD_CLOEXEC) };
+        if created < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        match self
+            .fd
+            .compare_exchange(NO_FD, created, ord::acqrel(), ord::acquire())
+        {
+            Ok(_) => Ok(created),
+            Err(winner) => {
+                unsafe { libc::close(created) };
+                Ok(winner)
+            }
+        }
+    }
+
+    /// Makes the fd readable. Free until someone asked for the fd.
+    #[inline]
+    pub(crate) fn signal(&self) {
+        let fd = self.fd.load(Ordering::SeqCst);
+        if fd != NO_FD && !self.signaled.swap(true, Ordering::SeqCst) {
+            #[cfg(test)]
+            self.writes.fetch_add(1, ord::relaxed());
+            let one: u64 = 1;
+            // Only fails if the counter would overflow, which still leaves
+            // the fd readable.
+            unsafe { libc::write(fd, (&one as *const u64).cast(), 8) };
+        }
+    }
+
+    /// Resets the fd to non-readable. Must run before the reader inspects
+    /// the dirty bit, so a publish racing the update can't be cleared away.
+    /// Free unless a publish has signalled since the last drain.
+    #[inline]
+    pub(crate) fn drain(&self) {
+        let fd = self.fd.load(ord::relaxed());
+        if fd != NO_FD && self.signaled.load(Ordering::SeqCst) {
+            #[cfg(test)]
+            self.reads.fetch_add(1, ord::relaxed());
+            let mut count: u64 = 0;
+            // EAGAIN just means nothing was pending.
+            unsafe { libc::read(fd, (&mut count as *mut u64).cast(), 8) };
+            // Cleared only after the read: a publish that skipped its write
+            // because `signaled` was still set is then seen by the dirty
+            // check that follows.
+            self.signaled.store(false, Ordering::SeqCst);
+            fence(Ordering::SeqCst);
+        }
+    }
+}
+
+impl Drop for EventFd {
+    fn drop(&mut self) {
+        let fd = *self.fd.get_mut();
+        if fd != NO_FD {
+            unsafe { libc::close(fd) };
+        }
+    }
+}
+
+/// Borrowed eventfd of a `TripleBuffer`, readable while a published frame
+/// hasn't been taken by `update()`. Register it with epoll/poll/mio.
+#[derive(Debug, Clone, Copy)]
+pub struct EventFdHandle<'a> {
+    fd: RawFd,
+    _buffer: PhantomData<&'a ()>,
+}
+
+impl AsRawFd for EventFdHandle<'_> {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd
+    }
+}
+
+impl AsFd for EventFdHandle<'_> {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        // The fd stays open until the buffer, which `'a` borrows, is dropped.
+        unsafe { BorrowedFd::borrow_raw(self.fd) }
+    }
+}
+
+impl<T, const SLOTS: usize, N> NBuffer<T, SLOTS, N> {
+    /// Returns the buffer's eventfd, creating it on first use. Publishes
+    /// only write to the eventfd once it exists, so a frame published
+    /// before that is signalled here.
+    pub fn eventfd(&self) -> io::Result<EventFdHandle<'_>> {
+        let fd = self.eventfd.get_or_create()?;
+        // Pairs with the fd load in `signal`: either that publish sees the
+        // fd, or this sees its dirty bit. A concurrent update that takes the
+        // frame drains the fd again.
+        fence(Ordering::SeqCst);
+        if is_dirty(self.back_info.load(ord::acquire())) {
+            self.eventfd.signal();
+        }
+        Ok(EventFdHandle {
+            fd,
+            _buffer: PhantomData,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TripleBuffer;
+
+    fn readable(handle: &EventFdHandle) -> bool {
+        let mut poll_fd = libc::pollfd {
+            fd: handle.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let ready = unsafe { libc::poll(&mut poll_fd, 1, 0) };
+        assert!(ready >= 0);
+        poll_fd.revents & libc::POLLIN != 0
+    }
+
+    #[test]
+    fn eventfd_readiness_follows_publish_and_update() {
+        let buffer = TripleBuffer::new(|| 0);
+        let mut writer = buffer.get_writer();
+        let mut reader = buffer.get_reader();
+
+        writer.write(1);
+        let handle = buffer.eventfd().unwrap();
+        assert_eq!(
+            handle.as_raw_fd(),
+            buffer.eventfd().unwrap().as_raw_fd()
+        );
+        assert!(readable(&handle));
+        assert_eq!(*reader.read(), 1);
+        assert!(!readable(&handle));
+
+        for i in 2..10 {
+            writer.write(i);
+            assert!(readable(&handle));
+            assert_eq!(*reader.read(), i);
+            assert!(!readable(&handle));
+        }
+
+        writer.write(10);
+        writer.write(11);
+        assert!(readable(&handle));
+        reader.update();
+        assert!(!readable(&handle));
+        assert!(!reader.update());
+    }
+
+    #[test]
+    fn unread_publishes_are_coalesced() {
+        let buffer = TripleBuffer::new(|| 0);
+        let mut writer = buffer.get_writer();
+        let mut reader = buffer.get_reader();
+        let handle = buffer.eventfd().unwrap();
+
+        for i in 0..100 {
+            writer.write(i);
+        }
+        assert!(readable(&handle));
+        assert_eq!(buffer.eventfd.writes.load(Ordering::Relaxed), 1);
+
+        for i in 0..100 {
+            writer.write(i);
+            reader.update();
+        }
+        assert!(!readable(&handle));
+        assert_eq!(buffer.eventfd.writes.load(Ordering::Relaxed), 100);
+    }
+
+    #[test]
+    fn updates_skip_the_read_while_nothing_is_signalled() {
+        let buffer = TripleBuffer::new(|| 0);
+        let mut writer = buffer.get_writer();
+        let mut reader = buffer.get_reader();
+        let handle = buffer.eventfd().unwrap();
+
+        for _ in 0..100 {
+            assert!(!reader.update());
+        }
+        assert_eq!(buffer.eventfd.reads.load(Ordering::Relaxed), 0);
+
+        writer.write(1);
+        assert!(reader.update());
+        assert!(!reader.update());
+        assert!(!readable(&handle));
+        assert_eq!(buffer.eventfd.reads.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn poll_loop_wakes_on_publish() {
+        static POLLED_BUFFER: TripleBuffer<u32> = TripleBuffer::<u32>::new_const(0, 0, 0);
+        let handle = POLLED_BUFFER.eventfd().unwrap();
+        let count = 100;
+
+        let jh = std::thread::spawn(move || {
+            let mut writer = POLLED_BUFFER.get_writer();
+            for i in 1..=count {
+                writer.write_blocking(i);
+            }
+        });
+
+        let mut reader = POLLED_BUFFER.get_reader();
+        let mut poll_fd = libc::pollfd {
+            fd: handle.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let mut last = 0;
+        while last != count {
+            assert_eq!(unsafe { libc::poll(&mut poll_fd, 1, 5000) }, 1);
+            if reader.update() {
+                assert_eq!(*reader.output_buffer(), last + 1);
+                last += 1;
+            }
+        }
+        jh.join().unwrap();
+    }
+}
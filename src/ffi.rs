@@ -0,0 +1,305 @@
+//! A C API over a triple buffer of byte frames, for the `ffi` feature; the
+//! declarations are in `include/tri_buffer.h`. Build a shared library with
+//! `cargo rustc --release --features ffi --crate-type cdylib`.
+//!
+//! Ownership: `tribuf_create` returns a buffer the caller owns until it
+//! passes it to `tribuf_destroy`, which refuses while a handle is still
+//! acquired. A handle from `tribuf_writer_acquire` or `tribuf_reader_acquire`
+//! is owned by the caller until released, and may be used from one thread
+//! at a time, which needn't be the one that acquired it. No function
+//! unwinds into C: a panic comes back as `TRIBUF_PANIC`.
+//!
+//! A callback set with `tribuf_set_on_publish` runs on the thread calling
+//! `tribuf_write`, after the frame is published, so it must not block; it
+//! must not call `tribuf_set_on_publish` on the same buffer either, as that
+//! waits for the callback to return.
+
+use std::boxed::Box;
+use std::ffi::c_void;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+use crate::atomic::{AtomicPtr, AtomicUsize, Ordering};
+use crate::{ord, BufferReader, BufferWriter, TripleBuffer};
+
+pub const TRIBUF_OK: i32 = 0;
+/// A pointer argument was null.
+pub const TRIBUF_NULL: i32 = -1;
+/// A frame or output length differed from the buffer's frame size.
+pub const TRIBUF_LENGTH: i32 = -2;
+/// `tribuf_destroy` found a handle still acquired.
+pub const TRIBUF_BUSY: i32 = -3;
+pub const TRIBUF_PANIC: i32 = -4;
+
+/// Called with its context after every publish.
+pub type TriBufCallback = Option<unsafe extern "C" fn(*mut c_void)>;
+
+pub struct TriBufHandle {
+    buffer: TripleBuffer<Box<[u8]>>,
+    on_publish: OnPublish,
+}
+
+pub struct TriBufWriter {
+    writer: BufferWriter<'static, Box<[u8]>>,
+    on_publish: &'static OnPublish,
+}
+
+struct Registration {
+    callback: unsafe extern "C" fn(*mut c_void),
+    ctx: *mut c_void,
+}
+
+/// The registered callback, boxed behind an atomic pointer. The box of a
+/// replaced one is freed once every publish that may have loaded it has
+/// returned from it, which the registering thread waits for; publishes only
+/// bump `started` and `finished` around the call, so they never wait.
+struct OnPublish {
+    registration: AtomicPtr<Registration>,
+    started: AtomicUsize,
+    finished: AtomicUsize,
+}
+
+impl OnPublish {
+    fn new() -> Self {
+        Self {
+            registration: AtomicPtr::new(ptr::null_mut()),
+            started: AtomicUsize::new(0),
+            finished: AtomicUsize::new(0),
+        }
+    }
+
+    fn set(&self, registration: Option<Registration>) {
+        let registration = registration.map_or(ptr::null_mut(), |r| Box::into_raw(Box::new(r)));
+        // `SeqCst` here and in `call`: either a publish counted itself in
+        // `started` before the swap, and is waited for, or it loads the new
+        // registration.
+        let old = self.registration.swap(registration, Ordering::SeqCst);
+        let started = self.started.load(Ordering::SeqCst);
+        // Publishes are serialized by the one writer, so `finished` catches
+        // up with `started` even while they keep coming.
+        while (self.finished.load(ord::acquire()).wrapping_sub(started) as isize) < 0 {
+            std::thread::yield_now();
+        }
+        if !old.is_null() {
+            drop(unsafe { Box::from_raw(old) });
+        }
+    }
+
+    fn call(&self) {
+        self.started.fetch_add(1, Ordering::SeqCst);
+        // Freed only after `finished` passes this publish.
+        if let Some(registration) = unsafe { self.registration.load(Ordering::SeqCst).as_ref() } {
+            unsafe { (registration.callback)(registration.ctx) };
+        }
+        self.finished.fetch_add(1, ord::release());
+    }
+}
+
+impl Drop for OnPublish {
+    fn drop(&mut self) {
+        let registration = *self.registration.get_mut();
+        if !registration.is_null() {
+            drop(unsafe { Box::from_raw(registration) });
+        }
+    }
+}
+
+pub struct TriBufReader {
+    reader: BufferReader<'static, Box<[u8]>>,
+}
+
+fn guarded(f: impl FnOnce() -> i32) -> i32 {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or(TRIBUF_PANIC)
+}
+
+fn guarded_ptr<P>(f: impl FnOnce() -> *mut P) -> *mut P {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or(ptr::null_mut())
+}
+
+/// A buffer of zeroed `size`-byte frames, or null if `size` is 0.
+#[no_mangle]
+pub extern "C" fn tribuf_create(size: usize) -> *mut TriBufHandle {
+    guarded_ptr(|| {
+        if size == 0 {
+            return ptr::null_mut();
+        }
+        Box::into_raw(Box::new(TriBufHandle {
+            buffer: TripleBuffer::new_boxed_slices(size, 0),
+            on_publish: OnPublish::new(),
+        }))
+    })
+}
+
+/// Frees `handle`, unless a writer or reader is still acquired on it.
+///
+/// # Safety
+///
+/// `handle` must be null or from `tribuf_create`, and not destroyed yet.
+#[no_mangle]
+pub unsafe extern "C" fn tribuf_destroy(handle: *mut TriBufHandle) -> i32 {
+    guarded(|| {
+        let Some(owned) = (unsafe { handle.as_ref() }) else {
+            return TRIBUF_NULL;
+        };
+        let state = owned.buffer.state();
+        if state.reader_attached || state.writer_attached {
+            return TRIBUF_BUSY;
+        }
+        drop(unsafe { Box::from_raw(handle) });
+        TRIBUF_OK
+    })
+}
+
+/// The buffer's writer, or null while another is acquired.
+///
+/// # Safety
+///
+/// `handle` must be null or a live buffer from `tribuf_create`.
+#[no_mangle]
+pub unsafe extern "C" fn tribuf_writer_acquire(handle: *mut TriBufHandle) -> *mut TriBufWriter {
+    guarded_ptr(|| {
+        // `tribuf_destroy` keeps the buffer alive while the writer exists.
+        let handle: Option<&'static TriBufHandle> = unsafe { handle.as_ref() };
+        let Some(handle) = handle else {
+            return ptr::null_mut();
+        };
+        match handle.buffer.try_get_writer() {
+            Some(writer) => Box::into_raw(Box::new(TriBufWriter {
+                writer,
+                on_publish: &handle.on_publish,
+            })),
+            None => ptr::null_mut(),
+        }
+    })
+}
+
+/// The buffer's reader, or null while another is acquired.
+///
+/// # Safety
+///
+/// `handle` must be null or a live buffer from `tribuf_create`.
+#[no_mangle]
+pub unsafe extern "C" fn tribuf_reader_acquire(handle: *mut TriBufHandle) -> *mut TriBufReader {
+    guarded_ptr(|| {
+        let handle: Option<&'static TriBufHandle> = unsafe { handle.as_ref() };
+        let Some(handle) = handle else {
+            return ptr::null_mut();
+        };
+        match handle.buffer.try_get_reader() {
+            Some(reader) => Box::into_raw(Box::new(TriBufReader { reader })),
+            None => ptr::null_mut(),
+        }
+    })
+}
+
+/// # Safety
+///
+/// `writer` must be null or from `tribuf_writer_acquire`, and not released
+/// yet.
+#[no_mangle]
+pub unsafe extern "C" fn tribuf_writer_release(writer: *mut TriBufWriter) {
+    if !writer.is_null() {
+        let _ = panic::catch_unwind(|| drop(unsafe { Box::from_raw(writer) }));
+    }
+}
+
+/// # Safety
+///
+/// `reader` must be null or from `tribuf_reader_acquire`, and not released
+/// yet.
+#[no_mangle]
+pub unsafe extern "C" fn tribuf_reader_release(reader: *mut TriBufReader) {
+    if !reader.is_null() {
+        let _ = panic::catch_unwind(|| drop(unsafe { Box::from_raw(reader) }));
+    }
+}
+
+/// Copies the `len` bytes at `data` into the next frame and publishes it.
+///
+/// # Safety
+///
+/// `writer` must be null or an acquired writer no other thread is using,
+/// and `data` null or valid for reading `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn tribuf_write(
+    writer: *mut TriBufWriter,
+    data: *const u8,
+    len: usize,
+) -> i32 {
+    guarded(|| {
+        let Some(writer) = (unsafe { writer.as_mut() }) else {
+            return TRIBUF_NULL;
+        };
+        if data.is_null() {
+            return TRIBUF_NULL;
+        }
+        let data = unsafe { std::slice::from_raw_parts(data, len) };
+        match writer.writer.write_slice(data) {
+            Ok(()) => {
+                writer.on_publish.call();
+                TRIBUF_OK
+            }
+            Err(_) => TRIBUF_LENGTH,
+        }
+    })
+}
+
+/// Copies the latest frame into the `len` bytes at `out`, and stores into
+/// `out_fresh`, unless it is null, whether the frame is new since the
+/// previous read.
+///
+/// # Safety
+///
+/// `reader` must be null or an acquired reader no other thread is using,
+/// `out` null or valid for writing `len` bytes, and `out_fresh` null or
+/// valid for writing a `bool`.
+#[no_mangle]
+pub unsafe extern "C" fn tribuf_reader_read(
+    reader: *mut TriBufReader,
+    out: *mut u8,
+    len: usize,
+    out_fresh: *mut bool,
+) -> i32 {
+    guarded(|| {
+        let Some(reader) = (unsafe { reader.as_mut() }) else {
+            return TRIBUF_NULL;
+        };
+        if out.is_null() {
+            return TRIBUF_NULL;
+        }
+        let out = unsafe { std::slice::from_raw_parts_mut(out, len) };
+        match reader.reader.read_slice(out) {
+            Ok(fresh) => {
+                if let Some(out_fresh) = unsafe { out_fresh.as_mut() } {
+                    *out_fresh = fresh;
+                }
+                TRIBUF_OK
+            }
+            Err(_) => TRIBUF_LENGTH,
+        }
+    })
+}
+
+/// Has every later publish on `handle` call `callback` with `ctx`, in place
+/// of the callback set before; a null `callback` removes it. Once this
+/// returns, the callback it replaced is neither running nor called again.
+///
+/// # Safety
+///
+/// `handle` must be null or a live buffer from `tribuf_create`, and
+/// `callback` null or safe to call with `ctx` from the writer's thread.
+#[no_mangle]
+pub unsafe extern "C" fn tribuf_set_on_publish(
+    handle: *mut TriBufHandle,
+    callback: TriBufCallback,
+    ctx: *mut c_void,
+) -> i32 {
+    guarded(|| {
+        let Some(handle) = (unsafe { handle.as_ref() }) else {
+            return TRIBUF_NULL;
+        };
+        let registration = callback.map(|callback| Registration { callback, ctx });
+        handle.on_publish.set(registration);
+        TRIBUF_OK
+    })
+}
@@ -0,0 +1,158 @@
+use core::fmt;
+
+use crate::{BufferReader, BufferWriter, Notifier};
+
+/// Variable-length packet in a fixed `N`-byte slot. The length lives in the
+/// slot next to the bytes, so it is published with them and can't be torn
+/// from the data it describes.
+#[derive(Clone, Copy)]
+pub struct FramedBytes<const N: usize> {
+    len: usize,
+    data: [u8; N],
+}
+
+impl<const N: usize> FramedBytes<N> {
+    pub const fn new() -> Self {
+        Self {
+            len: 0,
+            data: [0; N],
+        }
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        &self.data[..self.len]
+    }
+
+    /// Replaces the contents with `frame`.
+    pub fn set(&mut self, frame: &[u8]) -> Result<(), FrameTooLong> {
+        if frame.len() > N {
+            return Err(FrameTooLong {
+                len: frame.len(),
+                capacity: N,
+            });
+        }
+        self.data[..frame.len()].copy_from_slice(frame);
+        self.len = frame.len();
+        Ok(())
+    }
+}
+
+impl<const N: usize> Default for FramedBytes<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> fmt::Debug for FramedBytes<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("FramedBytes")
+            .field(&self.as_slice())
+            .finish()
+    }
+}
+
+/// A frame didn't fit into the slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FrameTooLong {
+    pub len: usize,
+    pub capacity: usize,
+}
+
+impl fmt::Display for FrameTooLong {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "frame of {} bytes exceeds slots of {} bytes",
+            self.len, self.capacity
+        )
+    }
+}
+
+impl core::error::Error for FrameTooLong {}
+
+impl<'a, const B: usize, N: Notifier, const SLOTS: usize>
+    BufferWriter<'a, FramedBytes<B>, N, SLOTS>
+{
+    /// Copies `frame` and its length into the input slot and publishes both.
+    /// Nothing is published if it doesn't fit.
+    pub fn write_frame(&mut self, frame: &[u8]) -> Result<(), FrameTooLong> {
+        self.input_buffer().set(frame)?;
+        self.publish();
+        Ok(())
+    }
+}
+
+impl<'a, const B: usize, N: Notifier, const SLOTS: usize>
+    BufferReader<'a, FramedBytes<B>, N, SLOTS>
+{
+    /// The latest frame, exactly as long as it was written.
+    pub fn read_frame(&mut self) -> &[u8] {
+        self.read().as_slice()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SpinNotifier, TripleBuffer};
+
+    #[test]
+    fn frames_keep_their_length() {
+        let buffer = TripleBuffer::new(FramedBytes::<8>::new);
+        let mut writer = buffer.get_writer();
+        let mut reader = buffer.get_reader();
+
+        assert_eq!(reader.read_frame(), b"");
+        writer.write_frame(b"abc").unwrap();
+        assert_eq!(reader.read_frame(), b"abc");
+        writer.write_frame(b"").unwrap();
+        assert_eq!(reader.read_frame(), b"");
+        writer.write_frame(b"12345678").unwrap();
+        assert_eq!(reader.read_frame(), b"12345678");
+
+        assert_eq!(
+            writer.write_frame(b"123456789"),
+            Err(FrameTooLong {
+                len: 9,
+                capacity: 8
+            })
+        );
+        assert!(!reader.updated(), "oversized frame published");
+        assert_eq!(reader.read_frame(), b"12345678");
+    }
+
+    #[test]
+    fn rapidly_varying_lengths_are_never_torn() {
+        static FRAMES: TripleBuffer<FramedBytes<64>, SpinNotifier> = TripleBuffer::with_notifiers(
+            FramedBytes::new(),
+            FramedBytes::new(),
+            FramedBytes::new(),
+            SpinNotifier,
+            SpinNotifier,
+        );
+        let count = if cfg!(miri) { 200 } else { 50_000 };
+
+        let jh = std::thread::spawn(move || {
+            let mut writer = FRAMES.get_writer();
+            let mut frame = [0; 64];
+            for i in 1..=count {
+                // Length and contents both derive from `i`.
+                let len = i % 65;
+                frame[..len].fill(len as u8);
+                writer.write_frame(&frame[..len]).unwrap();
+            }
+        });
+
+        let mut reader = FRAMES.get_reader();
+        while !jh.is_finished() {
+            let frame = reader.read_frame();
+            assert!(
+                frame.iter().all(|&byte| byte as usize == frame.len()),
+                "length torn from data"
+            );
+        }
+        jh.join().unwrap();
+        assert_eq!(reader.read_frame().len(), count % 65);
+    }
+}
@@ -0,0 +1,143 @@
+use core::sync::atomic::{fence, AtomicU32, Ordering};
+
+use std::time::Duration;
+
+use crate::{ord, Backoff, Notifier};
+
+const MAX_SLEEP_US: u32 = 1000;
+
+/// Sleeps on a futex over a generation counter.
+///
+/// Every notification bumps the counter, so a waiter that sampled the old
+/// generation before re-checking its condition can never miss a wakeup: the
+/// kernel refuses to put it to sleep once the value has moved on. Nothing
+/// is bumped or woken while `waiters` is zero.
+pub struct FutexNotifier {
+    counter: AtomicU32,
+    waiters: AtomicU32,
+    #[cfg(test)]
+    wakes: AtomicU32,
+}
+
+impl FutexNotifier {
+    pub const fn new() -> Self {
+        Self {
+            counter: AtomicU32::new(0),
+            waiters: AtomicU32::new(0),
+            #[cfg(test)]
+            wakes: AtomicU32::new(0),
+        }
+    }
+}
+
+impl Default for FutexNotifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Notifier for FutexNotifier {
+    fn wait(&self, until: impl Fn() -> bool) {
+        if until() {
+            return;
+        }
+        self.waiters.fetch_add(1, Ordering::SeqCst);
+        fence(Ordering::SeqCst);
+        loop {
+            let generation = self.counter.load(ord::acquire());
+            if until() {
+                break;
+            }
+            atomic_wait::wait(&self.counter, generation);
+        }
+        self.waiters.fetch_sub(1, ord::release());
+    }
+
+    /// `atomic_wait` has no timed wait, so this polls with a `Backoff` and
+    /// then sleeps in slices of at most a millisecond.
+    fn wait_timeout(&self, until: impl Fn() -> bool, timeout_us: u32) {
+        let mut backoff = Backoff::new();
+        while !backoff.is_completed() {
+            if until() {
+                return;
+            }
+            backoff.snooze();
+        }
+        if !until() {
+            std::thread::sleep(Duration::from_micros(timeout_us.min(MAX_SLEEP_US).into()));
+        }
+    }
+
+    #[inline]
+    fn notify(&self) {
+        if self.waiters.load(Ordering::SeqCst) == 0 {
+            return;
+        }
+        #[cfg(test)]
+        self.wakes.fetch_add(1, ord::relaxed());
+        self.counter.fetch_add(1, ord::release());
+        atomic_wait::wake_all(&self.counter);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TripleBuffer;
+    use std::sync::atomic::{AtomicBool, AtomicUsize};
+
+    #[test]
+    fn idle_waiter_does_not_spin() {
+        static NOTIFIER: FutexNotifier = FutexNotifier::new();
+        static READY: AtomicBool = AtomicBool::new(false);
+        static CHECKS: AtomicUsize = AtomicUsize::new(0);
+
+        let jh = std::thread::spawn(|| {
+            NOTIFIER.wait(|| {
+                CHECKS.fetch_add(1, Ordering::Relaxed);
+                READY.load(Ordering::Acquire)
+            })
+        });
+
+        std::thread::sleep(Duration::from_millis(200));
+        READY.store(true, Ordering::Release);
+        NOTIFIER.notify();
+        jh.join().unwrap();
+
+        assert!(CHECKS.load(Ordering::Relaxed) < 10);
+    }
+
+    #[test]
+    fn wakes_only_blocked_waiters() {
+        static BUFFER: TripleBuffer<u32, FutexNotifier> = TripleBuffer::with_notifiers(
+            0,
+            0,
+            0,
+            FutexNotifier::new(),
+            FutexNotifier::new(),
+        );
+        let count = 1000;
+        let mut writer = BUFFER.get_writer();
+        let mut reader = BUFFER.get_reader();
+        for i in 1..=count {
+            writer.write(i);
+            assert!(reader.update());
+        }
+        assert_eq!(BUFFER.reader_notifier.wakes.load(Ordering::Relaxed), 0);
+        assert_eq!(BUFFER.writer_notifier.wakes.load(Ordering::Relaxed), 0);
+
+        let jh = std::thread::spawn(move || {
+            for i in 1..=count {
+                assert_eq!(*reader.read_blocking(), i);
+            }
+        });
+        for i in 1..=count {
+            while !writer.consumed() || BUFFER.reader_notifier.waiters.load(Ordering::SeqCst) == 0 {
+                std::thread::yield_now();
+            }
+            writer.write(i);
+        }
+        jh.join().unwrap();
+        assert_eq!(BUFFER.reader_notifier.wakes.load(Ordering::Relaxed), count);
+    }
+}
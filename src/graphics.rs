@@ -0,0 +1,203 @@
+//! An `embedded-graphics` draw target over the writer's input slot, for the
+//! `embedded-graphics` feature: a UI renders straight into the frame it
+//! then publishes to the display task.
+
+use core::convert::Infallible;
+use core::fmt;
+
+use embedded_graphics_core::draw_target::DrawTarget;
+use embedded_graphics_core::geometry::{OriginDimensions, Size};
+use embedded_graphics_core::pixelcolor::PixelColor;
+use embedded_graphics_core::Pixel;
+
+use crate::{BufferWriter, Notifier};
+
+/// A frame's pixel count didn't match the dimensions asked for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FrameSizeMismatch {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: usize,
+}
+
+impl fmt::Display for FrameSizeMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}x{} target over a frame of {} pixels",
+            self.width, self.height, self.pixels
+        )
+    }
+}
+
+impl core::error::Error for FrameSizeMismatch {}
+
+/// Draws into the input slot, row-major, from `BufferWriter::draw_target`;
+/// `flush` publishes it. Pixels outside the frame are dropped, as
+/// `DrawTarget` asks.
+///
+/// The slot holds whatever frame it last held, not necessarily the last one
+/// published, so either `clear` it or redraw all of it.
+pub struct FrameTarget<'w, 'a, C, T, N: Notifier, const SLOTS: usize = 3> {
+    writer: &'w mut BufferWriter<'a, T, N, SLOTS>,
+    width: u32,
+    height: u32,
+    _color: core::marker::PhantomData<C>,
+}
+
+impl<'a, T, N: Notifier, const SLOTS: usize> BufferWriter<'a, T, N, SLOTS> {
+    /// A `width` by `height` draw target over the input slot, unless the
+    /// slot holds a different number of pixels.
+    pub fn draw_target<C: PixelColor>(
+        &mut self,
+        width: u32,
+        height: u32,
+    ) -> Result<FrameTarget<'_, 'a, C, T, N, SLOTS>, FrameSizeMismatch>
+    where
+        T: AsMut<[C]>,
+    {
+        let pixels = self.input_buffer().as_mut().len();
+        let area = (width as usize).checked_mul(height as usize);
+        if area != Some(pixels) {
+            return Err(FrameSizeMismatch {
+                width,
+                height,
+                pixels,
+            });
+        }
+        Ok(FrameTarget {
+            writer: self,
+            width,
+            height,
+            _color: core::marker::PhantomData,
+        })
+    }
+}
+
+impl<'w, 'a, C, T, N: Notifier, const SLOTS: usize> FrameTarget<'w, 'a, C, T, N, SLOTS> {
+    /// Publishes the frame; returns what `publish` does.
+    pub fn flush(self) -> bool {
+        self.writer.publish()
+    }
+}
+
+impl<'w, 'a, C, T, N: Notifier, const SLOTS: usize> OriginDimensions
+    for FrameTarget<'w, 'a, C, T, N, SLOTS>
+{
+    fn size(&self) -> Size {
+        Size::new(self.width, self.height)
+    }
+}
+
+impl<'w, 'a, C: PixelColor, T: AsMut<[C]>, N: Notifier, const SLOTS: usize> DrawTarget
+    for FrameTarget<'w, 'a, C, T, N, SLOTS>
+{
+    type Color = C;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Infallible>
+    where
+        I: IntoIterator<Item = Pixel<C>>,
+    {
+        let (width, height) = (self.width, self.height);
+        let frame = self.writer.input_buffer().as_mut();
+        for Pixel(point, color) in pixels {
+            let (Ok(x), Ok(y)) = (u32::try_from(point.x), u32::try_from(point.y)) else {
+                continue;
+            };
+            if x < width && y < height {
+                frame[y as usize * width as usize + x as usize] = color;
+            }
+        }
+        Ok(())
+    }
+
+    fn clear(&mut self, color: C) -> Result<(), Infallible> {
+        self.writer.input_buffer().as_mut().fill(color);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics::pixelcolor::{BinaryColor, Rgb565};
+    use embedded_graphics::prelude::*;
+    use embedded_graphics::primitives::{Line, PrimitiveStyle, Rectangle};
+    use std::boxed::Box;
+
+    use super::*;
+    use crate::TripleBuffer;
+
+    #[test]
+    fn mismatched_dimensions_are_rejected() {
+        let buffer = TripleBuffer::new(|| [BinaryColor::Off; 12]);
+        let mut writer = buffer.get_writer();
+        let mismatch = |width, height| FrameSizeMismatch {
+            width,
+            height,
+            pixels: 12,
+        };
+        assert_eq!(writer.draw_target(4, 4).err(), Some(mismatch(4, 4)));
+        assert_eq!(
+            writer.draw_target(u32::MAX, u32::MAX).err(),
+            Some(mismatch(u32::MAX, u32::MAX))
+        );
+        assert!(writer.draw_target::<BinaryColor>(4, 3).is_ok());
+    }
+
+    #[test]
+    fn primitives_are_published_on_flush() {
+        let buffer = TripleBuffer::new(|| [Rgb565::BLACK; 4 * 3]);
+        let mut writer = buffer.get_writer();
+        let mut reader = buffer.get_reader();
+
+        let mut target = writer.draw_target(4, 3).unwrap();
+        target.clear(Rgb565::BLUE).unwrap();
+        Rectangle::new(Point::new(1, 1), Size::new(2, 2))
+            .into_styled(PrimitiveStyle::with_fill(Rgb565::RED))
+            .draw(&mut target)
+            .unwrap();
+        // Clipped: only (3, 0) is inside the frame.
+        Pixel(Point::new(-1, 0), Rgb565::GREEN)
+            .draw(&mut target)
+            .unwrap();
+        Pixel(Point::new(3, 0), Rgb565::GREEN)
+            .draw(&mut target)
+            .unwrap();
+        Pixel(Point::new(4, 0), Rgb565::GREEN)
+            .draw(&mut target)
+            .unwrap();
+        assert!(!reader.updated(), "published before flush");
+        target.flush();
+
+        let (b, r, g) = (Rgb565::BLUE, Rgb565::RED, Rgb565::GREEN);
+        #[rustfmt::skip]
+        let expected = [
+            b, b, b, g,
+            b, r, r, b,
+            b, r, r, b,
+        ];
+        assert_eq!(*reader.read(), expected);
+    }
+
+    #[test]
+    fn boxed_frames_work_as_targets() {
+        let buffer = TripleBuffer::new(|| Box::from([BinaryColor::Off; 3 * 3]));
+        let mut writer = buffer.get_writer();
+        let mut reader = buffer.get_reader();
+
+        let mut target = writer.draw_target(3, 3).unwrap();
+        Line::new(Point::new(0, 0), Point::new(2, 2))
+            .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+            .draw(&mut target)
+            .unwrap();
+        target.flush();
+
+        let lit: Box<[bool]> = reader.read().iter().map(|&c| c.is_on()).collect();
+        assert_eq!(
+            *lit,
+            [true, false, false, false, true, false, false, false, true]
+        );
+    }
+}
@@ -0,0 +1,194 @@
+//! Who holds each handle, for the `debug-holders` feature: recorded when a
+//! handle is acquired, cleared when it is dropped, and named in the panic of
+//! a second `get_reader`/`get_writer`.
+
+use core::ptr;
+
+use crate::atomic::AtomicPtr;
+use crate::ord;
+
+/// The holder of a handle, from `BufferState`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Holder {
+    /// The thread that acquired the handle, without an id provider.
+    #[cfg(feature = "std")]
+    Thread(std::thread::ThreadId),
+    /// From the provider set with `set_holder_id_provider`, e.g. a core or
+    /// task number.
+    Id(u32),
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Holder {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        match self {
+            #[cfg(feature = "std")]
+            Self::Thread(_) => defmt::write!(f, "Thread(..)"),
+            Self::Id(id) => defmt::write!(f, "Id({=u32})", id),
+        }
+    }
+}
+
+static PROVIDER: AtomicPtr<()> = AtomicPtr::new(ptr::null_mut());
+
+/// Installs `provider` to identify whoever acquires a handle from then on,
+/// for every buffer. Without one, holders are recorded by thread with `std`
+/// and not at all without. `None` removes it.
+pub fn set_holder_id_provider(provider: Option<fn() -> u32>) {
+    let provider = provider.map_or(ptr::null_mut(), |provider| provider as *mut ());
+    PROVIDER.store(provider, ord::release());
+}
+
+fn provided_id() -> Option<u32> {
+    let provider = PROVIDER.load(ord::acquire());
+    if provider.is_null() {
+        return None;
+    }
+    // Only ever set from a `fn() -> u32` in `set_holder_id_provider`.
+    let provider = unsafe { core::mem::transmute::<*mut (), fn() -> u32>(provider) };
+    Some(provider())
+}
+
+/// One handle's holder. With `std` it keeps the whole `Thread` so the panic
+/// can name it; acquisition and drop are the only writers, so the lock is
+/// uncontended.
+pub(crate) struct HolderCell {
+    #[cfg(feature = "std")]
+    holder: std::sync::Mutex<Option<(Holder, Option<std::thread::Thread>)>>,
+    // `HELD | id`, or 0 while nobody holds the handle.
+    #[cfg(not(feature = "std"))]
+    holder: crate::atomic::AtomicU64,
+}
+
+#[cfg(not(feature = "std"))]
+const HELD: u64 = 1 << 32;
+
+impl HolderCell {
+    pub(crate) const fn new() -> Self {
+        Self {
+            #[cfg(feature = "std")]
+            holder: std::sync::Mutex::new(None),
+            #[cfg(not(feature = "std"))]
+            holder: crate::atomic::AtomicU64::new(0),
+        }
+    }
+
+    pub(crate) fn acquire(&self) {
+        #[cfg(feature = "std")]
+        {
+            let holder = match provided_id() {
+                Some(id) => (Holder::Id(id), None),
+                None => {
+                    let thread = std::thread::current();
+                    (Holder::Thread(thread.id()), Some(thread))
+                }
+            };
+            *self.lock() = Some(holder);
+        }
+        #[cfg(not(feature = "std"))]
+        {
+            let holder = provided_id().map_or(0, |id| HELD | id as u64);
+            self.holder.store(holder, ord::relaxed());
+        }
+    }
+
+    pub(crate) fn release(&self) {
+        #[cfg(feature = "std")]
+        {
+            *self.lock() = None;
+        }
+        #[cfg(not(feature = "std"))]
+        self.holder.store(0, ord::relaxed());
+    }
+
+    pub(crate) fn get(&self) -> Option<Holder> {
+        #[cfg(feature = "std")]
+        return self.lock().as_ref().map(|(holder, _)| *holder);
+        #[cfg(not(feature = "std"))]
+        {
+            let holder = self.holder.load(ord::relaxed());
+            (holder & HELD != 0).then_some(Holder::Id(holder as u32))
+        }
+    }
+
+    #[cfg(feature = "std")]
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<(Holder, Option<std::thread::Thread>)>> {
+        // Nothing panics while holding it, but don't turn one panic into two.
+        self.holder
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// The panic of `get_reader`/`get_writer` for `handle`, naming its
+    /// holder if it still has one.
+    #[cold]
+    pub(crate) fn already_exists(&self, handle: &str) -> ! {
+        #[cfg(feature = "std")]
+        if let Some((holder, thread)) = self.lock().clone() {
+            match thread.as_ref().and_then(|thread| thread.name()) {
+                Some(name) => {
+                    panic!("{handle} already exists, held by thread '{name}' ({holder:?})")
+                }
+                None => panic!("{handle} already exists, held by {holder:?}"),
+            }
+        }
+        #[cfg(not(feature = "std"))]
+        if let Some(holder) = self.get() {
+            panic!("{handle} already exists, held by {holder:?}");
+        }
+        panic!("{handle} already exists")
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::TripleBuffer;
+    use std::string::String;
+    use std::sync::mpsc;
+
+    #[test]
+    fn holders_are_named_in_state_and_panic() {
+        static BUFFER: TripleBuffer<u32> = TripleBuffer::new_const(0, 0, 0);
+        let (held, hold) = (mpsc::channel(), mpsc::channel::<()>());
+        let (held_tx, held_rx) = held;
+        let (release_tx, release_rx) = hold;
+
+        let jh = std::thread::Builder::new()
+            .name("frame-producer".into())
+            .spawn(move || {
+                let writer = BUFFER.get_writer();
+                held_tx.send(std::thread::current().id()).unwrap();
+                release_rx.recv().unwrap();
+                drop(writer);
+            })
+            .unwrap();
+        let id = held_rx.recv().unwrap();
+
+        assert_eq!(BUFFER.state().writer_holder, Some(Holder::Thread(id)));
+        assert_eq!(BUFFER.state().reader_holder, None);
+        let payload = std::panic::catch_unwind(|| drop(BUFFER.get_writer())).unwrap_err();
+        let message = payload.downcast_ref::<String>().unwrap();
+        assert_eq!(
+            *message,
+            format!("Writer already exists, held by thread 'frame-producer' (Thread({id:?}))")
+        );
+
+        release_tx.send(()).unwrap();
+        jh.join().unwrap();
+        assert_eq!(BUFFER.state().writer_holder, None);
+
+        // The provider is global, so this stays in the same test.
+        set_holder_id_provider(Some(|| 7));
+        let _reader = BUFFER.get_reader();
+        set_holder_id_provider(None);
+
+        assert_eq!(BUFFER.state().reader_holder, Some(Holder::Id(7)));
+        let payload = std::panic::catch_unwind(|| drop(BUFFER.get_reader())).unwrap_err();
+        assert_eq!(
+            payload.downcast_ref::<String>().unwrap(),
+            "Reader already exists, held by Id(7)"
+        );
+    }
+}
@@ -0,0 +1,156 @@
+use core::cell::UnsafeCell;
+use core::marker::PhantomData;
+use core::ptr;
+
+use crate::atomic::{AtomicPtr, AtomicU8};
+use crate::ord;
+
+/// Passed to the `on_publish` hook at the end of every `publish()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub struct PublishEvent {
+    /// The published frame replaced one the reader never took.
+    pub overwrote: bool,
+    /// Sequence number of the published frame, as `last_seq` reports it.
+    #[cfg(feature = "seq")]
+    pub seq: u64,
+}
+
+/// Passed to the `on_consume` hook when `update()` takes a published frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub struct ConsumeEvent {
+    /// Index of the slot that just became the reader's output.
+    pub slot: usize,
+}
+
+/// Receives a buffer's publish and consume counts, e.g. to forward them to a
+/// metrics facade; installed with `set_recorder`.
+pub trait Recorder: Sync {
+    fn on_publish(&self, overwrote: bool);
+
+    fn on_consume(&self);
+}
+
+/// A plain `fn(&E)` stored in an atomic pointer; null means "no hook".
+pub(crate) struct Hook<E> {
+    hook: AtomicPtr<()>,
+    _event: PhantomData<fn(&E)>,
+}
+
+impl<E> Hook<E> {
+    pub(crate) const fn new() -> Self {
+        Self {
+            hook: AtomicPtr::new(ptr::null_mut()),
+            _event: PhantomData,
+        }
+    }
+
+    pub(crate) fn set(&self, hook: Option<fn(&E)>) {
+        let hook = hook.map_or(ptr::null_mut(), |hook| hook as *mut ());
+        self.hook.store(hook, ord::release());
+    }
+
+    #[inline]
+    pub(crate) fn fire(&self, event: impl FnOnce() -> E) {
+        let hook = self.hook.load(ord::relaxed());
+        if !hook.is_null() {
+            // Only ever set from a `fn(&E)` in `set`.
+            let hook = unsafe { core::mem::transmute::<*mut (), fn(&E)>(hook) };
+            hook(&event());
+        }
+    }
+
+    /// Like `fire`, but passes `event` to `fallback` when no hook is set.
+    #[cfg(any(feature = "paranoid", feature = "redundant-control"))]
+    pub(crate) fn fire_or(&self, event: E, fallback: fn(&E)) {
+        let hook = self.hook.load(ord::acquire());
+        let hook = if hook.is_null() {
+            fallback
+        } else {
+            // Only ever set from a `fn(&E)` in `set`.
+            unsafe { core::mem::transmute::<*mut (), fn(&E)>(hook) }
+        };
+        hook(&event);
+    }
+}
+
+/// A plain `fn(&mut T)` run on every slot the writer takes over for staging;
+/// null means "none".
+pub(crate) struct Recycler<T> {
+    recycler: AtomicPtr<()>,
+    _slot: PhantomData<fn(&mut T)>,
+}
+
+impl<T> Recycler<T> {
+    pub(crate) const fn new() -> Self {
+        Self {
+            recycler: AtomicPtr::new(ptr::null_mut()),
+            _slot: PhantomData,
+        }
+    }
+
+    pub(crate) fn set(&self, recycler: Option<fn(&mut T)>) {
+        let recycler = recycler.map_or(ptr::null_mut(), |recycler| recycler as *mut ());
+        self.recycler.store(recycler, ord::release());
+    }
+
+    #[inline]
+    pub(crate) fn run(&self, slot: *mut T) {
+        let recycler = self.recycler.load(ord::acquire());
+        if !recycler.is_null() {
+            // Only ever set from a `fn(&mut T)` in `set`.
+            let recycler = unsafe { core::mem::transmute::<*mut (), fn(&mut T)>(recycler) };
+            recycler(unsafe { &mut *slot });
+        }
+    }
+}
+
+const EMPTY: u8 = 0;
+const SETTING: u8 = 1;
+const READY: u8 = 2;
+
+/// A `&'static dyn Recorder` set at most once: the fat pointer can't be
+/// swapped atomically, so unlike the hooks it is never replaced, and the
+/// hot path only has to check `state` before the call.
+pub(crate) struct RecorderCell {
+    state: AtomicU8,
+    recorder: UnsafeCell<Option<&'static dyn Recorder>>,
+}
+
+// `recorder` is only written while `state` is `SETTING`, by the one caller
+// that got there, and only read once it is `READY`.
+unsafe impl Sync for RecorderCell {}
+
+impl RecorderCell {
+    pub(crate) const fn new() -> Self {
+        Self {
+            state: AtomicU8::new(EMPTY),
+            recorder: UnsafeCell::new(None),
+        }
+    }
+
+    pub(crate) fn set(&self, recorder: &'static dyn Recorder) -> Result<(), &'static dyn Recorder> {
+        if self
+            .state
+            .compare_exchange(EMPTY, SETTING, ord::acquire(), ord::relaxed())
+            .is_err()
+        {
+            return Err(recorder);
+        }
+        unsafe { *self.recorder.get() = Some(recorder) };
+        self.state.store(READY, ord::release());
+        Ok(())
+    }
+
+    #[inline]
+    pub(crate) fn get(&self) -> Option<&'static dyn Recorder> {
+        if self.state.load(ord::acquire()) == READY {
+            unsafe { *self.recorder.get() }
+        } else {
+            None
+        }
+    }
+}
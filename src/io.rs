@@ -0,0 +1,122 @@
+//! `std::io` adapters for byte frames, e.g. `[u8; N]` or `Box<[u8]>`: a
+//! buffer spliced between a `Read` source and a `Write` sink, one whole
+//! frame at a time.
+
+use std::io::{self, ErrorKind, Read, Write};
+
+use crate::{BufferReader, BufferWriter, Notifier};
+
+impl<'a, T: AsMut<[u8]>, N: Notifier, const SLOTS: usize> BufferWriter<'a, T, N, SLOTS> {
+    /// Reads exactly one frame's worth of bytes from `source` into the
+    /// input slot and publishes it, retrying on `Interrupted` and after
+    /// short reads. Returns the frame's length, or 0 if `source` was at EOF
+    /// before the first byte. EOF later in the frame is `UnexpectedEof`;
+    /// then, and on any other error, nothing is published, though the
+    /// input slot may have been partly overwritten.
+    pub fn fill_from(&mut self, source: &mut impl Read) -> io::Result<usize> {
+        let frame = self.input_buffer().as_mut();
+        let mut filled = 0;
+        while filled < frame.len() {
+            match source.read(&mut frame[filled..]) {
+                Ok(0) if filled == 0 => return Ok(0),
+                Ok(0) => {
+                    return Err(io::Error::new(
+                        ErrorKind::UnexpectedEof,
+                        "source ended mid-frame",
+                    ))
+                }
+                Ok(read) => filled += read,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        self.publish();
+        Ok(filled)
+    }
+}
+
+impl<'a, T: AsRef<[u8]>, N: Notifier, const SLOTS: usize> BufferReader<'a, T, N, SLOTS> {
+    /// Updates and writes the whole latest frame to `sink`, as `write_all`
+    /// does; returns its length. On an error the frame is still taken.
+    pub fn copy_to(&mut self, sink: &mut impl Write) -> io::Result<usize> {
+        let frame = self.read().as_ref();
+        sink.write_all(frame)?;
+        Ok(frame.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::boxed::Box;
+    use std::io::Cursor;
+    use std::vec::Vec;
+
+    use super::*;
+    use crate::{NBuffer, TripleBuffer};
+
+    /// Hands out at most 3 bytes per read, failing with `Interrupted` on
+    /// every other call.
+    struct Chunky<R> {
+        inner: R,
+        calls: usize,
+    }
+
+    impl<R: Read> Read for Chunky<R> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.calls += 1;
+            if self.calls.is_multiple_of(2) {
+                return Err(ErrorKind::Interrupted.into());
+            }
+            let len = buf.len().min(3);
+            self.inner.read(&mut buf[..len])
+        }
+    }
+
+    #[test]
+    fn frames_are_spliced_from_a_reader_to_a_writer() {
+        let buffer = TripleBuffer::new(|| [0u8; 8]);
+        let mut writer = buffer.get_writer();
+        let mut reader = buffer.get_reader();
+        let mut source = Chunky {
+            inner: Cursor::new((0..16).collect::<Vec<u8>>()),
+            calls: 0,
+        };
+        let mut sink = Vec::new();
+
+        for _ in 0..2 {
+            assert_eq!(writer.fill_from(&mut source).unwrap(), 8);
+            assert_eq!(reader.copy_to(&mut sink).unwrap(), 8);
+        }
+        assert_eq!(sink, (0..16).collect::<Vec<u8>>());
+        assert_eq!(writer.fill_from(&mut source).unwrap(), 0);
+        assert!(!reader.updated(), "published at EOF");
+    }
+
+    #[test]
+    fn eof_mid_frame_publishes_nothing() {
+        let buffer = NBuffer::<Box<[u8]>, 4>::new_boxed_slices(4, 0);
+        let mut writer = buffer.get_writer();
+        let mut reader = buffer.get_reader();
+        let mut source = Chunky {
+            inner: Cursor::new([1, 2, 3, 4, 5, 6]),
+            calls: 0,
+        };
+
+        assert_eq!(writer.fill_from(&mut source).unwrap(), 4);
+        let error = writer.fill_from(&mut source).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::UnexpectedEof);
+        let mut sink = Cursor::new(Vec::new());
+        assert_eq!(reader.copy_to(&mut sink).unwrap(), 4);
+        assert_eq!(sink.into_inner(), [1, 2, 3, 4]);
+        assert!(!reader.updated());
+    }
+
+    #[test]
+    fn a_full_sink_fails_the_copy() {
+        let buffer = TripleBuffer::new(|| [7u8; 4]);
+        let mut reader = buffer.get_reader();
+        let mut sink = [0u8; 3];
+        let error = reader.copy_to(&mut &mut sink[..]).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::WriteZero);
+    }
+}
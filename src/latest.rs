@@ -0,0 +1,94 @@
+//! Traits over latest-value channels, so code that publishes or reads the
+//! latest `T` works with a `TripleBuffer`'s handles and a `SeqLock`'s alike;
+//! which one to pick is down to the tradeoffs in the `SeqLock` docs.
+
+use crate::{BufferReader, BufferWriter, Notifier, SeqLockReader, SeqLockWriter};
+
+pub trait LatestValue<T> {
+    /// The latest value, new or not.
+    fn latest(&mut self) -> T;
+
+    /// Whether a value was published since the last `latest`.
+    fn changed(&mut self) -> bool;
+
+    /// The latest value, if it is new since the last `latest`.
+    fn latest_if_changed(&mut self) -> Option<T> {
+        if self.changed() {
+            Some(self.latest())
+        } else {
+            None
+        }
+    }
+}
+
+pub trait PublishLatest<T> {
+    /// Makes `value` the latest value.
+    fn publish_latest(&mut self, value: T);
+}
+
+impl<'a, T: Copy, N: Notifier, const SLOTS: usize> LatestValue<T>
+    for BufferReader<'a, T, N, SLOTS>
+{
+    fn latest(&mut self) -> T {
+        *self.read()
+    }
+
+    fn changed(&mut self) -> bool {
+        self.updated()
+    }
+
+    fn latest_if_changed(&mut self) -> Option<T> {
+        self.update().then(|| *self.output_buffer())
+    }
+}
+
+impl<'a, T, N: Notifier, const SLOTS: usize> PublishLatest<T> for BufferWriter<'a, T, N, SLOTS> {
+    fn publish_latest(&mut self, value: T) {
+        self.write(value);
+    }
+}
+
+impl<'a, T: Copy> LatestValue<T> for SeqLockReader<'a, T> {
+    fn latest(&mut self) -> T {
+        self.read()
+    }
+
+    fn changed(&mut self) -> bool {
+        self.updated()
+    }
+}
+
+impl<'a, T: Copy> PublishLatest<T> for SeqLockWriter<'a, T> {
+    fn publish_latest(&mut self, value: T) {
+        self.write(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SeqLock, TripleBuffer};
+
+    fn relay(from: &mut impl LatestValue<u32>, to: &mut impl PublishLatest<u32>) -> bool {
+        from.latest_if_changed()
+            .map(|value| to.publish_latest(value + 1))
+            .is_some()
+    }
+
+    #[test]
+    fn both_backends_behave_alike() {
+        let buffer = TripleBuffer::new(|| 0);
+        let lock = SeqLock::new(0);
+        let (mut buffer_writer, mut buffer_reader) = (buffer.get_writer(), buffer.get_reader());
+        let (mut lock_writer, mut lock_reader) = (lock.get_writer(), lock.get_reader());
+
+        assert!(!relay(&mut buffer_reader, &mut lock_writer));
+        buffer_writer.publish_latest(1);
+        assert!(relay(&mut buffer_reader, &mut lock_writer));
+        assert!(!relay(&mut buffer_reader, &mut lock_writer));
+        assert!(relay(&mut lock_reader, &mut buffer_writer));
+        assert!(!lock_reader.changed());
+        assert_eq!(buffer_reader.latest(), 3);
+        assert_eq!(lock_reader.latest(), 2);
+    }
+}
@@ -0,0 +1,301 @@
+use core::cell::UnsafeCell;
+use core::mem::{align_of, offset_of, size_of};
+
+use crate::padded::CachePadded;
+use crate::{
+    event_log, hook, stats, AtomicBackBufferInfo, AtomicControlWord, AtomicFlag, ConsumeEvent,
+    NBuffer, PublishEvent,
+};
+
+/// Version of the layouts that `layout_fingerprint` covers, hashed into
+/// every fingerprint. Bumped when their meaning changes in a way sizes and
+/// offsets don't show, e.g. the bits of a control word.
+pub const LAYOUT_VERSION: u32 = 1;
+
+/// FNV-1a over `u64`s: the same on every run and host, unlike `Hash`.
+pub(crate) struct Fingerprint(u64);
+
+impl Fingerprint {
+    pub(crate) const fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325).add(LAYOUT_VERSION as u64)
+    }
+
+    pub(crate) const fn add(self, value: u64) -> Self {
+        let bytes = value.to_le_bytes();
+        let mut hash = self.0;
+        let mut i = 0;
+        while i < bytes.len() {
+            hash ^= bytes[i] as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+            i += 1;
+        }
+        Self(hash)
+    }
+
+    pub(crate) const fn finish(self) -> u64 {
+        self.0
+    }
+}
+
+/// Where an `NBuffer` puts its slots and control words, in bytes from the
+/// start of the struct, from `layout()`. The field order is the compiler's
+/// and may differ between targets, feature sets and `T`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BufferLayout<const SLOTS: usize> {
+    pub size: usize,
+    pub align: usize,
+    pub slots: [usize; SLOTS],
+    pub back_info: usize,
+    /// Under `packed-control` the input and output slots live in the
+    /// control word, so these two are `back_info` as well.
+    pub input_idx: usize,
+    pub output_idx: usize,
+    /// Bytes that belong to no field.
+    pub padding: usize,
+}
+
+impl<T, const SLOTS: usize, N> NBuffer<T, SLOTS, N> {
+    pub const fn layout() -> BufferLayout<SLOTS> {
+        let buffers = offset_of!(Self, buffers);
+        let mut slots = [0; SLOTS];
+        let mut i = 0;
+        while i < SLOTS {
+            slots[i] = buffers + i * size_of::<T>();
+            i += 1;
+        }
+        BufferLayout {
+            size: size_of::<Self>(),
+            align: align_of::<Self>(),
+            slots,
+            back_info: offset_of!(Self, back_info),
+            #[cfg(not(feature = "packed-control"))]
+            input_idx: offset_of!(Self, input_idx),
+            #[cfg(not(feature = "packed-control"))]
+            output_idx: offset_of!(Self, output_idx),
+            #[cfg(feature = "packed-control")]
+            input_idx: offset_of!(Self, back_info),
+            #[cfg(feature = "packed-control")]
+            output_idx: offset_of!(Self, back_info),
+            padding: size_of::<Self>() - Self::fields_size(),
+        }
+    }
+
+    /// Hash of `layout()`, the frame's size and alignment, the control
+    /// word's width and `LAYOUT_VERSION`: two builds that agree on it agree
+    /// on where everything `layout()` lists is. Frame types with the same
+    /// size and alignment share a fingerprint.
+    pub const fn layout_fingerprint() -> u64 {
+        let layout = Self::layout();
+        let mut hash = Fingerprint::new()
+            .add(layout.size as u64)
+            .add(layout.align as u64)
+            .add(size_of::<T>() as u64)
+            .add(align_of::<T>() as u64)
+            .add(size_of::<AtomicControlWord>() as u64)
+            .add(SLOTS as u64);
+        let mut i = 0;
+        while i < SLOTS {
+            hash = hash.add(layout.slots[i] as u64);
+            i += 1;
+        }
+        hash.add(layout.back_info as u64)
+            .add(layout.input_idx as u64)
+            .add(layout.output_idx as u64)
+            .finish()
+    }
+
+    /// Offset of slot `i`; panics unless `i < SLOTS`.
+    pub const fn slot_offset(i: usize) -> usize {
+        Self::layout().slots[i]
+    }
+
+    // Mirrors the fields of `NBuffer`, including their `cfg`s.
+    const fn fields_size() -> usize {
+        #[allow(unused_mut)]
+        let mut size = size_of::<UnsafeCell<[T; SLOTS]>>()
+            + size_of::<CachePadded<AtomicControlWord>>()
+            + size_of::<[AtomicBackBufferInfo; SLOTS]>()
+            + size_of::<AtomicBackBufferInfo>()
+            + 3 * size_of::<AtomicFlag>()
+            + 2 * size_of::<N>()
+            + size_of::<hook::Hook<PublishEvent>>()
+            + size_of::<hook::Hook<ConsumeEvent>>()
+            + size_of::<hook::Recycler<T>>()
+            + size_of::<hook::RecorderCell>()
+            + size_of::<stats::Counters>()
+            + size_of::<event_log::EventLog>();
+        #[cfg(feature = "debug-holders")]
+        {
+            size += 2 * size_of::<crate::holders::HolderCell>();
+        }
+        #[cfg(feature = "watermarks")]
+        {
+            size += size_of::<crate::watermark::Marks>();
+        }
+        #[cfg(feature = "seq")]
+        {
+            size += size_of::<[crate::atomic::AtomicU64; SLOTS]>()
+                + size_of::<crate::atomic::AtomicU64>();
+        }
+        #[cfg(feature = "meta")]
+        {
+            size += size_of::<[crate::atomic::AtomicU32; SLOTS]>()
+                + size_of::<crate::atomic::AtomicU32>();
+        }
+        #[cfg(feature = "crc")]
+        {
+            size += size_of::<crate::crc::Crcs<T, SLOTS>>();
+        }
+        #[cfg(all(feature = "eventfd", target_os = "linux"))]
+        {
+            size += size_of::<crate::eventfd::EventFd>();
+        }
+        #[cfg(not(feature = "packed-control"))]
+        {
+            size += 2 * size_of::<AtomicBackBufferInfo>();
+        }
+        size
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{QuadBuffer, TripleBuffer};
+    use core::ptr::addr_of;
+
+    fn offset<F>(buffer: *const u8, field: *const F) -> usize {
+        field as usize - buffer as usize
+    }
+
+    fn measured<T, const SLOTS: usize>(buffer: &NBuffer<T, SLOTS>) -> BufferLayout<SLOTS> {
+        let start = (buffer as *const NBuffer<T, SLOTS>).cast::<u8>();
+        let slots = buffer.buffers.get().cast::<T>();
+        BufferLayout {
+            size: size_of_val(buffer),
+            align: align_of_val(buffer),
+            slots: core::array::from_fn(|i| offset(start, unsafe { slots.add(i) })),
+            back_info: offset(start, addr_of!(buffer.back_info)),
+            #[cfg(not(feature = "packed-control"))]
+            input_idx: offset(start, addr_of!(buffer.input_idx)),
+            #[cfg(not(feature = "packed-control"))]
+            output_idx: offset(start, addr_of!(buffer.output_idx)),
+            #[cfg(feature = "packed-control")]
+            input_idx: offset(start, addr_of!(buffer.back_info)),
+            #[cfg(feature = "packed-control")]
+            output_idx: offset(start, addr_of!(buffer.back_info)),
+            padding: size_of_val(buffer) - fields_size(buffer),
+        }
+    }
+
+    fn fields_size<T, const SLOTS: usize>(buffer: &NBuffer<T, SLOTS>) -> usize {
+        let b = buffer;
+        #[allow(unused_mut)]
+        let mut size = size_of_val(&b.buffers)
+            + size_of_val(&b.back_info)
+            + size_of_val(&b.spare)
+            + size_of_val(&b.spare_head)
+            + size_of_val(&b.retracted)
+            + size_of_val(&b.is_reader_exist)
+            + size_of_val(&b.is_writer_exist)
+            + size_of_val(&b.reader_notifier)
+            + size_of_val(&b.writer_notifier)
+            + size_of_val(&b.on_publish)
+            + size_of_val(&b.on_consume)
+            + size_of_val(&b.recycler)
+            + size_of_val(&b.recorder)
+            + size_of_val(&b.stats)
+            + size_of_val(&b.events);
+        #[cfg(not(feature = "packed-control"))]
+        {
+            size += size_of_val(&b.input_idx) + size_of_val(&b.output_idx);
+        }
+        #[cfg(feature = "debug-holders")]
+        {
+            size += size_of_val(&b.reader_holder) + size_of_val(&b.writer_holder);
+        }
+        #[cfg(feature = "watermarks")]
+        {
+            size += size_of_val(&b.watermarks);
+        }
+        #[cfg(feature = "seq")]
+        {
+            size += size_of_val(&b.seqs) + size_of_val(&b.last_seq);
+        }
+        #[cfg(feature = "meta")]
+        {
+            size += size_of_val(&b.metas) + size_of_val(&b.next_meta);
+        }
+        #[cfg(feature = "crc")]
+        {
+            size += size_of_val(&b.crcs);
+        }
+        #[cfg(all(feature = "eventfd", target_os = "linux"))]
+        {
+            size += size_of_val(&b.eventfd);
+        }
+        size
+    }
+
+    #[test]
+    fn fingerprint_hashes_fixed_inputs() {
+        // FNV-1a of the bytes of `LAYOUT_VERSION` and 1, as u64s: the same
+        // inputs give the same fingerprint on every run and build.
+        assert_eq!(Fingerprint::new().add(1).finish(), 0x581c_d0fa_58d9_9645);
+        assert_ne!(
+            Fingerprint::new().add(1).add(2).finish(),
+            Fingerprint::new().add(2).add(1).finish()
+        );
+    }
+
+    #[test]
+    fn fingerprint_follows_the_frame_type() {
+        const AT_COMPILE_TIME: u64 = TripleBuffer::<u32>::layout_fingerprint();
+        assert_eq!(AT_COMPILE_TIME, TripleBuffer::<u32>::layout_fingerprint());
+        // Same size and alignment, same layout.
+        assert_eq!(AT_COMPILE_TIME, TripleBuffer::<f32>::layout_fingerprint());
+        assert_ne!(AT_COMPILE_TIME, TripleBuffer::<u64>::layout_fingerprint());
+        assert_ne!(
+            AT_COMPILE_TIME,
+            TripleBuffer::<[u8; 4]>::layout_fingerprint()
+        );
+        assert_ne!(AT_COMPILE_TIME, QuadBuffer::<u32>::layout_fingerprint());
+    }
+
+    #[test]
+    fn layout_matches_measured_addresses() {
+        let bytes = TripleBuffer::new(|| 0u8);
+        assert_eq!(TripleBuffer::<u8>::layout(), measured(&bytes));
+        let words = QuadBuffer::new(|| 0u64);
+        assert_eq!(QuadBuffer::<u64>::layout(), measured(&words));
+        let odd = TripleBuffer::new(|| [0u16; 5]);
+        assert_eq!(TripleBuffer::<[u16; 5]>::layout(), measured(&odd));
+        assert_eq!(
+            TripleBuffer::<[u16; 5]>::slot_offset(2),
+            TripleBuffer::<[u16; 5]>::slot_offset(0) + 20
+        );
+    }
+
+    #[test]
+    fn padding_gives_the_back_info_a_line() {
+        let layout = TripleBuffer::<u8>::layout();
+        let padded = size_of::<CachePadded<AtomicControlWord>>();
+        if !cfg!(feature = "padded") {
+            assert_eq!(padded, size_of::<AtomicControlWord>());
+            return;
+        }
+        // The cost: a whole line for one word, and the buffer aligned to a
+        // line, so even a `u8` buffer takes two.
+        let line = align_of::<CachePadded<AtomicControlWord>>();
+        assert!(line == 64 || line == 128);
+        assert_eq!(padded, line);
+        assert_eq!(layout.align, line);
+        assert!(layout.size >= 2 * line);
+        assert_eq!(layout.back_info % line, 0);
+        #[cfg(not(feature = "packed-control"))]
+        for word in [layout.input_idx, layout.output_idx] {
+            assert_ne!(word / line, layout.back_info / line);
+        }
+    }
+}
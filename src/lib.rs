@@ -1,324 +1,2418 @@
-#![cfg_attr(not(test), no_std)]
+#![cfg_attr(not(any(test, feature = "std")), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
 
 use core::cell::UnsafeCell;
-use portable_atomic::{AtomicBool, AtomicU8, Ordering};
+use core::fmt;
+use core::marker::PhantomData;
+use core::mem;
+use core::panic::{RefUnwindSafe, UnwindSafe};
+
+use atomic::Ordering;
+#[cfg(not(feature = "packed-control"))]
+use control::published;
+use control::{
+    is_dirty, publish_transition, retract_transition, update_transition, ControlWord, Word,
+    MAX_SLOTS, NO_SLOT,
+};
+use padded::CachePadded;
+
+mod aligned;
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+mod arc;
+#[cfg(feature = "rkyv")]
+mod archive;
+mod array;
+mod atomic;
+pub mod backoff;
+#[cfg(feature = "heapless")]
+mod bounded;
+#[cfg(feature = "alloc")]
+mod boxed;
+mod broadcast;
+#[cfg(feature = "bytemuck")]
+mod bytes;
+#[cfg(feature = "checked-rt")]
+mod checked;
+mod clock;
+#[cfg(feature = "postcard")]
+mod codec;
+mod control;
+#[cfg(feature = "crc")]
+mod crc;
+#[cfg(feature = "critical-section-notify")]
+mod cs;
+mod deadline;
+#[cfg(feature = "embedded-dma")]
+mod dma;
+mod double;
+mod duplex;
+#[cfg(feature = "bevy")]
+mod ecs;
+#[cfg(all(feature = "eventfd", target_os = "linux"))]
+mod eventfd;
+mod event_log;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod framed;
+#[cfg(feature = "futex")]
+mod futex;
+#[cfg(feature = "embedded-graphics")]
+mod graphics;
+#[cfg(feature = "debug-holders")]
+mod holders;
+mod hook;
+#[cfg(feature = "std")]
+mod io;
+pub mod latest;
+mod layout;
+mod lossless;
+mod mailbox;
+mod notify;
+mod ord;
+mod padded;
+#[cfg(feature = "paranoid")]
+mod paranoid;
+#[cfg(feature = "std")]
+mod park;
+#[cfg(all(kani, feature = "verification"))]
+mod proofs;
+#[cfg(feature = "redundant-control")]
+mod redundant;
+mod pump;
+mod revocable;
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "64"))]
+mod ring;
+#[cfg(feature = "rt-safe")]
+pub mod rt;
+#[cfg(feature = "rtt")]
+mod rtt;
+mod sample;
+mod seqlock;
+#[cfg(feature = "shared")]
+mod process;
+mod shared;
+mod stats;
+#[cfg(feature = "futures")]
+mod sink;
+pub mod snapshot;
+#[cfg(feature = "futures")]
+mod stream;
+mod sync;
+#[cfg(feature = "embassy-time")]
+mod timeout;
+#[cfg(feature = "tracing")]
+mod trace;
+mod unsync;
+#[cfg(feature = "watermarks")]
+mod watermark;
+#[cfg(feature = "async")]
+mod waker;
+#[cfg(feature = "watch-compat")]
+pub mod watch;
+#[cfg(feature = "cortex-m")]
+mod wfe;
+
+pub use aligned::{Align, Aligned, Alignment};
+pub use array::{ArrayReader, ArrayWriter, TripleBufferArray};
+pub use backoff::Backoff;
+#[cfg(feature = "alloc")]
+pub use boxed::LengthMismatch;
+pub use broadcast::{BroadcastReader, BroadcastTripleBuffer, BroadcastWriter};
+#[cfg(feature = "bytemuck")]
+pub use bytes::PodFrame;
+#[cfg(feature = "checked-rt")]
+pub use checked::{set_overwrite_policy, Overwrite, OverwritePolicy};
+#[cfg(feature = "std")]
+pub use clock::StdClock;
+pub use clock::{Clock, Stamped};
+#[cfg(all(feature = "crc", feature = "bytemuck"))]
+pub use crc::crc32_pod;
+#[cfg(feature = "crc")]
+pub use crc::{crc32, CrcMismatch};
+#[cfg(feature = "critical-section-notify")]
+pub use cs::CsNotifier;
+pub use deadline::Deadline;
+#[cfg(feature = "embedded-dma")]
+pub use dma::{DmaReadSlot, DmaWriteSlot};
+pub use double::{DoubleBuffer, DoubleReader, DoubleWriter, ReadGuard, WouldBlock};
+pub use duplex::{Duplex, DuplexEndpoint, DuplexEndpointA, DuplexEndpointB};
+#[cfg(feature = "bevy")]
+pub use ecs::{LatestChanged, TripleBufferPlugin, TripleBufferResource};
+#[cfg(all(feature = "eventfd", target_os = "linux"))]
+pub use eventfd::EventFdHandle;
+#[cfg(feature = "event-log")]
+pub use event_log::{Event, EventKind, EVENT_LOG_LEN};
+pub use framed::{FrameTooLong, FramedBytes};
+#[cfg(feature = "futex")]
+pub use futex::FutexNotifier;
+#[cfg(feature = "embedded-graphics")]
+pub use graphics::{FrameSizeMismatch, FrameTarget};
+#[cfg(feature = "debug-holders")]
+pub use holders::{set_holder_id_provider, Holder};
+pub use hook::{ConsumeEvent, PublishEvent, Recorder};
+pub use layout::{BufferLayout, LAYOUT_VERSION};
+pub use lossless::{Lossless, LosslessReader, LosslessWriter};
+pub use mailbox::Mailbox;
+pub use notify::{DefaultNotifier, Notifier, SpinNotifier};
+#[cfg(feature = "paranoid")]
+pub use paranoid::{set_violation_handler, Violation};
+#[cfg(feature = "std")]
+pub use park::ThreadNotifier;
+pub use pump::{pump, pump_blocking};
+#[cfg(feature = "redundant-control")]
+pub use redundant::{set_fault_handler, ControlFault, Unrepairable};
+#[cfg(feature = "async")]
+pub use pump::pump_async;
+pub use revocable::{RevocableReader, RevocableTripleBuffer, RevocableWriter, Revoked};
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "64"))]
+pub use ring::{RingReader, RingWriter, SnapshotRing};
+#[cfg(feature = "rtt")]
+pub use rtt::{decode_rtt_frame, RttChannel, RttMirror};
+pub use sample::Sample;
+pub use seqlock::{SeqLock, SeqLockReader, SeqLockWriter};
+#[cfg(feature = "shared")]
+pub use process::{LayoutError, ProcessReader, ProcessWriter, SharedTripleBuffer};
+pub use shared::{SharedWriter, WriterLock};
+#[cfg(feature = "stats")]
+pub use stats::Stats;
+#[cfg(feature = "futures")]
+pub use sink::{Disconnected, SinkMode, WriterSink};
+#[cfg(feature = "futures")]
+pub use stream::ReaderStream;
+pub use unsync::{UnsyncReader, UnsyncTripleBuffer, UnsyncWriter};
+#[cfg(feature = "watermarks")]
+pub use watermark::Watermarks;
+#[cfg(feature = "async")]
+pub use waker::{AsyncNotifier, Changed, Consumed, WakerNotifier};
+#[cfg(feature = "cortex-m")]
+pub use wfe::WfeNotifier;
 
-pub struct TripleBuffer<T> {
-    buffers: [UnsafeCell<T>; 3],
+/// A latest-value buffer with `SLOTS` slots: the writer's input, the
+/// reader's output, and the published back slot.
+///
+/// Three slots (`TripleBuffer`) is the usual choice. Extra slots are rotated
+/// through by the writer, so a slot the reader just let go of isn't written
+/// again right away. With two slots there is no back slot: the writer's next
+/// `input_buffer`/`write` takes an unread frame back to write into, so a
+/// writer that doesn't wait for `consumed` (e.g. with `write_blocking`) can
+/// starve a slow reader.
+///
+/// With a zero-sized `T`, e.g. `TripleBuffer<()>`, the buffer is an event
+/// flag: `publish` raises it, `update` takes it down and reports whether it
+/// was up, and no slot is ever touched.
+pub struct NBuffer<T, const SLOTS: usize, N = DefaultNotifier> {
+    buffers: UnsafeCell<[T; SLOTS]>,
 
-    back_info: AtomicBackBufferInfo,
+    // On a cache line of its own with `padded`: it is the one word both the
+    // writer and the reader swap on every operation.
+    back_info: CachePadded<AtomicControlWord>,
+    // Where the handles leave their slots when dropped, for the next ones
+    // to take over; attached handles keep their own. Under `packed-control`
+    // they are in `back_info` instead.
+    #[cfg(not(feature = "packed-control"))]
     input_idx: AtomicBackBufferInfo,
+    #[cfg(not(feature = "packed-control"))]
     output_idx: AtomicBackBufferInfo,
 
+    // Writer-private FIFO of the `SLOTS - 3` slots nobody holds.
+    spare: [AtomicBackBufferInfo; SLOTS],
+    spare_head: AtomicBackBufferInfo,
+    // Set when a two-slot writer took back an unread frame.
+    retracted: AtomicFlag,
+
     is_reader_exist: AtomicFlag,
     is_writer_exist: AtomicFlag,
+    #[cfg(feature = "debug-holders")]
+    reader_holder: holders::HolderCell,
+    #[cfg(feature = "debug-holders")]
+    writer_holder: holders::HolderCell,
+
+    reader_notifier: N,
+    writer_notifier: N,
+
+    on_publish: hook::Hook<PublishEvent>,
+    on_consume: hook::Hook<ConsumeEvent>,
+    recycler: hook::Recycler<T>,
+    recorder: hook::RecorderCell,
+    stats: stats::Counters,
+    events: event_log::EventLog,
+    #[cfg(feature = "watermarks")]
+    watermarks: watermark::Marks,
+
+    // Sequence number of the frame in each slot, and of the latest publish.
+    #[cfg(feature = "seq")]
+    seqs: [atomic::AtomicU64; SLOTS],
+    #[cfg(feature = "seq")]
+    last_seq: atomic::AtomicU64,
+
+    // Metadata published with the frame in each slot, and for the next
+    // publish.
+    #[cfg(feature = "meta")]
+    metas: [atomic::AtomicU32; SLOTS],
+    #[cfg(feature = "meta")]
+    next_meta: atomic::AtomicU32,
+
+    // CRC of the frame in each slot, and the function computing them.
+    #[cfg(feature = "crc")]
+    crcs: crc::Crcs<T, SLOTS>,
+
+    #[cfg(all(feature = "eventfd", target_os = "linux"))]
+    eventfd: eventfd::EventFd,
 }
 
-pub struct BufferReader<'a, T> {
-    read_buffer: &'a TripleBuffer<T>,
+pub type TripleBuffer<T, N = DefaultNotifier> = NBuffer<T, 3, N>;
+
+/// One more slot than `TripleBuffer`, so the writer rotates through two
+/// private slots and doesn't rewrite the one the reader just released
+/// right away. Costs a fourth copy of `T`; see `benches/slots.rs`.
+pub type QuadBuffer<T, N = DefaultNotifier> = NBuffer<T, 4, N>;
+
+pub struct BufferReader<'a, T, N: Notifier = DefaultNotifier, const SLOTS: usize = 3> {
+    read_buffer: &'a NBuffer<T, SLOTS, N>,
+    // The output slot; see `output`.
+    output_idx: u8,
+    _unshared: Unshared,
 }
 
-pub struct BufferWriter<'a, T> {
-    write_buffer: &'a TripleBuffer<T>,
+/// `write`, `input_buffer` and `publish` are lock-free and wait-free: no
+/// allocation, no critical section, no retry loop; under `packed-control`,
+/// `publish` is a CAS loop racing the reader's `update`, so only lock-free.
+/// With a notifier and hooks that are themselves interrupt-safe
+/// (`SpinNotifier`, `WfeNotifier`, `WakerNotifier`), the writer may run in
+/// an interrupt handler of any priority while the reader runs at a lower
+/// one. Under the
+/// `critical-section` feature each control-state access is a short critical
+/// section instead.
+///
+/// Dropping the writer leaves the control state as its last `publish` left
+/// it, so that frame stays pending: a reader that updates or attaches after
+/// the drop, e.g. once `state().writer_attached` reads false, gets it.
+pub struct BufferWriter<'a, T, N: Notifier = DefaultNotifier, const SLOTS: usize = 3> {
+    write_buffer: &'a NBuffer<T, SLOTS, N>,
+    // The input slot; see `input`. A `Cell`, as `publish` takes `&self`.
+    input_idx: core::cell::Cell<u8>,
+    _unshared: Unshared,
 }
 
-impl<'a, T> BufferReader<'a, T> {
+/// The control state of a buffer at one instant, from `NBuffer::state`.
+/// Both sides may change it right after it was taken, so it is stale by the
+/// time it is looked at unless neither side is running.
+///
+/// An attached handle keeps its own slot, so `NBuffer::state` has `input`
+/// or `output` as of when that handle was acquired; the handle's own
+/// `state`, and its `Debug`, have it as it is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BufferState {
+    /// The slot published for the reader to take next; `None` while a
+    /// two-slot writer holds it.
+    pub back: Option<usize>,
+    /// The back slot holds a frame the reader hasn't taken.
+    pub dirty: bool,
+    /// The writer's slot; `None` for a two-slot writer between a publish and
+    /// its next write.
+    pub input: Option<usize>,
+    /// The reader's slot.
+    pub output: usize,
+    pub reader_attached: bool,
+    pub writer_attached: bool,
+    #[cfg(feature = "debug-holders")]
+    pub reader_holder: Option<Holder>,
+    #[cfg(feature = "debug-holders")]
+    pub writer_holder: Option<Holder>,
+}
+
+impl<T, const SLOTS: usize, N> fmt::Debug for NBuffer<T, SLOTS, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // The frames are elided, so `T` needn't be `Debug`.
+        f.debug_struct("NBuffer")
+            .field("state", &self.state())
+            .field(
+                "values",
+                &format_args!("<{SLOTS} slots of {}>", core::any::type_name::<T>()),
+            )
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a, T, N: Notifier, const SLOTS: usize> fmt::Debug for BufferReader<'a, T, N, SLOTS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("BufferReader").field(&self.state()).finish()
+    }
+}
+
+impl<'a, T, N: Notifier, const SLOTS: usize> fmt::Debug for BufferWriter<'a, T, N, SLOTS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("BufferWriter").field(&self.state()).finish()
+    }
+}
+
+impl<'a, T, N: Notifier, const SLOTS: usize> BufferReader<'a, T, N, SLOTS> {
+    /// A reader taking over the output slot the last one left behind.
+    fn attach(read_buffer: &'a NBuffer<T, SLOTS, N>) -> Self {
+        Self {
+            read_buffer,
+            output_idx: read_buffer.parked_output(ord::relaxed()),
+            _unshared: PhantomData,
+        }
+    }
+
+    /// The output slot. Only the reader uses it, so it is kept here and
+    /// only left in the buffer by `park`; with `redundant-control` it is
+    /// also kept in the buffer, and read from there, so that a corrupted
+    /// copy is caught and `validate_and_resync` can repair it.
+    #[inline]
+    fn output(&self) -> u8 {
+        #[cfg(not(feature = "redundant-control"))]
+        {
+            self.output_idx
+        }
+        #[cfg(feature = "redundant-control")]
+        {
+            self.read_buffer.output_idx.load(ord::acquire())
+        }
+    }
+
+    #[inline]
+    fn set_output(&mut self, output_idx: u8) {
+        self.output_idx = output_idx;
+        #[cfg(feature = "redundant-control")]
+        self.read_buffer
+            .output_idx
+            .store(output_idx, ord::release());
+    }
+
+    /// Leaves the output slot in the buffer for the next reader to take
+    /// over; the release of the reader's claim publishes it. Under
+    /// `packed-control` the control word already holds it.
+    pub(crate) fn park(&self) {
+        #[cfg(not(feature = "packed-control"))]
+        self.read_buffer
+            .output_idx
+            .store(self.output_idx, ord::relaxed());
+    }
+
+    /// Like `NBuffer::state`, with this reader's output slot as it is.
+    pub fn state(&self) -> BufferState {
+        BufferState {
+            output: self.output() as usize,
+            ..self.read_buffer.state()
+        }
+    }
+
     pub fn read(&mut self) -> &T {
         self.update();
         self.output_buffer()
     }
 
     pub fn updated(&mut self) -> bool {
-        let back_info = self.read_buffer.back_info.load(Ordering::Acquire);
-        back_info & BACK_DIRTY_BIT != 0
+        is_dirty(self.read_buffer.back_info.load(ord::acquire()))
+    }
+
+    /// Sequence number of the frame in the output slot, as `last_seq` was
+    /// right after it was published. 0 for the initial frame. Stamped into
+    /// the slot before the publish, so it always belongs to that frame.
+    #[cfg(feature = "seq")]
+    pub fn seq(&mut self) -> u64 {
+        per_slot(&self.read_buffer.seqs, self.output()).load(ord::relaxed())
+    }
+
+    /// Metadata published with the frame in the output slot; 0 for the
+    /// initial frame and frames published without any.
+    #[cfg(feature = "meta")]
+    pub fn last_meta(&mut self) -> u32 {
+        per_slot(&self.read_buffer.metas, self.output()).load(ord::relaxed())
     }
 
     pub fn output_buffer(&mut self) -> &mut T {
-        let output_ptr = self.read_buffer.buffers
-            [self.read_buffer.output_idx.load(Ordering::Acquire) as usize]
-            .get();
+        #[cfg(all(feature = "paranoid", not(feature = "rt-safe")))]
+        self.read_buffer
+            .check_reader("output_buffer", self.output());
+        let output_idx = if mem::size_of::<T>() == 0 {
+            0
+        } else {
+            self.output()
+        };
+        let output_ptr = self.read_buffer.slot(output_idx);
+        // The writer never touches the output slot, and only `update` hands
+        // it back, which the borrow of `self` rules out while this lives.
         unsafe { &mut *output_ptr }
     }
 
     pub fn update(&mut self) -> bool {
         // let buffer_state = &(*self.buffer);
-        let updated = self.updated();
-        if updated {
-            let former_back_info = self.read_buffer.back_info.swap(
-                self.read_buffer.output_idx.load(Ordering::Acquire),
-                Ordering::AcqRel,
-            );
+        #[cfg(all(feature = "eventfd", target_os = "linux"))]
+        self.read_buffer.eventfd.drain();
+        // Read while the output slot is still ours; the writer restamps it.
+        #[cfg(all(feature = "tracing", not(feature = "rt-safe")))]
+        let previous_seq = trace::seq(self.read_buffer, self.output());
+        let released_idx = self.output();
+        let mut back_info = ControlWord(self.read_buffer.back_info.load(ord::acquire()));
+        let taken = loop {
+            let Some((next, output_idx)) = update_transition(back_info, released_idx) else {
+                break None;
+            };
+            // A CAS rather than a swap: a two-slot writer may take the frame
+            // back in the meantime.
+            match self.read_buffer.back_info.compare_exchange_weak(
+                back_info.0,
+                next.0,
+                Ordering::SeqCst,
+                ord::acquire(),
+            ) {
+                Ok(_) => break Some(output_idx),
+                Err(current) => back_info = ControlWord(current),
+            }
+        };
+        if let Some(output_idx) = taken {
             self.read_buffer
-                .output_idx
-                .store(former_back_info & BACK_INDEX_MASK, Ordering::Release);
+                .events
+                .record(event_log::CONSUME, back_info.back_info(), released_idx);
+            #[cfg(all(feature = "tracing", not(feature = "rt-safe")))]
+            trace::consumed(
+                output_idx,
+                trace::seq(self.read_buffer, output_idx),
+                previous_seq,
+            );
+            self.set_output(output_idx);
+
+            #[cfg(all(feature = "defmt-trace", not(feature = "rt-safe")))]
+            defmt::trace!("consumed slot={=u8}", output_idx);
+            self.read_buffer.stats.consumed();
+            if let Some(recorder) = self.read_buffer.recorder.get() {
+                recorder.on_consume();
+            }
+            self.read_buffer.writer_notifier.notify();
+            self.read_buffer.on_consume.fire(|| ConsumeEvent {
+                slot: output_idx as usize,
+            });
+        }
+        #[cfg(all(feature = "paranoid", not(feature = "rt-safe")))]
+        self.read_buffer.check_reader("update", self.output());
+        taken.is_some()
+    }
+
+    /// Waits on the reader notifier until a new frame is published, then reads it.
+    pub fn read_blocking(&mut self) -> &T {
+        let buffer = self.read_buffer;
+        #[cfg(feature = "tracing")]
+        let _span = trace::wait("reader");
+        buffer
+            .reader_notifier
+            .wait(|| is_dirty(buffer.back_info.load(ord::acquire())));
+        self.read()
+    }
+
+    /// Like `read_blocking`, but gives up and returns `None` once
+    /// `cancelled()` is true. `cancelled` is re-checked on every wakeup,
+    /// including manual ones from `TripleBuffer::notify_reader`.
+    pub fn read_blocking_unless(&mut self, cancelled: impl Fn() -> bool) -> Option<&T> {
+        let buffer = self.read_buffer;
+        #[cfg(feature = "tracing")]
+        let _span = trace::wait("reader");
+        let published = || is_dirty(buffer.back_info.load(ord::acquire()));
+        buffer.reader_notifier.wait(|| published() || cancelled());
+        if published() {
+            Some(self.read())
+        } else {
+            None
         }
-        updated
     }
 }
 
-impl<'a, T> Drop for BufferReader<'a, T> {
+impl<'a, T, N: Notifier, const SLOTS: usize> Drop for BufferReader<'a, T, N, SLOTS> {
     fn drop(&mut self) {
+        #[cfg(feature = "paranoid")]
+        self.read_buffer.check_reader("drop", self.output());
+        self.park();
+        self.read_buffer.record_handle(event_log::READER_RELEASED);
+        #[cfg(feature = "debug-holders")]
+        self.read_buffer.reader_holder.release();
         self.read_buffer
             .is_reader_exist
-            .store(false, Ordering::Release)
+            .store(false, Ordering::SeqCst);
+        // Lets a waiting writer notice the disconnect.
+        self.read_buffer.writer_notifier.notify();
     }
 }
 
-impl<'a, T> BufferWriter<'a, T> {
+impl<'a, T, N: Notifier, const SLOTS: usize> BufferWriter<'a, T, N, SLOTS> {
+    /// A writer taking over the input slot the last one left behind.
+    fn attach(write_buffer: &'a NBuffer<T, SLOTS, N>) -> Self {
+        Self {
+            write_buffer,
+            input_idx: core::cell::Cell::new(write_buffer.parked_input(ord::relaxed())),
+            _unshared: PhantomData,
+        }
+    }
+
+    /// The input slot, kept like the reader's `output`.
+    #[inline]
+    fn input(&self) -> u8 {
+        #[cfg(not(feature = "redundant-control"))]
+        {
+            self.input_idx.get()
+        }
+        #[cfg(feature = "redundant-control")]
+        {
+            self.write_buffer.input_idx.load(ord::acquire())
+        }
+    }
+
+    #[inline]
+    fn set_input(&self, input_idx: u8) {
+        self.input_idx.set(input_idx);
+        #[cfg(feature = "redundant-control")]
+        self.write_buffer.input_idx.store(input_idx, ord::release());
+    }
+
+    /// Leaves the input slot in the buffer for the next writer to take over.
+    pub(crate) fn park(&self) {
+        #[cfg(not(feature = "packed-control"))]
+        self.write_buffer
+            .input_idx
+            .store(self.input_idx.get(), ord::relaxed());
+        // The word holds the slot the last publish freed, which past three
+        // slots went to the spare ring.
+        #[cfg(feature = "packed-control")]
+        if SLOTS > 3 {
+            let input_idx = self.input_idx.get();
+            self.write_buffer
+                .transition(|ctrl| ctrl.with_input(input_idx));
+        }
+    }
+
+    /// Like `NBuffer::state`, with this writer's input slot as it is.
+    pub fn state(&self) -> BufferState {
+        let input = self.input();
+        BufferState {
+            input: (input != NO_SLOT).then_some(input as usize),
+            ..self.write_buffer.state()
+        }
+    }
+
     pub fn write(&mut self, value: T) {
         *self.input_buffer() = value;
         self.publish();
     }
 
     pub fn input_buffer(&mut self) -> &mut T {
-        let input_ptr = self.write_buffer.buffers
-            [self.write_buffer.input_idx.load(Ordering::Acquire) as usize]
-            .get();
+        // A two-slot writer still has to take the back slot, frame or not.
+        let input_idx = if mem::size_of::<T>() == 0 && SLOTS != 2 {
+            0
+        } else {
+            self.input_idx()
+        };
+        #[cfg(all(feature = "paranoid", not(feature = "rt-safe")))]
+        self.write_buffer.check_writer("input_buffer", self.input());
+        let input_ptr = self.write_buffer.slot(input_idx);
+        // Likewise for the input slot and `publish`.
         unsafe { &mut *input_ptr }
     }
 
+    fn input_idx(&self) -> u8 {
+        let buffer = self.write_buffer;
+        let input_idx = self.input();
+        if SLOTS != 2 || input_idx != NO_SLOT {
+            return input_idx;
+        }
+        // Two slots: write into the one the reader let go of, or take back
+        // the frame it hasn't read yet.
+        #[cfg(not(feature = "packed-control"))]
+        let (former, next) = (
+            ControlWord(buffer.back_info.swap(NO_SLOT, Ordering::SeqCst)),
+            ControlWord(NO_SLOT),
+        );
+        #[cfg(feature = "packed-control")]
+        let (former, next) = buffer.transition(|ctrl| retract_transition(ctrl).0);
+        buffer
+            .events
+            .record(event_log::RETRACT, former.back_info(), next.back_info());
+        let (_, input_idx, retracted) = retract_transition(former);
+        buffer.retracted.store(retracted, ord::relaxed());
+        self.set_input(input_idx);
+        buffer.recycler.run(buffer.slot(input_idx));
+        input_idx
+    }
+
     pub fn consumed(&self) -> bool {
-        let back_info = self.write_buffer.back_info.load(Ordering::Acquire);
-        back_info & BACK_DIRTY_BIT == 0
+        !is_dirty(self.write_buffer.back_info.load(ord::acquire()))
+    }
+
+    /// Sequence number of the latest publish: 1 for the first, wrapping
+    /// from `u64::MAX` to 0. 0 before any publish.
+    #[cfg(feature = "seq")]
+    pub fn last_seq(&self) -> u64 {
+        self.write_buffer.last_seq.load(ord::relaxed())
+    }
+
+    /// Like `publish`, but the reader's `last_meta` reads `meta` for as long
+    /// as this frame is its output; it is stamped into the slot before the
+    /// publish, so it can't be paired with another frame. `publish` and
+    /// `write` publish 0.
+    #[cfg(feature = "meta")]
+    pub fn publish_with_meta(&self, meta: u32) -> bool {
+        self.write_buffer.next_meta.store(meta, ord::relaxed());
+        self.publish()
     }
 
     pub fn publish(&self) -> bool {
-        let former_back_info = self.write_buffer.back_info.swap(
-            self.write_buffer.input_idx.load(Ordering::Acquire) | BACK_DIRTY_BIT,
-            Ordering::AcqRel,
+        let published_idx = self.input_idx();
+        #[cfg(feature = "seq")]
+        self.write_buffer.stamp(published_idx);
+        #[cfg(feature = "meta")]
+        per_slot(&self.write_buffer.metas, published_idx).store(
+            self.write_buffer.next_meta.swap(0, ord::relaxed()),
+            ord::relaxed(),
         );
-
+        #[cfg(feature = "crc")]
         self.write_buffer
-            .input_idx
-            .store(former_back_info & BACK_INDEX_MASK, Ordering::Release);
+            .crcs
+            .stamp(published_idx, self.write_buffer.slot(published_idx));
+        #[cfg(not(feature = "packed-control"))]
+        let (former, next) = {
+            let next = published(published_idx);
+            let former = self.write_buffer.back_info.swap(next, Ordering::SeqCst);
+            (ControlWord(former), ControlWord(next))
+        };
+        #[cfg(feature = "packed-control")]
+        let (former, next) = self
+            .write_buffer
+            .transition(|ctrl| publish_transition(ctrl, published_idx).0);
+        self.write_buffer
+            .events
+            .record(event_log::PUBLISH, former.back_info(), next.back_info());
+
+        let (_, freed_idx, overwrote) = publish_transition(former, published_idx);
+        let input_idx = self.write_buffer.recycle(freed_idx);
+        self.set_input(input_idx);
+        if SLOTS != 2 {
+            // Two slots run it once the next input slot is taken.
+            self.write_buffer
+                .recycler
+                .run(self.write_buffer.slot(input_idx));
+        }
 
-        former_back_info & BACK_DIRTY_BIT != 0
+        self.write_buffer.reader_notifier.notify();
+        #[cfg(all(feature = "eventfd", target_os = "linux"))]
+        self.write_buffer.eventfd.signal();
+
+        let overwrote =
+            overwrote || (SLOTS == 2 && self.write_buffer.retracted.swap(false, ord::relaxed()));
+        #[cfg(all(feature = "tracing", not(feature = "rt-safe")))]
+        trace::published(overwrote, trace::seq(self.write_buffer, published_idx));
+        #[cfg(all(feature = "defmt-trace", not(feature = "rt-safe")))]
+        defmt::trace!("published overwrote={=bool}", overwrote);
+        self.write_buffer.stats.published(overwrote);
+        if let Some(recorder) = self.write_buffer.recorder.get() {
+            recorder.on_publish(overwrote);
+        }
+        self.write_buffer.on_publish.fire(|| PublishEvent {
+            overwrote,
+            #[cfg(feature = "seq")]
+            seq: self.last_seq(),
+        });
+        #[cfg(all(feature = "paranoid", not(feature = "rt-safe")))]
+        self.write_buffer.check_writer("publish", self.input());
+        #[cfg(all(feature = "checked-rt", not(feature = "rt-safe")))]
+        if overwrote {
+            // The lost frame is always the previous publish.
+            checked::overwrote(|| checked::Overwrite {
+                #[cfg(feature = "seq")]
+                seq: self.last_seq().wrapping_sub(1),
+            });
+        }
+        overwrote
+    }
+
+    /// Waits on the writer notifier until the previous frame was consumed,
+    /// then writes `value`. Never overwrites an unread frame.
+    pub fn write_blocking(&mut self, value: T) {
+        let buffer = self.write_buffer;
+        #[cfg(feature = "tracing")]
+        let _span = trace::wait("writer");
+        buffer
+            .writer_notifier
+            .wait(|| !is_dirty(buffer.back_info.load(ord::acquire())));
+        self.write(value);
+    }
+
+    /// Like `write_blocking`, but gives `value` back once `cancelled()` is
+    /// true. `cancelled` is re-checked on every wakeup, including manual ones
+    /// from `TripleBuffer::notify_writer`.
+    pub fn write_blocking_unless(
+        &mut self,
+        value: T,
+        cancelled: impl Fn() -> bool,
+    ) -> Result<(), T> {
+        let buffer = self.write_buffer;
+        #[cfg(feature = "tracing")]
+        let _span = trace::wait("writer");
+        let consumed = || !is_dirty(buffer.back_info.load(ord::acquire()));
+        buffer.writer_notifier.wait(|| consumed() || cancelled());
+        if consumed() {
+            self.write(value);
+            Ok(())
+        } else {
+            Err(value)
+        }
     }
 }
 
-impl<'a, T> Drop for BufferWriter<'a, T> {
+impl<'a, T, N: Notifier, const SLOTS: usize> Drop for BufferWriter<'a, T, N, SLOTS> {
     fn drop(&mut self) {
+        #[cfg(feature = "paranoid")]
+        self.write_buffer.check_writer("drop", self.input());
+        self.park();
+        self.write_buffer.record_handle(event_log::WRITER_RELEASED);
+        #[cfg(feature = "debug-holders")]
+        self.write_buffer.writer_holder.release();
+        // Leaves `back_info` alone; the store releases the last publish to
+        // whoever sees the writer gone.
         self.write_buffer
             .is_writer_exist
-            .store(false, Ordering::Release)
+            .store(false, Ordering::SeqCst);
+        // Lets a waiting reader notice the disconnect.
+        self.write_buffer.reader_notifier.notify();
     }
 }
 
-unsafe impl<T> Sync for TripleBuffer<T> {}
+// Frames written on the writer's thread are read, and may be dropped, on the
+// reader's, so sharing the buffer moves `T` between threads.
+unsafe impl<T: Send, const SLOTS: usize, N: Sync> Sync for NBuffer<T, SLOTS, N> {}
 
-impl<T> TripleBuffer<T> {
+/// Opts a handle out of the auto traits, so it only has the impls written
+/// out for it.
+pub(crate) type Unshared = PhantomData<*const ()>;
+
+// Each handle is one end of the buffer, used by one thread at a time. It may
+// move to another with its frames and the notifiers both ends call, but is
+// never `Sync`: `publish` takes `&self`, and two threads publishing through
+// one writer would hand out the same slot. The reader stays `!Sync` too, so
+// its methods may change handle state through `&self` later.
+unsafe impl<'a, T: Send, N: Notifier + Sync, const SLOTS: usize> Send
+    for BufferReader<'a, T, N, SLOTS>
+{
+}
+unsafe impl<'a, T: Send, N: Notifier + Sync, const SLOTS: usize> Send
+    for BufferWriter<'a, T, N, SLOTS>
+{
+}
+
+// A panic only leaves an operation from a hook, the recycler or the recorder,
+// each called once the control state is whole again, so the buffer and its
+// handles stay usable after it. A frame the writer was staging when it
+// panicked stays staged as is; there is no poisoning.
+impl<T: RefUnwindSafe, const SLOTS: usize, N: RefUnwindSafe> RefUnwindSafe
+    for NBuffer<T, SLOTS, N>
+{
+}
+impl<T: UnwindSafe, const SLOTS: usize, N: UnwindSafe> UnwindSafe for NBuffer<T, SLOTS, N> {}
+// The writer's input slot only changes once the control state is whole.
+impl<'a, T: RefUnwindSafe, N: Notifier + RefUnwindSafe, const SLOTS: usize> RefUnwindSafe
+    for BufferWriter<'a, T, N, SLOTS>
+{
+}
+
+impl<T, const SLOTS: usize, N> NBuffer<T, SLOTS, N> {
+    /// Decodes the control state; see `BufferState` for how stale it is.
+    pub fn state(&self) -> BufferState {
+        let slot = |idx: u8| (idx != NO_SLOT).then_some(idx as usize);
+        let ctrl = ControlWord(self.back_info.load(Ordering::SeqCst));
+        BufferState {
+            back: slot(ctrl.back()),
+            dirty: is_dirty(ctrl.0),
+            input: slot(self.parked_input(Ordering::SeqCst)),
+            output: self.parked_output(Ordering::SeqCst) as usize,
+            reader_attached: self.is_reader_exist.load(Ordering::SeqCst),
+            writer_attached: self.is_writer_exist.load(Ordering::SeqCst),
+            #[cfg(feature = "debug-holders")]
+            reader_holder: self.reader_holder.get(),
+            #[cfg(feature = "debug-holders")]
+            writer_holder: self.writer_holder.get(),
+        }
+    }
+
+    /// The input slot the last writer left behind.
+    fn parked_input(&self, order: Ordering) -> u8 {
+        #[cfg(not(feature = "packed-control"))]
+        {
+            self.input_idx.load(order)
+        }
+        #[cfg(feature = "packed-control")]
+        {
+            ControlWord(self.back_info.load(order)).input()
+        }
+    }
+
+    /// The output slot the last reader left behind.
+    fn parked_output(&self, order: Ordering) -> u8 {
+        #[cfg(not(feature = "packed-control"))]
+        {
+            self.output_idx.load(order)
+        }
+        #[cfg(feature = "packed-control")]
+        {
+            ControlWord(self.back_info.load(order)).output()
+        }
+    }
+}
+
+impl<T, const SLOTS: usize> NBuffer<T, SLOTS> {
     pub fn new(generator: impl Fn() -> T) -> Self {
-        Self::new_const(generator(), generator(), generator())
+        Self::from_slots(core::array::from_fn(|_| generator()))
     }
 
+    pub const fn from_slots(slots: [T; SLOTS]) -> Self {
+        Self::from_slots_with_notifiers(slots, DefaultNotifier::new(), DefaultNotifier::new())
+    }
+}
+
+impl<T> TripleBuffer<T> {
+    /// `const` for any `T`, like every constructor that takes its slots by
+    /// value, so buffers can be `static`s; `tests/const_construction.rs`
+    /// holds them to it.
     pub const fn new_const(s1: T, s2: T, s3: T) -> Self {
+        Self::from_slots([s1, s2, s3])
+    }
+}
+
+impl<T> QuadBuffer<T> {
+    pub const fn new_const(s1: T, s2: T, s3: T, s4: T) -> Self {
+        Self::from_slots([s1, s2, s3, s4])
+    }
+}
+
+impl<T, N: Notifier> TripleBuffer<T, N> {
+    /// Like `new_const`, but wakes blocked readers and writers through the
+    /// given notifiers instead of the `DefaultNotifier`.
+    pub const fn with_notifiers(
+        s1: T,
+        s2: T,
+        s3: T,
+        reader_notifier: N,
+        writer_notifier: N,
+    ) -> Self {
+        Self::from_slots_with_notifiers([s1, s2, s3], reader_notifier, writer_notifier)
+    }
+}
+
+impl<T, const SLOTS: usize, N: Notifier> NBuffer<T, SLOTS, N> {
+    pub const fn from_slots_with_notifiers(
+        slots: [T; SLOTS],
+        reader_notifier: N,
+        writer_notifier: N,
+    ) -> Self {
+        const {
+            assert!(SLOTS >= 2 && SLOTS <= MAX_SLOTS);
+        }
+        let input_idx = if SLOTS == 2 { NO_SLOT } else { 1 };
+        let output_idx = if SLOTS == 2 { 1 } else { 2 };
+        let mut spare = [const { AtomicBackBufferInfo::new(0) }; SLOTS];
+        let mut i = 3;
+        while i < SLOTS {
+            // Shuttle's atomics have drop glue, which a `const fn` can't run.
+            #[allow(clippy::forget_non_drop)]
+            core::mem::forget(core::mem::replace(
+                &mut spare[i - 3],
+                AtomicBackBufferInfo::new(i as u8),
+            ));
+            i += 1;
+        }
         Self {
-            buffers: [
-                UnsafeCell::new(s1),
-                UnsafeCell::new(s2),
-                UnsafeCell::new(s3),
-            ],
-            back_info: AtomicBackBufferInfo::new(0),
-            input_idx: AtomicBackBufferInfo::new(1),
-            output_idx: AtomicBackBufferInfo::new(2),
+            buffers: UnsafeCell::new(slots),
+            back_info: CachePadded::new(AtomicControlWord::new(
+                ControlWord::new(0, input_idx, output_idx).0,
+            )),
+            #[cfg(not(feature = "packed-control"))]
+            input_idx: AtomicBackBufferInfo::new(input_idx),
+            #[cfg(not(feature = "packed-control"))]
+            output_idx: AtomicBackBufferInfo::new(output_idx),
+
+            spare,
+            spare_head: AtomicBackBufferInfo::new(0),
+            retracted: AtomicFlag::new(false),
 
             is_reader_exist: AtomicFlag::new(false),
             is_writer_exist: AtomicFlag::new(false),
+            #[cfg(feature = "debug-holders")]
+            reader_holder: holders::HolderCell::new(),
+            #[cfg(feature = "debug-holders")]
+            writer_holder: holders::HolderCell::new(),
+
+            reader_notifier,
+            writer_notifier,
+
+            on_publish: hook::Hook::new(),
+            on_consume: hook::Hook::new(),
+            recycler: hook::Recycler::new(),
+            recorder: hook::RecorderCell::new(),
+            stats: stats::Counters::new(),
+            events: event_log::EventLog::new(),
+            #[cfg(feature = "watermarks")]
+            watermarks: watermark::Marks::new(),
+
+            #[cfg(feature = "seq")]
+            seqs: [const { atomic::AtomicU64::new(0) }; SLOTS],
+            #[cfg(feature = "seq")]
+            last_seq: atomic::AtomicU64::new(0),
+
+            #[cfg(feature = "meta")]
+            metas: [const { atomic::AtomicU32::new(0) }; SLOTS],
+            #[cfg(feature = "meta")]
+            next_meta: atomic::AtomicU32::new(0),
+
+            #[cfg(feature = "crc")]
+            crcs: crc::Crcs::new(),
+
+            #[cfg(all(feature = "eventfd", target_os = "linux"))]
+            eventfd: eventfd::EventFd::new(),
         }
     }
 
-    pub fn get_reader(&self) -> BufferReader<T> {
+    /// Never goes through a reference to the whole array, which would
+    /// overlap the other handle's slot.
+    fn slot(&self, idx: u8) -> *mut T {
+        // Zero-sized frames all live at the same dangling address.
+        if mem::size_of::<T>() == 0 {
+            return core::ptr::NonNull::dangling().as_ptr();
+        }
+        unsafe { self.buffers.get().cast::<T>().add(idx as usize) }
+    }
+
+    /// Stamps the writer's input slot with the next sequence number; the
+    /// publish that follows releases it along with the frame.
+    #[cfg(feature = "seq")]
+    fn stamp(&self, input_idx: u8) {
+        let seq = self.last_seq.load(ord::relaxed()).wrapping_add(1);
+        per_slot(&self.seqs, input_idx).store(seq, ord::relaxed());
+        self.last_seq.store(seq, ord::relaxed());
+    }
+
+    /// Applies `transition` to the control word with a CAS loop; returns
+    /// the former and the new word. Only `packed-control` needs it, as its
+    /// words keep the other handle's slot.
+    #[cfg(feature = "packed-control")]
+    #[inline]
+    fn transition(
+        &self,
+        transition: impl Fn(ControlWord) -> ControlWord,
+    ) -> (ControlWord, ControlWord) {
+        let mut former = self.back_info.load(ord::relaxed());
         loop {
-            match self.is_reader_exist.compare_exchange(
-                false,
-                true,
-                Ordering::Acquire,
-                Ordering::Relaxed,
+            let next = transition(ControlWord(former)).0;
+            // SeqCst like the swap it replaces; success acquires the slot
+            // the reader released.
+            match self.back_info.compare_exchange_weak(
+                former,
+                next,
+                Ordering::SeqCst,
+                ord::relaxed(),
             ) {
-                Ok(_) => return BufferReader { read_buffer: self },
-                Err(false) => continue,
-                Err(true) => panic!("Reader already exists"),
+                Ok(_) => return (ControlWord(former), ControlWord(next)),
+                Err(current) => former = current,
             }
         }
     }
 
-    pub fn get_writer(&self) -> BufferWriter<T> {
-        loop {
-            match self.is_writer_exist.compare_exchange(
-                false,
-                true,
-                Ordering::Acquire,
-                Ordering::Relaxed,
-            ) {
-                Ok(_) => return BufferWriter { write_buffer: self },
-                Err(false) => continue,
-                Err(true) => panic!("Writer already exists"),
+    fn record_handle(&self, kind: u8) {
+        let back_info = ControlWord(self.back_info.load(ord::relaxed())).back_info();
+        self.events.record(kind, back_info, back_info);
+    }
+
+    /// Picks the writer's next input slot once `free` left the back slot.
+    fn recycle(&self, free: u8) -> u8 {
+        match SLOTS {
+            // `free` is always `NO_SLOT`; the next write takes one back.
+            2 => NO_SLOT,
+            3 => free,
+            _ => {
+                let head = self.spare_head.load(ord::relaxed());
+                let next = per_slot(&self.spare, head).swap(free, ord::relaxed());
+                self.spare_head
+                    .store((head + 1) % (SLOTS - 3) as u8, ord::relaxed());
+                next
             }
         }
     }
+
+    /// Wakes a reader blocked in the notifier without publishing anything;
+    /// it re-checks its condition and goes back to waiting. A cancellation
+    /// flag checked by that condition must be set with `SeqCst`, or the
+    /// notifier may conclude nobody is waiting.
+    pub fn notify_reader(&self) {
+        self.reader_notifier.notify();
+    }
+
+    /// Wakes a writer blocked in the notifier without consuming anything.
+    pub fn notify_writer(&self) {
+        self.writer_notifier.notify();
+    }
+
+    /// Installs `hook` to be called at the end of every `publish()`, on the
+    /// publishing thread. `None` removes it.
+    pub fn set_on_publish(&self, hook: Option<fn(&PublishEvent)>) {
+        self.on_publish.set(hook);
+    }
+
+    /// Installs `hook` to be called whenever `update()` takes a published
+    /// frame, on the reading thread. `None` removes it.
+    pub fn set_on_consume(&self, hook: Option<fn(&ConsumeEvent)>) {
+        self.on_consume.set(hook);
+    }
+
+    /// Installs `recorder` to be told about every publish and every frame
+    /// `update()` takes, on the thread doing it. A buffer takes one recorder
+    /// for good; later calls hand theirs back.
+    pub fn set_recorder(
+        &self,
+        recorder: &'static dyn Recorder,
+    ) -> Result<(), &'static dyn Recorder> {
+        self.recorder.set(recorder)
+    }
+
+    /// Frame counters since the buffer was created; readable from anywhere,
+    /// each counter on its own, so they may be mid-update relative to each
+    /// other while frames are exchanged.
+    #[cfg(feature = "stats")]
+    pub fn stats(&self) -> Stats {
+        self.stats.get()
+    }
+
+    /// Worst-case timing seen by the writer's `publish_timed` and the
+    /// reader's `read_timed`, each maximum on its own.
+    #[cfg(feature = "watermarks")]
+    pub fn watermarks(&self) -> Watermarks {
+        self.watermarks.get()
+    }
+
+    /// Starts both maxima over from zero.
+    #[cfg(feature = "watermarks")]
+    pub fn reset_watermarks(&self) {
+        self.watermarks.reset();
+    }
+
+    /// Passes the control-state transitions recorded since the previous
+    /// dump to `f`, oldest first, e.g. once a `paranoid` check or a watchdog
+    /// fired. Only the latest `EVENT_LOG_LEN` are kept.
+    #[cfg(feature = "event-log")]
+    pub fn dump_events(&self, f: &mut impl FnMut(Event)) {
+        self.events.dump(f);
+    }
+
+    /// Installs `recycler` to run on every slot the writer takes over for
+    /// staging, right after the `publish` that hands it over (for two slots,
+    /// on the first `input_buffer`/`write` after it) and before anything is
+    /// written into it. The slot holds a frame the reader is done with or
+    /// never saw, e.g. for `Vec::clear` so the writer refills it in place
+    /// through `input_buffer` and keeps its capacity. `None` removes it.
+    pub fn set_recycler(&self, recycler: Option<fn(&mut T)>) {
+        self.recycler.set(recycler);
+    }
+
+    /// Hands out both handles without the runtime existence check, which
+    /// `&mut self` makes unnecessary. Suited to RTIC `#[init]` locals, which
+    /// are `&'static mut`; never panics.
+    pub fn split(&mut self) -> (BufferReader<'_, T, N, SLOTS>, BufferWriter<'_, T, N, SLOTS>) {
+        *self.is_reader_exist.get_mut() = true;
+        *self.is_writer_exist.get_mut() = true;
+        #[cfg(feature = "paranoid")]
+        self.check_all("split");
+        self.record_handle(event_log::WRITER_ACQUIRED);
+        self.record_handle(event_log::READER_ACQUIRED);
+        #[cfg(feature = "debug-holders")]
+        {
+            self.writer_holder.acquire();
+            self.reader_holder.acquire();
+        }
+        (BufferReader::attach(self), BufferWriter::attach(self))
+    }
+
+    pub fn get_reader(&self) -> BufferReader<'_, T, N, SLOTS> {
+        match self.try_get_reader() {
+            Some(reader) => reader,
+            #[cfg(feature = "debug-holders")]
+            None => self.reader_holder.already_exists("Reader"),
+            #[cfg(not(feature = "debug-holders"))]
+            None => panic!("Reader already exists"),
+        }
+    }
+
+    pub fn get_writer(&self) -> BufferWriter<'_, T, N, SLOTS> {
+        match self.try_get_writer() {
+            Some(writer) => writer,
+            #[cfg(feature = "debug-holders")]
+            None => self.writer_holder.already_exists("Writer"),
+            #[cfg(not(feature = "debug-holders"))]
+            None => panic!("Writer already exists"),
+        }
+    }
+
+    /// Like `get_reader`, but returns `None` while a reader exists.
+    pub fn try_get_reader(&self) -> Option<BufferReader<'_, T, N, SLOTS>> {
+        // Pairs with the previous reader's drop, as in `try_get_writer`.
+        self.is_reader_exist
+            .compare_exchange(false, true, ord::acquire(), ord::relaxed())
+            .ok()?;
+        let reader = BufferReader::attach(self);
+        #[cfg(feature = "paranoid")]
+        self.check_reader("get_reader", reader.output());
+        self.record_handle(event_log::READER_ACQUIRED);
+        #[cfg(feature = "debug-holders")]
+        self.reader_holder.acquire();
+        Some(reader)
+    }
+
+    /// Like `get_writer`, but returns `None` while a writer exists.
+    pub fn try_get_writer(&self) -> Option<BufferWriter<'_, T, N, SLOTS>> {
+        // Acquire pairs with the previous writer's drop, so its writes to the
+        // slots, published or only staged, happen before this writer's.
+        self.is_writer_exist
+            .compare_exchange(false, true, ord::acquire(), ord::relaxed())
+            .ok()?;
+        let writer = BufferWriter::attach(self);
+        #[cfg(feature = "paranoid")]
+        self.check_writer("get_writer", writer.input());
+        self.record_handle(event_log::WRITER_ACQUIRED);
+        #[cfg(feature = "debug-holders")]
+        self.writer_holder.acquire();
+        Some(writer)
+    }
 }
 
-type AtomicBackBufferInfo = AtomicU8;
-type AtomicFlag = AtomicBool;
+#[cfg(not(feature = "redundant-control"))]
+type AtomicBackBufferInfo = sync::AtomicU8;
+#[cfg(feature = "redundant-control")]
+type AtomicBackBufferInfo = redundant::RedundantU8;
+#[cfg(not(feature = "packed-control"))]
+type AtomicControlWord = AtomicBackBufferInfo;
+#[cfg(feature = "packed-control")]
+type AtomicControlWord = sync::AtomicU32;
+type AtomicFlag = sync::AtomicBool;
 
-const BACK_INDEX_MASK: u8 = 0b11;
-const BACK_DIRTY_BIT: u8 = 0b100;
+/// Indexes a per-slot array without a bounds check, so the hot path has no
+/// panic branch: the control state only ever holds indices below `SLOTS`
+/// (`paranoid` checks it), and the spare ring's head stays below its length.
+#[inline(always)]
+fn per_slot<A, const SLOTS: usize>(array: &[A; SLOTS], idx: u8) -> &A {
+    debug_assert!((idx as usize) < SLOTS);
+    unsafe { array.get_unchecked(idx as usize) }
+}
 
 #[cfg(test)]
 mod tests {
-    use std::ptr::addr_of;
-
     use super::*;
-    use std::thread::Thread;
 
-    #[derive(Default, PartialEq, Eq, Debug)]
-    struct MyStruct {
-        goose: u32,
+    // Runs the suite once per slot count; `TripleBuffer` is shadowed by the
+    // `SLOTS`-slot buffer in each module.
+    macro_rules! buffer_suite {
+        ($($name:ident: $slots:literal),*) => {$(
+            mod $name {
+                use super::*;
+
+                const SLOTS: usize = $slots;
+                type TripleBuffer<T, N = DefaultNotifier> = NBuffer<T, SLOTS, N>;
+
+                #[derive(Default, PartialEq, Eq, Debug)]
+                struct MyStruct {
+                    goose: u32,
+                }
+
+                #[test]
+                fn my_test() {
+                    static GOOSE_BUFFER: TripleBuffer<MyStruct> =
+                        TripleBuffer::<MyStruct>::from_slots(
+                            [const { MyStruct { goose: 0 } }; SLOTS],
+                        );
+                    let jh = std::thread::spawn(|| {
+                        let mut goose_writer = GOOSE_BUFFER.get_writer();
+
+                        goose_writer.write(MyStruct { goose: 2 });
+                        goose_writer.write(MyStruct { goose: 3 });
+                        goose_writer.write(MyStruct { goose: 4 });
+                    });
+
+                    let mut goose_reader = GOOSE_BUFFER.get_reader();
+                    let _evil_goose_1 = goose_reader.read();
+                    let _evil_goose_2 = goose_reader.read();
+                    let evil_goose_3 = goose_reader.read();
+
+                    println!("{:?}", *evil_goose_3);
+                    jh.join().unwrap();
+
+                    assert!(*goose_reader.read() == MyStruct { goose: 4 })
+                }
+
+                #[test]
+                fn my_other_test() {
+                    static GOOSE_BUFFER: TripleBuffer<MyStruct> =
+                        TripleBuffer::<MyStruct>::from_slots(
+                            [const { MyStruct { goose: 0 } }; SLOTS],
+                        );
+
+                    let count = 1000;
+
+                    let jh = std::thread::spawn(move || {
+                        let mut goose_writer = GOOSE_BUFFER.get_writer();
+                        for i in 0..=count {
+                            goose_writer.write(MyStruct { goose: i });
+                        }
+                    });
+
+                    let mut goose_reader = GOOSE_BUFFER.get_reader();
+                    for _ in 0..=count {
+                        goose_reader.read();
+                    }
+                    jh.join().unwrap();
+                    assert!(*goose_reader.read() == MyStruct { goose: count })
+                }
+
+                #[test]
+                fn handles_are_send_for_interrupt_resources() {
+                    fn assert_send<S: Send>() {}
+                    fn assert_sync<S: Sync>() {}
+
+                    assert_send::<BufferWriter<'static, [i16; 3], SpinNotifier, SLOTS>>();
+                    assert_send::<BufferReader<'static, [i16; 3], SpinNotifier, SLOTS>>();
+                    assert_send::<BufferWriter<'static, MyStruct, DefaultNotifier, SLOTS>>();
+                    assert_send::<BufferReader<'static, MyStruct, DefaultNotifier, SLOTS>>();
+                    assert_sync::<TripleBuffer<MyStruct>>();
+                }
+
+                #[test]
+                fn split_hands_out_both_handles() {
+                    let mut buffer = TripleBuffer::<u32>::from_slots([0; SLOTS]);
+                    let (mut reader, mut writer) = buffer.split();
+                    assert!(reader.read_buffer.is_writer_exist.load(Ordering::Relaxed));
+
+                    std::thread::scope(|s| {
+                        s.spawn(move || writer.write(1));
+                    });
+                    assert_eq!(*reader.read(), 1);
+                    assert!(!reader.read_buffer.is_writer_exist.load(Ordering::Relaxed));
+                }
+
+                #[test]
+                #[should_panic]
+                fn reader_access_test() {
+                    static GOOSE_BUFFER: TripleBuffer<MyStruct> =
+                        TripleBuffer::<MyStruct>::from_slots(
+                            [const { MyStruct { goose: 0 } }; SLOTS],
+                        );
+                    let _goose_reader = GOOSE_BUFFER.get_reader();
+                    let _evil_reader = GOOSE_BUFFER.get_reader();
+                }
+
+                #[test]
+                fn good_reader_access_test() {
+                    static GOOSE_BUFFER: TripleBuffer<MyStruct> =
+                        TripleBuffer::<MyStruct>::from_slots(
+                            [const { MyStruct { goose: 0 } }; SLOTS],
+                        );
+                    {
+                        let _goose_reader = GOOSE_BUFFER.get_reader();
+                    }
+                    let _evil_reader = GOOSE_BUFFER.get_reader();
+                }
+
+                #[test]
+                fn direct_input_test() {
+                    #[derive(Default, PartialEq, Eq, Debug)]
+                    struct Cat {
+                        is_there_cat: bool,
+                    }
+
+                    impl Cat {
+                        fn bring_cat(&mut self) {
+                            self.is_there_cat = true;
+                        }
+                    }
+                    #[derive(Default, PartialEq, Eq, Debug)]
+                    struct MyBiggerStruct {
+                        goose: u32,
+                        duck: u32,
+                        cat: Cat,
+                    }
+                    static GOOSE_BUFFER: TripleBuffer<MyBiggerStruct> =
+                        TripleBuffer::<MyBiggerStruct>::from_slots(
+                            [const {
+                                MyBiggerStruct {
+                                    goose: 0,
+                                    duck: 1,
+                                    cat: Cat {
+                                        is_there_cat: false,
+                                    },
+                                }
+                            }; SLOTS],
+                        );
+                    let jh = std::thread::spawn(|| {
+                        let mut goose_writer = GOOSE_BUFFER.get_writer();
+
+                        let temp_goose = goose_writer.input_buffer();
+                        temp_goose.goose = 4;
+                        temp_goose.duck = 2;
+                        temp_goose.cat.bring_cat();
+                        goose_writer.publish()
+                    });
+
+                    let mut goose_reader = GOOSE_BUFFER.get_reader();
+                    let _evil_goose_1 = goose_reader.read();
+                    let _evil_goose_2 = goose_reader.read();
+                    let evil_goose_3 = goose_reader.read();
+
+                    println!("{:?}", *evil_goose_3);
+                    jh.join().unwrap();
+
+                    assert!(
+                        *goose_reader.read()
+                            == MyBiggerStruct {
+                                goose: 4,
+                                duck: 2,
+                                cat: Cat { is_there_cat: true }
+                            }
+                    )
+                }
+
+                fn lossless_exchange<N: Notifier + Sync>(
+                    buffer: &'static TripleBuffer<u32, N>,
+                    count: u32,
+                ) {
+                    let jh = std::thread::spawn(move || {
+                        let mut writer = buffer.get_writer();
+                        for i in 1..=count {
+                            writer.write_blocking(i);
+                        }
+                    });
+
+                    let mut reader = buffer.get_reader();
+                    for i in 1..=count {
+                        assert_eq!(*reader.read_blocking(), i);
+                    }
+                    jh.join().unwrap();
+                    assert!(!reader.update());
+                }
+
+                #[test]
+                fn spin_notifier_lossless_exchange() {
+                    static COUNTER_BUFFER: TripleBuffer<u32, SpinNotifier> =
+                        TripleBuffer::from_slots_with_notifiers(
+                            [0; SLOTS],
+                            SpinNotifier::new(),
+                            SpinNotifier::new(),
+                        );
+                    lossless_exchange(&COUNTER_BUFFER, 100);
+                }
+
+                #[cfg(feature = "std")]
+                #[test]
+                fn thread_notifier_lossless_exchange() {
+                    static COUNTER_BUFFER: TripleBuffer<u32, ThreadNotifier> =
+                        TripleBuffer::from_slots_with_notifiers(
+                            [0; SLOTS],
+                            ThreadNotifier::new(),
+                            ThreadNotifier::new(),
+                        );
+                    lossless_exchange(&COUNTER_BUFFER, 1_000_000);
+                }
+
+                #[cfg(feature = "futex")]
+                #[test]
+                fn futex_notifier_lossless_exchange() {
+                    static COUNTER_BUFFER: TripleBuffer<u32, FutexNotifier> =
+                        TripleBuffer::from_slots_with_notifiers(
+                            [0; SLOTS],
+                            FutexNotifier::new(),
+                            FutexNotifier::new(),
+                        );
+                    lossless_exchange(&COUNTER_BUFFER, 1_000_000);
+                }
+
+                #[derive(Default)]
+                struct CountingNotifier {
+                    notified: std::sync::atomic::AtomicUsize,
+                }
+
+                impl Notifier for CountingNotifier {
+                    fn notify(&self) {
+                        self.notified
+                            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                    }
+
+                    fn wait(&self, until: impl Fn() -> bool) {
+                        assert!(until(), "CountingNotifier cannot block");
+                    }
+                }
+
+                #[test]
+                fn publish_and_update_notify_the_other_side() {
+                    let buffer = TripleBuffer::from_slots_with_notifiers(
+                        [0; SLOTS],
+                        CountingNotifier::default(),
+                        CountingNotifier::default(),
+                    );
+                    let notified = |notifier: &CountingNotifier| {
+                        notifier.notified.load(std::sync::atomic::Ordering::Relaxed)
+                    };
+                    {
+                        let mut writer = buffer.get_writer();
+                        let mut reader = buffer.get_reader();
+
+                        writer.write(1);
+                        writer.write(2);
+                        assert_eq!(*reader.read(), 2);
+                        assert_eq!(*reader.read(), 2);
+                        writer.write_blocking(3);
+                        assert_eq!(*reader.read_blocking(), 3);
+                        assert_eq!(notified(&buffer.reader_notifier), 3);
+                        assert_eq!(notified(&buffer.writer_notifier), 2);
+                    }
+                    // Dropping a handle wakes the other side too.
+                    assert_eq!(notified(&buffer.reader_notifier), 4);
+                    assert_eq!(notified(&buffer.writer_notifier), 3);
+                }
+
+                #[test]
+                fn blocking_read_wakes_on_late_publish() {
+                    static LATE_BUFFER: TripleBuffer<MyStruct> =
+                        TripleBuffer::<MyStruct>::from_slots(
+                            [const { MyStruct { goose: 0 } }; SLOTS],
+                        );
+                    let jh = std::thread::spawn(|| {
+                        std::thread::sleep(std::time::Duration::from_millis(50));
+                        LATE_BUFFER.get_writer().write(MyStruct { goose: 7 });
+                    });
+
+                    let mut goose_reader = LATE_BUFFER.get_reader();
+                    assert_eq!(*goose_reader.read_blocking(), MyStruct { goose: 7 });
+                    jh.join().unwrap();
+                }
+
+                #[test]
+                fn on_publish_hook_reports_overwrites() {
+                    use std::sync::atomic::{AtomicUsize, Ordering};
+                    static PUBLISHED: AtomicUsize = AtomicUsize::new(0);
+                    static OVERWRITTEN: AtomicUsize = AtomicUsize::new(0);
+                    #[cfg(feature = "seq")]
+                    static SEQS: std::sync::Mutex<Vec<u64>> = std::sync::Mutex::new(Vec::new());
+
+                    fn count(event: &PublishEvent) {
+                        PUBLISHED.fetch_add(1, Ordering::Relaxed);
+                        if event.overwrote {
+                            OVERWRITTEN.fetch_add(1, Ordering::Relaxed);
+                        }
+                        #[cfg(feature = "seq")]
+                        SEQS.lock().unwrap().push(event.seq);
+                    }
+
+                    let buffer = TripleBuffer::new(|| 0);
+                    let mut writer = buffer.get_writer();
+                    let mut reader = buffer.get_reader();
+
+                    writer.write(1);
+                    buffer.set_on_publish(Some(count));
+                    writer.write(2);
+                    writer.write(3);
+                    reader.read();
+                    writer.write(4);
+                    buffer.set_on_publish(None);
+                    writer.write(5);
+
+                    assert_eq!(PUBLISHED.load(Ordering::Relaxed), 3);
+                    assert_eq!(OVERWRITTEN.load(Ordering::Relaxed), 2);
+                    #[cfg(feature = "seq")]
+                    assert_eq!(*SEQS.lock().unwrap(), [2, 3, 4]);
+                }
+
+                #[test]
+                fn on_consume_hook_reports_output_slot() {
+                    use std::sync::atomic::{AtomicUsize, Ordering};
+                    static LAST_SLOT: AtomicUsize = AtomicUsize::new(usize::MAX);
+
+                    fn record(event: &ConsumeEvent) {
+                        LAST_SLOT.store(event.slot, Ordering::Relaxed);
+                    }
+
+                    let buffer = TripleBuffer::new(|| 0);
+                    buffer.set_on_consume(Some(record));
+                    let mut writer = buffer.get_writer();
+                    let mut reader = buffer.get_reader();
+
+                    reader.update();
+                    assert_eq!(LAST_SLOT.load(Ordering::Relaxed), usize::MAX);
+                    writer.write(1);
+                    reader.update();
+                    assert_eq!(
+                        LAST_SLOT.load(Ordering::Relaxed),
+                        reader.output() as usize
+                    );
+                }
+
+                #[test]
+                fn recorder_counts_publishes_and_consumes() {
+                    use std::sync::atomic::{AtomicUsize, Ordering};
+
+                    struct Counting {
+                        published: AtomicUsize,
+                        overwritten: AtomicUsize,
+                        consumed: AtomicUsize,
+                    }
+
+                    impl Recorder for Counting {
+                        fn on_publish(&self, overwrote: bool) {
+                            self.published.fetch_add(1, Ordering::Relaxed);
+                            if overwrote {
+                                self.overwritten.fetch_add(1, Ordering::Relaxed);
+                            }
+                        }
+
+                        fn on_consume(&self) {
+                            self.consumed.fetch_add(1, Ordering::Relaxed);
+                        }
+                    }
+
+                    impl Counting {
+                        const fn new() -> Self {
+                            Self {
+                                published: AtomicUsize::new(0),
+                                overwritten: AtomicUsize::new(0),
+                                consumed: AtomicUsize::new(0),
+                            }
+                        }
+                    }
+
+                    static COUNTING: Counting = Counting::new();
+                    static LATE: Counting = Counting::new();
+
+                    let buffer = TripleBuffer::new(|| 0);
+                    let mut writer = buffer.get_writer();
+                    let mut reader = buffer.get_reader();
+
+                    writer.write(1);
+                    assert!(buffer.set_recorder(&COUNTING).is_ok());
+                    assert!(buffer.set_recorder(&LATE).is_err());
+                    writer.write(2);
+                    writer.write(3);
+                    reader.read();
+                    reader.read();
+                    writer.write(4);
+                    reader.read();
+
+                    assert_eq!(COUNTING.published.load(Ordering::Relaxed), 3);
+                    assert_eq!(COUNTING.overwritten.load(Ordering::Relaxed), 2);
+                    assert_eq!(COUNTING.consumed.load(Ordering::Relaxed), 2);
+                }
+
+                #[test]
+                fn publish_and_consume_hooks_drive_lock_step_exchange() {
+                    use std::sync::atomic::{AtomicBool, Ordering};
+                    static LOCK_STEP_BUFFER: TripleBuffer<u32> =
+                        TripleBuffer::<u32>::from_slots([0; SLOTS]);
+                    static CAN_READ: AtomicBool = AtomicBool::new(false);
+                    static CAN_WRITE: AtomicBool = AtomicBool::new(true);
+
+                    fn give_reader(_: &PublishEvent) {
+                        CAN_READ.store(true, Ordering::Release);
+                    }
+                    fn give_writer(_: &ConsumeEvent) {
+                        CAN_WRITE.store(true, Ordering::Release);
+                    }
+                    fn take(semaphore: &AtomicBool) {
+                        while !semaphore.swap(false, Ordering::Acquire) {
+                            std::thread::yield_now();
+                        }
+                    }
+
+                    LOCK_STEP_BUFFER.set_on_publish(Some(give_reader));
+                    LOCK_STEP_BUFFER.set_on_consume(Some(give_writer));
+                    let count = 1000;
+
+                    let jh = std::thread::spawn(move || {
+                        let mut writer = LOCK_STEP_BUFFER.get_writer();
+                        for i in 1..=count {
+                            take(&CAN_WRITE);
+                            *writer.input_buffer() = i;
+                            assert!(!writer.publish());
+                        }
+                    });
+
+                    let mut reader = LOCK_STEP_BUFFER.get_reader();
+                    for i in 1..=count {
+                        take(&CAN_READ);
+                        assert_eq!(*reader.read(), i);
+                    }
+                    jh.join().unwrap();
+                }
+
+                #[cfg(feature = "cortex-m")]
+                #[test]
+                fn wfe_notifier_lossless_exchange() {
+                    static COUNTER_BUFFER: TripleBuffer<u32, WfeNotifier> =
+                        TripleBuffer::from_slots_with_notifiers(
+                            [0; SLOTS],
+                            WfeNotifier::new(),
+                            WfeNotifier::new(),
+                        );
+                    lossless_exchange(&COUNTER_BUFFER, 100);
+                }
+
+                #[cfg(feature = "std")]
+                #[test]
+                fn manual_notify_rechecks_and_cancels_blocking_read() {
+                    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+                    use std::time::Duration;
+                    static CANCEL_BUFFER: TripleBuffer<u32, ThreadNotifier> =
+                        TripleBuffer::from_slots_with_notifiers(
+                            [0; SLOTS],
+                            ThreadNotifier::new(),
+                            ThreadNotifier::new(),
+                        );
+                    static CANCELLED: AtomicBool = AtomicBool::new(false);
+                    static CHECKS: AtomicUsize = AtomicUsize::new(0);
+
+                    let jh = std::thread::spawn(|| {
+                        let mut reader = CANCEL_BUFFER.get_reader();
+                        reader
+                            .read_blocking_unless(|| {
+                                CHECKS.fetch_add(1, Ordering::SeqCst);
+                                CANCELLED.load(Ordering::SeqCst)
+                            })
+                            .copied()
+                    });
+
+                    let settle = || {
+                        let mut checks = CHECKS.load(Ordering::SeqCst);
+                        loop {
+                            std::thread::sleep(Duration::from_millis(20));
+                            let now = CHECKS.load(Ordering::SeqCst);
+                            if now == checks {
+                                return now;
+                            }
+                            checks = now;
+                        }
+                    };
+                    let parked = settle();
+                    CANCEL_BUFFER.notify_reader();
+                    let reparked = settle();
+                    assert!(reparked > parked);
+                    assert!(!jh.is_finished());
+
+                    CANCELLED.store(true, Ordering::SeqCst);
+                    CANCEL_BUFFER.notify_reader();
+                    assert_eq!(jh.join().unwrap(), None);
+                }
+
+                #[test]
+                fn cancelled_blocking_write_returns_value() {
+                    let buffer = TripleBuffer::new(|| 0);
+                    let mut writer = buffer.get_writer();
+                    assert_eq!(writer.write_blocking_unless(1, || true), Ok(()));
+                    assert_eq!(writer.write_blocking_unless(2, || true), Err(2));
+
+                    let mut reader = buffer.get_reader();
+                    assert_eq!(reader.read_blocking_unless(|| true), Some(&1));
+                    assert_eq!(reader.read_blocking_unless(|| true), None);
+                }
+
+                #[test]
+                fn final_frame_survives_writer_drop() {
+                    let cycles = if cfg!(miri) { 20 } else { 5_000 };
+                    for cycle in 1..=cycles {
+                        let buffer = TripleBuffer::new(|| 0);
+                        // Every other cycle the reader attaches only after the
+                        // writer is gone.
+                        let mut reader = (cycle % 2 == 0).then(|| buffer.get_reader());
+                        let mut writer = buffer.get_writer();
+                        std::thread::scope(|s| {
+                            s.spawn(move || writer.write(cycle));
+                            if let Some(reader) = &mut reader {
+                                reader.update();
+                            }
+                            while buffer.state().writer_attached {
+                                std::thread::yield_now();
+                            }
+                            let mut reader = reader.unwrap_or_else(|| buffer.get_reader());
+                            assert_eq!(*reader.read(), cycle);
+                        });
+                    }
+                }
+
+                #[test]
+                fn every_slot_takes_turns() {
+                    use std::collections::BTreeSet;
+
+                    let buffer = TripleBuffer::new(|| 0);
+                    let mut writer = buffer.get_writer();
+                    let mut reader = buffer.get_reader();
+                    let mut slots = BTreeSet::new();
+                    for i in 1..=2 * SLOTS as u32 {
+                        writer.write(i);
+                        assert_eq!(*reader.read(), i);
+                        slots.insert(reader.output());
+                    }
+                    assert_eq!(slots, (0..SLOTS as u8).collect::<BTreeSet<_>>());
+                }
+            }
+        )*};
     }
 
+    buffer_suite!(two_slots: 2, three_slots: 3, four_slots: 4, eight_slots: 8);
+
     #[test]
-    fn my_test() {
-        static goose_buffer: TripleBuffer<MyStruct> = TripleBuffer::<MyStruct>::new_const(
-            MyStruct { goose: 0 },
-            MyStruct { goose: 0 },
-            MyStruct { goose: 0 },
-        );
-        let jh = std::thread::spawn(|| {
-            let mut goose_writer = goose_buffer.get_writer();
+    fn quad_buffer_reader_gets_newest_frame() {
+        static QUAD_BUFFER: QuadBuffer<u64> = QuadBuffer::<u64>::new_const(0, 0, 0, 0);
+        static PUBLISHED: crate::atomic::AtomicU64 = crate::atomic::AtomicU64::new(0);
+        let count = if cfg!(miri) { 200 } else { 100_000 };
 
-            goose_writer.write(MyStruct { goose: 2 });
-            goose_writer.write(MyStruct { goose: 3 });
-            goose_writer.write(MyStruct { goose: 4 });
+        let jh = std::thread::spawn(move || {
+            let mut writer = QUAD_BUFFER.get_writer();
+            for i in 1..=count {
+                writer.write(i);
+                PUBLISHED.store(i, Ordering::Release);
+            }
         });
 
-        let mut goose_reader = goose_buffer.get_reader();
-        let evil_goose_1 = goose_reader.read();
-        let evil_goose_2 = goose_reader.read();
-        let evil_goose_3 = goose_reader.read();
+        let mut reader = QUAD_BUFFER.get_reader();
+        let mut last = 0;
+        while last != count {
+            // Anything published before the read started must be visible.
+            let published = PUBLISHED.load(Ordering::Acquire);
+            let frame = *reader.read();
+            assert!(frame >= published, "stale frame {frame} < {published}");
+            assert!(frame >= last, "frame went back in time");
+            last = frame;
+        }
+        jh.join().unwrap();
+    }
+
+    #[derive(Debug, Default, PartialEq)]
+    struct Tick(());
+
+    fn zero_sized_frames_are_events<F: Default + PartialEq + fmt::Debug, const SLOTS: usize>() {
+        let buffer = NBuffer::<F, SLOTS>::new(F::default);
+        let mut writer = buffer.get_writer();
+        let mut reader = buffer.get_reader();
+        assert!(!reader.updated() && writer.consumed());
+        assert!(!reader.update());
+
+        assert!(!writer.publish());
+        assert!(reader.updated() && !writer.consumed());
+        assert!(writer.publish(), "second event overwrites the first");
+        assert!(reader.update());
+        assert!(!reader.update(), "one update per event burst");
+        assert!(!reader.updated() && writer.consumed());
+        assert_eq!(reader.read(), &F::default());
+        assert_eq!(
+            core::ptr::from_mut(writer.input_buffer()),
+            core::ptr::from_mut(reader.output_buffer())
+        );
+
+        for _ in 0..SLOTS * 2 {
+            drop(reader);
+            drop(writer);
+            writer = buffer.get_writer();
+            reader = buffer.get_reader();
+            writer.write(F::default());
+            assert!(reader.update());
+        }
+    }
+
+    #[test]
+    fn zero_sized_frames_carry_only_the_dirty_bit() {
+        zero_sized_frames_are_events::<(), 2>();
+        zero_sized_frames_are_events::<(), 3>();
+        zero_sized_frames_are_events::<(), 4>();
+        zero_sized_frames_are_events::<Tick, 2>();
+        zero_sized_frames_are_events::<Tick, 3>();
+        zero_sized_frames_are_events::<Tick, 8>();
+    }
 
-        println!("{:?}", *evil_goose_3);
+    #[cfg(feature = "seq")]
+    fn seq_matches_data<const SLOTS: usize>() {
+        let buffer: &'static NBuffer<u64, SLOTS, SpinNotifier> = Box::leak(Box::new(
+            NBuffer::from_slots_with_notifiers([0; SLOTS], SpinNotifier, SpinNotifier),
+        ));
+        let count = 20_000;
+
+        let jh = std::thread::spawn(move || {
+            let mut writer = buffer.get_writer();
+            for i in 1..=count {
+                writer.write(i);
+                assert_eq!(writer.last_seq(), i);
+            }
+        });
+
+        let mut reader = buffer.get_reader();
+        let mut last = 0;
+        while last != count {
+            let frame = *reader.read();
+            assert_eq!(reader.seq(), frame, "seq torn from its frame");
+            last = frame;
+        }
         jh.join().unwrap();
+    }
 
-        assert!(*goose_reader.read() == MyStruct { goose: 4 })
+    #[cfg(feature = "seq")]
+    #[test]
+    fn seq_stays_paired_with_its_frame() {
+        seq_matches_data::<2>();
+        seq_matches_data::<3>();
+        seq_matches_data::<4>();
     }
 
+    #[cfg(feature = "seq")]
     #[test]
-    fn my_other_test() {
-        static goose_buffer: TripleBuffer<MyStruct> = TripleBuffer::<MyStruct>::new_const(
-            MyStruct { goose: 0 },
-            MyStruct { goose: 0 },
-            MyStruct { goose: 0 },
-        );
+    fn seq_wraps_to_zero() {
+        let buffer = TripleBuffer::new(|| 0);
+        let mut writer = buffer.get_writer();
+        let mut reader = buffer.get_reader();
+        assert_eq!((writer.last_seq(), reader.seq()), (0, 0));
+
+        buffer.last_seq.store(u64::MAX - 1, Ordering::Relaxed);
+        writer.write(1);
+        assert_eq!(writer.last_seq(), u64::MAX);
+        writer.write(2);
+        assert_eq!(writer.last_seq(), 0);
+        assert_eq!(*reader.read(), 2);
+        assert_eq!(reader.seq(), 0);
+        writer.write(3);
+        assert_eq!((*reader.read(), reader.seq()), (3, 1));
+    }
 
-        let count = 1000;
+    #[cfg(feature = "meta")]
+    fn meta_matches_data<const SLOTS: usize>() {
+        let buffer: &'static NBuffer<u32, SLOTS, SpinNotifier> = Box::leak(Box::new(
+            NBuffer::from_slots_with_notifiers([0; SLOTS], SpinNotifier, SpinNotifier),
+        ));
+        let count = 20_000;
+        // Varies every bit, high ones included.
+        let meta = |frame: u32| frame.wrapping_mul(0x9e37_79b9);
 
         let jh = std::thread::spawn(move || {
-            let mut goose_writer = goose_buffer.get_writer();
-            for i in 0..=count {
-                goose_writer.write(MyStruct { goose: i as u32 });
+            let mut writer = buffer.get_writer();
+            for i in 1..=count {
+                *writer.input_buffer() = i;
+                writer.publish_with_meta(meta(i));
             }
         });
 
-        let mut goose_reader = goose_buffer.get_reader();
-        for _ in 0..=count {
-            goose_reader.read();
+        let mut reader = buffer.get_reader();
+        let mut last = 0;
+        while last != count {
+            let frame = *reader.read();
+            assert_eq!(reader.last_meta(), meta(frame), "meta torn from its frame");
+            last = frame;
         }
         jh.join().unwrap();
-        assert!(*goose_reader.read() == MyStruct { goose: count })
     }
 
+    #[cfg(feature = "meta")]
     #[test]
-    #[should_panic]
-    fn reader_access_test() {
-        static goose_buffer: TripleBuffer<MyStruct> = TripleBuffer::<MyStruct>::new_const(
-            MyStruct { goose: 0 },
-            MyStruct { goose: 0 },
-            MyStruct { goose: 0 },
-        );
-        let mut goose_reader = goose_buffer.get_reader();
-        let mut evil_reader = goose_buffer.get_reader();
+    fn meta_stays_paired_with_its_frame() {
+        meta_matches_data::<2>();
+        meta_matches_data::<3>();
+        meta_matches_data::<4>();
     }
 
+    #[cfg(feature = "meta")]
     #[test]
-    fn good_reader_access_test() {
-        static goose_buffer: TripleBuffer<MyStruct> = TripleBuffer::<MyStruct>::new_const(
-            MyStruct { goose: 0 },
-            MyStruct { goose: 0 },
-            MyStruct { goose: 0 },
+    fn plain_publish_carries_no_meta() {
+        let buffer = TripleBuffer::new(|| 0);
+        let mut writer = buffer.get_writer();
+        let mut reader = buffer.get_reader();
+        assert_eq!(reader.last_meta(), 0);
+
+        *writer.input_buffer() = 1;
+        writer.publish_with_meta(7);
+        assert_eq!((*reader.read(), reader.last_meta()), (1, 7));
+        writer.write(2);
+        assert_eq!((*reader.read(), reader.last_meta()), (2, 0));
+    }
+
+    #[test]
+    fn state_follows_publish_and_update() {
+        // Without `std` or a provider, holders aren't recorded.
+        #[cfg(all(feature = "debug-holders", feature = "std"))]
+        let here = Some(Holder::Thread(std::thread::current().id()));
+        #[cfg(all(feature = "debug-holders", not(feature = "std")))]
+        let here = None;
+        let state = |back, dirty, input, output| BufferState {
+            back: Some(back),
+            dirty,
+            input,
+            output,
+            reader_attached: true,
+            writer_attached: true,
+            #[cfg(feature = "debug-holders")]
+            reader_holder: here,
+            #[cfg(feature = "debug-holders")]
+            writer_holder: here,
+        };
+
+        let buffer = TripleBuffer::new(|| 0);
+        assert_eq!(
+            buffer.state(),
+            BufferState {
+                reader_attached: false,
+                writer_attached: false,
+                #[cfg(feature = "debug-holders")]
+                reader_holder: None,
+                #[cfg(feature = "debug-holders")]
+                writer_holder: None,
+                ..state(0, false, Some(1), 2)
+            }
+        );
+        let mut writer = buffer.get_writer();
+        let mut reader = buffer.get_reader();
+        writer.write(1);
+        assert_eq!(writer.state(), state(1, true, Some(0), 2));
+        writer.write(2);
+        assert_eq!(writer.state(), state(0, true, Some(1), 2));
+        reader.update();
+        assert_eq!(reader.state(), state(2, false, Some(1), 0));
+        assert!(format!("{reader:?}").contains("output: 0"));
+        drop(writer);
+        assert!(!buffer.state().writer_attached);
+        // The handles leave their slots behind when dropped.
+        drop(reader);
+        assert_eq!((buffer.state().input, buffer.state().output), (Some(1), 0));
+
+        let buffer = NBuffer::<u32, 2>::new(|| 0);
+        let mut writer = buffer.get_writer();
+        assert_eq!(writer.state().input, None);
+        *writer.input_buffer() = 1;
+        assert_eq!(writer.state().back, None, "two-slot writer holds the back slot");
+        assert_eq!(writer.state().input, Some(0));
+        writer.publish();
+        assert_eq!(
+            (writer.state().back, writer.state().dirty, writer.state().input),
+            (Some(0), true, None)
         );
-        {
-            let mut goose_reader = goose_buffer.get_reader();
-        }
-        let mut evil_reader = goose_buffer.get_reader();
     }
 
+    // The holders don't fit the snapshot.
+    #[cfg(not(feature = "debug-holders"))]
     #[test]
-    fn direct_input_test() {
-        #[derive(Default, PartialEq, Eq, Debug)]
-        struct Cat {
-            is_there_cat: bool,
+    fn debug_elides_frames() {
+        struct Opaque;
+
+        #[derive(Debug)]
+        #[allow(dead_code)]
+        struct Holder {
+            frames: TripleBuffer<Opaque, SpinNotifier>,
         }
 
-        impl Cat {
-            fn bring_cat(&mut self) {
-                self.is_there_cat = true;
+        let holder = Holder {
+            frames: TripleBuffer::with_notifiers(Opaque, Opaque, Opaque, SpinNotifier, SpinNotifier),
+        };
+        let _writer = holder.frames.get_writer();
+        assert_eq!(
+            format!("{holder:?}"),
+            "Holder { frames: NBuffer { state: BufferState { back: Some(0), dirty: false, \
+             input: Some(1), output: 2, reader_attached: false, writer_attached: true }, \
+             values: <3 slots of tri_buffer::tests::debug_elides_frames::Opaque>, .. } }"
+        );
+    }
+
+    #[cfg(feature = "defmt")]
+    #[test]
+    fn status_types_are_defmt_format() {
+        fn assert_format<F: defmt::Format>() {}
+
+        assert_format::<BufferState>();
+        assert_format::<BufferLayout<3>>();
+        assert_format::<PublishEvent>();
+        assert_format::<ConsumeEvent>();
+        assert_format::<FrameTooLong>();
+        assert_format::<WouldBlock>();
+        assert_format::<Sample<u8>>();
+        #[cfg(feature = "alloc")]
+        assert_format::<LengthMismatch>();
+        #[cfg(feature = "stats")]
+        assert_format::<Stats>();
+        #[cfg(feature = "watermarks")]
+        assert_format::<Watermarks>();
+        #[cfg(feature = "event-log")]
+        assert_format::<Event>();
+        #[cfg(feature = "paranoid")]
+        assert_format::<Violation>();
+        #[cfg(feature = "futures")]
+        assert_format::<SinkMode>();
+        #[cfg(feature = "futures")]
+        assert_format::<Disconnected>();
+        #[cfg(feature = "watch-compat")]
+        assert_format::<watch::RecvError>();
+        #[cfg(feature = "watch-compat")]
+        assert_format::<watch::SendError<u8>>();
+        #[cfg(feature = "shared")]
+        assert_format::<LayoutError>();
+    }
+}
+
+#[cfg(all(test, tri_buffer_loom))]
+mod loom_tests {
+    use super::*;
+    use loom::cell::UnsafeCell;
+    use loom::thread;
+
+    // Frames are loom cells, so every slot access is checked against the
+    // happens-before the control state establishes.
+    type Frame = UnsafeCell<usize>;
+
+    fn buffer<const SLOTS: usize>() -> &'static NBuffer<Frame, SLOTS, SpinNotifier> {
+        let buffer = Box::leak(Box::new(NBuffer::from_slots_with_notifiers(
+            core::array::from_fn(|_| UnsafeCell::new(0)),
+            SpinNotifier,
+            SpinNotifier,
+        )));
+        // Creates the shared loom atomics before any thread can race on them.
+        buffer.state();
+        buffer
+    }
+
+    fn stage<const SLOTS: usize>(
+        writer: &mut BufferWriter<'_, Frame, SpinNotifier, SLOTS>,
+        frame: usize,
+    ) {
+        writer
+            .input_buffer()
+            .with_mut(|slot| unsafe { *slot = frame });
+    }
+
+    fn write<const SLOTS: usize>(
+        writer: &mut BufferWriter<'_, Frame, SpinNotifier, SLOTS>,
+        frame: usize,
+    ) {
+        stage(writer, frame);
+        writer.publish();
+    }
+
+    fn read<const SLOTS: usize>(
+        reader: &mut BufferReader<'_, Frame, SpinNotifier, SLOTS>,
+    ) -> usize {
+        reader.update();
+        reader.output_buffer().with(|slot| unsafe { *slot })
+    }
+
+    #[test]
+    fn published_frame_is_visible_to_the_reader() {
+        loom::model(|| {
+            let buffer = buffer::<3>();
+            let mut writer = buffer.get_writer();
+            let mut reader = buffer.get_reader();
+
+            let writer = thread::spawn(move || write(&mut writer, 1));
+            assert!(read(&mut reader) <= 1);
+            writer.join().unwrap();
+            assert_eq!(read(&mut reader), 1);
+        });
+    }
+
+    fn rapid_overwrites<const SLOTS: usize>() {
+        loom::model(|| {
+            let buffer = buffer::<SLOTS>();
+            let mut writer = buffer.get_writer();
+            let mut reader = buffer.get_reader();
+
+            let writer = thread::spawn(move || {
+                for frame in 1..=3 {
+                    write(&mut writer, frame);
+                }
+            });
+            let first = read(&mut reader);
+            assert!(read(&mut reader) >= first);
+            writer.join().unwrap();
+            assert_eq!(read(&mut reader), 3);
+        });
+    }
+
+    #[test]
+    fn rapid_overwrites_never_share_a_slot() {
+        rapid_overwrites::<2>();
+        rapid_overwrites::<3>();
+        rapid_overwrites::<4>();
+    }
+
+    #[test]
+    fn reacquired_writer_sees_the_staged_slot() {
+        loom::model(|| {
+            let buffer = buffer::<3>();
+            let mut reader = buffer.get_reader();
+
+            let first = thread::spawn(move || {
+                if let Some(mut writer) = buffer.try_get_writer() {
+                    stage(&mut writer, 1);
+                }
+            });
+            let second = thread::spawn(move || {
+                if let Some(mut writer) = buffer.try_get_writer() {
+                    write(&mut writer, 2);
+                }
+            });
+            assert!(read(&mut reader) <= 2);
+            first.join().unwrap();
+            second.join().unwrap();
+        });
+    }
+
+    #[test]
+    fn frame_staged_by_a_dropped_writer_reaches_the_reader() {
+        loom::model(|| {
+            let buffer = buffer::<3>();
+            let mut reader = buffer.get_reader();
+
+            let first = thread::spawn(move || {
+                if let Some(mut writer) = buffer.try_get_writer() {
+                    stage(&mut writer, 7);
+                }
+            });
+            // Publishes whatever it finds staged: 7 if it came second.
+            let second = thread::spawn(move || {
+                buffer.try_get_writer().map(|mut writer| {
+                    let staged = writer.input_buffer().with(|slot| unsafe { *slot });
+                    writer.publish();
+                    staged
+                })
+            });
+            let frame = read(&mut reader);
+            assert!(frame == 0 || frame == 7, "{frame}");
+            first.join().unwrap();
+            if let Some(staged) = second.join().unwrap() {
+                assert_eq!(read(&mut reader), staged);
             }
-        }
-        #[derive(Default, PartialEq, Eq, Debug)]
-        struct MyBiggerStruct {
-            goose: u32,
-            duck: u32,
-            cat: Cat,
-        }
-        static goose_buffer: TripleBuffer<MyBiggerStruct> =
-            TripleBuffer::<MyBiggerStruct>::new_const(
-                MyBiggerStruct {
-                    goose: 0,
-                    duck: 1,
-                    cat: Cat {
-                        is_there_cat: false,
-                    },
-                },
-                MyBiggerStruct {
-                    goose: 0,
-                    duck: 1,
-                    cat: Cat {
-                        is_there_cat: false,
-                    },
-                },
-                MyBiggerStruct {
-                    goose: 0,
-                    duck: 1,
-                    cat: Cat {
-                        is_there_cat: false,
-                    },
-                },
-            );
-        let jh = std::thread::spawn(|| {
-            let mut goose_writer = goose_buffer.get_writer();
-
-            let temp_goose = goose_writer.input_buffer();
-            temp_goose.goose = 4;
-            temp_goose.duck = 2;
-            temp_goose.cat.bring_cat();
-            goose_writer.publish()
         });
+    }
 
-        let mut goose_reader = goose_buffer.get_reader();
-        let evil_goose_1 = goose_reader.read();
-        let evil_goose_2 = goose_reader.read();
-        let evil_goose_3 = goose_reader.read();
+    #[test]
+    fn handles_pass_their_slots_on_when_dropped() {
+        loom::model(|| {
+            let buffer = buffer::<3>();
+            let mut writer = buffer.get_writer();
+            let reader = buffer.get_reader();
 
-        println!("{:?}", *evil_goose_3);
-        jh.join().unwrap();
+            // Both handles leave, racing the other side's next handle.
+            let first = thread::spawn(move || {
+                write(&mut writer, 1);
+                drop(writer);
+                buffer.try_get_reader().map(|mut reader| read(&mut reader))
+            });
+            drop(reader);
+            let second = thread::spawn(move || {
+                if let Some(mut writer) = buffer.try_get_writer() {
+                    write(&mut writer, 2);
+                }
+            });
+            let seen = first.join().unwrap();
+            second.join().unwrap();
+            assert!(seen.map_or(true, |seen| (1..=2).contains(&seen)), "{seen:?}");
+
+            // Whoever came last, the parked slots are still all distinct.
+            let mut writer = buffer.get_writer();
+            let mut reader = buffer.get_reader();
+            write(&mut writer, 3);
+            assert_eq!(read(&mut reader), 3);
+        });
+    }
 
-        assert!(
-            *goose_reader.read()
-                == MyBiggerStruct {
-                    goose: 4,
-                    duck: 2,
-                    cat: Cat { is_there_cat: true }
+    fn final_frame_after_writer_drop<const SLOTS: usize>(attached: bool) {
+        loom::model(move || {
+            let buffer = buffer::<SLOTS>();
+            let reader = attached.then(|| buffer.get_reader());
+            let mut writer = buffer.get_writer();
+
+            let writer = thread::spawn(move || write(&mut writer, 1));
+            if !buffer.state().writer_attached {
+                let mut reader = reader.unwrap_or_else(|| buffer.get_reader());
+                assert_eq!(read(&mut reader), 1);
+            }
+            writer.join().unwrap();
+        });
+    }
+
+    #[test]
+    fn final_frame_survives_writer_drop() {
+        final_frame_after_writer_drop::<2>(true);
+        final_frame_after_writer_drop::<2>(false);
+        final_frame_after_writer_drop::<3>(true);
+        final_frame_after_writer_drop::<3>(false);
+    }
+
+    #[test]
+    fn reacquired_reader_sees_the_released_slot() {
+        loom::model(|| {
+            let buffer = buffer::<3>();
+            let mut writer = buffer.get_writer();
+
+            let first = thread::spawn(move || {
+                if let Some(mut reader) = buffer.try_get_reader() {
+                    read(&mut reader);
+                }
+            });
+            let second = thread::spawn(move || {
+                if let Some(mut reader) = buffer.try_get_reader() {
+                    reader.output_buffer().with_mut(|slot| unsafe { *slot = 0 });
+                    read(&mut reader);
                 }
-        )
+            });
+            write(&mut writer, 1);
+            first.join().unwrap();
+            second.join().unwrap();
+        });
+    }
+}
+
+// A failing schedule is printed along with the panic; pass it and the
+// scenario to `shuttle::replay` to run it again.
+#[cfg(all(test, tri_buffer_shuttle))]
+mod shuttle_tests {
+    use super::*;
+    use shuttle::sync::atomic::AtomicU64;
+    use shuttle::thread;
+
+    type Frame = [u64; 4];
+
+    fn buffer<const SLOTS: usize>() -> &'static NBuffer<Frame, SLOTS, SpinNotifier> {
+        Box::leak(Box::new(NBuffer::from_slots_with_notifiers(
+            [[0; 4]; SLOTS],
+            SpinNotifier,
+            SpinNotifier,
+        )))
+    }
+
+    fn check_frame(frame: &Frame, previous: u64) -> u64 {
+        assert!(frame.iter().all(|&word| word == frame[0]), "torn frame");
+        assert!(frame[0] >= previous, "frame went back in time");
+        frame[0]
+    }
+
+    fn spsc_exchange<const SLOTS: usize>() {
+        let buffer = buffer::<SLOTS>();
+        let last = 2_000;
+        let mut writer = buffer.get_writer();
+        let mut reader = buffer.get_reader();
+
+        let writer = thread::spawn(move || {
+            for i in 1..=last {
+                writer.write([i; 4]);
+            }
+        });
+        let mut previous = 0;
+        while previous != last {
+            previous = check_frame(reader.read(), previous);
+        }
+        writer.join().unwrap();
+    }
+
+    #[test]
+    fn sustained_spsc_exchange() {
+        shuttle::check_random(spsc_exchange::<2>, 100);
+        shuttle::check_random(spsc_exchange::<3>, 100);
+        shuttle::check_random(spsc_exchange::<4>, 100);
+    }
+
+    fn reader_churn() {
+        let buffer = buffer::<3>();
+        let last = 500;
+        // Newest frame seen by any reader; only ever touched by the one
+        // holding the reader, so the handoff orders it.
+        let seen: &'static AtomicU64 = Box::leak(Box::new(AtomicU64::new(0)));
+        let mut writer = buffer.get_writer();
+
+        let writer = thread::spawn(move || {
+            for i in 1..=last {
+                writer.write([i; 4]);
+            }
+        });
+        let readers: [_; 2] = core::array::from_fn(|_| {
+            thread::spawn(move || {
+                while seen.load(Ordering::Relaxed) != last {
+                    let Some(mut reader) = buffer.try_get_reader() else {
+                        thread::yield_now();
+                        continue;
+                    };
+                    let previous = seen.load(Ordering::Relaxed);
+                    seen.store(check_frame(reader.read(), previous), Ordering::Relaxed);
+                }
+            })
+        });
+        writer.join().unwrap();
+        for reader in readers {
+            reader.join().unwrap();
+        }
+        assert_eq!(*buffer.get_reader().read(), [last; 4]);
+    }
+
+    #[test]
+    fn reader_attach_detach_churn() {
+        shuttle::check_random(reader_churn, 200);
+    }
+}
+
+// Interleavings picked by parking a thread inside an operation; see
+// `sync::faults`.
+#[cfg(all(
+    test,
+    tri_buffer_faults,
+    not(any(tri_buffer_loom, tri_buffer_shuttle))
+))]
+mod fault_tests {
+    use super::*;
+    use std::sync::mpsc;
+    use sync::faults::{self, Op};
+
+    /// Parks the writer right after `publish` swaps the back info (or CASes
+    /// it, under `packed-control`), before it stores its next input slot,
+    /// and updates the reader in that window.
+    fn reader_updates_mid_publish<const SLOTS: usize>() {
+        let buffer = NBuffer::<u32, SLOTS, SpinNotifier>::from_slots_with_notifiers(
+            [0; SLOTS],
+            SpinNotifier,
+            SpinNotifier,
+        );
+        let mut writer = buffer.get_writer();
+        let mut reader = buffer.get_reader();
+        writer.write(1);
+        let back_info = &*buffer.back_info as *const AtomicControlWord as usize;
+        let publish = if cfg!(feature = "packed-control") {
+            Op::CompareExchange
+        } else {
+            Op::Swap
+        };
+        let (parked, on_parked) = mpsc::channel();
+        let (resume, on_resume) = mpsc::channel();
+
+        std::thread::scope(|s| {
+            s.spawn(move || {
+                faults::set_hook(move |op, atomic| {
+                    if op == publish && atomic as usize == back_info {
+                        parked.send(()).unwrap();
+                        on_resume.recv().unwrap();
+                    }
+                });
+                writer.write(2);
+                faults::clear_hook();
+                writer.write(3);
+            });
+
+            on_parked.recv().unwrap();
+            let state = buffer.state();
+            assert_eq!(*reader.read(), 2);
+            assert_eq!(reader.state().output, state.back.unwrap());
+            resume.send(()).unwrap();
+        });
+
+        // The writer was dropped with its thread, leaving its input slot.
+        let state = reader.state();
+        let mut slots = [state.back.unwrap(), state.input.unwrap(), state.output];
+        slots.sort();
+        slots.windows(2).for_each(|pair| assert_ne!(pair[0], pair[1]));
+        assert_eq!(*reader.read(), 3);
+    }
+
+    #[test]
+    fn reader_updates_while_writer_is_mid_publish() {
+        reader_updates_mid_publish::<3>();
+        reader_updates_mid_publish::<4>();
     }
 }
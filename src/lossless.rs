@@ -0,0 +1,204 @@
+use crate::{BufferReader, BufferWriter, DefaultNotifier, Notifier, TripleBuffer, WouldBlock};
+
+/// A `TripleBuffer` that behaves like a bounded(1) channel: every frame is
+/// read exactly once, in order. The writer waits for the previous frame to
+/// be taken before publishing, so with three slots one frame is in flight
+/// while the next is staged and the reader still holds the one before.
+///
+/// The writer waits forever if the reader detaches with a frame unread.
+pub struct Lossless<T, N = DefaultNotifier> {
+    buffer: TripleBuffer<T, N>,
+}
+
+pub struct LosslessReader<'a, T, N: Notifier = DefaultNotifier> {
+    reader: BufferReader<'a, T, N>,
+}
+
+pub struct LosslessWriter<'a, T, N: Notifier = DefaultNotifier> {
+    writer: BufferWriter<'a, T, N>,
+}
+
+impl<T> Lossless<T> {
+    pub fn new(generator: impl Fn() -> T) -> Self {
+        Self::new_const(generator(), generator(), generator())
+    }
+
+    pub const fn new_const(s1: T, s2: T, s3: T) -> Self {
+        Self {
+            buffer: TripleBuffer::new_const(s1, s2, s3),
+        }
+    }
+}
+
+impl<T, N: Notifier> Lossless<T, N> {
+    pub const fn with_notifiers(
+        s1: T,
+        s2: T,
+        s3: T,
+        reader_notifier: N,
+        writer_notifier: N,
+    ) -> Self {
+        Self {
+            buffer: TripleBuffer::with_notifiers(s1, s2, s3, reader_notifier, writer_notifier),
+        }
+    }
+
+    pub fn get_reader(&self) -> LosslessReader<'_, T, N> {
+        LosslessReader {
+            reader: self.buffer.get_reader(),
+        }
+    }
+
+    pub fn get_writer(&self) -> LosslessWriter<'_, T, N> {
+        LosslessWriter {
+            writer: self.buffer.get_writer(),
+        }
+    }
+}
+
+impl<'a, T, N: Notifier> LosslessReader<'a, T, N> {
+    /// Waits for the next frame and takes it.
+    pub fn read(&mut self) -> &T {
+        self.reader.read_blocking()
+    }
+
+    /// Takes the next frame if there is one.
+    pub fn try_read(&mut self) -> Option<&T> {
+        if self.reader.update() {
+            Some(self.reader.output_buffer())
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, T, N: Notifier> LosslessWriter<'a, T, N> {
+    /// The slot the next frame is staged in; never read before `publish`.
+    pub fn input_buffer(&mut self) -> &mut T {
+        self.writer.input_buffer()
+    }
+
+    /// Whether the previous frame was taken, so `publish` won't wait.
+    pub fn consumed(&self) -> bool {
+        self.writer.consumed()
+    }
+
+    /// Waits for the previous frame to be taken, then publishes the input
+    /// slot.
+    pub fn publish(&mut self) {
+        let buffer = self.writer.write_buffer;
+        buffer.writer_notifier.wait(|| self.writer.consumed());
+        self.writer.publish();
+    }
+
+    /// Publishes the input slot unless the previous frame is still unread.
+    pub fn try_publish(&mut self) -> Result<(), WouldBlock> {
+        if !self.writer.consumed() {
+            return Err(WouldBlock);
+        }
+        self.writer.publish();
+        Ok(())
+    }
+
+    pub fn write(&mut self, value: T) {
+        self.writer.write_blocking(value);
+    }
+
+    /// Like `write`, but hands `value` back instead of waiting.
+    pub fn try_write(&mut self, value: T) -> Result<(), T> {
+        if !self.writer.consumed() {
+            return Err(value);
+        }
+        self.writer.write(value);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SpinNotifier;
+    #[cfg(feature = "std")]
+    use crate::ThreadNotifier;
+
+    #[test]
+    fn writer_waits_for_each_frame() {
+        let channel = Lossless::with_notifiers(0, 0, 0, SpinNotifier, SpinNotifier);
+        let mut writer = channel.get_writer();
+        let mut reader = channel.get_reader();
+
+        assert_eq!(reader.try_read(), None);
+        assert_eq!(writer.try_write(1), Ok(()));
+        assert_eq!(writer.try_write(2), Err(2));
+        *writer.input_buffer() = 2;
+        assert_eq!(writer.try_publish(), Err(WouldBlock));
+        assert_eq!(reader.try_read(), Some(&1));
+        assert_eq!(reader.try_read(), None, "frame read twice");
+        assert_eq!(writer.try_publish(), Ok(()));
+        assert_eq!(*reader.read(), 2);
+    }
+
+    fn exchange_sequenced<N: Notifier + Sync>(channel: &'static Lossless<u64, N>, count: u64) {
+        let jh = std::thread::spawn(move || {
+            let mut writer = channel.get_writer();
+            for i in 1..=count {
+                writer.write(i);
+            }
+        });
+
+        let mut reader = channel.get_reader();
+        for i in 1..=count {
+            assert_eq!(*reader.read(), i, "frame lost or reordered");
+        }
+        assert_eq!(reader.try_read(), None, "frame delivered twice");
+        jh.join().unwrap();
+        assert_eq!(reader.try_read(), None);
+    }
+
+    #[test]
+    fn every_frame_arrives_once_in_order() {
+        static CHANNEL: Lossless<u64, SpinNotifier> =
+            Lossless::with_notifiers(0, 0, 0, SpinNotifier, SpinNotifier);
+        exchange_sequenced(&CHANNEL, 200);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn thread_notifier_delivers_a_million_frames() {
+        static CHANNEL: Lossless<u64, ThreadNotifier> =
+            Lossless::with_notifiers(0, 0, 0, ThreadNotifier::new(), ThreadNotifier::new());
+        exchange_sequenced(&CHANNEL, 1_000_000);
+    }
+}
+
+#[cfg(all(test, tri_buffer_shuttle))]
+mod shuttle_tests {
+    use super::*;
+    use crate::SpinNotifier;
+    use shuttle::thread;
+
+    fn delivery() {
+        let channel: &'static Lossless<u64, SpinNotifier> = Box::leak(Box::new(
+            Lossless::with_notifiers(0, 0, 0, SpinNotifier, SpinNotifier),
+        ));
+        let count = 1_000;
+        let mut writer = channel.get_writer();
+        let mut reader = channel.get_reader();
+
+        let writer = thread::spawn(move || {
+            for i in 1..=count {
+                writer.write(i);
+            }
+        });
+        for i in 1..=count {
+            assert_eq!(*reader.read(), i, "frame lost or repeated");
+        }
+        writer.join().unwrap();
+        assert_eq!(reader.try_read(), None, "frame delivered twice");
+    }
+
+    #[test]
+    fn every_frame_is_delivered_once() {
+        shuttle::check_random(delivery, 100);
+    }
+}
@@ -0,0 +1,96 @@
+use crate::{BufferReader, BufferWriter, DefaultNotifier, Notifier, TripleBuffer};
+
+/// A `TripleBuffer` of `Option<T>` where the reader takes values out rather
+/// than re-reading the latest one. A post replaces one that wasn't taken
+/// yet and hands it back to the writer, so every value is either taken
+/// exactly once or returned by `post`.
+///
+/// Only use `post`/`take` on a mailbox's handles: a bare `update` followed
+/// by another post lets the frame it took be overwritten unseen.
+pub type Mailbox<T, N = DefaultNotifier> = TripleBuffer<Option<T>, N>;
+
+impl<T> Mailbox<T> {
+    pub const fn empty() -> Self {
+        Self::new_const(None, None, None)
+    }
+}
+
+impl<'a, T, N: Notifier> BufferWriter<'a, Option<T>, N> {
+    /// Publishes `value`; returns the earlier post it replaced, if the
+    /// reader hadn't taken that one yet.
+    pub fn post(&mut self, value: T) -> Option<T> {
+        *self.input_buffer() = Some(value);
+        if self.publish() {
+            // The overwritten back slot is our input slot now.
+            self.input_buffer().take()
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, T, N: Notifier> BufferReader<'a, Option<T>, N> {
+    /// Takes the latest post, leaving the mailbox empty until the next one.
+    pub fn take(&mut self) -> Option<T> {
+        self.update();
+        self.output_buffer().take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SpinNotifier;
+
+    #[test]
+    fn posts_are_taken_once_or_handed_back() {
+        let mailbox = Mailbox::<u32>::empty();
+        let mut writer = mailbox.get_writer();
+        let mut reader = mailbox.get_reader();
+
+        assert_eq!(reader.take(), None);
+        assert_eq!(writer.post(1), None);
+        assert!(reader.updated());
+        assert_eq!(reader.take(), Some(1));
+        assert!(!reader.updated());
+        assert_eq!(reader.take(), None);
+
+        assert_eq!(writer.post(2), None);
+        assert_eq!(writer.post(3), Some(2));
+        assert_eq!(writer.post(4), Some(3));
+        assert_eq!(reader.take(), Some(4));
+        assert_eq!(writer.post(5), None);
+        assert_eq!(reader.take(), Some(5));
+        assert_eq!(reader.take(), None);
+    }
+
+    #[test]
+    fn no_value_is_seen_twice_under_stress() {
+        static MAILBOX: Mailbox<u32, SpinNotifier> =
+            TripleBuffer::with_notifiers(None, None, None, SpinNotifier, SpinNotifier);
+        let count = if cfg!(miri) { 200 } else { 100_000 };
+
+        let jh = std::thread::spawn(move || {
+            let mut writer = MAILBOX.get_writer();
+            (1..=count)
+                .filter_map(|i| writer.post(i))
+                .collect::<Vec<_>>()
+        });
+
+        let mut reader = MAILBOX.get_reader();
+        let mut taken = Vec::new();
+        while !jh.is_finished() {
+            taken.extend(reader.take());
+        }
+        let mut returned = jh.join().unwrap();
+        taken.extend(reader.take());
+
+        assert!(
+            taken.windows(2).all(|pair| pair[0] < pair[1]),
+            "taken twice"
+        );
+        returned.append(&mut taken);
+        returned.sort_unstable();
+        assert_eq!(returned, (1..=count).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,63 @@
+use crate::Backoff;
+
+/// Wake strategy used by the blocking reader and writer APIs.
+///
+/// Each `TripleBuffer` owns two notifiers: the reader's one is notified by
+/// `publish()`, the writer's one by a successful `update()`. `wait` must not
+/// return before `until()` was observed to be `true`, and `notify` must be
+/// cheap when nobody is waiting since it runs on every publish/update.
+///
+/// Notifiers skip the wake-up entirely unless a waiter announced itself.
+/// The waiter announces itself before its final `until()` check and the
+/// notifier looks for it after the condition changed, both `SeqCst`, so one
+/// of the two always sees the other.
+pub trait Notifier {
+    fn notify(&self);
+    fn wait(&self, until: impl Fn() -> bool);
+
+    /// Like `wait`, but may also return after roughly `timeout_us`
+    /// microseconds, or spuriously; callers re-check their deadline. The
+    /// default waits without a bound, which suits notifiers that poll
+    /// `until` anyway.
+    fn wait_timeout(&self, until: impl Fn() -> bool, timeout_us: u32) {
+        let _ = timeout_us;
+        self.wait(until);
+    }
+}
+
+/// Polls the condition with a `Backoff`; notifying is a no-op.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SpinNotifier;
+
+impl SpinNotifier {
+    pub const fn new() -> Self {
+        Self
+    }
+}
+
+impl Notifier for SpinNotifier {
+    #[inline]
+    fn notify(&self) {}
+
+    fn wait(&self, until: impl Fn() -> bool) {
+        let mut backoff = Backoff::new();
+        while !until() {
+            backoff.snooze();
+        }
+    }
+}
+
+/// Notifier used by `TripleBuffer::new` and `TripleBuffer::new_const`:
+/// `FutexNotifier` with the `futex` feature, `ThreadNotifier` with `std`,
+/// `SpinNotifier` otherwise.
+///
+/// On wasm it is always `SpinNotifier`: parking is `Atomics.wait`, which
+/// traps on a browser's main thread. A main-thread reader of a buffer in a
+/// `SharedArrayBuffer` sticks to `read`/`update`/`updated` between turns of
+/// the event loop; a blocking read spins.
+#[cfg(feature = "futex")]
+pub type DefaultNotifier = crate::FutexNotifier;
+#[cfg(all(feature = "std", not(feature = "futex"), not(target_family = "wasm")))]
+pub type DefaultNotifier = crate::ThreadNotifier;
+#[cfg(not(any(all(feature = "std", not(target_family = "wasm")), feature = "futex")))]
+pub type DefaultNotifier = SpinNotifier;
@@ -0,0 +1,43 @@
+//! The memory orderings of every atomic operation in the crate outside the
+//! tests. With the `strict-ordering` feature they are all `SeqCst`, to rule
+//! out (or in) an ordering bug before reaching for loom; without it each is
+//! a constant, so the default build compiles exactly as if written inline.
+
+use crate::atomic::Ordering;
+
+macro_rules! orderings {
+    ($($name:ident => $ordering:ident),*) => {$(
+        #[inline(always)]
+        pub(crate) const fn $name() -> Ordering {
+            if cfg!(feature = "strict-ordering") {
+                Ordering::SeqCst
+            } else {
+                Ordering::$ordering
+            }
+        }
+    )*};
+}
+
+orderings!(relaxed => Relaxed, acquire => Acquire, release => Release, acqrel => AcqRel);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn orderings_follow_the_feature() {
+        // Evaluated at compile time, so there is nothing left to call.
+        const ORDERINGS: [Ordering; 4] = [relaxed(), acquire(), release(), acqrel()];
+        let expected = if cfg!(feature = "strict-ordering") {
+            [Ordering::SeqCst; 4]
+        } else {
+            [
+                Ordering::Relaxed,
+                Ordering::Acquire,
+                Ordering::Release,
+                Ordering::AcqRel,
+            ]
+        };
+        assert_eq!(ORDERINGS, expected);
+    }
+}
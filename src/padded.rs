@@ -0,0 +1,53 @@
+//! With the `padded` feature, `CachePadded` gives the control word both
+//! handles swap a cache line of its own, so the traffic on it doesn't also
+//! invalidate the fields around it. Without it, it is just the value, for
+//! targets where the extra line costs more than it saves.
+
+use core::ops::{Deref, DerefMut};
+
+// 128 where the line is 128 bytes or the prefetcher pulls lines in pairs.
+#[cfg_attr(
+    all(
+        feature = "padded",
+        any(
+            target_arch = "x86_64",
+            target_arch = "aarch64",
+            target_arch = "powerpc64"
+        )
+    ),
+    repr(align(128))
+)]
+#[cfg_attr(
+    all(
+        feature = "padded",
+        not(any(
+            target_arch = "x86_64",
+            target_arch = "aarch64",
+            target_arch = "powerpc64"
+        ))
+    ),
+    repr(align(64))
+)]
+pub(crate) struct CachePadded<T>(T);
+
+impl<T> CachePadded<T> {
+    pub(crate) const fn new(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T> Deref for CachePadded<T> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for CachePadded<T> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
@@ -0,0 +1,238 @@
+//! Invariant checks for the `paranoid` feature, run on every operation that
+//! touches a slot and on handle acquisition and drop.
+//!
+//! Each side checks its own slots and the back slot only: the other side
+//! keeps its index to itself while attached. The full permutation is
+//! checked where neither side can be attached, in `split()`.
+
+use core::fmt;
+
+use crate::control::{ControlWord, Word, BACK_INDEX_MASK};
+use crate::hook::Hook;
+use crate::{is_dirty, ord, BufferState, NBuffer, NO_SLOT};
+
+/// A broken invariant, passed to the violation handler.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub struct Violation {
+    /// The operation that found it, e.g. `"publish"`.
+    pub op: &'static str,
+    pub check: &'static str,
+    /// The control state right after the check failed.
+    pub state: BufferState,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "tri-buffer invariant violated in {}: {} ({:?})",
+            self.op, self.check, self.state
+        )
+    }
+}
+
+static HANDLER: Hook<Violation> = Hook::new();
+
+/// Installs `handler` to be called on every violation, from the thread that
+/// found it, instead of panicking; e.g. to log and reset on targets without
+/// unwinding. The operation goes on once it returns. `None` restores the
+/// panic.
+pub fn set_violation_handler(handler: Option<fn(&Violation)>) {
+    HANDLER.set(handler);
+}
+
+impl<T, const SLOTS: usize, N> NBuffer<T, SLOTS, N> {
+    fn violated(&self, op: &'static str, check: &'static str) {
+        let violation = Violation {
+            op,
+            check,
+            state: self.state(),
+        };
+        HANDLER.fire_or(violation, |violation| panic!("{violation}"));
+    }
+
+    fn back_slot(&self, op: &'static str) -> u8 {
+        let back = ControlWord(self.back_info.load(ord::acquire())).back();
+        // A two-slot writer leaves `NO_SLOT` behind while it holds both.
+        if back as usize >= SLOTS && !(SLOTS == 2 && back == NO_SLOT) {
+            self.violated(op, "back slot out of range");
+        }
+        back
+    }
+
+    /// Bitmask of the writer's input and spare slots.
+    fn writer_slots(&self, op: &'static str, back: u8, input: u8) -> u128 {
+        let spares = self.spare[..SLOTS.saturating_sub(3)]
+            .iter()
+            .map(|spare| spare.load(ord::relaxed()));
+        let held = (SLOTS != 2 || input != NO_SLOT).then_some(input);
+        let mut seen = 0;
+        for slot in held.into_iter().chain(spares) {
+            if is_dirty(Word::from(slot)) {
+                self.violated(op, "writer slot has the dirty bit");
+            } else if slot as usize >= SLOTS {
+                self.violated(op, "writer slot out of range");
+            } else if seen & 1 << slot != 0 {
+                self.violated(op, "writer holds a slot twice");
+            } else if slot == back {
+                self.violated(op, "writer slot is also the back slot");
+            }
+            seen |= 1 << (slot & BACK_INDEX_MASK);
+        }
+        seen
+    }
+
+    /// Bitmask of the reader's output slot.
+    fn reader_slots(&self, op: &'static str, back: u8, output: u8) -> u128 {
+        if is_dirty(Word::from(output)) {
+            self.violated(op, "reader slot has the dirty bit");
+        } else if output as usize >= SLOTS {
+            self.violated(op, "reader slot out of range");
+        } else if output == back {
+            self.violated(op, "reader slot is also the back slot");
+        }
+        1 << (output & BACK_INDEX_MASK)
+    }
+
+    /// Checks the writer's slots, `input` being its input slot.
+    pub(crate) fn check_writer(&self, op: &'static str, input: u8) {
+        let back = self.back_slot(op);
+        self.writer_slots(op, back, input);
+    }
+
+    /// Checks the reader's slot, `output` being its output slot.
+    pub(crate) fn check_reader(&self, op: &'static str, output: u8) {
+        let back = self.back_slot(op);
+        self.reader_slots(op, back, output);
+    }
+
+    /// Every slot is held by exactly one of the writer, the reader and the
+    /// back, as the handles last left them. Only sound while neither handle
+    /// is attached.
+    pub(crate) fn check_all(&self, op: &'static str) {
+        let back = self.back_slot(op);
+        let writer = self.writer_slots(op, back, self.parked_input(ord::relaxed()));
+        let reader = self.reader_slots(op, back, self.parked_output(ord::relaxed()));
+        let back = if back == NO_SLOT { 0 } else { 1 << back };
+        let all = u128::MAX >> (128 - SLOTS);
+        if writer & reader != 0 || (writer | reader | back) != all {
+            self.violated(op, "slots don't form a permutation");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::BACK_DIRTY_BIT;
+    use crate::TripleBuffer;
+    use std::cell::RefCell;
+    use std::vec::Vec;
+
+    use crate::atomic::Ordering;
+
+    std::thread_local! {
+        static FOUND: RefCell<Vec<(&'static str, &'static str)>> = const { RefCell::new(Vec::new()) };
+    }
+
+    fn record(violation: &Violation) {
+        FOUND.with(|found| found.borrow_mut().push((violation.op, violation.check)));
+    }
+
+    /// Runs `f` and returns the violations it found on this thread.
+    fn violations(f: impl FnOnce()) -> Vec<(&'static str, &'static str)> {
+        set_violation_handler(Some(record));
+        f();
+        FOUND.with(|found| found.take())
+    }
+
+    fn exercise<const SLOTS: usize>() {
+        let buffer = NBuffer::<u32, SLOTS>::new(|| 0);
+        let mut writer = buffer.get_writer();
+        let mut reader = buffer.get_reader();
+        for i in 0..20 {
+            writer.write(i);
+            if i % 3 == 0 {
+                reader.read();
+            }
+            *writer.input_buffer() += 1;
+            reader.output_buffer();
+        }
+    }
+
+    #[test]
+    fn correct_use_passes() {
+        let found = violations(|| {
+            exercise::<2>();
+            exercise::<3>();
+            exercise::<4>();
+            exercise::<5>();
+            let mut buffer = TripleBuffer::new(|| 0);
+            buffer.split();
+        });
+        assert_eq!(found, []);
+    }
+
+    #[test]
+    #[cfg(not(feature = "rt-safe"))]
+    fn writer_slot_in_back_fires() {
+        let buffer = TripleBuffer::new(|| 0);
+        let mut writer = buffer.get_writer();
+        let found = violations(|| {
+            writer.set_input(ControlWord(buffer.back_info.load(Ordering::Relaxed)).back());
+            writer.input_buffer();
+        });
+        assert_eq!(
+            found,
+            [("input_buffer", "writer slot is also the back slot")]
+        );
+    }
+
+    #[test]
+    fn dirty_reader_slot_fires() {
+        let buffer = TripleBuffer::new(|| 0);
+        let mut reader = buffer.get_reader();
+        let found = violations(|| {
+            let output = reader.output();
+            reader.set_output(output | BACK_DIRTY_BIT);
+            buffer.check_reader("test", reader.output());
+            reader.set_output(output);
+            reader.read();
+        });
+        assert_eq!(found, [("test", "reader slot has the dirty bit")]);
+    }
+
+    #[test]
+    #[cfg(not(feature = "rt-safe"))]
+    fn out_of_range_spare_fires_on_publish() {
+        let buffer = NBuffer::<u32, 4>::new(|| 0);
+        let writer = buffer.get_writer();
+        let found = violations(|| {
+            buffer.spare[0].store(9, Ordering::Relaxed);
+            writer.publish();
+        });
+        assert!(found.contains(&("publish", "writer slot out of range")));
+    }
+
+    #[test]
+    fn shared_slot_breaks_the_permutation() {
+        let mut buffer = TripleBuffer::new(|| 0);
+        let found = violations(|| {
+            #[cfg(not(feature = "packed-control"))]
+            {
+                let input = buffer.input_idx.load(Ordering::Relaxed);
+                buffer.output_idx.store(input, Ordering::Relaxed);
+            }
+            #[cfg(feature = "packed-control")]
+            {
+                let ctrl = ControlWord(buffer.back_info.load(Ordering::Relaxed));
+                let shared = ControlWord::new(ctrl.back(), ctrl.input(), ctrl.input());
+                buffer.back_info.store(shared.0, Ordering::Relaxed);
+            }
+            buffer.split();
+        });
+        assert_eq!(found, [("split", "slots don't form a permutation")]);
+    }
+}
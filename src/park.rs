@@ -0,0 +1,163 @@
+use core::cell::UnsafeCell;
+use std::thread::{self, Thread};
+use std::time::Duration;
+
+use crate::atomic::{fence, AtomicU8, Ordering};
+use crate::{ord, Backoff, Notifier};
+
+const SLOT_EMPTY: u8 = 0;
+const SLOT_BUSY: u8 = 1;
+const SLOT_PARKED: u8 = 2;
+
+/// Parks the waiting thread and unparks it on notification.
+///
+/// Holds the `Thread` of at most one parked waiter. The waiter publishes its
+/// handle with `SLOT_PARKED`; whoever moves the state out of `SLOT_PARKED`
+/// owns the handle until it stores `SLOT_EMPTY`.
+pub struct ThreadNotifier {
+    state: AtomicU8,
+    thread: UnsafeCell<Option<Thread>>,
+    #[cfg(test)]
+    wakes: crate::atomic::AtomicU32,
+}
+
+unsafe impl Sync for ThreadNotifier {}
+
+// The handle only changes hands through `state`, never halfway through a
+// panic, so a notifier shared across `catch_unwind` stays consistent.
+impl std::panic::RefUnwindSafe for ThreadNotifier {}
+
+impl ThreadNotifier {
+    pub const fn new() -> Self {
+        Self {
+            state: AtomicU8::new(SLOT_EMPTY),
+            thread: UnsafeCell::new(None),
+            #[cfg(test)]
+            wakes: crate::atomic::AtomicU32::new(0),
+        }
+    }
+
+    fn register(&self) {
+        // A notifier may still be busy taking the previous registration.
+        let mut backoff = Backoff::new();
+        while self
+            .state
+            .compare_exchange_weak(SLOT_EMPTY, SLOT_BUSY, ord::acquire(), ord::relaxed())
+            .is_err()
+        {
+            backoff.snooze();
+        }
+        unsafe { *self.thread.get() = Some(thread::current()) };
+        self.state.store(SLOT_PARKED, Ordering::SeqCst);
+    }
+
+    fn unregister(&self) {
+        if self
+            .state
+            .compare_exchange(SLOT_PARKED, SLOT_BUSY, ord::acquire(), ord::relaxed())
+            .is_ok()
+        {
+            unsafe { *self.thread.get() = None };
+            self.state.store(SLOT_EMPTY, ord::release());
+        }
+    }
+
+    #[cold]
+    fn wake(&self) {
+        if self
+            .state
+            .compare_exchange(SLOT_PARKED, SLOT_BUSY, ord::acquire(), ord::relaxed())
+            .is_ok()
+        {
+            let thread = unsafe { (*self.thread.get()).take() };
+            self.state.store(SLOT_EMPTY, ord::release());
+            if let Some(thread) = thread {
+                #[cfg(test)]
+                self.wakes.fetch_add(1, ord::relaxed());
+                thread.unpark();
+            }
+        }
+    }
+}
+
+impl Default for ThreadNotifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Notifier for ThreadNotifier {
+    #[inline]
+    fn notify(&self) {
+        if self.state.load(Ordering::SeqCst) == SLOT_PARKED {
+            self.wake();
+        }
+    }
+
+    fn wait(&self, until: impl Fn() -> bool) {
+        while !until() {
+            self.register();
+            // Pairs with the SeqCst swap + load in the notifying path: either
+            // we observe the new state here or the notifier observes us.
+            fence(Ordering::SeqCst);
+            if !until() {
+                thread::park();
+            }
+            self.unregister();
+        }
+    }
+
+    fn wait_timeout(&self, until: impl Fn() -> bool, timeout_us: u32) {
+        if until() {
+            return;
+        }
+        self.register();
+        fence(Ordering::SeqCst);
+        if !until() {
+            thread::park_timeout(Duration::from_micros(timeout_us.into()));
+        }
+        self.unregister();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TripleBuffer;
+
+    #[test]
+    fn wakes_only_parked_waiters() {
+        static BUFFER: TripleBuffer<u32, ThreadNotifier> = TripleBuffer::with_notifiers(
+            0,
+            0,
+            0,
+            ThreadNotifier::new(),
+            ThreadNotifier::new(),
+        );
+        let count = 1000;
+        let mut writer = BUFFER.get_writer();
+        let mut reader = BUFFER.get_reader();
+        for i in 1..=count {
+            writer.write(i);
+            assert!(reader.update());
+        }
+        assert_eq!(BUFFER.reader_notifier.wakes.load(Ordering::Relaxed), 0);
+        assert_eq!(BUFFER.writer_notifier.wakes.load(Ordering::Relaxed), 0);
+
+        let jh = thread::spawn(move || {
+            for i in 1..=count {
+                assert_eq!(*reader.read_blocking(), i);
+            }
+        });
+        for i in 1..=count {
+            while !writer.consumed()
+                || BUFFER.reader_notifier.state.load(Ordering::SeqCst) != SLOT_PARKED
+            {
+                thread::yield_now();
+            }
+            writer.write(i);
+        }
+        jh.join().unwrap();
+        assert_eq!(BUFFER.reader_notifier.wakes.load(Ordering::Relaxed), count);
+    }
+}
@@ -0,0 +1,321 @@
+//! A triple buffer that lives in memory mapped by several processes, e.g.
+//! POSIX shared memory, so a producer and a consumer process can exchange
+//! the latest frame without a socket.
+//!
+//! The layout is `#[repr(C)]` and starts with a magic/version header and a
+//! layout fingerprint that `attach` validates, so both sides must be built
+//! with the same `T`. Frames are `Pod`; a pointer would be meaningless in
+//! the other process. The control state uses `core` atomics rather than
+//! `portable-atomic`, whose critical-section fallback wouldn't exclude
+//! another process.
+
+use core::cell::UnsafeCell;
+use core::fmt;
+use core::mem::{align_of, offset_of, size_of};
+use core::sync::atomic::{AtomicU32, AtomicU8};
+
+use bytemuck::Pod;
+
+use crate::control::{is_dirty, published, Word, BACK_INDEX_MASK};
+use crate::layout::Fingerprint;
+use crate::ord;
+
+const MAGIC: u32 = u32::from_be_bytes(*b"TRIB");
+const VERSION: u32 = 2;
+// Stamped into a handle flag while nobody holds that handle.
+const NO_PROCESS: u32 = 0;
+
+#[repr(C)]
+pub struct SharedTripleBuffer<T> {
+    magic: AtomicU32,
+    version: u32,
+    fingerprint: u64,
+    slot_size: u32,
+    slot_align: u32,
+
+    // Pid of the process holding each handle. Advisory: whoever attaches
+    // trusts the others to release their handles, and a crashed holder's
+    // stamp stays behind until `reset_handles`.
+    reader_pid: AtomicU32,
+    writer_pid: AtomicU32,
+
+    back_info: AtomicU8,
+    input_idx: AtomicU8,
+    output_idx: AtomicU8,
+
+    buffers: UnsafeCell<[T; 3]>,
+}
+
+unsafe impl<T: Pod + Send> Sync for SharedTripleBuffer<T> {}
+
+/// `attach` found memory that doesn't hold a `SharedTripleBuffer<T>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum LayoutError {
+    /// The pointer isn't aligned for `SharedTripleBuffer<T>`.
+    Misaligned,
+    /// No buffer was initialized there, or not yet.
+    BadMagic,
+    /// Initialized by an incompatible version of this crate.
+    Version(u32),
+    /// Initialized for a different frame type.
+    FrameLayout { size: u32, align: u32 },
+    /// Initialized by a build that lays the buffer out differently, e.g.
+    /// for another target.
+    Fingerprint(u64),
+}
+
+impl fmt::Display for LayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Misaligned => write!(f, "misaligned shared buffer"),
+            Self::BadMagic => write!(f, "no shared buffer initialized"),
+            Self::Version(version) => write!(f, "shared buffer has layout version {version}"),
+            Self::FrameLayout { size, align } => write!(
+                f,
+                "shared buffer holds frames of size {size} and alignment {align}"
+            ),
+            Self::Fingerprint(fingerprint) => {
+                write!(
+                    f,
+                    "shared buffer has layout fingerprint {fingerprint:#018x}"
+                )
+            }
+        }
+    }
+}
+
+impl core::error::Error for LayoutError {}
+
+impl<T: Pod> SharedTripleBuffer<T> {
+    /// Bytes the mapping must provide.
+    pub const SIZE: usize = size_of::<Self>();
+
+    /// Hash of the size and offsets of everything in the mapping, the
+    /// frame's size and alignment and `LAYOUT_VERSION`, stored in the header
+    /// by `init_at` and checked by `attach`.
+    pub const fn layout_fingerprint() -> u64 {
+        Fingerprint::new()
+            .add(size_of::<Self>() as u64)
+            .add(align_of::<Self>() as u64)
+            .add(size_of::<T>() as u64)
+            .add(align_of::<T>() as u64)
+            .add(offset_of!(Self, fingerprint) as u64)
+            .add(offset_of!(Self, slot_size) as u64)
+            .add(offset_of!(Self, reader_pid) as u64)
+            .add(offset_of!(Self, writer_pid) as u64)
+            .add(offset_of!(Self, back_info) as u64)
+            .add(offset_of!(Self, input_idx) as u64)
+            .add(offset_of!(Self, output_idx) as u64)
+            .add(offset_of!(Self, buffers) as u64)
+            .finish()
+    }
+
+    /// Initializes a buffer at `ptr` with every slot set to `initial`.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must point to `SIZE` writable bytes that stay mapped for `'a`,
+    /// and nobody may use a buffer already there while it is initialized.
+    pub unsafe fn init_at<'a>(ptr: *mut u8, initial: T) -> Result<&'a Self, LayoutError> {
+        if ptr.align_offset(align_of::<Self>()) != 0 {
+            return Err(LayoutError::Misaligned);
+        }
+        let buffer = ptr.cast::<Self>();
+        buffer.write(Self {
+            magic: AtomicU32::new(0),
+            version: VERSION,
+            fingerprint: Self::layout_fingerprint(),
+            slot_size: size_of::<T>() as u32,
+            slot_align: align_of::<T>() as u32,
+
+            reader_pid: AtomicU32::new(NO_PROCESS),
+            writer_pid: AtomicU32::new(NO_PROCESS),
+
+            back_info: AtomicU8::new(0),
+            input_idx: AtomicU8::new(1),
+            output_idx: AtomicU8::new(2),
+
+            buffers: UnsafeCell::new([initial; 3]),
+        });
+        // Attaching processes see the rest of the header once they see this.
+        (*buffer).magic.store(MAGIC, ord::release());
+        Ok(&*buffer)
+    }
+
+    /// Attaches to a buffer another process (or mapping) initialized.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must point to `SIZE` bytes that stay mapped for `'a`, and a
+    /// valid header there must have been written by `init_at` with this `T`.
+    pub unsafe fn attach<'a>(ptr: *const u8) -> Result<&'a Self, LayoutError> {
+        if ptr.align_offset(align_of::<Self>()) != 0 {
+            return Err(LayoutError::Misaligned);
+        }
+        let buffer = &*ptr.cast::<Self>();
+        if buffer.magic.load(ord::acquire()) != MAGIC {
+            return Err(LayoutError::BadMagic);
+        }
+        if buffer.version != VERSION {
+            return Err(LayoutError::Version(buffer.version));
+        }
+        if buffer.slot_size != size_of::<T>() as u32 || buffer.slot_align != align_of::<T>() as u32
+        {
+            return Err(LayoutError::FrameLayout {
+                size: buffer.slot_size,
+                align: buffer.slot_align,
+            });
+        }
+        if buffer.fingerprint != Self::layout_fingerprint() {
+            return Err(LayoutError::Fingerprint(buffer.fingerprint));
+        }
+        Ok(buffer)
+    }
+
+    fn slot(&self, idx: u8) -> *mut T {
+        unsafe { self.buffers.get().cast::<T>().add(idx as usize) }
+    }
+
+    pub fn get_reader(&self) -> ProcessReader<'_, T> {
+        match self.try_get_reader() {
+            Some(reader) => reader,
+            None => panic!("Reader already exists"),
+        }
+    }
+
+    pub fn get_writer(&self) -> ProcessWriter<'_, T> {
+        match self.try_get_writer() {
+            Some(writer) => writer,
+            None => panic!("Writer already exists"),
+        }
+    }
+
+    /// Like `get_reader`, but returns `None` while any process holds a
+    /// reader.
+    pub fn try_get_reader(&self) -> Option<ProcessReader<'_, T>> {
+        stamp(&self.reader_pid).then_some(ProcessReader { read_buffer: self })
+    }
+
+    /// Like `get_writer`, but returns `None` while any process holds a
+    /// writer.
+    pub fn try_get_writer(&self) -> Option<ProcessWriter<'_, T>> {
+        stamp(&self.writer_pid).then_some(ProcessWriter { write_buffer: self })
+    }
+
+    /// Pid of the process holding the reader, if any.
+    pub fn reader_pid(&self) -> Option<u32> {
+        holder(&self.reader_pid)
+    }
+
+    /// Pid of the process holding the writer, if any.
+    pub fn writer_pid(&self) -> Option<u32> {
+        holder(&self.writer_pid)
+    }
+
+    /// Clears both handle stamps, e.g. after a holder crashed.
+    ///
+    /// # Safety
+    ///
+    /// Neither stamped process may still be using its handle.
+    pub unsafe fn reset_handles(&self) {
+        self.reader_pid.store(NO_PROCESS, ord::release());
+        self.writer_pid.store(NO_PROCESS, ord::release());
+    }
+}
+
+fn stamp(pid: &AtomicU32) -> bool {
+    pid.compare_exchange(
+        NO_PROCESS,
+        std::process::id(),
+        ord::acquire(),
+        ord::relaxed(),
+    )
+    .is_ok()
+}
+
+fn holder(pid: &AtomicU32) -> Option<u32> {
+    Some(pid.load(ord::acquire())).filter(|&pid| pid != NO_PROCESS)
+}
+
+pub struct ProcessReader<'a, T: Pod> {
+    read_buffer: &'a SharedTripleBuffer<T>,
+}
+
+pub struct ProcessWriter<'a, T: Pod> {
+    write_buffer: &'a SharedTripleBuffer<T>,
+}
+
+impl<'a, T: Pod> ProcessReader<'a, T> {
+    pub fn read(&mut self) -> &T {
+        self.update();
+        self.output_buffer()
+    }
+
+    pub fn updated(&mut self) -> bool {
+        is_dirty(Word::from(self.read_buffer.back_info.load(ord::acquire())))
+    }
+
+    pub fn output_buffer(&mut self) -> &mut T {
+        let buffer = self.read_buffer;
+        unsafe { &mut *buffer.slot(buffer.output_idx.load(ord::relaxed())) }
+    }
+
+    pub fn update(&mut self) -> bool {
+        let buffer = self.read_buffer;
+        if !self.updated() {
+            return false;
+        }
+        let former_back_info = buffer
+            .back_info
+            .swap(buffer.output_idx.load(ord::relaxed()), ord::acqrel());
+        buffer
+            .output_idx
+            .store(former_back_info & BACK_INDEX_MASK, ord::relaxed());
+        true
+    }
+}
+
+impl<'a, T: Pod> Drop for ProcessReader<'a, T> {
+    fn drop(&mut self) {
+        self.read_buffer
+            .reader_pid
+            .store(NO_PROCESS, ord::release());
+    }
+}
+
+impl<'a, T: Pod> ProcessWriter<'a, T> {
+    pub fn write(&mut self, value: T) {
+        *self.input_buffer() = value;
+        self.publish();
+    }
+
+    pub fn input_buffer(&mut self) -> &mut T {
+        let buffer = self.write_buffer;
+        unsafe { &mut *buffer.slot(buffer.input_idx.load(ord::relaxed())) }
+    }
+
+    pub fn consumed(&self) -> bool {
+        !is_dirty(Word::from(self.write_buffer.back_info.load(ord::acquire())))
+    }
+
+    pub fn publish(&self) -> bool {
+        let buffer = self.write_buffer;
+        let former_back_info = buffer.back_info.swap(
+            published(buffer.input_idx.load(ord::relaxed())),
+            ord::acqrel(),
+        );
+        buffer
+            .input_idx
+            .store(former_back_info & BACK_INDEX_MASK, ord::relaxed());
+        is_dirty(Word::from(former_back_info))
+    }
+}
+
+impl<'a, T: Pod> Drop for ProcessWriter<'a, T> {
+    fn drop(&mut self) {
+        self.write_buffer
+            .writer_pid
+            .store(NO_PROCESS, ord::release());
+    }
+}
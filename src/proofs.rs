@@ -0,0 +1,134 @@
+//! Kani proofs for the three-slot rotation `publish` and `update` perform
+//! through `publish_transition` and `update_transition`. Run them with
+//! `cargo kani --features verification`, and with
+//! `--features verification,packed-control` for the packed control word.
+
+use crate::control::published;
+use crate::{is_dirty, publish_transition, update_transition, ControlWord};
+
+const STEPS: usize = 8;
+
+/// The control values, plus the frame each slot holds: frames are numbered
+/// by the publish that staged them, 0 for the initial ones.
+struct Rotation {
+    back_info: ControlWord,
+    input_idx: u8,
+    output_idx: u8,
+    frames: [u32; 3],
+    published: u32,
+    consumed: u32,
+    overwritten: u32,
+    last_consumed: u32,
+}
+
+impl Rotation {
+    /// Any reachable state: each slot has one owner, and the back slot may
+    /// hold an unread frame.
+    fn any() -> Self {
+        let back: u8 = kani::any();
+        let input_idx: u8 = kani::any();
+        kani::assume(back < 3 && input_idx < 3 && back != input_idx);
+        let output_idx = 3 - back - input_idx;
+        let dirty: bool = kani::any();
+        let back_info = if dirty { published(back) } else { back };
+        let mut rotation = Self {
+            back_info: ControlWord::new(back_info, input_idx, output_idx),
+            input_idx,
+            output_idx,
+            frames: [0; 3],
+            published: 0,
+            consumed: 0,
+            overwritten: 0,
+            last_consumed: 0,
+        };
+        if dirty {
+            rotation.published = 1;
+            rotation.frames[back as usize] = 1;
+        }
+        rotation
+    }
+
+    fn publish(&mut self) {
+        self.published += 1;
+        self.frames[self.input_idx as usize] = self.published;
+        let (back_info, input_idx, overwrote) = publish_transition(self.back_info, self.input_idx);
+        self.back_info = back_info;
+        self.input_idx = input_idx;
+        if overwrote {
+            self.overwritten += 1;
+        }
+    }
+
+    fn update(&mut self) {
+        if let Some((back_info, output_idx)) = update_transition(self.back_info, self.output_idx) {
+            self.back_info = back_info;
+            self.output_idx = output_idx;
+            let frame = self.frames[output_idx as usize];
+            assert!(frame > self.last_consumed, "frame consumed twice");
+            self.last_consumed = frame;
+            self.consumed += 1;
+        }
+    }
+
+    fn step(&mut self) {
+        if kani::any() {
+            self.publish();
+        } else {
+            self.update();
+        }
+    }
+
+    fn back_idx(&self) -> u8 {
+        self.back_info.back()
+    }
+}
+
+#[kani::proof]
+#[kani::unwind(9)]
+fn indices_stay_a_permutation() {
+    let mut rotation = Rotation::any();
+    for _ in 0..STEPS {
+        rotation.step();
+        let (back, input, output) = (rotation.back_idx(), rotation.input_idx, rotation.output_idx);
+        assert!(back < 3 && input < 3 && output < 3);
+        assert!(back != input && input != output && output != back);
+    }
+}
+
+#[kani::proof]
+#[kani::unwind(9)]
+fn reader_never_maps_the_staging_slot() {
+    let mut rotation = Rotation::any();
+    for _ in 0..STEPS {
+        rotation.step();
+        assert!(rotation.output_idx != rotation.input_idx);
+    }
+}
+
+#[kani::proof]
+#[kani::unwind(9)]
+fn published_frames_are_consumed_or_overwritten_once() {
+    let mut rotation = Rotation::any();
+    for _ in 0..STEPS {
+        rotation.step();
+        let unread = is_dirty(rotation.back_info.0) as u32;
+        assert_eq!(
+            rotation.published,
+            rotation.consumed + rotation.overwritten + unread
+        );
+    }
+}
+
+/// Under `packed-control` the word also carries the handles' slots, which
+/// have to stay the ones the rotation hands them.
+#[cfg(feature = "packed-control")]
+#[kani::proof]
+#[kani::unwind(9)]
+fn packed_word_tracks_the_handles() {
+    let mut rotation = Rotation::any();
+    for _ in 0..STEPS {
+        rotation.step();
+        assert_eq!(rotation.back_info.input(), rotation.input_idx);
+        assert_eq!(rotation.back_info.output(), rotation.output_idx);
+    }
+}
@@ -0,0 +1,169 @@
+use crate::{is_dirty, ord, BufferReader, BufferWriter, Notifier};
+
+/// One pipeline step: if `reader` has a fresh frame, transforms it with `f`
+/// into `writer`'s input slot and publishes that. Returns whether it did;
+/// without a fresh frame nothing is republished downstream.
+pub fn pump<T, U, N: Notifier, M: Notifier, const S: usize, const R: usize>(
+    reader: &mut BufferReader<'_, T, N, S>,
+    writer: &mut BufferWriter<'_, U, M, R>,
+    mut f: impl FnMut(&T, &mut U),
+) -> bool {
+    if !reader.update() {
+        return false;
+    }
+    f(reader.output_buffer(), writer.input_buffer());
+    writer.publish();
+    true
+}
+
+fn upstream_closed<T, N: Notifier, const S: usize>(reader: &BufferReader<'_, T, N, S>) -> bool {
+    !reader.read_buffer.is_writer_exist.load(ord::acquire())
+}
+
+/// Pumps every fresh frame, waiting on the reader notifier in between,
+/// until the upstream writer is dropped and its last frame was pumped.
+pub fn pump_blocking<T, U, N: Notifier, M: Notifier, const S: usize, const R: usize>(
+    reader: &mut BufferReader<'_, T, N, S>,
+    writer: &mut BufferWriter<'_, U, M, R>,
+    mut f: impl FnMut(&T, &mut U),
+) {
+    loop {
+        // Checked before pumping, so a last frame published right before the
+        // writer detached still goes through.
+        let closed = upstream_closed(reader);
+        if !pump(reader, writer, &mut f) && closed {
+            return;
+        }
+        let buffer = reader.read_buffer;
+        buffer.reader_notifier.wait(|| {
+            is_dirty(buffer.back_info.load(ord::acquire()))
+                || !buffer.is_writer_exist.load(ord::acquire())
+        });
+    }
+}
+
+/// Like `pump_blocking`, but awaits fresh frames instead of blocking.
+#[cfg(feature = "async")]
+pub async fn pump_async<
+    T,
+    U,
+    N: crate::AsyncNotifier,
+    M: Notifier,
+    const S: usize,
+    const R: usize,
+>(
+    reader: &mut BufferReader<'_, T, N, S>,
+    writer: &mut BufferWriter<'_, U, M, R>,
+    mut f: impl FnMut(&T, &mut U),
+) {
+    use core::task::Poll;
+
+    loop {
+        let closed = upstream_closed(reader);
+        if !pump(reader, writer, &mut f) && closed {
+            return;
+        }
+        core::future::poll_fn(|cx| {
+            let buffer = reader.read_buffer;
+            let ready = || {
+                is_dirty(buffer.back_info.load(ord::acquire()))
+                    || !buffer.is_writer_exist.load(ord::acquire())
+            };
+            if ready() {
+                return Poll::Ready(());
+            }
+            reader.register_waker(cx.waker());
+            // A publish or detach between the check and the registration
+            // woke nobody.
+            if ready() {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        })
+        .await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TripleBuffer;
+
+    #[test]
+    fn pumps_only_fresh_frames_down_the_chain() {
+        let source = TripleBuffer::new(|| 0u32);
+        let doubled = TripleBuffer::new(|| 0u64);
+        let labelled = TripleBuffer::new(String::new);
+        let mut source_writer = source.get_writer();
+        let (mut source_reader, mut doubled_writer) = (source.get_reader(), doubled.get_writer());
+        let (mut doubled_reader, mut labelled_writer) =
+            (doubled.get_reader(), labelled.get_writer());
+        let mut sink = labelled.get_reader();
+
+        let mut double = |value: &u32, out: &mut u64| *out = 2 * *value as u64;
+        let mut label = |value: &u64, out: &mut String| *out = format!("#{value}");
+
+        assert!(!pump(&mut source_reader, &mut doubled_writer, &mut double));
+        source_writer.write(1);
+        source_writer.write(2);
+        assert!(pump(&mut source_reader, &mut doubled_writer, &mut double));
+        assert!(pump(&mut doubled_reader, &mut labelled_writer, &mut label));
+        assert_eq!(sink.read(), "#4");
+
+        assert!(!pump(&mut source_reader, &mut doubled_writer, &mut double));
+        assert!(!pump(&mut doubled_reader, &mut labelled_writer, &mut label));
+        assert!(!sink.updated(), "republished without a fresh frame");
+    }
+
+    #[test]
+    fn blocking_pumps_run_until_the_source_detaches() {
+        let source = TripleBuffer::new(|| 0u32);
+        let doubled = TripleBuffer::new(|| 0u64);
+        let incremented = TripleBuffer::new(|| 0u64);
+        let count = 1000;
+
+        // Taken before the pumps start, which would otherwise see no
+        // upstream writer and return right away.
+        let mut source_writer = source.get_writer();
+        std::thread::scope(|scope| {
+            let (mut reader, mut writer) = (source.get_reader(), doubled.get_writer());
+            scope.spawn(move || {
+                pump_blocking(&mut reader, &mut writer, |value, out| {
+                    *out = 2 * *value as u64
+                });
+            });
+            let (mut reader, mut writer) = (doubled.get_reader(), incremented.get_writer());
+            scope.spawn(move || {
+                pump_blocking(&mut reader, &mut writer, |value, out| *out = value + 1)
+            });
+
+            for i in 1..=count {
+                source_writer.write(i);
+            }
+            drop(source_writer);
+        });
+
+        assert_eq!(*incremented.get_reader().read(), 2 * count as u64 + 1);
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn async_pump_ends_after_the_last_frame() {
+        use crate::WakerNotifier;
+
+        let buffer =
+            || TripleBuffer::with_notifiers(0u32, 0, 0, WakerNotifier::new(), WakerNotifier::new());
+        let (source, squared) = (buffer(), buffer());
+        let (mut reader, mut writer) = (source.get_reader(), squared.get_writer());
+
+        let mut source_writer = source.get_writer();
+        source_writer.write(2);
+        source_writer.write(3);
+        drop(source_writer);
+        futures::executor::block_on(pump_async(&mut reader, &mut writer, |value, out| {
+            *out = value * value
+        }));
+        assert_eq!(*squared.get_reader().read(), 9);
+    }
+}
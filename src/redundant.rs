@@ -0,0 +1,346 @@
+//! Control words stored twice, the second copy inverted, for the
+//! `redundant-control` feature: a bit flipped in either copy shows up on the
+//! next access instead of silently aliasing two slots. Both copies share one
+//! 16-bit atomic, so every operation is still a single load, swap or CAS.
+
+use core::fmt;
+use core::ptr;
+
+use crate::atomic::Ordering;
+use crate::control::BACK_INDEX_MASK;
+use crate::hook::Hook;
+use crate::sync::AtomicU16;
+use crate::{ord, NBuffer, NO_SLOT};
+
+/// A control word whose copies disagree, passed to the fault handler.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub struct ControlFault {
+    /// The copy the operation goes on with.
+    pub primary: u8,
+    /// The inverted copy, inverted back.
+    pub copy: u8,
+}
+
+impl fmt::Display for ControlFault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "tri-buffer control word corrupted: {:#04x}, copy {:#04x}",
+            self.primary, self.copy
+        )
+    }
+}
+
+/// `validate_and_resync` found no consistent way to repair the control
+/// state, e.g. after two upsets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Unrepairable;
+
+impl fmt::Display for Unrepairable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "control state can't be repaired")
+    }
+}
+
+impl core::error::Error for Unrepairable {}
+
+static HANDLER: Hook<ControlFault> = Hook::new();
+
+/// Installs `handler` to be called on every access that finds a corrupted
+/// control word, from the thread that found it, instead of panicking; e.g.
+/// to log it and call `validate_and_resync`. The operation goes on with the
+/// primary copy once it returns. `None` restores the panic.
+pub fn set_fault_handler(handler: Option<fn(&ControlFault)>) {
+    HANDLER.set(handler);
+}
+
+const fn pack(value: u8) -> u16 {
+    value as u16 | (!value as u16) << 8
+}
+
+const fn copies(word: u16) -> (u8, u8) {
+    (word as u8, !(word >> 8) as u8)
+}
+
+/// The primary copy of `word`, reporting a fault if the copies disagree.
+fn unpack(word: u16) -> u8 {
+    let (primary, copy) = copies(word);
+    if primary != copy {
+        HANDLER.fire_or(ControlFault { primary, copy }, |fault| panic!("{fault}"));
+    }
+    primary
+}
+
+pub(crate) struct RedundantU8(AtomicU16);
+
+// Each instance only needs what the buffer calls on that type.
+#[allow(dead_code)]
+impl RedundantU8 {
+    pub(crate) const fn new(value: u8) -> Self {
+        Self(AtomicU16::new(pack(value)))
+    }
+
+    pub(crate) fn load(&self, order: Ordering) -> u8 {
+        unpack(self.0.load(order))
+    }
+
+    pub(crate) fn store(&self, value: u8, order: Ordering) {
+        self.0.store(pack(value), order)
+    }
+
+    pub(crate) fn swap(&self, value: u8, order: Ordering) -> u8 {
+        unpack(self.0.swap(pack(value), order))
+    }
+
+    pub(crate) fn compare_exchange(
+        &self,
+        current: u8,
+        new: u8,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<u8, u8> {
+        self.exchange(current, |word| {
+            self.0.compare_exchange(word, pack(new), success, failure)
+        })
+    }
+
+    pub(crate) fn compare_exchange_weak(
+        &self,
+        current: u8,
+        new: u8,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<u8, u8> {
+        self.exchange(current, |word| {
+            self.0
+                .compare_exchange_weak(word, pack(new), success, failure)
+        })
+    }
+
+    /// A corrupted word whose primary copy is `current` is replaced too, as
+    /// loads go on with that copy; otherwise a CAS loop fed by `load` would
+    /// never get past it.
+    fn exchange(&self, current: u8, cas: impl Fn(u16) -> Result<u16, u16>) -> Result<u8, u8> {
+        match cas(pack(current)) {
+            Ok(_) => Ok(current),
+            Err(word) if word != pack(current) && copies(word).0 == current => {
+                unpack(word);
+                cas(word).map(|_| current).map_err(unpack)
+            }
+            Err(word) => Err(unpack(word)),
+        }
+    }
+
+    /// Both copies, without reporting a fault.
+    fn copies(&self) -> (u16, u8, u8) {
+        let word = self.0.load(ord::acquire());
+        let (primary, copy) = copies(word);
+        (word, primary, copy)
+    }
+
+    /// Replaces `word` with `value`, unless an operation got there first.
+    fn repair(&self, word: u16, value: u8) -> bool {
+        self.0
+            .compare_exchange(word, pack(value), ord::acqrel(), ord::relaxed())
+            .is_ok()
+    }
+
+    /// Flips `bits` of the stored word; the low byte is the primary copy.
+    #[cfg(test)]
+    pub(crate) fn flip(&self, bits: u16) {
+        self.0
+            .store(self.0.load(ord::relaxed()) ^ bits, ord::relaxed());
+    }
+}
+
+impl<T, const SLOTS: usize, N> NBuffer<T, SLOTS, N> {
+    /// Rebuilds any control word whose copies disagree: an index word takes
+    /// the copy that leaves every slot held once, and the back slot is the
+    /// one nobody else holds. A back word that disagrees only on the dirty
+    /// bit comes back clean, so the reader misses a frame rather than going
+    /// back to an older one. Returns whether anything was repaired.
+    ///
+    /// Only reliable while neither handle is inside an operation, as any
+    /// other look at the whole control state; a handle's operation may also
+    /// overwrite a corrupted word before it is repaired.
+    pub fn validate_and_resync(&self) -> Result<bool, Unrepairable> {
+        let spares = &self.spare[..SLOTS.saturating_sub(3)];
+        let indices = || {
+            [&self.input_idx, &self.output_idx]
+                .into_iter()
+                .chain(spares)
+        };
+        // A two-slot writer holding both slots leaves `NO_SLOT` as its input
+        // and as the back slot.
+        let slot_or_empty = |word: &RedundantU8, value: u8| {
+            (value as usize) < SLOTS
+                || (SLOTS == 2 && value == NO_SLOT && !ptr::eq(word, &self.output_idx))
+        };
+        let bit = |value: u8| if value == NO_SLOT { 0 } else { 1u128 << value };
+        let fits = |word, value, held| slot_or_empty(word, value) && held & bit(value) == 0;
+
+        let mut held = 0;
+        let (back_word, back, back_copy) = self.back_info.copies();
+        if back == back_copy {
+            if !slot_or_empty(&self.back_info, back & BACK_INDEX_MASK) {
+                return Err(Unrepairable);
+            }
+            held |= bit(back & BACK_INDEX_MASK);
+        }
+        for word in indices() {
+            let (_, value, copy) = word.copies();
+            if value == copy {
+                if !fits(word, value, held) {
+                    return Err(Unrepairable);
+                }
+                held |= bit(value);
+            }
+        }
+
+        let mut repaired = false;
+        for word in indices() {
+            let (raw, primary, copy) = word.copies();
+            if primary == copy {
+                continue;
+            }
+            let value = match (fits(word, primary, held), fits(word, copy, held)) {
+                (true, false) => primary,
+                (false, true) => copy,
+                _ => return Err(Unrepairable),
+            };
+            held |= bit(value);
+            repaired |= word.repair(raw, value);
+        }
+
+        // Either head of the spare ring keeps it a ring of distinct slots.
+        let (raw, primary, copy) = self.spare_head.copies();
+        if primary != copy {
+            let head = [primary, copy]
+                .into_iter()
+                .find(|&head| (head as usize) < SLOTS.saturating_sub(3).max(1))
+                .ok_or(Unrepairable)?;
+            repaired |= self.spare_head.repair(raw, head);
+        }
+
+        let free = (u128::MAX >> (128 - SLOTS)) & !held;
+        if back == back_copy {
+            return if free == 0 {
+                Ok(repaired)
+            } else {
+                Err(Unrepairable)
+            };
+        }
+        let expected = match free.count_ones() {
+            0 if SLOTS == 2 => NO_SLOT,
+            1 => free.trailing_zeros() as u8,
+            _ => return Err(Unrepairable),
+        };
+        // Copies that differ only in the dirty bit fall through to clean.
+        let back = match (
+            back & BACK_INDEX_MASK == expected,
+            back_copy & BACK_INDEX_MASK == expected,
+        ) {
+            (true, false) => back,
+            (false, true) => back_copy,
+            _ => expected,
+        };
+        Ok(self.back_info.repair(back_word, back) | repaired)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::BACK_DIRTY_BIT;
+    use crate::BufferState;
+
+    use crate::atomic::AtomicUsize;
+
+    static FAULTS: AtomicUsize = AtomicUsize::new(0);
+
+    fn count_faults() -> usize {
+        set_fault_handler(Some(|_| {
+            FAULTS.fetch_add(1, ord::relaxed());
+        }));
+        FAULTS.load(ord::relaxed())
+    }
+
+    #[test]
+    fn flipped_back_bit_is_detected_and_repaired() {
+        let before = count_faults();
+        let buffer = NBuffer::<u32, 3>::new(|| 0);
+        let mut writer = buffer.get_writer();
+        let mut reader = buffer.get_reader();
+        writer.write(1);
+
+        // The primary's low index bit: the back slot now aliases another.
+        buffer.back_info.flip(0b1);
+        assert_eq!(buffer.validate_and_resync(), Ok(true));
+        assert!(
+            FAULTS.load(ord::relaxed()) == before,
+            "resync reports nothing"
+        );
+        assert_eq!(*reader.read(), 1);
+        writer.write(2);
+        assert_eq!(*reader.read(), 2);
+        assert_eq!(buffer.validate_and_resync(), Ok(false));
+
+        buffer.output_idx.flip(0b1 << 8);
+        reader.update();
+        assert!(FAULTS.load(ord::relaxed()) > before);
+    }
+
+    /// Flips every bit of every control word in a few states and checks
+    /// that the resync restores the state.
+    fn every_single_upset_is_repaired<const SLOTS: usize>() {
+        count_faults();
+        let buffer = NBuffer::<u32, SLOTS>::new(|| 0);
+        let mut writer = buffer.get_writer();
+        let mut reader = buffer.get_reader();
+        let words = || {
+            [&*buffer.back_info, &buffer.input_idx, &buffer.output_idx]
+                .into_iter()
+                .chain(&buffer.spare[..SLOTS.saturating_sub(3)])
+                .chain((SLOTS > 3).then_some(&buffer.spare_head))
+        };
+        for step in 0..2 * SLOTS as u32 {
+            match step % 3 {
+                0 => writer.write(step),
+                1 => *writer.input_buffer() = step,
+                _ => {
+                    reader.update();
+                }
+            }
+            let state = buffer.state();
+            for (index, word) in words().enumerate() {
+                for bit in 0..16 {
+                    word.flip(1 << bit);
+                    let repaired = buffer.validate_and_resync();
+                    let expected = BufferState {
+                        dirty: state.dirty && (index != 0 || bit % 8 != 7),
+                        ..state
+                    };
+                    assert_eq!(
+                        (repaired, buffer.state()),
+                        (Ok(true), expected),
+                        "word {index}, bit {bit}, step {step}"
+                    );
+                    if expected != state {
+                        buffer.back_info.flip(u16::from(BACK_DIRTY_BIT) * 0x101);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn every_single_upset_is_repaired_for_each_slot_count() {
+        every_single_upset_is_repaired::<2>();
+        every_single_upset_is_repaired::<3>();
+        every_single_upset_is_repaired::<4>();
+        every_single_upset_is_repaired::<6>();
+    }
+}
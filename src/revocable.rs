@@ -0,0 +1,361 @@
+use core::fmt;
+use core::mem::ManuallyDrop;
+use core::ops::{Deref, DerefMut};
+
+use crate::atomic::AtomicU32;
+use crate::{ord, BufferReader, BufferWriter, DefaultNotifier, Notifier, TripleBuffer};
+
+// An endpoint word: whether a handle is attached, whether it is inside an
+// operation, and above those the generation it was issued at.
+const ATTACHED: u32 = 0b01;
+const ACTIVE: u32 = 0b10;
+const GENERATION: u32 = 0b100;
+
+/// The handle was revoked by `revoke_reader`/`revoke_writer`; it fails every
+/// operation from then on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Revoked;
+
+impl fmt::Display for Revoked {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "handle was revoked")
+    }
+}
+
+impl core::error::Error for Revoked {}
+
+/// A `TripleBuffer` whose handles can be revoked, so an endpoint held by a
+/// leaked or stuck handle can be handed out again.
+///
+/// Handles check their generation on every operation, and only reach a
+/// slot inside one, through `read_with`/`write_with`. A fresh handle is only
+/// issued once the revoked one has left any operation it was in, so the two
+/// never touch the same slot.
+pub struct RevocableTripleBuffer<T, N = DefaultNotifier> {
+    buffer: TripleBuffer<T, N>,
+    reader: Endpoint,
+    writer: Endpoint,
+}
+
+impl<T> RevocableTripleBuffer<T> {
+    pub fn new(generator: impl Fn() -> T) -> Self {
+        Self::new_const(generator(), generator(), generator())
+    }
+
+    pub const fn new_const(s1: T, s2: T, s3: T) -> Self {
+        Self {
+            buffer: TripleBuffer::new_const(s1, s2, s3),
+            reader: Endpoint::new(),
+            writer: Endpoint::new(),
+        }
+    }
+}
+
+impl<T, N: Notifier> RevocableTripleBuffer<T, N> {
+    pub fn get_reader(&self) -> RevocableReader<'_, T, N> {
+        self.try_get_reader().expect("Reader already exists")
+    }
+
+    pub fn get_writer(&self) -> RevocableWriter<'_, T, N> {
+        self.try_get_writer().expect("Writer already exists")
+    }
+
+    /// Like `get_reader`, but returns `None` while a reader is attached or a
+    /// revoked one is still inside an operation.
+    pub fn try_get_reader(&self) -> Option<RevocableReader<'_, T, N>> {
+        let generation = self.reader.attach()?;
+        Some(RevocableReader {
+            buffer: self,
+            generation,
+        })
+    }
+
+    /// Like `get_writer`, but returns `None` while a writer is attached or a
+    /// revoked one is still inside an operation.
+    pub fn try_get_writer(&self) -> Option<RevocableWriter<'_, T, N>> {
+        let generation = self.writer.attach()?;
+        Some(RevocableWriter {
+            buffer: self,
+            generation,
+        })
+    }
+
+    /// Revokes the attached reader, if any; returns whether there was one.
+    pub fn revoke_reader(&self) -> bool {
+        self.reader.revoke()
+    }
+
+    /// Revokes the attached writer, if any; returns whether there was one.
+    pub fn revoke_writer(&self) -> bool {
+        self.writer.revoke()
+    }
+
+    /// An inner handle for one operation. It never runs its `Drop`, as
+    /// the endpoints, not the inner flags, say who may use it.
+    fn reader(&self) -> Inner<BufferReader<'_, T, N>> {
+        Inner(ManuallyDrop::new(BufferReader::attach(&self.buffer)))
+    }
+
+    fn writer(&self) -> Inner<BufferWriter<'_, T, N>> {
+        Inner(ManuallyDrop::new(BufferWriter::attach(&self.buffer)))
+    }
+}
+
+trait Park {
+    fn park(&self);
+}
+
+impl<T, N: Notifier> Park for BufferReader<'_, T, N> {
+    fn park(&self) {
+        BufferReader::park(self);
+    }
+}
+
+impl<T, N: Notifier> Park for BufferWriter<'_, T, N> {
+    fn park(&self) {
+        BufferWriter::park(self);
+    }
+}
+
+/// Leaves the inner handle's slot for the next operation's, even when the
+/// operation unwinds.
+struct Inner<H: Park>(ManuallyDrop<H>);
+
+impl<H: Park> Deref for Inner<H> {
+    type Target = H;
+
+    fn deref(&self) -> &H {
+        &self.0
+    }
+}
+
+impl<H: Park> DerefMut for Inner<H> {
+    fn deref_mut(&mut self) -> &mut H {
+        &mut self.0
+    }
+}
+
+impl<H: Park> Drop for Inner<H> {
+    fn drop(&mut self) {
+        self.0.park();
+    }
+}
+
+struct Endpoint {
+    word: AtomicU32,
+}
+
+impl Endpoint {
+    const fn new() -> Self {
+        Self {
+            word: AtomicU32::new(0),
+        }
+    }
+
+    /// Claims the endpoint for a new handle, returning its generation.
+    fn attach(&self) -> Option<u32> {
+        self.word
+            .fetch_update(ord::acquire(), ord::relaxed(), |word| {
+                (word & (ATTACHED | ACTIVE) == 0).then_some(word | ATTACHED)
+            })
+            .ok()
+    }
+
+    /// Starts an operation of the handle issued at `generation`. Only the
+    /// handle itself sets `ACTIVE`, so any other change means it was revoked.
+    fn enter(&self, generation: u32) -> Result<Active<'_>, Revoked> {
+        self.word
+            .compare_exchange(
+                generation | ATTACHED,
+                generation | ATTACHED | ACTIVE,
+                ord::acquire(),
+                ord::relaxed(),
+            )
+            .map_err(|_| Revoked)?;
+        Ok(Active(self))
+    }
+
+    /// Moves on to the next generation, leaving `ACTIVE` for the revoked
+    /// handle to clear.
+    fn revoke(&self) -> bool {
+        self.word
+            .fetch_update(ord::acqrel(), ord::relaxed(), |word| {
+                (word & ATTACHED != 0).then(|| {
+                    ((word & !(ATTACHED | ACTIVE)).wrapping_add(GENERATION)) | word & ACTIVE
+                })
+            })
+            .is_ok()
+    }
+
+    /// Releases the endpoint, unless the handle was revoked meanwhile.
+    fn detach(&self, generation: u32) {
+        let _ = self.word.compare_exchange(
+            generation | ATTACHED,
+            generation,
+            ord::release(),
+            ord::relaxed(),
+        );
+    }
+}
+
+/// Clears `ACTIVE` when the operation ends, even by unwinding.
+struct Active<'e>(&'e Endpoint);
+
+impl Drop for Active<'_> {
+    fn drop(&mut self) {
+        self.0.word.fetch_and(!ACTIVE, ord::release());
+    }
+}
+
+pub struct RevocableReader<'a, T, N: Notifier = DefaultNotifier> {
+    buffer: &'a RevocableTripleBuffer<T, N>,
+    generation: u32,
+}
+
+impl<'a, T, N: Notifier> RevocableReader<'a, T, N> {
+    pub fn update(&mut self) -> Result<bool, Revoked> {
+        let _active = self.buffer.reader.enter(self.generation)?;
+        Ok(self.buffer.reader().update())
+    }
+
+    /// Takes the latest frame, if there is a new one, and passes it to `f`.
+    pub fn read_with<R>(&mut self, f: impl FnOnce(&T) -> R) -> Result<R, Revoked> {
+        let _active = self.buffer.reader.enter(self.generation)?;
+        Ok(f(self.buffer.reader().read()))
+    }
+}
+
+impl<'a, T, N: Notifier> Drop for RevocableReader<'a, T, N> {
+    fn drop(&mut self) {
+        self.buffer.reader.detach(self.generation);
+    }
+}
+
+pub struct RevocableWriter<'a, T, N: Notifier = DefaultNotifier> {
+    buffer: &'a RevocableTripleBuffer<T, N>,
+    generation: u32,
+}
+
+impl<'a, T, N: Notifier> RevocableWriter<'a, T, N> {
+    pub fn write(&mut self, value: T) -> Result<(), Revoked> {
+        let _active = self.buffer.writer.enter(self.generation)?;
+        self.buffer.writer().write(value);
+        Ok(())
+    }
+
+    /// Passes the input slot to `f` to stage the next frame in.
+    pub fn write_with<R>(&mut self, f: impl FnOnce(&mut T) -> R) -> Result<R, Revoked> {
+        let _active = self.buffer.writer.enter(self.generation)?;
+        Ok(f(self.buffer.writer().input_buffer()))
+    }
+
+    /// Returns whether the reader missed the previous frame.
+    pub fn publish(&mut self) -> Result<bool, Revoked> {
+        let _active = self.buffer.writer.enter(self.generation)?;
+        Ok(self.buffer.writer().publish())
+    }
+}
+
+impl<'a, T, N: Notifier> Drop for RevocableWriter<'a, T, N> {
+    fn drop(&mut self) {
+        self.buffer.writer.detach(self.generation);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn revoked_handles_fail_and_free_the_endpoint() {
+        let buffer = RevocableTripleBuffer::new(|| 0);
+        let mut writer = buffer.get_writer();
+        let mut reader = buffer.get_reader();
+        assert!(buffer.try_get_reader().is_none());
+
+        writer.write(1).unwrap();
+        assert!(buffer.revoke_reader());
+        assert!(!buffer.revoke_reader(), "nothing left to revoke");
+        assert_eq!(reader.read_with(|frame| *frame), Err(Revoked));
+
+        let mut fresh = buffer.get_reader();
+        drop(reader);
+        assert!(buffer.try_get_reader().is_none(), "old drop kept the claim");
+        assert_eq!(fresh.read_with(|frame| *frame), Ok(1));
+
+        assert!(buffer.revoke_writer());
+        assert_eq!(writer.write(2), Err(Revoked));
+        assert_eq!(writer.publish(), Err(Revoked));
+        let mut writer = buffer.get_writer();
+        writer.write_with(|frame| *frame = 3).unwrap();
+        assert_eq!(writer.publish(), Ok(false));
+        assert_eq!(fresh.read_with(|frame| *frame), Ok(3));
+    }
+
+    #[test]
+    fn revoking_a_busy_reader_waits_for_its_operation() {
+        let buffer = RevocableTripleBuffer::new(|| 0);
+        let mut writer = buffer.get_writer();
+        let reader = buffer.get_reader();
+
+        thread::scope(|s| {
+            let old = s.spawn(move || {
+                let mut reader = reader;
+                let mut reads = 0u64;
+                while let Ok(frame) = reader.read_with(|frame| *frame) {
+                    assert!(frame <= 1_000_000);
+                    reads += 1;
+                }
+                reads
+            });
+            for frame in 1..=1_000 {
+                writer.write(frame).unwrap();
+            }
+            assert!(buffer.revoke_reader());
+
+            let mut fresh = loop {
+                if let Some(reader) = buffer.try_get_reader() {
+                    break reader;
+                }
+                thread::yield_now();
+            };
+            writer.write(1_000_000).unwrap();
+            assert_eq!(fresh.read_with(|frame| *frame), Ok(1_000_000));
+            old.join().unwrap();
+        });
+    }
+
+    #[test]
+    fn revoking_a_busy_writer_hands_over_cleanly() {
+        let buffer = RevocableTripleBuffer::new(|| [0u32; 32]);
+        let mut reader = buffer.get_reader();
+        let writer = buffer.get_writer();
+
+        thread::scope(|s| {
+            let old = s.spawn(move || {
+                let mut writer = writer;
+                let mut frame = 0;
+                while writer.write([frame; 32]).is_ok() {
+                    frame += 1;
+                }
+            });
+            while reader.read_with(|frame| frame[0]).unwrap() < 100 {
+                thread::yield_now();
+            }
+            assert!(buffer.revoke_writer());
+
+            let mut fresh = loop {
+                if let Some(writer) = buffer.try_get_writer() {
+                    break writer;
+                }
+                thread::yield_now();
+            };
+            old.join().unwrap();
+            fresh.write([u32::MAX; 32]).unwrap();
+            let frame = reader.read_with(|frame| *frame).unwrap();
+            assert_eq!(frame, [u32::MAX; 32]);
+        });
+    }
+}
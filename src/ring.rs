@@ -0,0 +1,243 @@
+use core::cell::UnsafeCell;
+use core::mem::MaybeUninit;
+use core::ptr;
+
+use crate::atomic::{fence, AtomicU64};
+use crate::{ord, BufferReader, BufferWriter, DefaultNotifier, Notifier, TripleBuffer};
+
+/// A `TripleBuffer` whose writer also keeps its last `K` published frames in
+/// a ring, tagged with their sequence numbers (starting at 1), for
+/// diagnostic readers next to the regular one.
+///
+/// Ring slots are seqlocks: a `RingReader` copies a frame out and discards
+/// it if the writer overwrote the slot meanwhile, so it never sees a torn
+/// frame but may miss one. Frames are `Copy` so a discarded copy is never
+/// dropped or otherwise used.
+pub struct SnapshotRing<T, const K: usize, N = DefaultNotifier> {
+    buffer: TripleBuffer<T, N>,
+    ring: [RingSlot<T>; K],
+    // Sequence number of the latest frame in the ring, 0 before the first.
+    latest: AtomicU64,
+}
+
+struct RingSlot<T> {
+    // Twice the sequence number of the frame in the slot; odd while the
+    // writer is replacing it.
+    stamp: AtomicU64,
+    frame: UnsafeCell<MaybeUninit<T>>,
+}
+
+unsafe impl<T: Send> Sync for RingSlot<T> {}
+
+impl<T: Copy, const K: usize> SnapshotRing<T, K> {
+    pub const fn new_const(initial: T) -> Self {
+        Self::with_notifiers(initial, DefaultNotifier::new(), DefaultNotifier::new())
+    }
+}
+
+impl<T: Copy, const K: usize, N: Notifier> SnapshotRing<T, K, N> {
+    pub const fn with_notifiers(initial: T, reader_notifier: N, writer_notifier: N) -> Self {
+        const {
+            assert!(K > 0);
+        }
+        Self {
+            buffer: TripleBuffer::with_notifiers(
+                initial,
+                initial,
+                initial,
+                reader_notifier,
+                writer_notifier,
+            ),
+            ring: [const {
+                RingSlot {
+                    stamp: AtomicU64::new(0),
+                    frame: UnsafeCell::new(MaybeUninit::uninit()),
+                }
+            }; K],
+            latest: AtomicU64::new(0),
+        }
+    }
+
+    /// The regular latest-value reader.
+    pub fn get_reader(&self) -> BufferReader<'_, T, N> {
+        self.buffer.get_reader()
+    }
+
+    pub fn get_writer(&self) -> RingWriter<'_, T, K, N> {
+        RingWriter {
+            ring: self,
+            writer: self.buffer.get_writer(),
+            sequence: self.latest.load(ord::relaxed()),
+        }
+    }
+
+    /// A reader of the ring; any number of them may exist.
+    pub fn ring_reader(&self) -> RingReader<'_, T, K, N> {
+        RingReader { ring: self }
+    }
+
+    fn slot(&self, sequence: u64) -> &RingSlot<T> {
+        &self.ring[(sequence % K as u64) as usize]
+    }
+}
+
+pub struct RingWriter<'a, T, const K: usize, N: Notifier = DefaultNotifier> {
+    ring: &'a SnapshotRing<T, K, N>,
+    writer: BufferWriter<'a, T, N>,
+    sequence: u64,
+}
+
+impl<'a, T: Copy, const K: usize, N: Notifier> RingWriter<'a, T, K, N> {
+    pub fn write(&mut self, value: T) {
+        *self.input_buffer() = value;
+        self.publish();
+    }
+
+    pub fn input_buffer(&mut self) -> &mut T {
+        self.writer.input_buffer()
+    }
+
+    pub fn consumed(&self) -> bool {
+        self.writer.consumed()
+    }
+
+    /// Records the input slot in the ring, then publishes it. Returns
+    /// whether the regular reader missed the previous frame.
+    pub fn publish(&mut self) -> bool {
+        let sequence = self.sequence + 1;
+        let frame = *self.writer.input_buffer();
+        let slot = self.ring.slot(sequence);
+        slot.stamp.store(2 * sequence + 1, ord::relaxed());
+        fence(ord::release());
+        unsafe { ptr::write_volatile(slot.frame.get(), MaybeUninit::new(frame)) };
+        slot.stamp.store(2 * sequence, ord::release());
+
+        self.sequence = sequence;
+        let overwrote = self.writer.publish();
+        self.ring.latest.store(sequence, ord::release());
+        overwrote
+    }
+}
+
+pub struct RingReader<'a, T, const K: usize, N = DefaultNotifier> {
+    ring: &'a SnapshotRing<T, K, N>,
+}
+
+impl<'a, T, const K: usize, N> Clone for RingReader<'a, T, K, N> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, T, const K: usize, N> Copy for RingReader<'a, T, K, N> {}
+
+impl<'a, T: Copy, const K: usize, N: Notifier> RingReader<'a, T, K, N> {
+    /// Sequence number of the latest published frame, 0 before the first.
+    pub fn latest_sequence(&self) -> u64 {
+        self.ring.latest.load(ord::acquire())
+    }
+
+    /// The latest frame and its sequence number.
+    pub fn latest(&self) -> Option<(u64, T)> {
+        loop {
+            let sequence = self.latest_sequence();
+            if sequence == 0 {
+                return None;
+            }
+            // Only misses if `K` newer frames were published meanwhile.
+            if let Some(frame) = self.get(sequence) {
+                return Some((sequence, frame));
+            }
+        }
+    }
+
+    /// Frame `sequence`, unless it was never published or already evicted.
+    pub fn get(&self, sequence: u64) -> Option<T> {
+        let slot = self.ring.slot(sequence);
+        if sequence == 0 || slot.stamp.load(ord::acquire()) != 2 * sequence {
+            return None;
+        }
+        // This copy may race with the writer's. Rust has no data-race-free
+        // way to copy an arbitrary `T` like that, so, like other seqlocks,
+        // this relies on a volatile copy yielding some bytes that the stamp
+        // check below throws away. Miri reports the race.
+        let frame = unsafe { ptr::read_volatile(slot.frame.get()) };
+        fence(ord::acquire());
+        if slot.stamp.load(ord::relaxed()) != 2 * sequence {
+            // Overwritten while copying; the copy may be torn.
+            return None;
+        }
+        Some(unsafe { frame.assume_init() })
+    }
+
+    /// Every resident frame from sequence `from` on, oldest first; the
+    /// first item is the oldest frame with a sequence of at least `from`.
+    pub fn range(&self, from: u64) -> impl Iterator<Item = (u64, T)> + 'a {
+        let reader = *self;
+        let latest = self.latest_sequence();
+        let from = from.max(latest.saturating_sub(K as u64 - 1)).max(1);
+        (from..=latest).filter_map(move |sequence| Some((sequence, reader.get(sequence)?)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SpinNotifier;
+
+    #[test]
+    fn ring_keeps_last_k_frames() {
+        let ring =
+            SnapshotRing::<u32, 4, SpinNotifier>::with_notifiers(0, SpinNotifier, SpinNotifier);
+        let mut writer = ring.get_writer();
+        let mut reader = ring.get_reader();
+        let diagnostics = ring.ring_reader();
+
+        assert_eq!(diagnostics.latest(), None);
+        assert_eq!(diagnostics.range(0).count(), 0);
+        for value in 1..=10 {
+            writer.write(value * 10);
+        }
+        assert_eq!(*reader.read(), 100);
+
+        assert_eq!(diagnostics.latest(), Some((10, 100)));
+        assert_eq!(diagnostics.get(6), None, "evicted frame");
+        assert_eq!(diagnostics.get(7), Some(70));
+        assert_eq!(diagnostics.get(11), None, "not published yet");
+        assert_eq!(
+            diagnostics.range(0).collect::<Vec<_>>(),
+            [(7, 70), (8, 80), (9, 90), (10, 100)]
+        );
+        assert_eq!(diagnostics.range(9).next(), Some((9, 90)));
+    }
+
+    #[test]
+    // Races on purpose; see `RingReader::get`.
+    #[cfg_attr(miri, ignore)]
+    fn overwrites_while_reading_are_never_torn() {
+        static RING: SnapshotRing<[u64; 16], 8, SpinNotifier> =
+            SnapshotRing::with_notifiers([0; 16], SpinNotifier, SpinNotifier);
+        let count = 50_000;
+
+        let jh = std::thread::spawn(move || {
+            let mut writer = RING.get_writer();
+            for sequence in 1..=count {
+                writer.write([sequence; 16]);
+            }
+        });
+
+        let diagnostics = RING.ring_reader();
+        let mut previous = 0;
+        while previous != count {
+            for (sequence, frame) in diagnostics.range(previous) {
+                assert_eq!(frame, [sequence; 16], "torn or mislabelled frame");
+                previous = sequence;
+            }
+            if let Some((sequence, frame)) = diagnostics.latest() {
+                assert_eq!(frame, [sequence; 16], "torn or mislabelled frame");
+                assert!(sequence >= previous, "ring went back in time");
+            }
+        }
+        jh.join().unwrap();
+    }
+}
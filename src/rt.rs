@@ -0,0 +1,127 @@
+//! The subset of the handles' API that is safe to call from a real-time
+//! thread, e.g. an audio callback: it never blocks, allocates, locks or
+//! panics. Taking `impl RtReader<T>` or `impl RtWriter<T>` instead of a
+//! handle keeps the callback to it.
+//!
+//! With the `rt-safe` feature the diagnostics that could do any of those,
+//! `tracing`, `defmt-trace`, `paranoid` and `checked-rt`, are compiled out
+//! of these methods (and only these); `tests/no_panic.rs` and
+//! `tests/rt_safe.rs` check what's left. Three things stay the caller's:
+//! hooks and recorders, dropping the frame a `write` replaces, and the
+//! notifier's `notify`, which for every notifier in this crate only wakes a
+//! waiter.
+
+use crate::{BufferReader, BufferWriter, Notifier};
+
+pub trait RtReader<T> {
+    /// Takes the latest frame if there is one; see `BufferReader::update`.
+    fn update(&mut self) -> bool;
+
+    /// Whether a frame is waiting to be taken.
+    fn updated(&mut self) -> bool;
+
+    /// Updates, then the latest frame; see `BufferReader::read`.
+    fn read(&mut self) -> &T;
+
+    /// Takes and returns a new frame, or `None` if nothing was published
+    /// since the previous update.
+    fn try_read(&mut self) -> Option<&T>;
+
+    /// The frame the previous update took, without updating.
+    fn peek(&mut self) -> &T;
+}
+
+pub trait RtWriter<T> {
+    /// See `BufferWriter::input_buffer`.
+    fn input_buffer(&mut self) -> &mut T;
+
+    /// See `BufferWriter::publish`.
+    fn publish(&mut self) -> bool;
+
+    /// Copies `value` into the input slot and publishes it; unlike `write`,
+    /// it has no old frame to drop.
+    fn write_copy(&mut self, value: &T) -> bool
+    where
+        T: Copy;
+
+    /// Whether the reader took the latest frame.
+    fn consumed(&self) -> bool;
+}
+
+impl<'a, T, N: Notifier, const SLOTS: usize> RtReader<T> for BufferReader<'a, T, N, SLOTS> {
+    fn update(&mut self) -> bool {
+        BufferReader::update(self)
+    }
+
+    fn updated(&mut self) -> bool {
+        BufferReader::updated(self)
+    }
+
+    fn read(&mut self) -> &T {
+        BufferReader::read(self)
+    }
+
+    fn try_read(&mut self) -> Option<&T> {
+        if BufferReader::update(self) {
+            Some(self.output_buffer())
+        } else {
+            None
+        }
+    }
+
+    fn peek(&mut self) -> &T {
+        self.output_buffer()
+    }
+}
+
+impl<'a, T, N: Notifier, const SLOTS: usize> RtWriter<T> for BufferWriter<'a, T, N, SLOTS> {
+    fn input_buffer(&mut self) -> &mut T {
+        BufferWriter::input_buffer(self)
+    }
+
+    fn publish(&mut self) -> bool {
+        BufferWriter::publish(self)
+    }
+
+    fn write_copy(&mut self, value: &T) -> bool
+    where
+        T: Copy,
+    {
+        *BufferWriter::input_buffer(self) = *value;
+        BufferWriter::publish(self)
+    }
+
+    fn consumed(&self) -> bool {
+        BufferWriter::consumed(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{QuadBuffer, TripleBuffer};
+
+    fn callback(reader: &mut impl RtReader<u32>, writer: &mut impl RtWriter<u32>) {
+        if let Some(&input) = reader.try_read() {
+            writer.write_copy(&(input * 2));
+        }
+    }
+
+    #[test]
+    fn the_subset_exchanges_frames() {
+        let input = TripleBuffer::new(|| 0u32);
+        let output = QuadBuffer::new(|| 0u32);
+        let (mut source, mut reader) = (input.get_writer(), input.get_reader());
+        let (mut writer, mut sink) = (output.get_writer(), output.get_reader());
+
+        callback(&mut reader, &mut writer);
+        assert!(!RtReader::updated(&mut sink), "published without input");
+
+        source.write(21);
+        callback(&mut reader, &mut writer);
+        assert_eq!(RtReader::try_read(&mut sink), Some(&42));
+        assert_eq!(RtReader::try_read(&mut sink), None);
+        assert_eq!(*RtReader::peek(&mut sink), 42);
+        assert!(RtWriter::consumed(&writer));
+    }
+}
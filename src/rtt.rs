@@ -0,0 +1,192 @@
+//! Mirrors published frames to an RTT up-channel, for the `rtt` feature, so
+//! a debug probe can watch them live during board bring-up. Each frame goes
+//! out as its `Pod` bytes behind a little-endian `u16` length, in a single
+//! channel write; in the default `NoBlockSkip` mode (or a blocking one) a
+//! frame is thus sent whole or not at all, and the host splits the stream
+//! back up with `decode_rtt_frame`.
+
+use core::mem::size_of;
+
+use crate::{BufferReader, FrameTooLong, Notifier, PodFrame};
+
+const PREFIX: usize = size_of::<u16>();
+
+/// Where `RttMirror` sends frames: an `rtt_target::UpChannel`, or a fake one
+/// in tests.
+pub trait RttChannel {
+    /// Writes what fits of `bytes`; returns how much that was.
+    fn write(&mut self, bytes: &[u8]) -> usize;
+}
+
+impl RttChannel for rtt_target::UpChannel {
+    fn write(&mut self, bytes: &[u8]) -> usize {
+        rtt_target::UpChannel::write(self, bytes)
+    }
+}
+
+/// A reader that sends every new frame it takes to `channel`, framed in
+/// `scratch`.
+pub struct RttMirror<'s, 'a, C, T, N: Notifier, const SLOTS: usize = 3> {
+    reader: BufferReader<'a, T, N, SLOTS>,
+    channel: C,
+    scratch: &'s mut [u8],
+}
+
+impl<'s, 'a, C: RttChannel, T: PodFrame, N: Notifier, const SLOTS: usize>
+    RttMirror<'s, 'a, C, T, N, SLOTS>
+{
+    /// Fails if a framed frame doesn't fit into `scratch`, which needs two
+    /// bytes more than the frame, or into the `u16` length.
+    pub fn new(
+        reader: BufferReader<'a, T, N, SLOTS>,
+        channel: C,
+        scratch: &'s mut [u8],
+    ) -> Result<Self, FrameTooLong> {
+        let len = size_of::<T::Pod>();
+        let capacity = scratch.len().saturating_sub(PREFIX).min(u16::MAX as usize);
+        if len > capacity {
+            return Err(FrameTooLong { len, capacity });
+        }
+        Ok(Self {
+            reader,
+            channel,
+            scratch,
+        })
+    }
+
+    /// Takes the latest frame and sends it, if it is new. Returns whether it
+    /// was sent: a frame the channel has no room for is dropped, and the
+    /// next one sent instead.
+    pub fn poll(&mut self) -> bool {
+        if !self.reader.update() {
+            return false;
+        }
+        let payload = bytemuck::bytes_of(self.reader.output_buffer().pod());
+        let framed = PREFIX + payload.len();
+        self.scratch[..PREFIX].copy_from_slice(&(payload.len() as u16).to_le_bytes());
+        self.scratch[PREFIX..framed].copy_from_slice(payload);
+        self.channel.write(&self.scratch[..framed]) == framed
+    }
+
+    pub fn into_parts(self) -> (BufferReader<'a, T, N, SLOTS>, C) {
+        (self.reader, self.channel)
+    }
+}
+
+/// Splits the first frame off what the host read from the channel: returns
+/// its bytes and the rest of `stream`, or `None` until it is all there.
+pub fn decode_rtt_frame(stream: &[u8]) -> Option<(&[u8], &[u8])> {
+    let (prefix, rest) = stream.split_first_chunk::<PREFIX>()?;
+    let len = u16::from_le_bytes(*prefix) as usize;
+    (rest.len() >= len).then(|| rest.split_at(len))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::vec::Vec;
+
+    use super::*;
+    use crate::TripleBuffer;
+
+    /// An up-channel in `NoBlockSkip` mode whose host never reads: a write
+    /// that doesn't fit into what's left of `room` is skipped whole.
+    struct FakeChannel {
+        sent: Vec<u8>,
+        room: usize,
+    }
+
+    impl RttChannel for FakeChannel {
+        fn write(&mut self, bytes: &[u8]) -> usize {
+            if self.sent.len() + bytes.len() > self.room {
+                return 0;
+            }
+            self.sent.extend_from_slice(bytes);
+            bytes.len()
+        }
+    }
+
+    fn frames(mut stream: &[u8]) -> Vec<[u16; 3]> {
+        let mut frames = Vec::new();
+        while let Some((frame, rest)) = decode_rtt_frame(stream) {
+            frames.push(bytemuck::pod_read_unaligned(frame));
+            stream = rest;
+        }
+        assert!(stream.is_empty(), "{} stray bytes", stream.len());
+        frames
+    }
+
+    #[test]
+    fn frames_round_trip_through_the_framing() {
+        let buffer = TripleBuffer::new(|| [0u16; 3]);
+        let mut writer = buffer.get_writer();
+        let channel = FakeChannel {
+            sent: Vec::new(),
+            room: 1024,
+        };
+        let mut scratch = [0; 8];
+        let mut mirror = RttMirror::new(buffer.get_reader(), channel, &mut scratch).unwrap();
+
+        assert!(!mirror.poll());
+        writer.write([1, 2, 3]);
+        assert!(mirror.poll());
+        assert!(!mirror.poll(), "sent the same frame twice");
+        writer.write([4, 5, 6]);
+        writer.write([7, 8, 0xffff]);
+        assert!(mirror.poll());
+
+        let (_, channel) = mirror.into_parts();
+        assert_eq!(&channel.sent[..2], [6, 0]);
+        assert_eq!(frames(&channel.sent), [[1, 2, 3], [7, 8, 0xffff]]);
+    }
+
+    #[test]
+    fn a_full_channel_drops_whole_frames() {
+        let buffer = TripleBuffer::new(|| [0u16; 3]);
+        let mut writer = buffer.get_writer();
+        let channel = FakeChannel {
+            sent: Vec::new(),
+            room: 20,
+        };
+        let mut scratch = [0; 8];
+        let mut mirror = RttMirror::new(buffer.get_reader(), channel, &mut scratch).unwrap();
+
+        let sent: Vec<bool> = (1..=4)
+            .map(|i| {
+                writer.write([i; 3]);
+                mirror.poll()
+            })
+            .collect();
+        assert_eq!(sent, [true, true, false, false]);
+        assert_eq!(frames(&mirror.into_parts().1.sent), [[1; 3], [2; 3]]);
+    }
+
+    #[test]
+    fn partial_streams_wait_for_the_rest() {
+        let stream = [3, 0, 9, 8, 7, 1, 0];
+        assert_eq!(decode_rtt_frame(&stream[..1]), None);
+        assert_eq!(decode_rtt_frame(&stream[..4]), None);
+        let (frame, rest) = decode_rtt_frame(&stream).unwrap();
+        assert_eq!(frame, [9, 8, 7]);
+        assert_eq!(decode_rtt_frame(rest), None);
+    }
+
+    #[test]
+    fn a_short_scratch_buffer_is_rejected() {
+        let buffer = TripleBuffer::new(|| [0u16; 3]);
+        let channel = FakeChannel {
+            sent: Vec::new(),
+            room: 0,
+        };
+        let mut scratch = [0; 7];
+        let error = RttMirror::new(buffer.get_reader(), channel, &mut scratch)
+            .err()
+            .unwrap();
+        assert_eq!(
+            error,
+            FrameTooLong {
+                len: 6,
+                capacity: 5
+            }
+        );
+    }
+}
@@ -0,0 +1,60 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{BufferReader, Notifier};
+
+/// The reader's latest frame and what it knows about it, from `sample`.
+/// Under the `serde` feature it serializes as a struct of these fields, as
+/// does `serialize_latest` without cloning the frame.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Sample<T> {
+    pub value: T,
+    /// Published since the reader's previous update.
+    pub fresh: bool,
+    #[cfg(feature = "seq")]
+    pub seq: u64,
+}
+
+impl<'a, T, N: Notifier, const SLOTS: usize> BufferReader<'a, T, N, SLOTS> {
+    /// Updates and clones the latest frame out. A `Stamped` frame carries
+    /// its timestamp along.
+    pub fn sample(&mut self) -> Sample<T>
+    where
+        T: Clone,
+    {
+        let sample = self.latest();
+        Sample {
+            value: sample.value.clone(),
+            fresh: sample.fresh,
+            #[cfg(feature = "seq")]
+            seq: sample.seq,
+        }
+    }
+
+    /// Updates and serializes the latest frame as `sample` would return
+    /// it, straight from the output slot.
+    #[cfg(feature = "serde")]
+    pub fn serialize_latest<S: serde::Serializer>(
+        &mut self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize,
+    {
+        self.latest().serialize(serializer)
+    }
+
+    fn latest(&mut self) -> Sample<&T> {
+        let fresh = self.update();
+        #[cfg(feature = "seq")]
+        let seq = self.seq();
+        Sample {
+            value: self.output_buffer(),
+            fresh,
+            #[cfg(feature = "seq")]
+            seq,
+        }
+    }
+}
@@ -0,0 +1,185 @@
+use core::cell::UnsafeCell;
+
+use crate::atomic::AtomicBool;
+use crate::{ord, Backoff, BufferWriter, DefaultNotifier, Notifier};
+
+/// Owns a `BufferWriter` and lets several `SharedWriter`s publish through it.
+///
+/// Each write stages and publishes under a CAS-held lock, so frames never
+/// mix data from two writers. Only writers contend for the lock; the reader
+/// never waits for it. Concurrent writes are last-writer-wins: the reader
+/// sees whichever frame was published last, like any other overwrite.
+pub struct WriterLock<'a, T, N: Notifier = DefaultNotifier, const SLOTS: usize = 3> {
+    locked: AtomicBool,
+    writer: UnsafeCell<BufferWriter<'a, T, N, SLOTS>>,
+}
+
+unsafe impl<'a, T, N: Notifier, const SLOTS: usize> Sync for WriterLock<'a, T, N, SLOTS> where
+    BufferWriter<'a, T, N, SLOTS>: Send
+{
+}
+
+impl<'a, T, N: Notifier, const SLOTS: usize> WriterLock<'a, T, N, SLOTS> {
+    pub fn new(writer: BufferWriter<'a, T, N, SLOTS>) -> Self {
+        Self {
+            locked: AtomicBool::new(false),
+            writer: UnsafeCell::new(writer),
+        }
+    }
+
+    pub fn writer(&self) -> SharedWriter<'_, 'a, T, N, SLOTS> {
+        SharedWriter { lock: self }
+    }
+
+    pub fn into_inner(self) -> BufferWriter<'a, T, N, SLOTS> {
+        self.writer.into_inner()
+    }
+
+    fn try_lock(&self) -> bool {
+        self.locked
+            .compare_exchange(false, true, ord::acquire(), ord::relaxed())
+            .is_ok()
+    }
+
+    /// Must only be called while holding the lock.
+    unsafe fn write_locked(&self, value: T) {
+        let _unlock = Unlock(&self.locked);
+        (*self.writer.get()).write(value);
+    }
+}
+
+/// Releases the writer lock when the write ends, even by unwinding.
+struct Unlock<'l>(&'l AtomicBool);
+
+impl Drop for Unlock<'_> {
+    fn drop(&mut self) {
+        self.0.store(false, ord::release());
+    }
+}
+
+pub struct SharedWriter<'l, 'a, T, N: Notifier = DefaultNotifier, const SLOTS: usize = 3> {
+    lock: &'l WriterLock<'a, T, N, SLOTS>,
+}
+
+impl<'l, 'a, T, N: Notifier, const SLOTS: usize> Clone for SharedWriter<'l, 'a, T, N, SLOTS> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'l, 'a, T, N: Notifier, const SLOTS: usize> Copy for SharedWriter<'l, 'a, T, N, SLOTS> {}
+
+impl<'l, 'a, T, N: Notifier, const SLOTS: usize> SharedWriter<'l, 'a, T, N, SLOTS> {
+    /// Publishes `value`, spinning while another writer is mid-publish.
+    pub fn write(&self, value: T) {
+        let mut backoff = Backoff::new();
+        while !self.lock.try_lock() {
+            backoff.snooze();
+        }
+        unsafe { self.lock.write_locked(value) };
+    }
+
+    /// Publishes `value` unless another writer is mid-publish, in which case
+    /// it is handed back.
+    pub fn try_write(&self, value: T) -> Result<(), T> {
+        if self.lock.try_lock() {
+            unsafe { self.lock.write_locked(value) };
+            Ok(())
+        } else {
+            Err(value)
+        }
+    }
+}
+
+impl<'a, T, N: Notifier, const SLOTS: usize> BufferWriter<'a, T, N, SLOTS> {
+    pub fn into_shared(self) -> WriterLock<'a, T, N, SLOTS> {
+        WriterLock::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::atomic::{AtomicUsize, Ordering};
+    use crate::{SpinNotifier, TripleBuffer};
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Frame {
+        writer: u64,
+        sequence: u64,
+        payload: [u64; 4],
+        checksum: u64,
+    }
+
+    impl Frame {
+        const fn new(writer: u64, sequence: u64) -> Self {
+            let word = writer << 32 | sequence;
+            Self {
+                writer,
+                sequence,
+                payload: [word, !word, word.rotate_left(17), word ^ 0x5555],
+                checksum: word.wrapping_mul(0x9e37_79b9_7f4a_7c15),
+            }
+        }
+    }
+
+    #[test]
+    fn try_write_hands_value_back_while_locked() {
+        let buffer = TripleBuffer::with_notifiers(0, 0, 0, SpinNotifier, SpinNotifier);
+        let mut reader = buffer.get_reader();
+        let lock = buffer.get_writer().into_shared();
+        let writer = lock.writer();
+
+        assert!(lock.try_lock());
+        assert_eq!(writer.try_write(1), Err(1));
+        unsafe { lock.write_locked(2) };
+        assert_eq!(writer.clone().try_write(3), Ok(()));
+        assert_eq!(*reader.read(), 3);
+    }
+
+    #[test]
+    fn four_writers_never_mix_frames() {
+        static BUFFER: TripleBuffer<Frame, SpinNotifier> = TripleBuffer::with_notifiers(
+            Frame::new(0, 0),
+            Frame::new(0, 0),
+            Frame::new(0, 0),
+            SpinNotifier,
+            SpinNotifier,
+        );
+        let per_writer = if cfg!(miri) { 50 } else { 5_000 };
+        let finished = AtomicUsize::new(0);
+        let lock = BUFFER.get_writer().into_shared();
+        let mut reader = BUFFER.get_reader();
+
+        std::thread::scope(|scope| {
+            for id in 1..=4 {
+                let writer = lock.writer();
+                let finished = &finished;
+                scope.spawn(move || {
+                    for sequence in 1..=per_writer {
+                        writer.write(Frame::new(id, sequence));
+                    }
+                    finished.fetch_add(1, Ordering::Release);
+                });
+            }
+
+            let mut latest = [0; 5];
+            loop {
+                let done = finished.load(Ordering::Acquire) == 4;
+                let frame = *reader.read();
+                assert_eq!(
+                    frame,
+                    Frame::new(frame.writer, frame.sequence),
+                    "torn frame"
+                );
+                let previous = &mut latest[frame.writer as usize];
+                assert!(frame.sequence >= *previous, "frame went back in time");
+                *previous = frame.sequence;
+                if done {
+                    // Last writer wins: the final frame is some writer's last.
+                    assert_eq!(frame.sequence, per_writer);
+                    break;
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,190 @@
+use core::fmt;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use futures_sink::Sink;
+
+use crate::{ord, AsyncNotifier, BufferWriter, Notifier, WakerNotifier};
+
+/// Whether `WriterSink` may replace frames the reader hasn't taken yet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SinkMode {
+    /// `poll_ready` is always ready; unread frames are coalesced.
+    Lossy,
+    /// `poll_ready` waits until the reader took the previous frame.
+    Lossless,
+}
+
+/// The reader detached from the buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Disconnected;
+
+impl fmt::Display for Disconnected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "reader disconnected")
+    }
+}
+
+impl core::error::Error for Disconnected {}
+
+/// `Sink` adapter owning a `BufferWriter`. `start_send` stages the item in
+/// the input slot and `poll_flush` publishes it. Every operation fails with
+/// `Disconnected` while no reader is attached.
+///
+/// Cancel safe once an item was accepted: a staged item whose flush was
+/// dropped is published by the next `poll_ready` or `poll_flush`. Items
+/// still held by a dropped `SinkExt::send` future before `start_send` are
+/// dropped with it, as with any sink. In `Lossy` mode `poll_ready` never
+/// pends, so that can't happen there.
+pub struct WriterSink<'a, T, N: Notifier = WakerNotifier, const SLOTS: usize = 3> {
+    writer: BufferWriter<'a, T, N, SLOTS>,
+    mode: SinkMode,
+    staged: bool,
+}
+
+impl<'a, T, N: Notifier, const SLOTS: usize> WriterSink<'a, T, N, SLOTS> {
+    pub fn new(writer: BufferWriter<'a, T, N, SLOTS>, mode: SinkMode) -> Self {
+        Self {
+            writer,
+            mode,
+            staged: false,
+        }
+    }
+
+    pub fn mode(&self) -> SinkMode {
+        self.mode
+    }
+
+    /// Returns the writer; a staged but unflushed item stays in its input
+    /// slot unpublished.
+    pub fn into_inner(self) -> BufferWriter<'a, T, N, SLOTS> {
+        self.writer
+    }
+
+    fn check_connected(&self) -> Result<(), Disconnected> {
+        if self
+            .writer
+            .write_buffer
+            .is_reader_exist
+            .load(ord::acquire())
+        {
+            Ok(())
+        } else {
+            Err(Disconnected)
+        }
+    }
+
+    fn publish_staged(&mut self) {
+        if self.staged {
+            self.writer.publish();
+            self.staged = false;
+        }
+    }
+}
+
+impl<'a, T, N: Notifier, const SLOTS: usize> BufferWriter<'a, T, N, SLOTS> {
+    pub fn into_sink(self, mode: SinkMode) -> WriterSink<'a, T, N, SLOTS> {
+        WriterSink::new(self, mode)
+    }
+}
+
+impl<'a, T, N: AsyncNotifier, const SLOTS: usize> Sink<T> for WriterSink<'a, T, N, SLOTS> {
+    type Error = Disconnected;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Disconnected>> {
+        let this = self.get_mut();
+        this.check_connected()?;
+        // The staged item was admitted while the back slot was free, so it
+        // may be published before waiting for the next one.
+        this.publish_staged();
+        if this.mode == SinkMode::Lossless && !this.writer.consumed() {
+            this.writer.register_waker(cx.waker());
+            this.check_connected()?;
+            if !this.writer.consumed() {
+                return Poll::Pending;
+            }
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: T) -> Result<(), Disconnected> {
+        let this = self.get_mut();
+        this.check_connected()?;
+        *this.writer.input_buffer() = item;
+        this.staged = true;
+        Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Disconnected>> {
+        let this = self.get_mut();
+        this.check_connected()?;
+        this.publish_staged();
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Disconnected>> {
+        self.poll_flush(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TripleBuffer;
+    use futures::executor::block_on;
+    use futures::{stream, SinkExt, StreamExt};
+
+    fn buffer() -> TripleBuffer<u32, WakerNotifier> {
+        TripleBuffer::with_notifiers(0, 0, 0, WakerNotifier::new(), WakerNotifier::new())
+    }
+
+    #[test]
+    fn lossy_sink_coalesces_unread_frames() {
+        let buffer = buffer();
+        let mut reader = buffer.get_reader();
+        let sink = buffer.get_writer().into_sink(SinkMode::Lossy);
+
+        block_on(stream::iter(1..=100).map(Ok).forward(sink)).unwrap();
+
+        assert!(reader.update());
+        assert_eq!(*reader.output_buffer(), 100);
+        assert!(!reader.update());
+    }
+
+    #[test]
+    fn lossless_sink_delivers_every_frame() {
+        let buffer = buffer();
+        let mut reader = buffer.get_reader();
+        let sink = buffer.get_writer().into_sink(SinkMode::Lossless);
+
+        let forward = stream::iter(1..=100).map(Ok).forward(sink);
+        let receive = async {
+            let mut seen = Vec::new();
+            while seen.last() != Some(&100) {
+                reader.changed().await;
+                seen.push(*reader.read());
+            }
+            seen
+        };
+        let (forwarded, seen) = block_on(futures::future::join(forward, receive));
+
+        forwarded.unwrap();
+        assert_eq!(seen, (1..=100).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn sink_reports_reader_disconnect() {
+        let buffer = buffer();
+        let reader = buffer.get_reader();
+        let mut sink = buffer.get_writer().into_sink(SinkMode::Lossless);
+
+        block_on(sink.send(1)).unwrap();
+        let waiting = async {
+            let result = sink.send(2).await;
+            assert_eq!(result, Err(Disconnected));
+        };
+        let disconnect = async { drop(reader) };
+        block_on(futures::future::join(waiting, disconnect));
+    }
+}
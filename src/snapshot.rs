@@ -0,0 +1,185 @@
+//! Single-producer, many-consumer snapshots of a `Clone` value.
+//!
+//! All receivers share one `TripleBuffer`. `recv` briefly takes the
+//! buffer's single reader handle, which doubles as a lock between
+//! receivers, and clones the latest frame out of it; the sender never waits
+//! for receivers. Freshness is tracked per receiver against a shared send
+//! counter.
+//!
+//! Consistency model:
+//! - `recv` returns a complete frame at least as new as the newest one whose
+//!   send had finished when `recv` started.
+//! - Frames returned to one receiver never go back in time.
+//! - The counter is bumped after the frame is published, so a `recv`
+//!   racing a `send` can return the new frame while `has_changed` still
+//!   reports it as unseen afterwards; it never misses one.
+
+use crate::atomic::AtomicUsize;
+
+use crate::{ord, Backoff, BufferWriter, SpinNotifier, TripleBuffer};
+
+pub struct Snapshot<T> {
+    buffer: TripleBuffer<T, SpinNotifier>,
+    sends: AtomicUsize,
+}
+
+impl<T> Snapshot<T> {
+    pub const fn new_const(s1: T, s2: T, s3: T) -> Self {
+        Self {
+            buffer: TripleBuffer::with_notifiers(
+                s1,
+                s2,
+                s3,
+                SpinNotifier::new(),
+                SpinNotifier::new(),
+            ),
+            sends: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn new(initial: T) -> Self
+    where
+        T: Clone,
+    {
+        Self::new_const(initial.clone(), initial.clone(), initial)
+    }
+}
+
+pub struct SnapshotSender<'a, T> {
+    snapshot: &'a Snapshot<T>,
+    writer: BufferWriter<'a, T, SpinNotifier>,
+}
+
+/// Cloning a receiver keeps its notion of what it has seen.
+pub struct SnapshotReceiver<'a, T> {
+    snapshot: &'a Snapshot<T>,
+    seen: usize,
+}
+
+/// Attaches the sender and a first receiver; clone it for more. The initial
+/// value counts as seen.
+pub fn channel<T>(snapshot: &Snapshot<T>) -> (SnapshotSender<'_, T>, SnapshotReceiver<'_, T>) {
+    let sender = SnapshotSender {
+        snapshot,
+        writer: snapshot.buffer.get_writer(),
+    };
+    let receiver = SnapshotReceiver {
+        snapshot,
+        seen: snapshot.sends.load(ord::acquire()),
+    };
+    (sender, receiver)
+}
+
+impl<'a, T> SnapshotSender<'a, T> {
+    pub fn send(&mut self, value: T) {
+        self.writer.write(value);
+        self.snapshot.sends.fetch_add(1, ord::release());
+    }
+}
+
+impl<'a, T> Clone for SnapshotReceiver<'a, T> {
+    fn clone(&self) -> Self {
+        Self {
+            snapshot: self.snapshot,
+            seen: self.seen,
+        }
+    }
+}
+
+impl<'a, T> SnapshotReceiver<'a, T> {
+    /// Whether a frame was sent since this receiver's last `recv`.
+    pub fn has_changed(&self) -> bool {
+        self.snapshot.sends.load(ord::acquire()) != self.seen
+    }
+
+    /// Clones the latest frame and marks it as seen. Spins while another
+    /// receiver is cloning.
+    pub fn recv(&mut self) -> T
+    where
+        T: Clone,
+    {
+        let sends = self.snapshot.sends.load(ord::acquire());
+        let mut backoff = Backoff::new();
+        let value = loop {
+            if let Some(mut reader) = self.snapshot.buffer.try_get_reader() {
+                break reader.read().clone();
+            }
+            backoff.snooze();
+        };
+        self.seen = sends;
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn receivers_track_freshness_independently() {
+        let snapshot = Snapshot::new(0);
+        let (mut tx, mut first) = channel(&snapshot);
+        let mut second = first.clone();
+        assert!(!first.has_changed());
+        assert_eq!(first.recv(), 0);
+
+        tx.send(1);
+        assert!(first.has_changed() && second.has_changed());
+        assert_eq!(first.recv(), 1);
+        assert!(!first.has_changed());
+        assert!(second.has_changed());
+
+        let mut third = first.clone();
+        assert!(!third.has_changed());
+        tx.send(2);
+        assert_eq!(second.recv(), 2);
+        assert_eq!(third.recv(), 2);
+        assert_eq!(first.recv(), 2);
+        assert!(!second.has_changed());
+    }
+
+    #[derive(Clone)]
+    struct Frame {
+        sequence: u64,
+        checksum: u64,
+    }
+
+    impl Frame {
+        const fn new(sequence: u64) -> Self {
+            Self {
+                sequence,
+                checksum: !sequence,
+            }
+        }
+    }
+
+    #[test]
+    fn eight_receivers_against_fast_writer() {
+        static SNAPSHOT: Snapshot<Frame> =
+            Snapshot::new_const(Frame::new(0), Frame::new(0), Frame::new(0));
+        let last = if cfg!(miri) { 100 } else { 20_000 };
+        let (mut tx, rx) = channel(&SNAPSHOT);
+
+        let receivers: Vec<_> = (0..8)
+            .map(|_| {
+                let mut rx = rx.clone();
+                std::thread::spawn(move || {
+                    let mut previous = 0;
+                    while previous != last {
+                        let frame = rx.recv();
+                        assert_eq!(frame.checksum, !frame.sequence, "torn frame");
+                        assert!(frame.sequence >= previous, "frame went back in time");
+                        previous = frame.sequence;
+                    }
+                })
+            })
+            .collect();
+
+        for sequence in 1..=last {
+            tx.send(Frame::new(sequence));
+        }
+        for receiver in receivers {
+            receiver.join().unwrap();
+        }
+    }
+}
@@ -0,0 +1,121 @@
+#[cfg(feature = "stats")]
+use crate::atomic::AtomicU64;
+
+#[cfg(feature = "stats")]
+use crate::ord;
+
+/// Frame counters of a buffer since it was created, from `stats()`.
+///
+/// `published` is `consumed + overwritten`, plus one while a frame is
+/// waiting to be read.
+#[cfg(feature = "stats")]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Stats {
+    pub published: u64,
+    pub consumed: u64,
+    /// Published frames replaced before the reader took them.
+    pub overwritten: u64,
+}
+
+/// Relaxed counters with the `stats` feature, zero-sized no-ops without.
+pub(crate) struct Counters {
+    #[cfg(feature = "stats")]
+    published: AtomicU64,
+    #[cfg(feature = "stats")]
+    consumed: AtomicU64,
+    #[cfg(feature = "stats")]
+    overwritten: AtomicU64,
+}
+
+impl Counters {
+    pub(crate) const fn new() -> Self {
+        Self {
+            #[cfg(feature = "stats")]
+            published: AtomicU64::new(0),
+            #[cfg(feature = "stats")]
+            consumed: AtomicU64::new(0),
+            #[cfg(feature = "stats")]
+            overwritten: AtomicU64::new(0),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn published(&self, overwrote: bool) {
+        #[cfg(feature = "stats")]
+        {
+            self.published.fetch_add(1, ord::relaxed());
+            if overwrote {
+                self.overwritten.fetch_add(1, ord::relaxed());
+            }
+        }
+        #[cfg(not(feature = "stats"))]
+        let _ = overwrote;
+    }
+
+    #[inline]
+    pub(crate) fn consumed(&self) {
+        #[cfg(feature = "stats")]
+        self.consumed.fetch_add(1, ord::relaxed());
+    }
+
+    #[cfg(feature = "stats")]
+    pub(crate) fn get(&self) -> Stats {
+        Stats {
+            published: self.published.load(ord::relaxed()),
+            consumed: self.consumed.load(ord::relaxed()),
+            overwritten: self.overwritten.load(ord::relaxed()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(not(feature = "stats"))]
+    #[test]
+    fn counters_vanish_without_the_feature() {
+        // A zero-sized, byte-aligned field adds nothing to the buffer.
+        assert_eq!(core::mem::size_of::<Counters>(), 0);
+        assert_eq!(core::mem::align_of::<Counters>(), 1);
+    }
+
+    #[cfg(feature = "stats")]
+    #[test]
+    fn counters_follow_scripted_interleaving() {
+        use crate::TripleBuffer;
+
+        let buffer = TripleBuffer::new(|| 0);
+        let mut writer = buffer.get_writer();
+        let mut reader = buffer.get_reader();
+        assert_eq!(buffer.stats(), Stats::default());
+
+        writer.write(1);
+        writer.write(2);
+        writer.write(3);
+        assert_eq!(
+            buffer.stats(),
+            Stats {
+                published: 3,
+                consumed: 0,
+                overwritten: 2
+            }
+        );
+        reader.read();
+        reader.read();
+        writer.write(4);
+        reader.read();
+        writer.write(5);
+        assert_eq!(
+            buffer.stats(),
+            Stats {
+                published: 5,
+                consumed: 2,
+                overwritten: 2
+            }
+        );
+        let stats = buffer.stats();
+        assert_eq!(stats.published, stats.consumed + stats.overwritten + 1);
+    }
+}
@@ -0,0 +1,132 @@
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use futures_core::{FusedStream, Stream};
+
+use crate::{ord, AsyncNotifier, BufferReader, Notifier, WakerNotifier};
+
+/// `Stream` adapter owning a `BufferReader`, yielding a clone of every frame
+/// it takes. Frames published faster than they're polled are coalesced. The
+/// stream ends once no writer is attached and nothing is left unread, and
+/// stays ended even if a writer attaches later.
+///
+/// Cancel safe: a frame is only taken in the `poll_next` call that returns
+/// it, so dropping a pending `next()` loses nothing.
+pub struct ReaderStream<'a, T, N: Notifier = WakerNotifier, const SLOTS: usize = 3> {
+    reader: BufferReader<'a, T, N, SLOTS>,
+    terminated: bool,
+}
+
+impl<'a, T, N: Notifier, const SLOTS: usize> ReaderStream<'a, T, N, SLOTS> {
+    pub fn new(reader: BufferReader<'a, T, N, SLOTS>) -> Self {
+        Self {
+            reader,
+            terminated: false,
+        }
+    }
+
+    /// Returns the reader; unread frames stay in the buffer.
+    pub fn into_inner(self) -> BufferReader<'a, T, N, SLOTS> {
+        self.reader
+    }
+
+    fn next_ready(&mut self) -> Option<Option<T>>
+    where
+        T: Clone,
+    {
+        // Checked before `update`, so a last frame published right before the
+        // writer detached is still delivered.
+        let closed = !self.reader.read_buffer.is_writer_exist.load(ord::acquire());
+        if self.reader.update() {
+            Some(Some(self.reader.output_buffer().clone()))
+        } else if closed {
+            Some(None)
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, T, N: Notifier, const SLOTS: usize> BufferReader<'a, T, N, SLOTS> {
+    pub fn into_stream(self) -> ReaderStream<'a, T, N, SLOTS> {
+        ReaderStream::new(self)
+    }
+}
+
+impl<'a, T: Clone, N: AsyncNotifier, const SLOTS: usize> Stream for ReaderStream<'a, T, N, SLOTS> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let this = self.get_mut();
+        if this.terminated {
+            return Poll::Ready(None);
+        }
+        let item = match this.next_ready() {
+            Some(item) => item,
+            None => {
+                this.reader.register_waker(cx.waker());
+                match this.next_ready() {
+                    Some(item) => item,
+                    None => return Poll::Pending,
+                }
+            }
+        };
+        this.terminated = item.is_none();
+        Poll::Ready(item)
+    }
+}
+
+impl<'a, T: Clone, N: AsyncNotifier, const SLOTS: usize> FusedStream
+    for ReaderStream<'a, T, N, SLOTS>
+{
+    fn is_terminated(&self) -> bool {
+        self.terminated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TripleBuffer;
+    use futures::executor::block_on;
+    use futures::StreamExt;
+
+    fn buffer() -> TripleBuffer<u32, WakerNotifier> {
+        TripleBuffer::with_notifiers(0, 0, 0, WakerNotifier::new(), WakerNotifier::new())
+    }
+
+    #[test]
+    fn stream_yields_latest_frame_and_ends_with_writer() {
+        let buffer = buffer();
+        let mut writer = buffer.get_writer();
+        let mut stream = buffer.get_reader().into_stream();
+
+        writer.write(1);
+        assert_eq!(block_on(stream.next()), Some(1));
+        writer.write(2);
+        writer.write(3);
+        assert_eq!(block_on(stream.next()), Some(3));
+
+        writer.write(4);
+        drop(writer);
+        assert_eq!(block_on(stream.next()), Some(4));
+        assert_eq!(block_on(stream.next()), None);
+    }
+
+    #[test]
+    fn stream_wakes_on_publish_from_other_thread() {
+        static BUFFER: TripleBuffer<u32, WakerNotifier> =
+            TripleBuffer::with_notifiers(0, 0, 0, WakerNotifier::new(), WakerNotifier::new());
+        let mut writer = BUFFER.get_writer();
+        let stream = BUFFER.get_reader().into_stream();
+        let jh = std::thread::spawn(move || {
+            for i in 1..=100 {
+                block_on(writer.consumed_async());
+                writer.write(i);
+            }
+        });
+
+        let frames: Vec<u32> = block_on(stream.collect());
+        assert_eq!(frames, (1..=100).collect::<Vec<_>>());
+        jh.join().unwrap();
+    }
+}
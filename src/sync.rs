@@ -0,0 +1,334 @@
+//! The atomics behind `NBuffer`'s control state: `crate::atomic`'s, cells
+//! guarded by `critical_section::with` under the `critical-section` feature,
+//! or loom's, shuttle's or the fault injector's when their tests are built.
+//! Run them with
+//! `RUSTFLAGS="--cfg tri_buffer_loom" cargo test --release --lib loom`,
+//! `RUSTFLAGS="--cfg tri_buffer_shuttle" cargo test --release --lib shuttle`
+//! and `RUSTFLAGS="--cfg tri_buffer_faults" cargo test --lib fault`.
+//!
+//! Every backend provides the same operations, and the buffer uses no
+//! others: `new`, `get_mut`, `load`, `store`, `swap`, `compare_exchange` and
+//! `compare_exchange_weak`. A test double only has to implement those.
+//!
+//! Index words are `AtomicU8`s, or `AtomicU16`s holding a checked copy under
+//! `redundant-control`, and the control word is an `AtomicU32` under
+//! `packed-control`, so some of them go unused.
+
+#[cfg(all(
+    feature = "critical-section",
+    not(all(any(tri_buffer_loom, tri_buffer_shuttle, tri_buffer_faults), test))
+))]
+#[allow(unused_imports)]
+pub(crate) use self::cs::{AtomicBool, AtomicU16, AtomicU32, AtomicU8};
+#[cfg(all(tri_buffer_faults, not(any(tri_buffer_loom, tri_buffer_shuttle)), test))]
+#[allow(unused_imports)]
+pub(crate) use self::faults::{AtomicBool, AtomicU16, AtomicU32, AtomicU8};
+#[cfg(all(tri_buffer_loom, test))]
+#[allow(unused_imports)]
+pub(crate) use self::model::{AtomicBool, AtomicU16, AtomicU32, AtomicU8};
+#[cfg(not(any(
+    feature = "critical-section",
+    all(any(tri_buffer_loom, tri_buffer_shuttle, tri_buffer_faults), test)
+)))]
+#[allow(unused_imports)]
+pub(crate) use crate::atomic::{AtomicBool, AtomicU16, AtomicU32, AtomicU8};
+#[cfg(all(tri_buffer_shuttle, not(tri_buffer_loom), test))]
+#[allow(unused_imports)]
+pub(crate) use shuttle::sync::atomic::{AtomicBool, AtomicU16, AtomicU32, AtomicU8};
+
+// loom atomics can only be created inside `loom::model`, but the buffer is
+// built by `const fn`s, so each one is created on first use from `init`.
+// A plain pointer keeps the wrapper free of drop glue, which `const fn`s
+// can't run; the loom atomic is leaked along with the model's buffer.
+#[cfg(all(tri_buffer_loom, test))]
+mod model {
+    use std::boxed::Box;
+    use std::ptr;
+    use std::sync::atomic::AtomicPtr;
+
+    use crate::atomic::Ordering;
+
+    macro_rules! lazy_atomic {
+        ($atomic:ident, $int:ty) => {
+            pub(crate) struct $atomic {
+                init: $int,
+                atomic: AtomicPtr<loom::sync::atomic::$atomic>,
+            }
+
+            // Each instance only needs what the buffer calls on that type.
+            #[allow(dead_code)]
+            impl $atomic {
+                pub(crate) const fn new(init: $int) -> Self {
+                    Self {
+                        init,
+                        atomic: AtomicPtr::new(ptr::null_mut()),
+                    }
+                }
+
+                fn atomic(&self) -> &loom::sync::atomic::$atomic {
+                    let mut atomic = self.atomic.load(Ordering::Acquire);
+                    if atomic.is_null() {
+                        let new =
+                            Box::into_raw(Box::new(loom::sync::atomic::$atomic::new(self.init)));
+                        atomic = match self.atomic.compare_exchange(
+                            ptr::null_mut(),
+                            new,
+                            Ordering::AcqRel,
+                            Ordering::Acquire,
+                        ) {
+                            Ok(_) => new,
+                            Err(current) => {
+                                drop(unsafe { Box::from_raw(new) });
+                                current
+                            }
+                        };
+                    }
+                    unsafe { &*atomic }
+                }
+
+                pub(crate) fn get_mut(&mut self) -> &mut $int {
+                    let atomic = core::mem::replace(self.atomic.get_mut(), ptr::null_mut());
+                    if !atomic.is_null() {
+                        self.init = unsafe { Box::from_raw(atomic) }.into_inner();
+                    }
+                    &mut self.init
+                }
+
+                pub(crate) fn load(&self, order: Ordering) -> $int {
+                    self.atomic().load(order)
+                }
+
+                pub(crate) fn store(&self, value: $int, order: Ordering) {
+                    self.atomic().store(value, order)
+                }
+
+                pub(crate) fn swap(&self, value: $int, order: Ordering) -> $int {
+                    self.atomic().swap(value, order)
+                }
+
+                pub(crate) fn compare_exchange(
+                    &self,
+                    current: $int,
+                    new: $int,
+                    success: Ordering,
+                    failure: Ordering,
+                ) -> Result<$int, $int> {
+                    self.atomic()
+                        .compare_exchange(current, new, success, failure)
+                }
+
+                pub(crate) fn compare_exchange_weak(
+                    &self,
+                    current: $int,
+                    new: $int,
+                    success: Ordering,
+                    failure: Ordering,
+                ) -> Result<$int, $int> {
+                    self.atomic()
+                        .compare_exchange_weak(current, new, success, failure)
+                }
+            }
+        };
+    }
+
+    lazy_atomic!(AtomicU8, u8);
+    lazy_atomic!(AtomicU16, u16);
+    lazy_atomic!(AtomicU32, u32);
+    lazy_atomic!(AtomicBool, bool);
+}
+
+// For targets without compare-and-swap, e.g. thumbv6m or AVR: each
+// operation runs inside a critical section, which also orders it, so the
+// `Ordering`s are only there to keep the signatures. The state machine on
+// top is unchanged.
+#[cfg(all(
+    feature = "critical-section",
+    not(all(any(tri_buffer_loom, tri_buffer_shuttle, tri_buffer_faults), test))
+))]
+mod cs {
+    use core::cell::Cell;
+
+    use critical_section::Mutex;
+
+    use crate::atomic::Ordering;
+
+    macro_rules! cs_atomic {
+        ($atomic:ident, $int:ty) => {
+            pub(crate) struct $atomic(Mutex<Cell<$int>>);
+
+            // Each instance only needs what the buffer calls on that type.
+            #[allow(dead_code)]
+            impl $atomic {
+                pub(crate) const fn new(init: $int) -> Self {
+                    Self(Mutex::new(Cell::new(init)))
+                }
+
+                pub(crate) fn get_mut(&mut self) -> &mut $int {
+                    self.0.get_mut().get_mut()
+                }
+
+                pub(crate) fn load(&self, _: Ordering) -> $int {
+                    critical_section::with(|cs| self.0.borrow(cs).get())
+                }
+
+                pub(crate) fn store(&self, value: $int, _: Ordering) {
+                    critical_section::with(|cs| self.0.borrow(cs).set(value))
+                }
+
+                pub(crate) fn swap(&self, value: $int, _: Ordering) -> $int {
+                    critical_section::with(|cs| self.0.borrow(cs).replace(value))
+                }
+
+                pub(crate) fn compare_exchange(
+                    &self,
+                    current: $int,
+                    new: $int,
+                    _: Ordering,
+                    _: Ordering,
+                ) -> Result<$int, $int> {
+                    critical_section::with(|cs| {
+                        let cell = self.0.borrow(cs);
+                        let value = cell.get();
+                        if value == current {
+                            cell.set(new);
+                            Ok(value)
+                        } else {
+                            Err(value)
+                        }
+                    })
+                }
+
+                pub(crate) fn compare_exchange_weak(
+                    &self,
+                    current: $int,
+                    new: $int,
+                    success: Ordering,
+                    failure: Ordering,
+                ) -> Result<$int, $int> {
+                    self.compare_exchange(current, new, success, failure)
+                }
+            }
+        };
+    }
+
+    cs_atomic!(AtomicU8, u8);
+    cs_atomic!(AtomicU16, u16);
+    cs_atomic!(AtomicU32, u32);
+    cs_atomic!(AtomicBool, bool);
+}
+
+// `crate::atomic`'s atomics, plus a hook each thread can install to run
+// after every operation it makes, e.g. to record them or to park the thread
+// at a chosen point so another runs into that window. Unlike loom, a test
+// picks the one interleaving it wants and runs it on real threads.
+#[cfg(all(tri_buffer_faults, not(any(tri_buffer_loom, tri_buffer_shuttle)), test))]
+pub(crate) mod faults {
+    use std::boxed::Box;
+    use std::cell::RefCell;
+
+    use crate::atomic::Ordering;
+
+    /// An operation on a control atomic, as the hook sees it.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub(crate) enum Op {
+        Load,
+        Store,
+        Swap,
+        CompareExchange,
+    }
+
+    type AfterHook = Box<dyn FnMut(Op, *const ())>;
+
+    std::thread_local! {
+        static AFTER: RefCell<Option<AfterHook>> = const { RefCell::new(None) };
+    }
+
+    /// Runs `hook` after every operation this thread makes, with the
+    /// atomic's address, until `clear_hook`. The hook's own operations
+    /// don't call it again.
+    pub(crate) fn set_hook(hook: impl FnMut(Op, *const ()) + 'static) {
+        AFTER.with(|after| *after.borrow_mut() = Some(Box::new(hook)));
+    }
+
+    pub(crate) fn clear_hook() {
+        AFTER.with(|after| after.borrow_mut().take());
+    }
+
+    fn after(op: Op, atomic: *const ()) {
+        let Some(mut hook) = AFTER.with(|after| after.borrow_mut().take()) else {
+            return;
+        };
+        hook(op, atomic);
+        AFTER.with(|after| {
+            after.borrow_mut().get_or_insert(hook);
+        });
+    }
+
+    macro_rules! fault_atomic {
+        ($atomic:ident, $int:ty) => {
+            pub(crate) struct $atomic(crate::atomic::$atomic);
+
+            // Each instance only needs what the buffer calls on that type.
+            #[allow(dead_code)]
+            impl $atomic {
+                pub(crate) const fn new(init: $int) -> Self {
+                    Self(crate::atomic::$atomic::new(init))
+                }
+
+                fn addr(&self) -> *const () {
+                    (self as *const Self).cast()
+                }
+
+                pub(crate) fn get_mut(&mut self) -> &mut $int {
+                    self.0.get_mut()
+                }
+
+                pub(crate) fn load(&self, order: Ordering) -> $int {
+                    let value = self.0.load(order);
+                    after(Op::Load, self.addr());
+                    value
+                }
+
+                pub(crate) fn store(&self, value: $int, order: Ordering) {
+                    self.0.store(value, order);
+                    after(Op::Store, self.addr());
+                }
+
+                pub(crate) fn swap(&self, value: $int, order: Ordering) -> $int {
+                    let value = self.0.swap(value, order);
+                    after(Op::Swap, self.addr());
+                    value
+                }
+
+                pub(crate) fn compare_exchange(
+                    &self,
+                    current: $int,
+                    new: $int,
+                    success: Ordering,
+                    failure: Ordering,
+                ) -> Result<$int, $int> {
+                    let result = self.0.compare_exchange(current, new, success, failure);
+                    after(Op::CompareExchange, self.addr());
+                    result
+                }
+
+                pub(crate) fn compare_exchange_weak(
+                    &self,
+                    current: $int,
+                    new: $int,
+                    success: Ordering,
+                    failure: Ordering,
+                ) -> Result<$int, $int> {
+                    let result = self.0.compare_exchange_weak(current, new, success, failure);
+                    after(Op::CompareExchange, self.addr());
+                    result
+                }
+            }
+        };
+    }
+
+    fault_atomic!(AtomicU8, u8);
+    fault_atomic!(AtomicU16, u16);
+    fault_atomic!(AtomicU32, u32);
+    fault_atomic!(AtomicBool, bool);
+}
@@ -0,0 +1,66 @@
+use embassy_time::{with_timeout, Duration, TimeoutError};
+
+use crate::{AsyncNotifier, BufferReader, BufferWriter};
+
+impl<'a, T, N: AsyncNotifier, const SLOTS: usize> BufferReader<'a, T, N, SLOTS> {
+    /// `changed` bounded by `timeout`. Cancel safe like `changed`: on
+    /// timeout nothing was taken.
+    pub async fn changed_timeout(&mut self, timeout: Duration) -> Result<(), TimeoutError> {
+        with_timeout(timeout, self.changed()).await
+    }
+}
+
+impl<'a, T, N: AsyncNotifier, const SLOTS: usize> BufferWriter<'a, T, N, SLOTS> {
+    /// `consumed_async` bounded by `timeout`.
+    pub async fn consumed_timeout(&mut self, timeout: Duration) -> Result<(), TimeoutError> {
+        with_timeout(timeout, self.consumed_async()).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::waker::tests::block_on;
+    use crate::{TripleBuffer, WakerNotifier};
+    use embassy_time::Instant;
+
+    #[test]
+    fn changed_timeout_expires_then_resolves() {
+        let buffer =
+            TripleBuffer::with_notifiers(0, 0, 0, WakerNotifier::new(), WakerNotifier::new());
+        let mut reader = buffer.get_reader();
+        let mut writer = buffer.get_writer();
+
+        let start = Instant::now();
+        let timeout = Duration::from_millis(20);
+        assert_eq!(block_on(reader.changed_timeout(timeout)), Err(TimeoutError));
+        assert!(start.elapsed() >= timeout);
+
+        writer.write(1);
+        assert_eq!(block_on(reader.changed_timeout(timeout)), Ok(()));
+        assert_eq!(*reader.read(), 1);
+    }
+
+    #[test]
+    fn consumed_timeout_wakes_on_update() {
+        static BUFFER: TripleBuffer<u32, WakerNotifier> =
+            TripleBuffer::with_notifiers(0, 0, 0, WakerNotifier::new(), WakerNotifier::new());
+        let mut writer = BUFFER.get_writer();
+        let mut reader = BUFFER.get_reader();
+
+        writer.write(1);
+        assert_eq!(
+            block_on(writer.consumed_timeout(Duration::from_millis(10))),
+            Err(TimeoutError)
+        );
+        let jh = std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(10));
+            reader.update();
+        });
+        assert_eq!(
+            block_on(writer.consumed_timeout(Duration::from_secs(5))),
+            Ok(())
+        );
+        jh.join().unwrap();
+    }
+}
@@ -0,0 +1,43 @@
+//! `tracing` events for the `tracing` feature. Kept out of line so the hot
+//! paths only pay for a call, into code that checks whether anyone listens.
+
+use tracing::span::EnteredSpan;
+
+use crate::NBuffer;
+
+/// Sequence number stamped into slot `idx`, with the `seq` feature.
+#[cfg_attr(feature = "rt-safe", allow(dead_code))]
+pub(crate) fn seq<T, const SLOTS: usize, N>(buffer: &NBuffer<T, SLOTS, N>, idx: u8) -> Option<u64> {
+    #[cfg(feature = "seq")]
+    return Some(buffer.seqs[idx as usize].load(crate::ord::relaxed()));
+    #[cfg(not(feature = "seq"))]
+    {
+        let _ = (buffer, idx);
+        None
+    }
+}
+
+#[cold]
+#[inline(never)]
+#[cfg_attr(feature = "rt-safe", allow(dead_code))]
+pub(crate) fn published(overwrote: bool, seq: Option<u64>) {
+    tracing::trace!(target: "tri_buffer", overwrote, seq, "published");
+}
+
+/// `skipped` counts the frames published between the previous one taken and
+/// this one, which the reader never saw.
+#[cold]
+#[inline(never)]
+#[cfg_attr(feature = "rt-safe", allow(dead_code))]
+pub(crate) fn consumed(slot: u8, seq: Option<u64>, previous: Option<u64>) {
+    let skipped = seq
+        .zip(previous)
+        .map(|(seq, previous)| seq.wrapping_sub(previous).wrapping_sub(1));
+    tracing::trace!(target: "tri_buffer", slot, seq, skipped, "consumed");
+}
+
+#[cold]
+#[inline(never)]
+pub(crate) fn wait(side: &'static str) -> EnteredSpan {
+    tracing::debug_span!(target: "tri_buffer", "wait", side).entered()
+}
@@ -0,0 +1,229 @@
+use core::cell::{Cell, UnsafeCell};
+
+use crate::{is_dirty, publish_transition, update_transition, ControlWord, Word};
+
+/// `TripleBuffer` for a producer and consumer on the same thread, e.g. two
+/// steps of a cooperative scheduler. Same handles and slot rotation, but the
+/// state lives in `Cell`s: no atomics, no critical sections, and `!Sync`.
+/// There are no notifiers, since a blocking wait could never be woken.
+pub struct UnsyncTripleBuffer<T> {
+    buffers: UnsafeCell<[T; 3]>,
+
+    back_info: Cell<Word>,
+    input_idx: Cell<u8>,
+    output_idx: Cell<u8>,
+
+    is_reader_exist: Cell<bool>,
+    is_writer_exist: Cell<bool>,
+}
+
+pub struct UnsyncReader<'a, T> {
+    read_buffer: &'a UnsyncTripleBuffer<T>,
+}
+
+pub struct UnsyncWriter<'a, T> {
+    write_buffer: &'a UnsyncTripleBuffer<T>,
+}
+
+impl<'a, T> UnsyncReader<'a, T> {
+    pub fn read(&mut self) -> &T {
+        self.update();
+        self.output_buffer()
+    }
+
+    pub fn updated(&mut self) -> bool {
+        is_dirty(self.read_buffer.back_info.get())
+    }
+
+    pub fn output_buffer(&mut self) -> &mut T {
+        let output_ptr = self.read_buffer.slot(self.read_buffer.output_idx.get());
+        unsafe { &mut *output_ptr }
+    }
+
+    pub fn update(&mut self) -> bool {
+        let buffer = self.read_buffer;
+        let taken = update_transition(ControlWord(buffer.back_info.get()), buffer.output_idx.get());
+        if let Some((back_info, output_idx)) = taken {
+            buffer.back_info.set(back_info.0);
+            buffer.output_idx.set(output_idx);
+        }
+        taken.is_some()
+    }
+}
+
+impl<'a, T> Drop for UnsyncReader<'a, T> {
+    fn drop(&mut self) {
+        self.read_buffer.is_reader_exist.set(false);
+    }
+}
+
+impl<'a, T> UnsyncWriter<'a, T> {
+    pub fn write(&mut self, value: T) {
+        *self.input_buffer() = value;
+        self.publish();
+    }
+
+    pub fn input_buffer(&mut self) -> &mut T {
+        let input_ptr = self.write_buffer.slot(self.write_buffer.input_idx.get());
+        unsafe { &mut *input_ptr }
+    }
+
+    pub fn consumed(&self) -> bool {
+        !is_dirty(self.write_buffer.back_info.get())
+    }
+
+    pub fn publish(&self) -> bool {
+        let buffer = self.write_buffer;
+        let (back_info, input_idx, overwrote) =
+            publish_transition(ControlWord(buffer.back_info.get()), buffer.input_idx.get());
+        buffer.back_info.set(back_info.0);
+        buffer.input_idx.set(input_idx);
+        overwrote
+    }
+}
+
+impl<'a, T> Drop for UnsyncWriter<'a, T> {
+    fn drop(&mut self) {
+        self.write_buffer.is_writer_exist.set(false);
+    }
+}
+
+impl<T> UnsyncTripleBuffer<T> {
+    pub fn new(generator: impl Fn() -> T) -> Self {
+        Self::new_const(generator(), generator(), generator())
+    }
+
+    pub const fn new_const(s1: T, s2: T, s3: T) -> Self {
+        Self {
+            buffers: UnsafeCell::new([s1, s2, s3]),
+            back_info: Cell::new(ControlWord::new(0, 1, 2).0),
+            input_idx: Cell::new(1),
+            output_idx: Cell::new(2),
+
+            is_reader_exist: Cell::new(false),
+            is_writer_exist: Cell::new(false),
+        }
+    }
+
+    fn slot(&self, idx: u8) -> *mut T {
+        unsafe { self.buffers.get().cast::<T>().add(idx as usize) }
+    }
+
+    pub fn split(&mut self) -> (UnsyncReader<'_, T>, UnsyncWriter<'_, T>) {
+        self.is_reader_exist.set(true);
+        self.is_writer_exist.set(true);
+        (
+            UnsyncReader { read_buffer: self },
+            UnsyncWriter { write_buffer: self },
+        )
+    }
+
+    pub fn get_reader(&self) -> UnsyncReader<'_, T> {
+        match self.try_get_reader() {
+            Some(reader) => reader,
+            None => panic!("Reader already exists"),
+        }
+    }
+
+    pub fn get_writer(&self) -> UnsyncWriter<'_, T> {
+        match self.try_get_writer() {
+            Some(writer) => writer,
+            None => panic!("Writer already exists"),
+        }
+    }
+
+    /// Like `get_reader`, but returns `None` while a reader exists.
+    pub fn try_get_reader(&self) -> Option<UnsyncReader<'_, T>> {
+        (!self.is_reader_exist.replace(true)).then_some(UnsyncReader { read_buffer: self })
+    }
+
+    /// Like `get_writer`, but returns `None` while a writer exists.
+    pub fn try_get_writer(&self) -> Option<UnsyncWriter<'_, T>> {
+        (!self.is_writer_exist.replace(true)).then_some(UnsyncWriter { write_buffer: self })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default, PartialEq, Eq, Debug)]
+    struct MyStruct {
+        goose: u32,
+    }
+
+    #[test]
+    fn reader_gets_latest_frame() {
+        let buffer = UnsyncTripleBuffer::new(MyStruct::default);
+        let mut writer = buffer.get_writer();
+        let mut reader = buffer.get_reader();
+
+        assert_eq!(*reader.read(), MyStruct { goose: 0 });
+        writer.write(MyStruct { goose: 3 });
+        assert!(!writer.consumed() && reader.updated());
+        *writer.input_buffer() = MyStruct { goose: 4 };
+        assert!(writer.publish(), "unread frame overwritten");
+        assert_eq!(*reader.read(), MyStruct { goose: 4 });
+        assert!(writer.consumed());
+        assert!(!reader.update());
+        assert_eq!(*reader.read(), MyStruct { goose: 4 });
+    }
+
+    #[test]
+    fn interleaved_steps_are_lossless() {
+        let mut buffer = UnsyncTripleBuffer::new(|| 0);
+        let (mut reader, mut writer) = buffer.split();
+        for i in 1..=1000 {
+            writer.write(i);
+            assert!(reader.updated());
+            assert_eq!(*reader.read(), i);
+        }
+    }
+
+    #[test]
+    fn direct_input_is_published() {
+        let buffer = UnsyncTripleBuffer::new_const([0; 4], [0; 4], [0; 4]);
+        let mut writer = buffer.get_writer();
+        let mut reader = buffer.get_reader();
+
+        writer.input_buffer()[2] = 7;
+        assert_eq!(*reader.read(), [0; 4]);
+        writer.publish();
+        assert_eq!(*reader.read(), [0, 0, 7, 0]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn reader_access_test() {
+        let buffer = UnsyncTripleBuffer::new(MyStruct::default);
+        let _goose_reader = buffer.get_reader();
+        let _evil_reader = buffer.get_reader();
+    }
+
+    #[test]
+    fn good_reader_access_test() {
+        let buffer = UnsyncTripleBuffer::new(MyStruct::default);
+        {
+            let _goose_reader = buffer.get_reader();
+        }
+        let _evil_reader = buffer.get_reader();
+        assert!(buffer.try_get_reader().is_none());
+        assert!(buffer.try_get_writer().is_some());
+    }
+
+    #[test]
+    fn every_slot_takes_turns() {
+        use std::collections::BTreeSet;
+
+        let buffer = UnsyncTripleBuffer::new(|| 0);
+        let mut writer = buffer.get_writer();
+        let mut reader = buffer.get_reader();
+        let mut slots = BTreeSet::new();
+        for i in 1..=6 {
+            writer.write(i);
+            assert_eq!(*reader.read(), i);
+            slots.insert(buffer.output_idx.get());
+        }
+        assert_eq!(slots, (0..3).collect::<BTreeSet<_>>());
+    }
+}
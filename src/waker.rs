@@ -0,0 +1,432 @@
+#[cfg(not(feature = "embassy"))]
+use core::cell::UnsafeCell;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll, Waker};
+#[cfg(feature = "embassy")]
+use embassy_sync::waitqueue::AtomicWaker;
+
+#[cfg(not(feature = "embassy"))]
+use crate::atomic::AtomicUsize;
+use crate::atomic::{fence, AtomicBool, Ordering};
+use crate::{is_dirty, ord, Backoff, BufferReader, BufferWriter, Notifier};
+
+/// A notifier that async tasks can register their `Waker` with.
+pub trait AsyncNotifier: Notifier {
+    fn register(&self, waker: &Waker);
+}
+
+/// Wakes the last registered `Waker` on notification.
+///
+/// With the `embassy` feature the waker lives in an
+/// `embassy_sync::waitqueue::AtomicWaker`, which guards it with a critical
+/// section and can therefore be shared between executors of different
+/// priorities. Blocking waits fall back to polling with a `Backoff`, since
+/// there is no thread to park.
+///
+/// Registering arms the notifier and the first notification disarms it,
+/// so publishes nobody is waiting for don't touch the waker slot.
+pub struct WakerNotifier {
+    waker: AtomicWaker,
+    armed: AtomicBool,
+    #[cfg(test)]
+    wakes: crate::atomic::AtomicU32,
+}
+
+impl WakerNotifier {
+    pub const fn new() -> Self {
+        Self {
+            waker: AtomicWaker::new(),
+            armed: AtomicBool::new(false),
+            #[cfg(test)]
+            wakes: crate::atomic::AtomicU32::new(0),
+        }
+    }
+}
+
+impl Default for WakerNotifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Notifier for WakerNotifier {
+    #[inline]
+    fn notify(&self) {
+        if self.armed.load(Ordering::SeqCst) && self.armed.swap(false, Ordering::SeqCst) {
+            #[cfg(test)]
+            self.wakes.fetch_add(1, ord::relaxed());
+            self.waker.wake();
+        }
+    }
+
+    fn wait(&self, until: impl Fn() -> bool) {
+        let mut backoff = Backoff::new();
+        while !until() {
+            backoff.snooze();
+        }
+    }
+}
+
+impl AsyncNotifier for WakerNotifier {
+    fn register(&self, waker: &Waker) {
+        self.armed.store(true, Ordering::SeqCst);
+        self.waker.register(waker);
+        // The caller re-checks its condition after registering; this pairs
+        // with the `armed` load in `notify`.
+        fence(Ordering::SeqCst);
+    }
+}
+
+#[cfg(not(feature = "embassy"))]
+const WAITING: usize = 0;
+#[cfg(not(feature = "embassy"))]
+const REGISTERING: usize = 0b01;
+#[cfg(not(feature = "embassy"))]
+const WAKING: usize = 0b10;
+
+/// Single-slot waker cell; the same protocol as `futures`' `AtomicWaker`.
+#[cfg(not(feature = "embassy"))]
+pub(crate) struct AtomicWaker {
+    state: AtomicUsize,
+    waker: UnsafeCell<Option<Waker>>,
+}
+
+#[cfg(not(feature = "embassy"))]
+unsafe impl Send for AtomicWaker {}
+#[cfg(not(feature = "embassy"))]
+unsafe impl Sync for AtomicWaker {}
+
+#[cfg(not(feature = "embassy"))]
+impl AtomicWaker {
+    pub(crate) const fn new() -> Self {
+        Self {
+            state: AtomicUsize::new(WAITING),
+            waker: UnsafeCell::new(None),
+        }
+    }
+
+    pub(crate) fn register(&self, waker: &Waker) {
+        match self
+            .state
+            .compare_exchange(WAITING, REGISTERING, ord::acquire(), ord::acquire())
+            .unwrap_or_else(|state| state)
+        {
+            WAITING => {
+                unsafe {
+                    let slot = &mut *self.waker.get();
+                    match slot {
+                        Some(old) if old.will_wake(waker) => {}
+                        _ => *slot = Some(waker.clone()),
+                    }
+                }
+                if self
+                    .state
+                    .compare_exchange(REGISTERING, WAITING, ord::acqrel(), ord::acquire())
+                    .is_err()
+                {
+                    // A wake raced the registration; it left the waker to us.
+                    let waker = unsafe { (*self.waker.get()).take() };
+                    self.state.swap(WAITING, ord::acqrel());
+                    if let Some(waker) = waker {
+                        waker.wake();
+                    }
+                }
+            }
+            WAKING => {
+                // Currently being woken; make sure the new task polls again.
+                waker.wake_by_ref();
+            }
+            _ => {
+                // Concurrent registration; only one registrant may exist.
+            }
+        }
+    }
+
+    pub(crate) fn wake(&self) {
+        if let Some(waker) = self.take() {
+            waker.wake();
+        }
+    }
+
+    fn take(&self) -> Option<Waker> {
+        match self.state.fetch_or(WAKING, ord::acqrel()) {
+            WAITING => {
+                let waker = unsafe { (*self.waker.get()).take() };
+                self.state.fetch_and(!WAKING, ord::release());
+                waker
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Future returned by `BufferReader::changed`.
+///
+/// Cancel safe: it never takes the frame, so dropping it, pending or
+/// ready, leaves the frame for the next `read`/`update`. A dropped pending
+/// future leaves its waker registered, costing at most a spurious wakeup.
+pub struct Changed<'r, 'a, T, N: Notifier, const SLOTS: usize = 3> {
+    reader: &'r mut BufferReader<'a, T, N, SLOTS>,
+    done: bool,
+}
+
+impl<'r, 'a, T, N: AsyncNotifier, const SLOTS: usize> Future for Changed<'r, 'a, T, N, SLOTS> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let this = self.get_mut();
+        let poll = this.reader.poll_changed(cx);
+        this.done = poll.is_ready();
+        poll
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<'r, 'a, T, N: AsyncNotifier, const SLOTS: usize> futures_core::FusedFuture
+    for Changed<'r, 'a, T, N, SLOTS>
+{
+    fn is_terminated(&self) -> bool {
+        self.done
+    }
+}
+
+/// Future returned by `BufferWriter::consumed_async`. Cancel safe in the
+/// same way as `Changed`.
+pub struct Consumed<'w, 'a, T, N: Notifier, const SLOTS: usize = 3> {
+    writer: &'w mut BufferWriter<'a, T, N, SLOTS>,
+    done: bool,
+}
+
+impl<'w, 'a, T, N: AsyncNotifier, const SLOTS: usize> Future for Consumed<'w, 'a, T, N, SLOTS> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let this = self.get_mut();
+        let poll = this.writer.poll_consumed(cx);
+        this.done = poll.is_ready();
+        poll
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<'w, 'a, T, N: AsyncNotifier, const SLOTS: usize> futures_core::FusedFuture
+    for Consumed<'w, 'a, T, N, SLOTS>
+{
+    fn is_terminated(&self) -> bool {
+        self.done
+    }
+}
+
+impl<'a, T, N: AsyncNotifier, const SLOTS: usize> BufferReader<'a, T, N, SLOTS> {
+    /// Registers `waker` to be woken by the next `publish()`, replacing the
+    /// previously registered one.
+    pub fn register_waker(&mut self, waker: &Waker) {
+        self.read_buffer.reader_notifier.register(waker);
+    }
+
+    /// Ready once a frame is published that the reader hasn't taken yet;
+    /// otherwise registers `cx`'s waker for the next publish.
+    pub fn poll_changed(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        let buffer = self.read_buffer;
+        let published = || is_dirty(buffer.back_info.load(ord::acquire()));
+        if published() {
+            return Poll::Ready(());
+        }
+        self.register_waker(cx.waker());
+        // A publish between the check and the registration woke nobody.
+        if published() {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+
+    /// Resolves once a frame is published that the reader hasn't taken yet.
+    pub fn changed(&mut self) -> Changed<'_, 'a, T, N, SLOTS> {
+        Changed {
+            reader: self,
+            done: false,
+        }
+    }
+}
+
+impl<'a, T, N: AsyncNotifier, const SLOTS: usize> BufferWriter<'a, T, N, SLOTS> {
+    /// Registers `waker` to be woken by the next consuming `update()`,
+    /// replacing the previously registered one.
+    pub fn register_waker(&mut self, waker: &Waker) {
+        self.write_buffer.writer_notifier.register(waker);
+    }
+
+    /// Ready once the reader has taken the last published frame; otherwise
+    /// registers `cx`'s waker for the next consuming update.
+    pub fn poll_consumed(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        let buffer = self.write_buffer;
+        let consumed = || !is_dirty(buffer.back_info.load(ord::acquire()));
+        if consumed() {
+            return Poll::Ready(());
+        }
+        self.register_waker(cx.waker());
+        if consumed() {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+
+    /// Resolves once the reader has taken the last published frame.
+    pub fn consumed_async(&mut self) -> Consumed<'_, 'a, T, N, SLOTS> {
+        Consumed {
+            writer: self,
+            done: false,
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::TripleBuffer;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::Arc;
+    use std::task::Wake;
+    use std::thread::Thread;
+
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    pub(crate) fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = core::pin::pin!(future);
+        let waker = Waker::from(Arc::new(ThreadWaker(std::thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+            std::thread::park();
+        }
+    }
+
+    struct CountingWaker(AtomicUsize);
+
+    impl Wake for CountingWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, ord::relaxed());
+        }
+    }
+
+    #[cfg(not(feature = "embassy"))]
+    #[test]
+    fn registered_waker_is_woken_once() {
+        let counter = Arc::new(CountingWaker(AtomicUsize::new(0)));
+        let waker = Waker::from(counter.clone());
+        let atomic_waker = AtomicWaker::new();
+
+        atomic_waker.wake();
+        atomic_waker.register(&waker);
+        atomic_waker.wake();
+        atomic_waker.wake();
+
+        assert_eq!(counter.0.load(ord::relaxed()), 1);
+    }
+
+    #[test]
+    fn changed_resolves_on_publish() {
+        static BUFFER: TripleBuffer<u32, WakerNotifier> = TripleBuffer::with_notifiers(
+            0,
+            0,
+            0,
+            WakerNotifier::new(),
+            WakerNotifier::new(),
+        );
+        let jh = std::thread::spawn(|| {
+            let mut writer = BUFFER.get_writer();
+            for i in 1..=100 {
+                block_on(writer.consumed_async());
+                writer.write(i);
+            }
+        });
+
+        let mut reader = BUFFER.get_reader();
+        for i in 1..=100 {
+            block_on(reader.changed());
+            assert_eq!(*reader.read(), i);
+        }
+        jh.join().unwrap();
+    }
+
+    #[test]
+    fn reregistration_replaces_waker() {
+        let first = Arc::new(CountingWaker(AtomicUsize::new(0)));
+        let second = Arc::new(CountingWaker(AtomicUsize::new(0)));
+        let buffer =
+            TripleBuffer::with_notifiers(0, 0, 0, WakerNotifier::new(), WakerNotifier::new());
+        let mut reader = buffer.get_reader();
+        let mut writer = buffer.get_writer();
+
+        reader.register_waker(&Waker::from(first.clone()));
+        reader.register_waker(&Waker::from(second.clone()));
+        writer.write(1);
+
+        assert_eq!(first.0.load(ord::relaxed()), 0);
+        assert_eq!(second.0.load(ord::relaxed()), 1);
+    }
+
+    #[test]
+    fn wakes_only_registered_waiters() {
+        let buffer =
+            TripleBuffer::with_notifiers(0, 0, 0, WakerNotifier::new(), WakerNotifier::new());
+        let mut reader = buffer.get_reader();
+        let mut writer = buffer.get_writer();
+        for i in 1..=100 {
+            writer.write(i);
+            assert!(reader.update());
+        }
+        assert_eq!(buffer.reader_notifier.wakes.load(ord::relaxed()), 0);
+        assert_eq!(buffer.writer_notifier.wakes.load(ord::relaxed()), 0);
+
+        let counter = Arc::new(CountingWaker(AtomicUsize::new(0)));
+        let waker = Waker::from(counter.clone());
+        let mut cx = Context::from_waker(&waker);
+        for i in 1..=100 {
+            assert!(reader.poll_changed(&mut cx).is_pending());
+            writer.write(i);
+            writer.write(i);
+            assert!(reader.poll_changed(&mut cx).is_ready());
+            reader.update();
+        }
+        assert_eq!(buffer.reader_notifier.wakes.load(ord::relaxed()), 100);
+        assert_eq!(counter.0.load(ord::relaxed()), 100);
+    }
+
+    #[test]
+    fn poll_changed_races_publish_without_lost_wakeups() {
+        static RACE_BUFFER: TripleBuffer<u32, WakerNotifier> = TripleBuffer::with_notifiers(
+            0,
+            0,
+            0,
+            WakerNotifier::new(),
+            WakerNotifier::new(),
+        );
+        let count = 100_000;
+        let jh = std::thread::spawn(move || {
+            let mut writer = RACE_BUFFER.get_writer();
+            for i in 1..=count {
+                block_on(core::future::poll_fn(|cx| writer.poll_consumed(cx)));
+                writer.write(i);
+            }
+        });
+
+        let mut reader = RACE_BUFFER.get_reader();
+        for i in 1..=count {
+            block_on(core::future::poll_fn(|cx| reader.poll_changed(cx)));
+            assert_eq!(*reader.read(), i);
+        }
+        jh.join().unwrap();
+    }
+}
@@ -0,0 +1,305 @@
+//! `tokio::sync::watch`-style wrappers over a reader/writer pair.
+//!
+//! Differences from tokio:
+//! - There is exactly one receiver per buffer; `WatchReceiver` is not
+//!   `Clone` and `channel` panics if the buffer already has a reader.
+//! - The sender can't borrow or modify the current value, because its
+//!   staging slot doesn't hold the latest frame.
+//! - `borrow` returns a plain reference rather than a `Ref` guard.
+//! - The channel is closed while no writer is attached to the buffer, so a
+//!   sender re-acquired later re-opens it.
+
+use core::fmt;
+use core::future::poll_fn;
+use core::task::Poll;
+
+use crate::{
+    is_dirty, ord, AsyncNotifier, BufferReader, BufferWriter, Notifier, TripleBuffer,
+    WakerNotifier,
+};
+
+/// Returned by `WatchSender::send` when the receiver is gone.
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub struct SendError<T>(pub T);
+
+impl<T> fmt::Debug for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SendError").finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl<T> defmt::Format for SendError<T> {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        defmt::write!(f, "SendError(..)")
+    }
+}
+
+impl<T> fmt::Display for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "channel closed")
+    }
+}
+
+impl<T> core::error::Error for SendError<T> {}
+
+/// Returned by `WatchReceiver::changed` and `has_changed` when the sender
+/// is gone.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RecvError(());
+
+impl fmt::Display for RecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "channel closed")
+    }
+}
+
+impl core::error::Error for RecvError {}
+
+pub struct WatchSender<'a, T, N: Notifier = WakerNotifier> {
+    buffer: &'a TripleBuffer<T, N>,
+    writer: BufferWriter<'a, T, N>,
+}
+
+pub struct WatchReceiver<'a, T, N: Notifier = WakerNotifier> {
+    buffer: &'a TripleBuffer<T, N>,
+    reader: BufferReader<'a, T, N>,
+    // Taken from the buffer by `borrow` but not yet marked as seen.
+    unseen: bool,
+}
+
+/// Attaches a sender and the single receiver to `buffer`. The buffer's
+/// current output is the initial, already seen, value.
+pub fn channel<T, N: Notifier>(
+    buffer: &TripleBuffer<T, N>,
+) -> (WatchSender<'_, T, N>, WatchReceiver<'_, T, N>) {
+    let sender = WatchSender {
+        buffer,
+        writer: buffer.get_writer(),
+    };
+    let receiver = WatchReceiver {
+        buffer,
+        reader: buffer.get_reader(),
+        unseen: false,
+    };
+    (sender, receiver)
+}
+
+impl<'a, T, N: Notifier> WatchSender<'a, T, N> {
+    /// Publishes `value`, failing if the receiver was dropped.
+    pub fn send(&mut self, value: T) -> Result<(), SendError<T>> {
+        if self.is_closed() {
+            return Err(SendError(value));
+        }
+        self.writer.write(value);
+        Ok(())
+    }
+
+    pub fn is_closed(&self) -> bool {
+        !self.buffer.is_reader_exist.load(ord::acquire())
+    }
+}
+
+impl<'a, T, N: AsyncNotifier> WatchSender<'a, T, N> {
+    /// Resolves once the receiver was dropped.
+    pub async fn closed(&mut self) {
+        let buffer = self.buffer;
+        poll_fn(|cx| {
+            if self.is_closed() {
+                return Poll::Ready(());
+            }
+            buffer.writer_notifier.register(cx.waker());
+            if self.is_closed() {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        })
+        .await
+    }
+}
+
+impl<'a, T, N: Notifier> WatchReceiver<'a, T, N> {
+    /// Returns the latest value without marking it as seen.
+    pub fn borrow(&mut self) -> &T {
+        if self.reader.update() {
+            self.unseen = true;
+        }
+        self.reader.output_buffer()
+    }
+
+    /// Returns the latest value and marks it as seen.
+    pub fn borrow_and_update(&mut self) -> &T {
+        self.mark_seen();
+        self.reader.output_buffer()
+    }
+
+    pub fn mark_changed(&mut self) {
+        self.unseen = true;
+    }
+
+    pub fn mark_unchanged(&mut self) {
+        self.mark_seen();
+    }
+
+    /// Whether a value newer than the last seen one exists. Fails once the
+    /// sender is gone.
+    pub fn has_changed(&mut self) -> Result<bool, RecvError> {
+        if self.is_closed() {
+            return Err(RecvError(()));
+        }
+        Ok(self.pending())
+    }
+
+    fn is_closed(&self) -> bool {
+        !self.buffer.is_writer_exist.load(ord::acquire())
+    }
+
+    fn pending(&mut self) -> bool {
+        self.unseen || self.reader.updated()
+    }
+
+    fn mark_seen(&mut self) {
+        self.reader.update();
+        self.unseen = false;
+    }
+}
+
+impl<'a, T, N: AsyncNotifier> WatchReceiver<'a, T, N> {
+    /// Waits for a value newer than the last seen one and marks it as seen.
+    /// Fails once the sender is gone and no unseen value is left. Cancel
+    /// safe: the value is only marked as seen when the future completes.
+    pub async fn changed(&mut self) -> Result<(), RecvError> {
+        let buffer = self.buffer;
+        let result = poll_fn(|cx| {
+            let published = || is_dirty(buffer.back_info.load(ord::acquire()));
+            for registered in [false, true] {
+                if self.unseen || published() {
+                    return Poll::Ready(Ok(()));
+                }
+                if self.is_closed() {
+                    return Poll::Ready(Err(RecvError(())));
+                }
+                if !registered {
+                    buffer.reader_notifier.register(cx.waker());
+                }
+            }
+            Poll::Pending
+        })
+        .await;
+        if result.is_ok() {
+            self.mark_seen();
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::waker::tests::block_on;
+
+    fn buffer() -> TripleBuffer<&'static str, WakerNotifier> {
+        TripleBuffer::with_notifiers("one", "one", "one", WakerNotifier::new(), WakerNotifier::new())
+    }
+
+    #[test]
+    fn single_rx_recv() {
+        let buffer = buffer();
+        let (mut tx, mut rx) = channel(&buffer);
+        assert_eq!(*rx.borrow(), "one");
+        assert!(!rx.has_changed().unwrap());
+
+        tx.send("two").unwrap();
+        assert!(rx.has_changed().unwrap());
+        block_on(rx.changed()).unwrap();
+        assert!(!rx.has_changed().unwrap());
+        assert_eq!(*rx.borrow(), "two");
+    }
+
+    #[test]
+    fn borrow_does_not_mark_seen() {
+        let buffer = buffer();
+        let (mut tx, mut rx) = channel(&buffer);
+        tx.send("two").unwrap();
+
+        assert_eq!(*rx.borrow(), "two");
+        assert!(rx.has_changed().unwrap());
+        assert_eq!(*rx.borrow_and_update(), "two");
+        assert!(!rx.has_changed().unwrap());
+    }
+
+    #[test]
+    fn sends_coalesce_into_one_change() {
+        let buffer = buffer();
+        let (mut tx, mut rx) = channel(&buffer);
+        tx.send("two").unwrap();
+        tx.send("three").unwrap();
+
+        block_on(rx.changed()).unwrap();
+        assert_eq!(*rx.borrow(), "three");
+        assert!(!rx.has_changed().unwrap());
+    }
+
+    #[test]
+    fn mark_changed_and_unchanged() {
+        let buffer = buffer();
+        let (mut tx, mut rx) = channel(&buffer);
+        rx.mark_changed();
+        assert!(rx.has_changed().unwrap());
+        block_on(rx.changed()).unwrap();
+
+        tx.send("two").unwrap();
+        rx.mark_unchanged();
+        assert!(!rx.has_changed().unwrap());
+        assert_eq!(*rx.borrow(), "two");
+    }
+
+    #[test]
+    fn rx_observes_final_value() {
+        let buffer = buffer();
+        let (mut tx, mut rx) = channel(&buffer);
+        tx.send("two").unwrap();
+        drop(tx);
+
+        assert!(rx.has_changed().is_err());
+        block_on(rx.changed()).unwrap();
+        assert_eq!(*rx.borrow(), "two");
+        assert!(block_on(rx.changed()).is_err());
+    }
+
+    #[test]
+    fn changed_errors_when_sender_dropped_while_waiting() {
+        static BUFFER: TripleBuffer<u32, WakerNotifier> =
+            TripleBuffer::with_notifiers(0, 0, 0, WakerNotifier::new(), WakerNotifier::new());
+        let (tx, mut rx) = channel(&BUFFER);
+        let jh = std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(20));
+            drop(tx);
+        });
+
+        assert!(block_on(rx.changed()).is_err());
+        jh.join().unwrap();
+    }
+
+    #[test]
+    fn send_fails_and_closed_resolves_after_rx_drop() {
+        let buffer = buffer();
+        let (mut tx, rx) = channel(&buffer);
+        assert!(!tx.is_closed());
+        drop(rx);
+
+        assert!(tx.is_closed());
+        block_on(tx.closed());
+        assert_eq!(tx.send("two"), Err(SendError("two")));
+    }
+
+    #[test]
+    #[should_panic]
+    fn only_one_receiver() {
+        let buffer = buffer();
+        let _reader = buffer.get_reader();
+        let _channel = channel(&buffer);
+    }
+}
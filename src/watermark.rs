@@ -0,0 +1,123 @@
+use crate::atomic::AtomicU64;
+
+use crate::ord;
+
+/// Worst-case timing since the buffer was created or last reset, from
+/// `watermarks()`, in ticks of the clock passed to `publish_timed` and
+/// `read_timed`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Watermarks {
+    /// Longest gap between two timed publishes.
+    pub max_publish_interval: u64,
+    /// Oldest frame a timed read returned, measured from its publish.
+    pub max_read_staleness: u64,
+}
+
+/// Relaxed maxima, updated with a compare-and-swap `fetch_max`.
+pub(crate) struct Marks {
+    // Tick of the latest timed publish, `NEVER` before the first; only the
+    // writer touches it.
+    last_publish: AtomicU64,
+    max_publish_interval: AtomicU64,
+    max_read_staleness: AtomicU64,
+}
+
+const NEVER: u64 = u64::MAX;
+
+impl Marks {
+    pub(crate) const fn new() -> Self {
+        Self {
+            last_publish: AtomicU64::new(NEVER),
+            max_publish_interval: AtomicU64::new(0),
+            max_read_staleness: AtomicU64::new(0),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn published_at(&self, now: u64) {
+        let last = self.last_publish.swap(now, ord::relaxed());
+        if last != NEVER {
+            self.max_publish_interval
+                .fetch_max(now.saturating_sub(last), ord::relaxed());
+        }
+    }
+
+    #[inline]
+    pub(crate) fn read_at(&self, now: u64, stamp: u64) {
+        self.max_read_staleness
+            .fetch_max(now.saturating_sub(stamp), ord::relaxed());
+    }
+
+    pub(crate) fn get(&self) -> Watermarks {
+        Watermarks {
+            max_publish_interval: self.max_publish_interval.load(ord::relaxed()),
+            max_read_staleness: self.max_read_staleness.load(ord::relaxed()),
+        }
+    }
+
+    pub(crate) fn reset(&self) {
+        self.max_publish_interval.store(0, ord::relaxed());
+        self.max_read_staleness.store(0, ord::relaxed());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Clock, Stamped, TripleBuffer};
+    use core::cell::Cell;
+
+    #[test]
+    fn watermarks_follow_scripted_clock() {
+        /// Reports whatever tick the test sets.
+        struct ScriptedClock(Cell<u64>);
+
+        impl Clock for ScriptedClock {
+            type Instant = u64;
+
+            fn now(&self) -> u64 {
+                self.0.get()
+            }
+        }
+
+        let buffer = TripleBuffer::new(|| Stamped::new(0));
+        let clock = ScriptedClock(Cell::new(0));
+        let at = |tick| {
+            clock.0.set(tick);
+            &clock
+        };
+        let mut writer = buffer.get_writer();
+        let mut reader = buffer.get_reader();
+
+        reader.read_timed(at(5));
+        assert_eq!(buffer.watermarks(), Watermarks::default(), "initial frame");
+
+        writer.write_timed(1, at(10));
+        writer.write_timed(2, at(13));
+        writer.write_timed(3, at(20));
+        assert_eq!(reader.read_timed(at(24)).frame, 3);
+        assert_eq!(reader.read_timed(at(29)).frame, 3, "stale re-read");
+        writer.write_timed(4, at(25));
+        assert_eq!(reader.read_timed(at(26)).frame, 4);
+        assert_eq!(
+            buffer.watermarks(),
+            Watermarks {
+                max_publish_interval: 7,
+                max_read_staleness: 9,
+            }
+        );
+
+        buffer.reset_watermarks();
+        assert_eq!(buffer.watermarks(), Watermarks::default());
+        writer.write_timed(5, at(27));
+        reader.read_timed(at(28));
+        assert_eq!(
+            buffer.watermarks(),
+            Watermarks {
+                max_publish_interval: 2,
+                max_read_staleness: 1,
+            }
+        );
+    }
+}
@@ -0,0 +1,35 @@
+use crate::Notifier;
+
+/// Sleeps in `WFE` between condition checks and issues `SEV` on notify.
+///
+/// The event register latches a `SEV` that lands between the check and the
+/// `WFE`, and spurious wakeups just lead to another check, so no wakeup can
+/// be lost. Off Cortex-M targets it degrades to a `Backoff` loop so code
+/// using it still builds and runs in host tests.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct WfeNotifier;
+
+impl WfeNotifier {
+    pub const fn new() -> Self {
+        Self
+    }
+}
+
+impl Notifier for WfeNotifier {
+    #[inline]
+    fn notify(&self) {
+        #[cfg(all(target_arch = "arm", target_os = "none"))]
+        cortex_m::asm::sev();
+    }
+
+    fn wait(&self, until: impl Fn() -> bool) {
+        #[cfg(not(all(target_arch = "arm", target_os = "none")))]
+        let mut backoff = crate::Backoff::new();
+        while !until() {
+            #[cfg(all(target_arch = "arm", target_os = "none"))]
+            cortex_m::asm::wfe();
+            #[cfg(not(all(target_arch = "arm", target_os = "none")))]
+            backoff.snooze();
+        }
+    }
+}
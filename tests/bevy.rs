@@ -0,0 +1,67 @@
+#![cfg(feature = "bevy")]
+
+use std::thread;
+use std::time::Duration;
+
+use bevy_app::{App, Update};
+use bevy_ecs::prelude::{ResMut, Resource};
+use tri_buffer::{LatestChanged, TripleBuffer, TripleBufferPlugin};
+
+const FRAMES: u64 = 200;
+
+#[derive(Resource, Default)]
+struct Seen {
+    frames: Vec<u64>,
+    fresh: Vec<bool>,
+}
+
+fn observe(latest: LatestChanged<u64>, mut seen: ResMut<Seen>) {
+    seen.frames.push(*latest.get());
+    seen.fresh.push(latest.is_fresh());
+}
+
+#[test]
+fn systems_see_frames_a_thread_publishes() {
+    let buffer: &'static TripleBuffer<u64> = Box::leak(Box::new(TripleBuffer::new(|| 0)));
+    let mut writer = buffer.get_writer();
+
+    let mut app = App::new();
+    app.add_plugins(TripleBufferPlugin::new(buffer.get_reader()))
+        .init_resource::<Seen>()
+        .add_systems(Update, observe);
+
+    let publisher = thread::spawn(move || {
+        for frame in 1..=FRAMES {
+            writer.write(frame);
+            thread::sleep(Duration::from_micros(50));
+        }
+        writer
+    });
+    while app.world().resource::<Seen>().frames.last() != Some(&FRAMES) {
+        app.update();
+    }
+    let mut writer = publisher.join().unwrap();
+
+    let seen = app.world().resource::<Seen>();
+    for (i, pair) in seen.frames.windows(2).enumerate() {
+        // A fresh frame is newer than the last one; a stale one is it again.
+        if seen.fresh[i + 1] {
+            assert!(pair[1] > pair[0], "frame went back in time: {pair:?}");
+        } else {
+            assert_eq!(pair[1], pair[0]);
+        }
+    }
+
+    // The frame is taken once per run, so every system in it sees it fresh.
+    let check = |latest: LatestChanged<u64>| {
+        assert_eq!(*latest.get(), FRAMES + 1);
+        assert!(latest.is_fresh());
+    };
+    app.add_systems(Update, (check, check));
+    writer.write(FRAMES + 1);
+    app.update();
+    assert_eq!(
+        app.world().resource::<Seen>().frames.last(),
+        Some(&(FRAMES + 1))
+    );
+}
@@ -0,0 +1,19 @@
+//! Pins the borrow rules the slot handoff relies on: a reference from the
+//! reader lives no longer than the next call on it, and a writer's slot no
+//! longer than the next publish. Enable with
+//! `cargo test --features borrow-ui --test borrow_rules`.
+
+// trybuild runs cargo, which Miri can't spawn.
+#![cfg(all(feature = "borrow-ui", not(miri)))]
+
+#[test]
+fn borrow_rules() {
+    let cases = trybuild::TestCases::new();
+    cases.pass("tests/ui/borrow/borrows_that_compile.rs");
+    cases.compile_fail("tests/ui/borrow/read_across_update.rs");
+    cases.compile_fail("tests/ui/borrow/read_across_read.rs");
+    cases.compile_fail("tests/ui/borrow/read_outlives_reader.rs");
+    cases.compile_fail("tests/ui/borrow/input_across_publish.rs");
+    cases.compile_fail("tests/ui/borrow/guard_across_read.rs");
+    cases.compile_fail("tests/ui/borrow/guard_sent_to_thread.rs");
+}
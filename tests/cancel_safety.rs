@@ -0,0 +1,241 @@
+#![cfg(feature = "futures")]
+
+use std::future::Future;
+use std::pin::{pin, Pin};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use std::time::Duration;
+
+use futures::{SinkExt, Stream, StreamExt};
+use tri_buffer::{SinkMode, TripleBuffer, WakerNotifier};
+
+fn buffer() -> TripleBuffer<u32, WakerNotifier> {
+    TripleBuffer::with_notifiers(0, 0, 0, WakerNotifier::new(), WakerNotifier::new())
+}
+
+/// Deterministic executor: every poll is explicit, wakeups are only
+/// counted.
+struct Wakes(AtomicUsize);
+
+impl Wake for Wakes {
+    fn wake(self: Arc<Self>) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+struct Manual {
+    wakes: Arc<Wakes>,
+    waker: Waker,
+}
+
+impl Manual {
+    fn new() -> Self {
+        let wakes = Arc::new(Wakes(AtomicUsize::new(0)));
+        let waker = Waker::from(wakes.clone());
+        Self { wakes, waker }
+    }
+
+    fn poll<F: Future>(&self, future: Pin<&mut F>) -> Poll<F::Output> {
+        future.poll(&mut Context::from_waker(&self.waker))
+    }
+
+    fn poll_next<S: Stream + Unpin>(&self, stream: &mut S) -> Poll<Option<S::Item>> {
+        Pin::new(stream).poll_next(&mut Context::from_waker(&self.waker))
+    }
+
+    fn wakes(&self) -> usize {
+        self.wakes.0.load(Ordering::SeqCst)
+    }
+}
+
+/// xorshift, so failing sequences are reproducible.
+struct Rng(u32);
+
+impl Rng {
+    fn below(&mut self, n: u32) -> u32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        self.0 % n
+    }
+}
+
+#[test]
+fn changed_survives_adversarial_drops() {
+    let buffer = buffer();
+    let mut reader = buffer.get_reader();
+    let mut writer = buffer.get_writer();
+    let executor = Manual::new();
+    let mut rng = Rng(0x9e37_79b9);
+
+    for frame in 1..=1000 {
+        let drop_before_publish = rng.below(2) == 0;
+        {
+            let mut changed = pin!(reader.changed());
+            for _ in 0..rng.below(3) {
+                assert!(executor.poll(changed.as_mut()).is_pending());
+            }
+            if !drop_before_publish {
+                assert!(executor.poll(changed.as_mut()).is_pending());
+                let wakes = executor.wakes();
+                writer.write(frame);
+                assert_eq!(executor.wakes(), wakes + 1, "lost wakeup");
+                // Ready but possibly dropped before anyone reads the frame.
+                assert!(executor.poll(changed.as_mut()).is_ready());
+            }
+        }
+        if drop_before_publish {
+            writer.write(frame);
+            let mut changed = pin!(reader.changed());
+            assert!(executor.poll(changed.as_mut()).is_ready());
+        }
+        assert_eq!(*reader.read(), frame, "lost frame");
+    }
+}
+
+#[test]
+fn consumed_survives_adversarial_drops() {
+    let buffer = buffer();
+    let mut reader = buffer.get_reader();
+    let mut writer = buffer.get_writer();
+    let executor = Manual::new();
+    let mut rng = Rng(0x2545_f491);
+
+    for frame in 1..=1000 {
+        writer.write(frame);
+        let drop_before_update = rng.below(2) == 0;
+        {
+            let mut consumed = pin!(writer.consumed_async());
+            for _ in 0..rng.below(3) {
+                assert!(executor.poll(consumed.as_mut()).is_pending());
+            }
+            if !drop_before_update {
+                assert!(executor.poll(consumed.as_mut()).is_pending());
+                let wakes = executor.wakes();
+                assert!(reader.update());
+                assert_eq!(executor.wakes(), wakes + 1, "lost wakeup");
+                assert!(executor.poll(consumed.as_mut()).is_ready());
+            }
+        }
+        if drop_before_update {
+            assert!(reader.update());
+            let mut consumed = pin!(writer.consumed_async());
+            assert!(executor.poll(consumed.as_mut()).is_ready());
+        }
+        assert!(writer.consumed());
+        assert_eq!(*reader.output_buffer(), frame);
+    }
+}
+
+#[test]
+fn stream_survives_adversarial_drops() {
+    let buffer = buffer();
+    let mut writer = buffer.get_writer();
+    let mut stream = buffer.get_reader().into_stream();
+    let executor = Manual::new();
+    let mut rng = Rng(0x1234_5678);
+
+    for frame in 1..=1000 {
+        for _ in 0..rng.below(3) {
+            assert!(executor.poll_next(&mut stream).is_pending());
+        }
+        if rng.below(2) == 0 {
+            // Cancel the `next()` and hand the reader around meanwhile.
+            let mut next = stream.next();
+            assert!(executor.poll(Pin::new(&mut next)).is_pending());
+            drop(next);
+            stream = stream.into_inner().into_stream();
+        }
+        writer.write(frame);
+        assert_eq!(executor.poll_next(&mut stream), Poll::Ready(Some(frame)));
+    }
+    drop(writer);
+    assert_eq!(executor.poll_next(&mut stream), Poll::Ready(None));
+}
+
+#[test]
+fn dropped_flush_keeps_staged_item() {
+    let buffer = buffer();
+    let mut reader = buffer.get_reader();
+    let mut sink = buffer.get_writer().into_sink(SinkMode::Lossless);
+    let executor = Manual::new();
+
+    for frame in 1..=100 {
+        assert!(executor.poll(pin!(sink.feed(frame))).is_ready());
+        drop(sink.flush());
+        assert!(!reader.update(), "fed item published before flush");
+
+        // Publishes the staged item, then waits for the reader to take it.
+        let mut ready = pin!(futures::future::poll_fn(|cx| sink.poll_ready_unpin(cx)));
+        assert!(executor.poll(ready.as_mut()).is_pending());
+        let wakes = executor.wakes();
+        assert_eq!(*reader.read(), frame);
+        assert_eq!(executor.wakes(), wakes + 1, "lost wakeup");
+        assert!(executor.poll(ready.as_mut()).is_ready());
+    }
+}
+
+static TOKIO_FRAMES: TripleBuffer<u32, WakerNotifier> =
+    TripleBuffer::with_notifiers(0, 0, 0, WakerNotifier::new(), WakerNotifier::new());
+static TOKIO_STREAM: TripleBuffer<u32, WakerNotifier> =
+    TripleBuffer::with_notifiers(0, 0, 0, WakerNotifier::new(), WakerNotifier::new());
+
+const TOKIO_LAST: u32 = 200;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn tokio_select_loops_lose_nothing() {
+    let mut writer = TOKIO_FRAMES.get_writer();
+    let mut reader = TOKIO_FRAMES.get_reader();
+    let publisher = tokio::spawn(async move {
+        let mut frame = 1;
+        while frame <= TOKIO_LAST {
+            tokio::select! {
+                _ = writer.consumed_async() => {
+                    writer.write(frame);
+                    frame += 1;
+                }
+                _ = tokio::time::sleep(Duration::from_micros(50)) => {}
+            }
+        }
+    });
+
+    let mut last = 0;
+    while last != TOKIO_LAST {
+        tokio::select! {
+            _ = reader.changed() => {
+                let frame = *reader.read();
+                assert_eq!(frame, last + 1);
+                last = frame;
+            }
+            _ = tokio::time::sleep(Duration::from_micros(50)) => {}
+        }
+    }
+    publisher.await.unwrap();
+}
+
+#[tokio::test]
+async fn tokio_select_on_stream_next_loses_nothing() {
+    let mut writer = TOKIO_STREAM.get_writer();
+    let mut stream = TOKIO_STREAM.get_reader().into_stream();
+    let publisher = tokio::spawn(async move {
+        for frame in 1..=TOKIO_LAST {
+            writer.consumed_async().await;
+            writer.write(frame);
+            tokio::task::yield_now().await;
+        }
+    });
+
+    let mut frames = Vec::new();
+    loop {
+        tokio::select! {
+            frame = stream.next() => match frame {
+                Some(frame) => frames.push(frame),
+                None => break,
+            },
+            _ = tokio::task::yield_now() => {}
+        }
+    }
+    publisher.await.unwrap();
+    assert_eq!(frames, (1..=TOKIO_LAST).collect::<Vec<_>>());
+}
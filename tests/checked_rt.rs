@@ -0,0 +1,93 @@
+//! The `checked-rt` overwrite policies. The policy is global, so the tests
+//! take turns setting it. Run with `--features checked-rt`, and again with
+//! `seq` to check the lost sequence numbers.
+
+#![cfg(all(feature = "checked-rt", not(feature = "rt-safe")))]
+
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Mutex, MutexGuard};
+
+use tri_buffer::{set_overwrite_policy, NBuffer, Overwrite, OverwritePolicy};
+
+static POLICY: Mutex<()> = Mutex::new(());
+static LOST: Mutex<Vec<Overwrite>> = Mutex::new(Vec::new());
+
+fn with_policy(policy: OverwritePolicy) -> MutexGuard<'static, ()> {
+    let guard = POLICY.lock().unwrap_or_else(|e| e.into_inner());
+    set_overwrite_policy(policy);
+    guard
+}
+
+fn allow_keeps_dropping_frames<const SLOTS: usize>() {
+    let buffer = NBuffer::<u32, SLOTS>::new(|| 0);
+    let mut writer = buffer.get_writer();
+    let mut reader = buffer.get_reader();
+    writer.write(1);
+    *writer.input_buffer() = 2;
+    assert!(writer.publish());
+    assert_eq!(*reader.read(), 2);
+}
+
+#[test]
+fn allow_keeps_dropping_frames_for_each_slot_count() {
+    let _policy = with_policy(OverwritePolicy::Allow);
+    allow_keeps_dropping_frames::<2>();
+    allow_keeps_dropping_frames::<3>();
+    allow_keeps_dropping_frames::<4>();
+}
+
+fn panic_fails_the_overwriting_publish<const SLOTS: usize>() {
+    let buffer = NBuffer::<u32, SLOTS>::new(|| 0);
+    let mut writer = buffer.get_writer();
+    let mut reader = buffer.get_reader();
+    writer.write(1);
+    assert_eq!(*reader.read(), 1);
+    writer.write(2);
+
+    let panicked = panic::catch_unwind(AssertUnwindSafe(|| writer.write(3)));
+    let message = *panicked.unwrap_err().downcast::<String>().unwrap();
+    assert!(message.contains("overwrote an unread frame"), "{message}");
+    // The publish went through before the panic.
+    assert_eq!(*reader.read(), 3);
+    writer.write(4);
+    assert_eq!(*reader.read(), 4);
+}
+
+#[test]
+fn panic_fails_the_overwriting_publish_for_each_slot_count() {
+    let _policy = with_policy(OverwritePolicy::Panic);
+    panic_fails_the_overwriting_publish::<2>();
+    panic_fails_the_overwriting_publish::<3>();
+    panic_fails_the_overwriting_publish::<4>();
+}
+
+fn hook_sees_each_lost_frame<const SLOTS: usize>() {
+    LOST.lock().unwrap().clear();
+    let buffer = NBuffer::<u32, SLOTS>::new(|| 0);
+    let mut writer = buffer.get_writer();
+    let mut reader = buffer.get_reader();
+    for i in 1..=4 {
+        writer.write(i);
+    }
+    assert_eq!(*reader.read(), 4);
+    writer.write(5);
+    assert_eq!(*reader.read(), 5);
+
+    let lost = LOST.lock().unwrap();
+    assert_eq!(lost.len(), 3);
+    #[cfg(feature = "seq")]
+    assert_eq!(
+        lost.iter().map(|lost| lost.seq).collect::<Vec<_>>(),
+        [1, 2, 3]
+    );
+}
+
+#[test]
+fn hook_sees_each_lost_frame_for_each_slot_count() {
+    let _policy = with_policy(OverwritePolicy::Hook(|lost| {
+        LOST.lock().unwrap().push(*lost)
+    }));
+    hook_sees_each_lost_frame::<2>();
+    hook_sees_each_lost_frame::<3>();
+    hook_sees_each_lost_frame::<4>();
+}
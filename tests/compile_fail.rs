@@ -0,0 +1,14 @@
+// trybuild runs cargo, which Miri can't spawn.
+#![cfg(not(miri))]
+
+#[test]
+fn ui() {
+    let cases = trybuild::TestCases::new();
+    cases.pass("tests/ui/send_frames_share.rs");
+    cases.pass("tests/ui/handle_traits.rs");
+    cases.compile_fail("tests/ui/rc_frames_not_sync.rs");
+    cases.compile_fail("tests/ui/handles_not_sync.rs");
+    cases.compile_fail("tests/ui/rc_writer_not_send.rs");
+    cases.compile_fail("tests/ui/read_guard_not_send.rs");
+    cases.compile_fail("tests/ui/aligned_not_power_of_two.rs");
+}
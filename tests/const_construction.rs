@@ -0,0 +1,131 @@
+//! Every `const` constructor stays usable in `static`s and `const`s for
+//! payloads that aren't `Copy` and have drop glue. Without the `std`
+//! feature the crate builds here as it does for a `no_std` binary, which
+//! would declare the same statics. A constructor that stops being `const`
+//! fails to compile this file.
+
+use std::thread;
+
+use tri_buffer::{
+    DoubleBuffer, Duplex, Lossless, Mailbox, NBuffer, QuadBuffer, RevocableTripleBuffer,
+    SpinNotifier, TripleBuffer,
+};
+
+/// Neither `Copy` nor `Default`, and with drop glue.
+#[derive(Debug, PartialEq)]
+struct Frame {
+    samples: Vec<u16>,
+    label: &'static str,
+}
+
+impl Frame {
+    const fn new(label: &'static str) -> Self {
+        Frame {
+            samples: Vec::new(),
+            label,
+        }
+    }
+}
+
+// Const evaluation can't run drop glue, so the buffers are forgotten.
+const _: () = {
+    core::mem::forget(TripleBuffer::new_const(
+        Frame::new("a"),
+        Frame::new("b"),
+        Frame::new("c"),
+    ));
+    core::mem::forget(QuadBuffer::new_const(
+        Frame::new("a"),
+        Frame::new("b"),
+        Frame::new("c"),
+        Frame::new("d"),
+    ));
+    core::mem::forget(NBuffer::<Frame, 5>::from_slots(
+        [const { Frame::new("") }; 5],
+    ));
+    core::mem::forget(
+        NBuffer::<Frame, 2, SpinNotifier>::from_slots_with_notifiers(
+            [const { Frame::new("") }; 2],
+            SpinNotifier,
+            SpinNotifier,
+        ),
+    );
+    core::mem::forget(TripleBuffer::with_notifiers(
+        Frame::new("a"),
+        Frame::new("b"),
+        Frame::new("c"),
+        SpinNotifier,
+        SpinNotifier,
+    ));
+    core::mem::forget(Mailbox::<Frame>::empty());
+    core::mem::forget(DoubleBuffer::new_const(Frame::new("a"), Frame::new("b")));
+    core::mem::forget(Lossless::new_const(
+        Frame::new("a"),
+        Frame::new("b"),
+        Frame::new("c"),
+    ));
+    core::mem::forget(RevocableTripleBuffer::new_const(
+        Frame::new("a"),
+        Frame::new("b"),
+        Frame::new("c"),
+    ));
+};
+
+static TRIPLE: TripleBuffer<Frame> =
+    TripleBuffer::new_const(Frame::new("a"), Frame::new("b"), Frame::new("c"));
+
+static CHANNELS: [TripleBuffer<Frame>; 4] =
+    [const { TripleBuffer::new_const(Frame::new("a"), Frame::new("b"), Frame::new("c")) }; 4];
+
+static MAILBOX: Mailbox<Frame> = Mailbox::empty();
+
+static DOUBLE: DoubleBuffer<Frame> = DoubleBuffer::new_const(Frame::new("a"), Frame::new("b"));
+
+static DUPLEX: Duplex<Frame, u32> =
+    Duplex::new_const(Frame::new("a"), Frame::new("b"), Frame::new("c"), 0, 0, 0);
+
+fn frame(label: &'static str, samples: &[u16]) -> Frame {
+    Frame {
+        samples: samples.to_vec(),
+        label,
+    }
+}
+
+#[test]
+fn static_buffer_exchanges_frames() {
+    thread::spawn(|| TRIPLE.get_writer().write(frame("x", &[1, 2, 3])))
+        .join()
+        .unwrap();
+    let mut reader = TRIPLE.get_reader();
+    assert_eq!(*reader.read(), frame("x", &[1, 2, 3]));
+}
+
+#[test]
+fn array_of_static_buffers_are_independent() {
+    thread::scope(|s| {
+        for (channel, buffer) in CHANNELS.iter().enumerate() {
+            s.spawn(move || buffer.get_writer().write(frame("ch", &[channel as u16])));
+        }
+    });
+    for (channel, buffer) in CHANNELS.iter().enumerate() {
+        assert_eq!(buffer.get_reader().read().samples, [channel as u16]);
+    }
+}
+
+#[test]
+fn static_mailbox_double_and_duplex_work() {
+    let mut writer = MAILBOX.get_writer();
+    let mut reader = MAILBOX.get_reader();
+    assert_eq!(writer.post(frame("m", &[7])), None);
+    assert_eq!(reader.take(), Some(frame("m", &[7])));
+
+    DOUBLE.get_writer().write(frame("d", &[8]));
+    assert_eq!(DOUBLE.get_reader().read().label, "d");
+
+    let mut a = DUPLEX.endpoint_a();
+    let mut b = DUPLEX.endpoint_b();
+    a.write(frame("out", &[9]));
+    b.write(10);
+    assert_eq!(b.read().samples, [9]);
+    assert_eq!(*a.read(), 10);
+}
@@ -0,0 +1,144 @@
+//! Runs the same scripts against this crate and the `triple_buffer` crate,
+//! comparing what their readers and writers observe. Enable with
+//! `cargo test --features differential --test differential`.
+//!
+//! Where the two are meant to differ, the harness doesn't compare:
+//! - After a publish, the writer's input slot holds whatever the slot last
+//!   held. `triple_buffer` hands back the previous back slot; this crate
+//!   may recycle another (more slots, or a `set_recycler` reset), so a
+//!   frame published without being staged since the last publish can
+//!   differ.
+
+#![cfg(all(feature = "differential", not(miri)))]
+
+use proptest::prelude::*;
+use tri_buffer::NBuffer;
+
+#[derive(Debug, Clone, Copy)]
+enum Op {
+    Write(u32),
+    Stage(u32),
+    Publish,
+    Update,
+    Read,
+    Updated,
+    Consumed,
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        3 => any::<u32>().prop_map(Op::Write),
+        2 => any::<u32>().prop_map(Op::Stage),
+        2 => Just(Op::Publish),
+        2 => Just(Op::Update),
+        3 => Just(Op::Read),
+        1 => Just(Op::Updated),
+        1 => Just(Op::Consumed),
+    ]
+}
+
+/// Which frames are fully specified: staged since the last publish.
+struct Exceptions {
+    staged: bool,
+    pending: Option<bool>,
+    view: bool,
+}
+
+impl Exceptions {
+    fn publish(&mut self) {
+        self.pending = Some(self.staged);
+        self.staged = false;
+    }
+
+    fn update(&mut self) {
+        if let Some(specified) = self.pending.take() {
+            self.view = specified;
+        }
+    }
+}
+
+fn run<const SLOTS: usize>(ops: &[Op]) {
+    let buffer = NBuffer::<u32, SLOTS>::new(|| 0);
+    let mut writer = buffer.get_writer();
+    let mut reader = buffer.get_reader();
+    let (mut input, mut output) = triple_buffer::triple_buffer(&0u32);
+    let mut exceptions = Exceptions {
+        staged: true,
+        pending: None,
+        view: true,
+    };
+
+    for (step, &op) in ops.iter().enumerate() {
+        match op {
+            Op::Write(value) => {
+                writer.write(value);
+                input.write(value);
+                exceptions.staged = true;
+                exceptions.publish();
+            }
+            Op::Stage(value) => {
+                *writer.input_buffer() = value;
+                *input.input_buffer_mut() = value;
+                exceptions.staged = true;
+            }
+            Op::Publish => {
+                assert_eq!(writer.publish(), input.publish(), "step {step}: {op:?}");
+                exceptions.publish();
+            }
+            Op::Update => {
+                assert_eq!(reader.update(), output.update(), "step {step}: {op:?}");
+                exceptions.update();
+            }
+            Op::Read => {
+                let (ours, theirs) = (*reader.read(), *output.read());
+                exceptions.update();
+                if exceptions.view {
+                    assert_eq!(ours, theirs, "step {step}: {op:?}");
+                }
+            }
+            Op::Updated => {
+                assert_eq!(reader.updated(), output.updated(), "step {step}: {op:?}");
+            }
+            Op::Consumed => {
+                assert_eq!(writer.consumed(), input.consumed(), "step {step}: {op:?}");
+            }
+        }
+    }
+}
+
+#[test]
+fn scripted_history_matches() {
+    use Op::*;
+    let ops = [
+        Consumed,
+        Updated,
+        Read,
+        Write(1),
+        Consumed,
+        Updated,
+        Write(2),
+        Read,
+        Stage(3),
+        Update,
+        Publish,
+        Publish,
+        Read,
+        Consumed,
+    ];
+    run::<3>(&ops);
+    run::<4>(&ops);
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(2_000))]
+
+    #[test]
+    fn triple_buffers_match(ops in prop::collection::vec(op(), 0..64)) {
+        run::<3>(&ops);
+    }
+
+    #[test]
+    fn quad_buffers_match(ops in prop::collection::vec(op(), 0..64)) {
+        run::<4>(&ops);
+    }
+}
@@ -0,0 +1,181 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashSet;
+
+#[cfg(not(miri))]
+use proptest::prelude::*;
+use tri_buffer::{Mailbox, NBuffer};
+
+/// Tracks which `Counted` values are alive; a value dropped twice, or one
+/// still alive once the buffer is gone, fails the test.
+#[derive(Default)]
+struct Counter {
+    next_id: Cell<u64>,
+    live: RefCell<HashSet<u64>>,
+}
+
+impl Counter {
+    fn value(&self) -> Counted<'_> {
+        let id = self.next_id.get();
+        self.next_id.set(id + 1);
+        self.live.borrow_mut().insert(id);
+        Counted { counter: self, id }
+    }
+
+    fn assert_all_dropped(&self) {
+        assert!(
+            self.live.borrow().is_empty(),
+            "leaked {:?}",
+            self.live.borrow()
+        );
+    }
+}
+
+struct Counted<'a> {
+    counter: &'a Counter,
+    id: u64,
+}
+
+impl Drop for Counted<'_> {
+    fn drop(&mut self) {
+        assert!(
+            self.counter.live.borrow_mut().remove(&self.id),
+            "value {} dropped twice",
+            self.id
+        );
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Op {
+    Write,
+    Stage,
+    Publish,
+    Update,
+    Read,
+    Post,
+    Take,
+    ReacquireReader,
+    ReacquireWriter,
+}
+
+#[cfg(not(miri))]
+fn op() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        Just(Op::Write),
+        Just(Op::Stage),
+        Just(Op::Publish),
+        Just(Op::Update),
+        Just(Op::Read),
+        Just(Op::Post),
+        Just(Op::Take),
+        Just(Op::ReacquireReader),
+        Just(Op::ReacquireWriter),
+    ]
+}
+
+fn run_buffer<const SLOTS: usize>(ops: &[Op]) {
+    let counter = Counter::default();
+    let buffer = NBuffer::<Counted, SLOTS>::new(|| counter.value());
+    let mut writer = buffer.get_writer();
+    let mut reader = buffer.get_reader();
+    for op in ops {
+        match op {
+            Op::Write | Op::Post => writer.write(counter.value()),
+            Op::Stage => *writer.input_buffer() = counter.value(),
+            Op::Publish => {
+                writer.publish();
+            }
+            Op::Update | Op::Take => {
+                reader.update();
+            }
+            Op::Read => assert!(reader.read().id < counter.next_id.get()),
+            Op::ReacquireReader => {
+                drop(reader);
+                reader = buffer.get_reader();
+            }
+            Op::ReacquireWriter => {
+                drop(writer);
+                writer = buffer.get_writer();
+            }
+        }
+    }
+    drop((reader, writer));
+    assert_eq!(counter.live.borrow().len(), SLOTS);
+    drop(buffer);
+    counter.assert_all_dropped();
+}
+
+fn run_mailbox(ops: &[Op]) {
+    let counter = Counter::default();
+    let mailbox = Mailbox::<Counted>::empty();
+    let mut writer = mailbox.get_writer();
+    let mut reader = mailbox.get_reader();
+    for op in ops {
+        match op {
+            Op::Write => writer.write(Some(counter.value())),
+            Op::Stage => *writer.input_buffer() = Some(counter.value()),
+            Op::Publish => {
+                writer.publish();
+            }
+            Op::Update => {
+                reader.update();
+            }
+            Op::Read => {
+                reader.read();
+            }
+            Op::Post => drop(writer.post(counter.value())),
+            Op::Take => drop(reader.take()),
+            Op::ReacquireReader => {
+                drop(reader);
+                reader = mailbox.get_reader();
+            }
+            Op::ReacquireWriter => {
+                drop(writer);
+                writer = mailbox.get_writer();
+            }
+        }
+    }
+    drop((reader, writer));
+    drop(mailbox);
+    counter.assert_all_dropped();
+}
+
+#[test]
+fn every_slot_value_is_dropped_once() {
+    use Op::*;
+    let ops = [
+        Write,
+        Write,
+        Read,
+        Stage,
+        Stage,
+        Publish,
+        ReacquireWriter,
+        Update,
+        Post,
+        Take,
+        Post,
+        Post,
+        ReacquireReader,
+        Read,
+    ];
+    run_buffer::<2>(&ops);
+    run_buffer::<3>(&ops);
+    run_buffer::<4>(&ops);
+    run_mailbox(&ops);
+}
+
+// proptest persists failures through the filesystem, which Miri isolates.
+#[cfg(not(miri))]
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(500))]
+
+    #[test]
+    fn random_histories_drop_every_value_once(ops in prop::collection::vec(op(), 0..64)) {
+        run_buffer::<2>(&ops);
+        run_buffer::<3>(&ops);
+        run_buffer::<4>(&ops);
+        run_buffer::<8>(&ops);
+        run_mailbox(&ops);
+    }
+}
@@ -0,0 +1,52 @@
+#![cfg(feature = "embassy")]
+
+use std::sync::mpsc;
+use std::time::Duration;
+
+use embassy_executor::{Executor, Spawner};
+use embassy_time::Timer;
+use tri_buffer::{BufferReader, BufferWriter, TripleBuffer, WakerNotifier};
+
+static FRAMES: TripleBuffer<u32, WakerNotifier> =
+    TripleBuffer::with_notifiers(0, 0, 0, WakerNotifier::new(), WakerNotifier::new());
+
+const LAST_FRAME: u32 = 20;
+
+#[embassy_executor::task]
+async fn publisher(mut writer: BufferWriter<'static, u32, WakerNotifier>) {
+    for frame in 1..=LAST_FRAME {
+        Timer::after_millis(2).await;
+        writer.consumed_async().await;
+        writer.write(frame);
+    }
+}
+
+#[embassy_executor::task]
+async fn subscriber(
+    mut reader: BufferReader<'static, u32, WakerNotifier>,
+    done: mpsc::Sender<Vec<u32>>,
+) {
+    let mut seen = Vec::new();
+    while seen.last() != Some(&LAST_FRAME) {
+        reader.changed().await;
+        seen.push(*reader.read());
+    }
+    done.send(seen).unwrap();
+}
+
+fn start(spawner: Spawner, done: mpsc::Sender<Vec<u32>>) {
+    spawner.spawn(subscriber(FRAMES.get_reader(), done)).unwrap();
+    spawner.spawn(publisher(FRAMES.get_writer())).unwrap();
+}
+
+#[test]
+fn embassy_task_awaits_published_frames() {
+    let (done, frames) = mpsc::channel();
+    std::thread::spawn(move || {
+        let executor: &'static mut Executor = Box::leak(Box::new(Executor::new()));
+        executor.run(|spawner| start(spawner, done));
+    });
+
+    let seen = frames.recv_timeout(Duration::from_secs(10)).unwrap();
+    assert_eq!(seen, (1..=LAST_FRAME).collect::<Vec<_>>());
+}
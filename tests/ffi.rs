@@ -0,0 +1,245 @@
+#![cfg(feature = "ffi")]
+
+use std::ffi::c_void;
+use std::fs;
+use std::ptr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use tri_buffer::ffi::*;
+
+const FRAME: usize = 16;
+
+fn frame(i: u8) -> [u8; FRAME] {
+    let mut frame = [i; FRAME];
+    frame[FRAME - 1] = frame[..FRAME - 1]
+        .iter()
+        .fold(0, |sum, &b| sum ^ b.rotate_left(3));
+    frame
+}
+
+/// Calls the API as a C caller would: raw pointers and status codes only.
+#[test]
+fn frames_cross_the_c_api() {
+    unsafe {
+        assert!(tribuf_create(0).is_null());
+        let handle = tribuf_create(FRAME);
+        assert!(!handle.is_null());
+
+        let writer = tribuf_writer_acquire(handle);
+        let reader = tribuf_reader_acquire(handle);
+        assert!(!writer.is_null() && !reader.is_null());
+        assert!(
+            tribuf_writer_acquire(handle).is_null(),
+            "writer acquired twice"
+        );
+        assert_eq!(tribuf_destroy(handle), TRIBUF_BUSY);
+
+        let mut out = [0xff; FRAME];
+        let mut fresh = true;
+        assert_eq!(
+            tribuf_reader_read(reader, out.as_mut_ptr(), FRAME, &mut fresh),
+            TRIBUF_OK
+        );
+        assert_eq!((out, fresh), ([0; FRAME], false));
+
+        let short = [1u8; FRAME - 1];
+        assert_eq!(
+            tribuf_write(writer, short.as_ptr(), short.len()),
+            TRIBUF_LENGTH
+        );
+        assert_eq!(tribuf_write(writer, ptr::null(), FRAME), TRIBUF_NULL);
+        assert_eq!(
+            tribuf_write(ptr::null_mut(), short.as_ptr(), FRAME),
+            TRIBUF_NULL
+        );
+        assert_eq!(
+            tribuf_reader_read(reader, out.as_mut_ptr(), 1, ptr::null_mut()),
+            TRIBUF_LENGTH
+        );
+
+        // A C audio callback on its own thread; the pointer is just an address.
+        let last = 200u8;
+        let writer_addr = writer as usize;
+        let producer = thread::spawn(move || {
+            let writer = writer_addr as *mut TriBufWriter;
+            for i in 1..=last {
+                assert_eq!(tribuf_write(writer, frame(i).as_ptr(), FRAME), TRIBUF_OK);
+            }
+            tribuf_writer_release(writer);
+        });
+        let mut previous = 0;
+        while previous != last {
+            assert_eq!(
+                tribuf_reader_read(reader, out.as_mut_ptr(), FRAME, ptr::null_mut()),
+                TRIBUF_OK
+            );
+            assert_eq!(out, frame(out[0]), "torn frame");
+            assert!(out[0] >= previous, "frame went back in time");
+            previous = out[0];
+        }
+        producer.join().unwrap();
+
+        assert_eq!(
+            tribuf_reader_read(reader, out.as_mut_ptr(), FRAME, &mut fresh),
+            TRIBUF_OK
+        );
+        assert!(!fresh);
+        assert_eq!(tribuf_destroy(handle), TRIBUF_BUSY);
+        tribuf_reader_release(reader);
+        tribuf_reader_release(ptr::null_mut());
+        assert_eq!(tribuf_destroy(handle), TRIBUF_OK);
+        assert_eq!(tribuf_destroy(ptr::null_mut()), TRIBUF_NULL);
+    }
+}
+
+unsafe extern "C" fn count(ctx: *mut c_void) {
+    unsafe { &*ctx.cast::<AtomicUsize>() }.fetch_add(1, Ordering::Relaxed);
+}
+
+fn ctx(counter: &AtomicUsize) -> *mut c_void {
+    ptr::from_ref(counter).cast_mut().cast()
+}
+
+/// Every publish calls exactly one callback, including those racing with
+/// its replacement, and none once it is removed.
+#[test]
+fn publishes_call_the_registered_callback() {
+    let publishes = if cfg!(miri) { 200 } else { 20_000 };
+    let (first, second) = (AtomicUsize::new(0), AtomicUsize::new(0));
+    unsafe {
+        let handle = tribuf_create(FRAME);
+        assert_eq!(
+            tribuf_set_on_publish(ptr::null_mut(), Some(count), ctx(&first)),
+            TRIBUF_NULL
+        );
+        assert_eq!(
+            tribuf_set_on_publish(handle, Some(count), ctx(&first)),
+            TRIBUF_OK
+        );
+        let writer = tribuf_writer_acquire(handle);
+        let short = [0u8; 1];
+        tribuf_write(writer, short.as_ptr(), short.len());
+        assert_eq!(first.load(Ordering::Relaxed), 0, "called without a publish");
+
+        let writer_addr = writer as usize;
+        let producer = thread::spawn(move || {
+            let writer = writer_addr as *mut TriBufWriter;
+            for i in 0..publishes {
+                assert_eq!(
+                    tribuf_write(writer, frame(i as u8).as_ptr(), FRAME),
+                    TRIBUF_OK
+                );
+            }
+        });
+        while first.load(Ordering::Relaxed) < publishes / 2 {
+            thread::yield_now();
+        }
+        assert_eq!(
+            tribuf_set_on_publish(handle, Some(count), ctx(&second)),
+            TRIBUF_OK
+        );
+        producer.join().unwrap();
+        assert_eq!(
+            first.load(Ordering::Relaxed) + second.load(Ordering::Relaxed),
+            publishes
+        );
+
+        assert_eq!(
+            tribuf_set_on_publish(handle, None, ptr::null_mut()),
+            TRIBUF_OK
+        );
+        assert_eq!(tribuf_write(writer, frame(0).as_ptr(), FRAME), TRIBUF_OK);
+        assert_eq!(
+            first.load(Ordering::Relaxed) + second.load(Ordering::Relaxed),
+            publishes
+        );
+        tribuf_writer_release(writer);
+        // A callback still set is freed with the buffer.
+        tribuf_set_on_publish(handle, Some(count), ctx(&first));
+        assert_eq!(tribuf_destroy(handle), TRIBUF_OK);
+    }
+}
+
+/// The C spelling cbindgen gives each Rust type in the API.
+fn c_type(rust: &str) -> String {
+    let pointee = |ty: &str| match ty {
+        "u8" => "uint8_t".to_owned(),
+        "bool" => "bool".to_owned(),
+        "c_void" => "void".to_owned(),
+        ty => ty.to_owned(),
+    };
+    if let Some(ty) = rust.strip_prefix("*mut ") {
+        format!("{} *", pointee(ty))
+    } else if let Some(ty) = rust.strip_prefix("*const ") {
+        format!("const {} *", pointee(ty))
+    } else {
+        match rust {
+            "usize" => "uintptr_t",
+            "i32" => "int32_t",
+            "TriBufCallback" => "TriBufCallback",
+            "" => "void",
+            other => panic!("no C type for {other}"),
+        }
+        .to_owned()
+    }
+}
+
+/// `ty name`, or `ty *name` for pointers.
+fn declare(ty: &str, name: &str) -> String {
+    if ty.ends_with('*') {
+        format!("{ty}{name}")
+    } else {
+        format!("{ty} {name}")
+    }
+}
+
+/// The header declares every function and constant in `src/ffi.rs`, with
+/// the same signature, and nothing else.
+#[test]
+fn header_matches_the_rust_declarations() {
+    let root = env!("CARGO_MANIFEST_DIR");
+    let rust = fs::read_to_string(format!("{root}/src/ffi.rs")).unwrap();
+    let header = fs::read_to_string(format!("{root}/include/tri_buffer.h")).unwrap();
+
+    let mut functions = 0;
+    for item in rust.split("extern \"C\" fn ").skip(1) {
+        let signature = item[..item.find('{').unwrap()]
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        let (name, rest) = signature.split_once('(').unwrap();
+        let (params, ret) = rest.rsplit_once(')').unwrap();
+        let params = params
+            .split(',')
+            .map(str::trim)
+            .filter(|param| !param.is_empty())
+            .map(|param| {
+                let (name, ty) = param.split_once(": ").unwrap();
+                declare(&c_type(ty), name)
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        let ret = c_type(ret.trim().trim_start_matches("->").trim());
+        let declaration = format!("{}({params});", declare(&ret, name));
+        assert!(
+            header.contains(&declaration),
+            "header lacks `{declaration}`"
+        );
+        functions += 1;
+    }
+    let declared = header
+        .lines()
+        .filter(|line| line.contains("tribuf_") && line.ends_with(");"))
+        .count();
+    assert_eq!(declared, functions);
+
+    let mut constants = 0;
+    for line in rust.lines().filter(|line| line.starts_with("pub const ")) {
+        let (name, value) = line["pub const ".len()..].split_once(": i32 = ").unwrap();
+        let define = format!("#define {name} {}", value.trim_end_matches(';'));
+        assert!(header.contains(&define), "header lacks `{define}`");
+        constants += 1;
+    }
+    assert_eq!(header.matches("#define TRIBUF_").count(), constants);
+}
@@ -0,0 +1,48 @@
+// proptest persists failures through the filesystem, which Miri isolates.
+#![cfg(not(miri))]
+
+#[path = "support/model.rs"]
+mod model;
+
+use model::{run, Op};
+use proptest::prelude::*;
+
+fn op() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        3 => any::<u32>().prop_map(Op::Write),
+        2 => any::<u32>().prop_map(Op::Stage),
+        2 => Just(Op::Publish),
+        2 => Just(Op::Update),
+        3 => Just(Op::Read),
+        1 => Just(Op::Updated),
+        1 => Just(Op::Consumed),
+        1 => Just(Op::DropReader),
+        1 => Just(Op::DropWriter),
+        1 => Just(Op::AcquireReader),
+        1 => Just(Op::AcquireWriter),
+    ]
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(2_000))]
+
+    #[test]
+    fn two_slots_match_the_model(ops in prop::collection::vec(op(), 0..64)) {
+        run::<2>(&ops);
+    }
+
+    #[test]
+    fn three_slots_match_the_model(ops in prop::collection::vec(op(), 0..64)) {
+        run::<3>(&ops);
+    }
+
+    #[test]
+    fn four_slots_match_the_model(ops in prop::collection::vec(op(), 0..64)) {
+        run::<4>(&ops);
+    }
+
+    #[test]
+    fn eight_slots_match_the_model(ops in prop::collection::vec(op(), 0..64)) {
+        run::<8>(&ops);
+    }
+}
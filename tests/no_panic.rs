@@ -0,0 +1,113 @@
+//! Builds `examples/hot_path.rs` with optimizations and checks its LLVM IR:
+//! nothing the `hot_*` functions reach may call into core's panic paths,
+//! e.g. a bounds check. `seq` and `meta` are on, as their per-slot arrays
+//! are indexed on publish, and `rt-safe` for its subset's methods. Hooks
+//! and recorders are indirect calls and aren't followed; a panic there is
+//! the caller's own.
+
+// Miri can't run cargo.
+#![cfg(not(miri))]
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+const PANIC_PATHS: [&str; 5] = [
+    "panic",
+    "unwrap_failed",
+    "expect_failed",
+    "assert_failed",
+    "_fail",
+];
+
+/// Maps each function defined in `ir` to the functions it calls directly,
+/// and each alias to the function it stands for.
+fn call_graph(ir: &str) -> HashMap<String, Vec<String>> {
+    let mut graph = HashMap::new();
+    let mut current: Option<(String, Vec<String>)> = None;
+    for line in ir.lines() {
+        if let Some(rest) = line.strip_prefix("define ") {
+            let name = symbol(rest).expect("define without a name");
+            current = Some((name, Vec::new()));
+        } else if line.starts_with('@') && line.contains(" alias ") {
+            // Identical functions are merged, leaving aliases to one of them.
+            let name = symbol(line).expect("alias without a name");
+            let target = line.rfind(", ptr @").and_then(|at| symbol(&line[at..]));
+            graph.insert(name, target.into_iter().collect());
+        } else if line == "}" {
+            if let Some((name, callees)) = current.take() {
+                graph.insert(name, callees);
+            }
+        } else if let Some((_, callees)) = &mut current {
+            let line = line.trim_start();
+            let call = ["call ", "invoke ", "tail call "]
+                .iter()
+                .any(|op| line.starts_with(op) || line.contains(&format!("= {op}")));
+            if call {
+                callees.extend(symbol(line));
+            }
+        }
+    }
+    graph
+}
+
+/// The first `@name` in `text`, quoted or not.
+fn symbol(text: &str) -> Option<String> {
+    let start = text.find('@')? + 1;
+    let rest = &text[start..];
+    let name = match rest.strip_prefix('"') {
+        Some(quoted) => &quoted[..quoted.find('"')?],
+        None => &rest[..rest.find(|c: char| c == '(' || c.is_whitespace())?],
+    };
+    Some(name.to_owned())
+}
+
+#[test]
+fn hot_path_has_no_panic_branches() {
+    let target = Path::new(env!("CARGO_TARGET_TMPDIR")).join("no-panic");
+    let status = Command::new(env!("CARGO"))
+        .args(["rustc", "--release", "--example", "hot_path"])
+        .args(["--features", "seq,meta,rt-safe", "--"])
+        .args(["--emit=llvm-ir", "-Ccodegen-units=1"])
+        .env("CARGO_TARGET_DIR", &target)
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .status()
+        .expect("failed to run cargo");
+    assert!(status.success());
+
+    let examples = target.join("release/examples");
+    let ir = fs::read_dir(&examples)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| {
+            let name = path.file_name().unwrap().to_str().unwrap();
+            name.starts_with("hot_path-") && name.ends_with(".ll")
+        })
+        // Earlier builds with other flags leave their IR behind.
+        .max_by_key(|path| path.metadata().unwrap().modified().unwrap())
+        .expect("no IR emitted");
+    let graph = call_graph(&fs::read_to_string(ir).unwrap());
+
+    let mut pending: Vec<&str> = graph
+        .keys()
+        .filter(|name| name.starts_with("hot_"))
+        .map(String::as_str)
+        .collect();
+    assert_eq!(pending.len(), 14, "missing hot path functions");
+    let mut seen = HashSet::new();
+    while let Some(function) = pending.pop() {
+        if !seen.insert(function) {
+            continue;
+        }
+        for callee in &graph[function] {
+            assert!(
+                !PANIC_PATHS.iter().any(|path| callee.contains(path)),
+                "{function} may panic through {callee}"
+            );
+            if graph.contains_key(callee) {
+                pending.push(callee);
+            }
+        }
+    }
+}
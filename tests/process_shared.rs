@@ -0,0 +1,122 @@
+#![cfg(feature = "shared")]
+
+use std::fs::{File, OpenOptions};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use memmap2::MmapMut;
+use tri_buffer::{LayoutError, SharedTripleBuffer};
+
+type Frame = [u64; 8];
+
+fn checksum(frame: &Frame) -> u64 {
+    frame[..7]
+        .iter()
+        .fold(0, |sum, &word| sum ^ word.rotate_left(7))
+}
+
+/// Two mappings of one file: the same memory at different addresses, as a
+/// second process would see it.
+fn mapped_twice(len: usize) -> (File, MmapMut, MmapMut) {
+    static FILES: AtomicUsize = AtomicUsize::new(0);
+    let file = FILES.fetch_add(1, Ordering::Relaxed);
+    let path = std::env::temp_dir().join(format!("tri-buffer-{}-{file}", std::process::id()));
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(&path)
+        .unwrap();
+    std::fs::remove_file(&path).unwrap();
+    file.set_len(len as u64).unwrap();
+    let first = unsafe { MmapMut::map_mut(&file).unwrap() };
+    let second = unsafe { MmapMut::map_mut(&file).unwrap() };
+    (file, first, second)
+}
+
+#[test]
+fn frames_cross_between_mappings() {
+    let (_file, mut producer_view, mut consumer_view) =
+        mapped_twice(SharedTripleBuffer::<Frame>::SIZE);
+    assert_ne!(producer_view.as_ptr(), consumer_view.as_ptr());
+
+    assert_eq!(
+        unsafe { SharedTripleBuffer::<Frame>::attach(consumer_view.as_ptr()) }.err(),
+        Some(LayoutError::BadMagic)
+    );
+    let produced =
+        unsafe { SharedTripleBuffer::<Frame>::init_at(producer_view.as_mut_ptr(), [0; 8]) }
+            .unwrap();
+    assert_eq!(
+        unsafe { SharedTripleBuffer::<[u32; 3]>::attach(consumer_view.as_ptr()) }.err(),
+        Some(LayoutError::FrameLayout { size: 64, align: 8 })
+    );
+    let consumed =
+        unsafe { SharedTripleBuffer::<Frame>::attach(consumer_view.as_mut_ptr()) }.unwrap();
+
+    let count = 20_000;
+    std::thread::scope(|scope| {
+        let mut writer = produced.get_writer();
+        assert_eq!(consumed.writer_pid(), Some(std::process::id()));
+        assert!(consumed.try_get_writer().is_none(), "writer taken twice");
+
+        scope.spawn(move || {
+            for i in 1..=count {
+                let mut frame = [i; 8];
+                frame[7] = checksum(&frame);
+                writer.write(frame);
+            }
+        });
+
+        let mut reader = consumed.get_reader();
+        let mut last = 0;
+        while last != count {
+            let frame = reader.read();
+            assert_eq!(frame[7], checksum(frame), "torn frame");
+            assert!(frame[0] >= last, "frame went back in time");
+            last = frame[0];
+        }
+    });
+    assert_eq!(produced.reader_pid(), None);
+    assert_eq!(produced.writer_pid(), None);
+}
+
+#[test]
+fn attach_checks_the_layout_fingerprint() {
+    type Words = [u32; 4];
+    let (_file, mut producer_view, consumer_view) = mapped_twice(SharedTripleBuffer::<Words>::SIZE);
+    unsafe { SharedTripleBuffer::<Words>::init_at(producer_view.as_mut_ptr(), [0; 4]) }.unwrap();
+
+    // As if a build with another layout had initialized the header.
+    let fingerprint = SharedTripleBuffer::<Words>::layout_fingerprint();
+    let at = producer_view
+        .windows(8)
+        .position(|bytes| bytes == fingerprint.to_ne_bytes())
+        .expect("fingerprint not in the header");
+    producer_view[at] ^= 1;
+    assert_eq!(
+        unsafe { SharedTripleBuffer::<Words>::attach(consumer_view.as_ptr()) }.err(),
+        Some(LayoutError::Fingerprint(
+            fingerprint ^ u64::from_ne_bytes([1, 0, 0, 0, 0, 0, 0, 0])
+        ))
+    );
+    producer_view[at] ^= 1;
+    assert!(unsafe { SharedTripleBuffer::<Words>::attach(consumer_view.as_ptr()) }.is_ok());
+}
+
+#[test]
+fn fingerprint_is_stable_and_follows_the_frame_type() {
+    let fingerprint = SharedTripleBuffer::<Frame>::layout_fingerprint();
+    assert_eq!(
+        fingerprint,
+        SharedTripleBuffer::<Frame>::layout_fingerprint()
+    );
+    assert_ne!(
+        fingerprint,
+        SharedTripleBuffer::<[u32; 16]>::layout_fingerprint()
+    );
+    assert_ne!(fingerprint, SharedTripleBuffer::<u64>::layout_fingerprint());
+    // The layout is `repr(C)`, so a 64-bit build always computes this.
+    #[cfg(target_pointer_width = "64")]
+    assert_eq!(fingerprint, 0xef70_a2ae_f2c9_bd3b);
+}
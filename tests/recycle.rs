@@ -0,0 +1,88 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use tri_buffer::{NBuffer, TripleBuffer};
+
+/// Counts allocations made by the current thread only, so other tests
+/// running in parallel don't disturb the count.
+struct Counting;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(Cell::get)
+}
+
+fn exchange<const SLOTS: usize>(buffer: &NBuffer<Vec<u32>, SLOTS>, rounds: u32) -> usize {
+    let mut writer = buffer.get_writer();
+    let mut reader = buffer.get_reader();
+    let before = allocations();
+    for round in 0..rounds {
+        // Frames of varying length, refilled in place.
+        let len = 100 + round % 400;
+        writer.input_buffer().extend(0..len);
+        writer.publish();
+        if round % 3 != 0 {
+            assert_eq!(reader.read().len(), len as usize);
+        }
+    }
+    allocations() - before
+}
+
+fn reaches_zero_allocations<const SLOTS: usize>() {
+    let buffer = NBuffer::<Vec<u32>, SLOTS>::new(Vec::new);
+    buffer.set_recycler(Some(Vec::clear));
+
+    assert!(exchange(&buffer, 1_000) > 0, "warm-up allocates");
+    assert_eq!(exchange(&buffer, 10_000), 0, "allocated after warm-up");
+}
+
+#[test]
+// Tens of millions of pushes; the other test covers the recycler under Miri.
+#[cfg_attr(miri, ignore)]
+fn recycled_vec_frames_stop_allocating() {
+    reaches_zero_allocations::<2>();
+    reaches_zero_allocations::<3>();
+    reaches_zero_allocations::<4>();
+}
+
+#[test]
+fn recycler_sees_every_frame_handed_back() {
+    let buffer = TripleBuffer::new(Vec::<u32>::new);
+    let mut writer = buffer.get_writer();
+    let mut reader = buffer.get_reader();
+    buffer.set_recycler(Some(|slot| slot.push(u32::MAX)));
+
+    writer.input_buffer().push(1);
+    writer.publish();
+    // The slot handed over is the initial back slot, recycled once.
+    assert_eq!(*writer.input_buffer(), [u32::MAX]);
+    assert_eq!(*reader.read(), [1]);
+
+    buffer.set_recycler(Some(Vec::clear));
+    writer.input_buffer().push(2);
+    writer.publish();
+    assert!(writer.input_buffer().is_empty());
+    assert_eq!(*reader.read(), [u32::MAX, 2]);
+}
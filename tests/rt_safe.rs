@@ -0,0 +1,89 @@
+//! The `rt-safe` subset under load: a writer and a reader thread run only
+//! `RtWriter`/`RtReader` methods inside `assert_no_alloc`, with a panic hook
+//! counting any panic, caught or not. Run with `--features rt-safe`, and
+//! with the diagnostics it compiles out, e.g. `rt-safe,tracing,paranoid`.
+
+#![cfg(feature = "rt-safe")]
+
+use std::panic;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use assert_no_alloc::{assert_no_alloc, violation_count, AllocDisabler};
+use tri_buffer::rt::{RtReader, RtWriter};
+use tri_buffer::NBuffer;
+
+#[global_allocator]
+static ALLOCATOR: AllocDisabler = AllocDisabler;
+
+static PANICS: AtomicUsize = AtomicUsize::new(0);
+
+type Frame = [u64; 8];
+
+const FRAMES: u64 = 50_000;
+
+fn produce(writer: &mut impl RtWriter<Frame>) {
+    for i in 1..=FRAMES {
+        if i % 2 == 0 {
+            writer.write_copy(&[i; 8]);
+        } else {
+            *writer.input_buffer() = [i; 8];
+            writer.publish();
+        }
+        let _ = writer.consumed();
+    }
+}
+
+/// The frames seen, and how many were torn or out of order.
+fn consume(reader: &mut impl RtReader<Frame>) -> (u64, u64) {
+    let (mut seen, mut broken, mut previous) = (0, 0, 0);
+    while previous != FRAMES {
+        let _ = reader.updated();
+        let frame = match reader.try_read() {
+            Some(frame) => *frame,
+            None => *reader.peek(),
+        };
+        if frame.iter().any(|&word| word != frame[0]) || frame[0] < previous {
+            broken += 1;
+        }
+        if frame[0] != previous {
+            seen += 1;
+        }
+        previous = frame[0];
+    }
+    (seen, broken)
+}
+
+fn hammer<const SLOTS: usize>() {
+    let buffer = NBuffer::<Frame, SLOTS>::new(Default::default);
+    let (mut reader, mut writer) = (buffer.get_reader(), buffer.get_writer());
+    let (seen, broken, violations) = thread::scope(|s| {
+        s.spawn(move || {
+            assert_no_alloc(|| produce(&mut writer));
+            assert_eq!(violation_count(), 0, "writer allocated");
+        });
+        s.spawn(move || {
+            let (seen, broken) = assert_no_alloc(|| consume(&mut reader));
+            (seen, broken, violation_count())
+        })
+        .join()
+        .unwrap()
+    });
+    assert_eq!(violations, 0, "reader allocated");
+    assert_eq!(broken, 0, "torn or reordered frames");
+    assert!(seen > 0);
+}
+
+#[test]
+fn the_subset_never_allocates_or_panics() {
+    let report = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        PANICS.fetch_add(1, Ordering::Relaxed);
+        report(info);
+    }));
+    hammer::<2>();
+    hammer::<3>();
+    hammer::<4>();
+    let _ = panic::take_hook();
+    assert_eq!(PANICS.load(Ordering::Relaxed), 0, "a panic was caught");
+}
@@ -0,0 +1,120 @@
+#![cfg(feature = "futures")]
+
+use futures::channel::mpsc;
+use futures::executor::block_on;
+use futures::future::FusedFuture;
+use futures::stream::FusedStream;
+use futures::{select, StreamExt};
+use tri_buffer::{Changed, Consumed, ReaderStream, TripleBuffer, WakerNotifier};
+
+const LAST_FRAME: u32 = 500;
+
+fn assert_unpin<U: Unpin>() {}
+
+#[test]
+fn futures_and_stream_are_unpin() {
+    assert_unpin::<Changed<'_, '_, u32, WakerNotifier>>();
+    assert_unpin::<Consumed<'_, '_, u32, WakerNotifier>>();
+    assert_unpin::<ReaderStream<'_, u32, WakerNotifier>>();
+}
+
+#[test]
+fn terminated_after_completion_until_recreated() {
+    let buffer =
+        TripleBuffer::with_notifiers(0, 0, 0, WakerNotifier::new(), WakerNotifier::new());
+    let mut reader = buffer.get_reader();
+    let mut writer = buffer.get_writer();
+
+    writer.write(1);
+    let mut changed = reader.changed();
+    assert!(!changed.is_terminated());
+    block_on(&mut changed);
+    assert!(changed.is_terminated());
+    assert!(!reader.changed().is_terminated());
+
+    let mut consumed = writer.consumed_async();
+    assert!(!consumed.is_terminated());
+    reader.update();
+    block_on(&mut consumed);
+    assert!(consumed.is_terminated());
+    drop(writer);
+
+    let mut stream = reader.into_stream();
+    assert!(!stream.is_terminated());
+    assert_eq!(block_on(stream.next()), None);
+    assert!(stream.is_terminated());
+    buffer.get_writer().write(2);
+    assert_eq!(block_on(stream.next()), None);
+}
+
+/// The publisher pings the control channel after every frame and closes it
+/// to shut the consumer down.
+#[test]
+fn select_loop_alternating_data_and_control() {
+    static FRAMES: TripleBuffer<u32, WakerNotifier> =
+        TripleBuffer::with_notifiers(0, 0, 0, WakerNotifier::new(), WakerNotifier::new());
+    let (control, mut pings) = mpsc::unbounded::<()>();
+    let jh = std::thread::spawn(move || {
+        let mut writer = FRAMES.get_writer();
+        for frame in 1..=LAST_FRAME {
+            block_on(writer.consumed_async());
+            writer.write(frame);
+            control.unbounded_send(()).unwrap();
+        }
+    });
+
+    let mut reader = FRAMES.get_reader();
+    let mut frames = Vec::new();
+    let mut ping_count = 0;
+    block_on(async {
+        loop {
+            select! {
+                _ = reader.changed() => frames.push(*reader.read()),
+                ping = pings.next() => match ping {
+                    Some(()) => ping_count += 1,
+                    None => break,
+                },
+            }
+        }
+    });
+    if reader.update() {
+        frames.push(*reader.output_buffer());
+    }
+    jh.join().unwrap();
+
+    assert_eq!(ping_count, LAST_FRAME);
+    assert_eq!(frames, (1..=LAST_FRAME).collect::<Vec<_>>());
+}
+
+#[test]
+fn select_on_stream_with_shutdown() {
+    static FRAMES: TripleBuffer<u32, WakerNotifier> =
+        TripleBuffer::with_notifiers(0, 0, 0, WakerNotifier::new(), WakerNotifier::new());
+    let (shutdown_tx, shutdown) = futures::channel::oneshot::channel::<()>();
+    // Attached before the stream is first polled, which would end otherwise.
+    let mut writer = FRAMES.get_writer();
+    let jh = std::thread::spawn(move || {
+        for frame in 1..=LAST_FRAME {
+            block_on(writer.consumed_async());
+            writer.write(frame);
+        }
+        block_on(writer.consumed_async());
+        shutdown_tx.send(()).unwrap();
+        writer
+    });
+
+    let mut stream = FRAMES.get_reader().into_stream();
+    let mut shutdown = shutdown;
+    let mut frames = Vec::new();
+    block_on(async {
+        loop {
+            select! {
+                frame = stream.next() => frames.push(frame.unwrap()),
+                _ = shutdown => break,
+            }
+        }
+    });
+    drop(jh.join().unwrap());
+
+    assert_eq!(frames, (1..=LAST_FRAME).collect::<Vec<_>>());
+}
@@ -0,0 +1,173 @@
+//! The reference model `tests/model.rs` checks buffers against, shared with
+//! the fuzz target.
+
+use tri_buffer::{BufferReader, BufferWriter, NBuffer};
+
+#[derive(Debug, Clone, Copy)]
+pub enum Op {
+    Write(u32),
+    Stage(u32),
+    Publish,
+    Update,
+    Read,
+    Updated,
+    Consumed,
+    DropReader,
+    DropWriter,
+    AcquireReader,
+    AcquireWriter,
+}
+
+/// What a reader and writer can observe of a buffer, one step at a time.
+/// Frame values are `None` where the buffer leaves them unspecified, e.g.
+/// the recycled slot the writer stages into after a publish.
+#[derive(Debug)]
+struct Model {
+    slots: usize,
+    // Contents of the writer's next frame.
+    staged: Option<u32>,
+    // The published frame the reader hasn't taken, if any.
+    pending: Option<Option<u32>>,
+    // The frame the reader's output slot holds.
+    view: Option<u32>,
+    // Two slots: the writer took the pending frame back to stage into.
+    retracted: bool,
+    reader: bool,
+    writer: bool,
+}
+
+impl Model {
+    fn new(slots: usize) -> Self {
+        Self {
+            slots,
+            staged: Some(0),
+            pending: None,
+            view: Some(0),
+            retracted: false,
+            reader: true,
+            writer: true,
+        }
+    }
+
+    /// With two slots the writer's first touch after a publish takes the
+    /// back slot, along with an unread frame in it.
+    fn take_input(&mut self) {
+        if self.slots == 2 {
+            if let Some(frame) = self.pending.take() {
+                self.staged = frame;
+                self.retracted = true;
+            }
+        }
+    }
+
+    fn stage(&mut self, value: u32) {
+        self.take_input();
+        self.staged = Some(value);
+    }
+
+    fn publish(&mut self) -> bool {
+        self.take_input();
+        let overwrote = self.pending.is_some() || self.retracted;
+        self.pending = Some(self.staged.take());
+        self.retracted = false;
+        overwrote
+    }
+
+    fn update(&mut self) -> bool {
+        match self.pending.take() {
+            Some(frame) => {
+                self.view = frame;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+struct Handles<'a, const SLOTS: usize> {
+    reader: Option<BufferReader<'a, u32, tri_buffer::DefaultNotifier, SLOTS>>,
+    writer: Option<BufferWriter<'a, u32, tri_buffer::DefaultNotifier, SLOTS>>,
+}
+
+fn check_frame(actual: u32, expected: Option<u32>) {
+    if let Some(expected) = expected {
+        assert_eq!(actual, expected, "read diverged from the model");
+    }
+}
+
+pub fn run<const SLOTS: usize>(ops: &[Op]) {
+    let buffer = NBuffer::<u32, SLOTS>::new(|| 0);
+    let mut model = Model::new(SLOTS);
+    let mut handles = Handles {
+        reader: Some(buffer.get_reader()),
+        writer: Some(buffer.get_writer()),
+    };
+
+    for &op in ops {
+        match op {
+            Op::Write(value) => {
+                if let Some(writer) = &mut handles.writer {
+                    *writer.input_buffer() = value;
+                    model.stage(value);
+                    assert_eq!(writer.publish(), model.publish(), "{op:?}");
+                }
+            }
+            Op::Stage(value) => {
+                if let Some(writer) = &mut handles.writer {
+                    *writer.input_buffer() = value;
+                    model.stage(value);
+                }
+            }
+            Op::Publish => {
+                if let Some(writer) = &handles.writer {
+                    assert_eq!(writer.publish(), model.publish(), "{op:?}");
+                }
+            }
+            Op::Update => {
+                if let Some(reader) = &mut handles.reader {
+                    assert_eq!(reader.update(), model.update(), "{op:?}");
+                }
+            }
+            Op::Read => {
+                if let Some(reader) = &mut handles.reader {
+                    model.update();
+                    check_frame(*reader.read(), model.view);
+                }
+            }
+            Op::Updated => {
+                if let Some(reader) = &mut handles.reader {
+                    assert_eq!(reader.updated(), model.pending.is_some(), "{op:?}");
+                }
+            }
+            Op::Consumed => {
+                if let Some(writer) = &handles.writer {
+                    assert_eq!(writer.consumed(), model.pending.is_none(), "{op:?}");
+                }
+            }
+            Op::DropReader => {
+                handles.reader = None;
+                model.reader = false;
+            }
+            Op::DropWriter => {
+                handles.writer = None;
+                model.writer = false;
+            }
+            Op::AcquireReader => {
+                let reader = buffer.try_get_reader();
+                assert_eq!(reader.is_some(), !model.reader, "{op:?}");
+                if let Some(reader) = reader {
+                    handles.reader = Some(reader);
+                    model.reader = true;
+                }
+            }
+            Op::AcquireWriter => {
+                let writer = buffer.try_get_writer();
+                assert_eq!(writer.is_some(), !model.writer, "{op:?}");
+                if let Some(writer) = writer {
+                    handles.writer = Some(writer);
+                    model.writer = true;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,82 @@
+#![cfg(all(feature = "tracing", not(feature = "rt-safe")))]
+
+use std::io;
+use std::sync::{Arc, Mutex};
+
+use tracing_subscriber::fmt::MakeWriter;
+use tri_buffer::TripleBuffer;
+
+/// Collects everything the subscriber formats.
+#[derive(Clone, Default)]
+struct Captured(Arc<Mutex<Vec<u8>>>);
+
+impl io::Write for Captured {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for Captured {
+    type Writer = Self;
+
+    fn make_writer(&'a self) -> Self {
+        self.clone()
+    }
+}
+
+fn traced(f: impl FnOnce()) -> Vec<String> {
+    let captured = Captured::default();
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::TRACE)
+        .with_writer(captured.clone())
+        .with_ansi(false)
+        .without_time()
+        .finish();
+    tracing::subscriber::with_default(subscriber, f);
+    let output = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+    output.lines().map(str::to_owned).collect()
+}
+
+#[test]
+fn publish_and_consume_emit_events() {
+    let lines = traced(|| {
+        let buffer = TripleBuffer::new(|| 0);
+        let mut writer = buffer.get_writer();
+        let mut reader = buffer.get_reader();
+        writer.write(1);
+        writer.write(2);
+        reader.update();
+        assert!(!reader.update(), "no event for a failed update");
+        writer.write_blocking(3);
+    });
+
+    let seq = |seq: u64| {
+        if cfg!(feature = "seq") {
+            format!(" seq={seq}")
+        } else {
+            String::new()
+        }
+    };
+    let skipped = if cfg!(feature = "seq") {
+        " skipped=1"
+    } else {
+        ""
+    };
+    assert_eq!(
+        lines,
+        [
+            format!("TRACE tri_buffer: published overwrote=false{}", seq(1)),
+            format!("TRACE tri_buffer: published overwrote=true{}", seq(2)),
+            format!("TRACE tri_buffer: consumed slot=0{}{skipped}", seq(2)),
+            format!(
+                "TRACE wait{{side=\"writer\"}}: tri_buffer: published overwrote=false{}",
+                seq(3)
+            ),
+        ]
+    );
+}
@@ -0,0 +1,126 @@
+//! Stress tests for ThreadSanitizer, which must report no races. Run them on
+//! a nightly toolchain with an instrumented std; an uninstrumented one hides
+//! the synchronization of thread spawns and joins and reports races on them:
+//!
+//! ```text
+//! RUSTFLAGS="-Zsanitizer=thread --cfg tri_buffer_tsan" \
+//!     cargo +nightly test -Zbuild-std --target x86_64-unknown-linux-gnu --test tsan
+//! ```
+//!
+//! ThreadSanitizer doesn't model standalone fences, so every handoff of
+//! slot memory goes through an acquire/release pair on an atomic both sides
+//! use: `back_info` for `NBuffer`, the handle flags for reacquired handles,
+//! `latest` and `held` for `BroadcastTripleBuffer`. The crate's fences only
+//! order the checks before a notifier sleeps, and choose which slot the
+//! broadcast writer takes; no slot access relies on one. `SnapshotRing`'s
+//! seqlock copies race by design and are volatile, which it doesn't check.
+
+#![cfg(tri_buffer_tsan)]
+
+use std::thread;
+
+use tri_buffer::{BroadcastTripleBuffer, Mailbox, NBuffer};
+
+type Frame = [u64; 8];
+
+fn check_frame(frame: &Frame, previous: u64) -> u64 {
+    assert!(frame.iter().all(|&word| word == frame[0]), "torn frame");
+    assert!(frame[0] >= previous, "went back to an older frame");
+    frame[0]
+}
+
+fn exchange<const SLOTS: usize>() {
+    let buffer = NBuffer::<Frame, SLOTS>::new(|| [0; 8]);
+    let last = 20_000;
+    let mut writer = buffer.get_writer();
+    let mut reader = buffer.get_reader();
+    thread::scope(|s| {
+        s.spawn(move || {
+            for i in 1..=last {
+                writer.write([i; 8]);
+            }
+        });
+        let mut previous = 0;
+        while previous != last {
+            previous = check_frame(reader.read(), previous);
+        }
+    });
+}
+
+#[test]
+fn spsc_exchange() {
+    exchange::<2>();
+    exchange::<3>();
+    exchange::<4>();
+}
+
+#[test]
+fn handles_change_threads() {
+    let buffer = NBuffer::<Frame, 3>::new(|| [0; 8]);
+    let rounds = 200;
+    thread::scope(|s| {
+        s.spawn(|| {
+            for round in 1..=rounds {
+                let mut writer = loop {
+                    if let Some(writer) = buffer.try_get_writer() {
+                        break writer;
+                    }
+                    thread::yield_now();
+                };
+                // The next round's writer may run on another thread.
+                thread::scope(|s| {
+                    s.spawn(move || writer.write([round; 8]));
+                });
+            }
+        });
+        let mut previous = 0;
+        while previous != rounds {
+            let Some(mut reader) = buffer.try_get_reader() else {
+                thread::yield_now();
+                continue;
+            };
+            previous = check_frame(reader.read(), previous);
+        }
+    });
+}
+
+#[test]
+fn mailbox_moves_values() {
+    let mailbox = Mailbox::<Box<Frame>>::empty();
+    let last = 20_000;
+    let mut writer = mailbox.get_writer();
+    let mut reader = mailbox.get_reader();
+    thread::scope(|s| {
+        s.spawn(move || {
+            for i in 1..=last {
+                drop(writer.post(Box::new([i; 8])));
+            }
+        });
+        let mut previous = 0;
+        while previous != last {
+            if let Some(frame) = reader.take() {
+                previous = check_frame(&frame, previous);
+            }
+        }
+    });
+}
+
+#[test]
+fn broadcast_to_readers() {
+    let buffer = BroadcastTripleBuffer::<Frame, 3>::new(|| [0; 8]);
+    let last = 20_000;
+    let mut writer = buffer.get_writer();
+    thread::scope(|s| {
+        for mut reader in buffer.take_readers() {
+            s.spawn(move || {
+                let mut previous = 0;
+                while previous != last {
+                    previous = check_frame(reader.read(), previous);
+                }
+            });
+        }
+        for i in 1..=last {
+            writer.write([i; 8]);
+        }
+    });
+}
@@ -0,0 +1,5 @@
+use tri_buffer::{Aligned, TripleBuffer};
+
+fn main() {
+    let _: Option<TripleBuffer<Aligned<24, [u8; 20]>>> = None;
+}
@@ -0,0 +1,26 @@
+use std::thread;
+
+use tri_buffer::{DoubleBuffer, TripleBuffer};
+
+fn main() {
+    let buffer = TripleBuffer::new(|| 0u32);
+    let mut reader = buffer.get_reader();
+    let mut writer = buffer.get_writer();
+
+    *writer.input_buffer() = 1;
+    writer.publish();
+    let frame = *reader.read();
+    reader.update();
+    assert_eq!(frame + *reader.read(), 2);
+
+    let double = DoubleBuffer::new(|| 0u32);
+    let mut reader = double.get_reader();
+    let guard = reader.read();
+    // A guard may be shared with a thread, just not sent to one.
+    thread::scope(|s| {
+        let guard = &guard;
+        s.spawn(move || assert_eq!(**guard, 0));
+    });
+    drop(guard);
+    assert_eq!(*reader.read(), 0);
+}
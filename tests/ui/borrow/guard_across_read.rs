@@ -0,0 +1,10 @@
+use tri_buffer::DoubleBuffer;
+
+fn main() {
+    let buffer = DoubleBuffer::new(|| 0u32);
+    let mut reader = buffer.get_reader();
+    let first = reader.read();
+    // The writer may publish over the front slot once `first` is gone.
+    let second = reader.read();
+    assert_eq!(*first, *second);
+}
@@ -0,0 +1,12 @@
+use std::thread;
+
+use tri_buffer::DoubleBuffer;
+
+fn main() {
+    let buffer = DoubleBuffer::new(|| 0u32);
+    let mut reader = buffer.get_reader();
+    let guard = reader.read();
+    thread::scope(|s| {
+        s.spawn(move || assert_eq!(*guard, 0));
+    });
+}
@@ -0,0 +1,10 @@
+use tri_buffer::TripleBuffer;
+
+fn main() {
+    let buffer = TripleBuffer::new(|| 0u32);
+    let mut writer = buffer.get_writer();
+    let slot = writer.input_buffer();
+    // After the publish the reader may be reading the slot.
+    writer.publish();
+    *slot = 1;
+}
@@ -0,0 +1,9 @@
+use tri_buffer::TripleBuffer;
+
+fn main() {
+    let buffer = TripleBuffer::new(|| 0u32);
+    let mut reader = buffer.get_reader();
+    let first = reader.read();
+    let second = reader.read();
+    assert_eq!(*first, *second);
+}
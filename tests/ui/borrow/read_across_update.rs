@@ -0,0 +1,10 @@
+use tri_buffer::TripleBuffer;
+
+fn main() {
+    let buffer = TripleBuffer::new(|| 0u32);
+    let mut reader = buffer.get_reader();
+    let frame = reader.read();
+    // The slot `frame` points into may go back to the writer here.
+    reader.update();
+    assert_eq!(*frame, 0);
+}
@@ -0,0 +1,10 @@
+use tri_buffer::TripleBuffer;
+
+fn main() {
+    let buffer = TripleBuffer::new(|| 0u32);
+    let frame = {
+        let mut reader = buffer.get_reader();
+        reader.read()
+    };
+    assert_eq!(*frame, 0);
+}
@@ -0,0 +1,20 @@
+// The auto traits the handles do have; the compile-fail cases next to this
+// file cover the ones they don't.
+
+use std::cell::Cell;
+
+use tri_buffer::{BufferReader, BufferWriter, DoubleReader, DoubleWriter, ReadGuard, SpinNotifier};
+
+fn assert_send<T: Send>() {}
+fn assert_sync<T: Sync>() {}
+
+fn main() {
+    // A frame that isn't `Sync` still moves with its handle.
+    assert_send::<BufferReader<'static, Cell<u8>, SpinNotifier, 3>>();
+    assert_send::<BufferWriter<'static, Cell<u8>, SpinNotifier, 3>>();
+    assert_send::<BufferReader<'static, u8, SpinNotifier, 2>>();
+    assert_send::<BufferWriter<'static, u8, SpinNotifier, 4>>();
+    assert_send::<DoubleReader<'static, Cell<u8>, SpinNotifier>>();
+    assert_send::<DoubleWriter<'static, Cell<u8>, SpinNotifier>>();
+    assert_sync::<ReadGuard<'static, 'static, u8, SpinNotifier>>();
+}
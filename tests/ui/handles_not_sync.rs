@@ -0,0 +1,10 @@
+use tri_buffer::{BufferReader, BufferWriter, DoubleReader, DoubleWriter};
+
+fn assert_sync<T: Sync>() {}
+
+fn main() {
+    assert_sync::<BufferWriter<'static, u8>>();
+    assert_sync::<BufferReader<'static, u8>>();
+    assert_sync::<DoubleWriter<'static, u8>>();
+    assert_sync::<DoubleReader<'static, u8>>();
+}
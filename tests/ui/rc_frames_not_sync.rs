@@ -0,0 +1,9 @@
+use std::rc::Rc;
+
+use tri_buffer::TripleBuffer;
+
+fn assert_sync<T: Sync>() {}
+
+fn main() {
+    assert_sync::<TripleBuffer<Rc<u8>>>();
+}
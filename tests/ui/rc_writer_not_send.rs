@@ -0,0 +1,12 @@
+use std::rc::Rc;
+use std::thread;
+
+use tri_buffer::TripleBuffer;
+
+fn main() {
+    let buffer = TripleBuffer::new(|| Rc::new(0u8));
+    let mut writer = buffer.get_writer();
+    thread::scope(|s| {
+        s.spawn(move || writer.write(Rc::new(1)));
+    });
+}
@@ -0,0 +1,7 @@
+use tri_buffer::{ReadGuard, SpinNotifier};
+
+fn assert_send<T: Send>() {}
+
+fn main() {
+    assert_send::<ReadGuard<'static, 'static, u8, SpinNotifier>>();
+}
@@ -0,0 +1,12 @@
+use std::thread;
+
+use tri_buffer::TripleBuffer;
+
+fn main() {
+    let buffer = TripleBuffer::new(|| 0u32);
+    thread::scope(|s| {
+        let mut writer = buffer.get_writer();
+        s.spawn(move || writer.write(1));
+    });
+    assert_eq!(*buffer.get_reader().read(), 1);
+}
@@ -0,0 +1,123 @@
+use std::panic::{catch_unwind, AssertUnwindSafe, RefUnwindSafe, UnwindSafe};
+
+use tri_buffer::{BufferReader, BufferWriter, DoubleBuffer, ReadGuard, TripleBuffer};
+
+/// Panics when dropped while armed.
+struct Bomb(bool);
+
+impl Drop for Bomb {
+    fn drop(&mut self) {
+        if self.0 {
+            panic!("bomb");
+        }
+    }
+}
+
+fn unwind_safe<T: UnwindSafe + RefUnwindSafe>() {}
+
+#[test]
+fn buffers_and_handles_are_unwind_safe() {
+    unwind_safe::<TripleBuffer<Vec<u8>>>();
+    unwind_safe::<&TripleBuffer<Vec<u8>>>();
+    unwind_safe::<BufferReader<'_, Vec<u8>>>();
+    unwind_safe::<BufferWriter<'_, Vec<u8>>>();
+    unwind_safe::<DoubleBuffer<Vec<u8>>>();
+    fn guard<T: UnwindSafe>() {}
+    guard::<ReadGuard<'_, '_, Vec<u8>, tri_buffer::DefaultNotifier>>();
+}
+
+#[test]
+fn writer_panicking_mid_stage_leaves_the_buffer_usable() {
+    let buffer = TripleBuffer::new(Vec::new);
+    let mut reader = buffer.get_reader();
+    buffer.get_writer().write(vec![1]);
+
+    let writer = buffer.get_writer();
+    assert!(catch_unwind(move || {
+        let mut writer = writer;
+        writer.input_buffer().push(2);
+        panic!("mid-stage");
+    })
+    .is_err());
+
+    // The unwind dropped the writer, and the half-staged frame is still
+    // staged for the next one.
+    let mut writer = buffer.get_writer();
+    assert_eq!(reader.read(), &[1]);
+    writer.input_buffer().push(3);
+    writer.publish();
+    assert_eq!(reader.read(), &[2, 3]);
+}
+
+#[test]
+fn reader_panicking_mid_read_leaves_the_buffer_usable() {
+    let buffer = TripleBuffer::new(|| 0);
+    let mut writer = buffer.get_writer();
+    writer.write(1);
+
+    let reader = buffer.get_reader();
+    assert!(catch_unwind(move || {
+        let mut reader = reader;
+        let frame = *reader.read();
+        panic!("read {frame}");
+    })
+    .is_err());
+
+    let mut reader = buffer.get_reader();
+    assert_eq!(*reader.read(), 1);
+    writer.write(2);
+    assert_eq!(*reader.read(), 2);
+}
+
+#[test]
+fn panicking_hook_leaves_the_frame_published() {
+    let buffer = TripleBuffer::new(|| 0);
+    let mut writer = buffer.get_writer();
+    let mut reader = buffer.get_reader();
+    buffer.set_on_publish(Some(|_| panic!("hook")));
+    *writer.input_buffer() = 1;
+
+    let publisher = &writer;
+    assert!(catch_unwind(|| publisher.publish()).is_err());
+    buffer.set_on_publish(None);
+
+    assert!(reader.update());
+    assert_eq!(*reader.read(), 1);
+    writer.write(2);
+    assert_eq!(*reader.read(), 2);
+}
+
+#[test]
+fn guard_dropped_by_an_unwind_releases_the_front_slot() {
+    let buffer = DoubleBuffer::new(|| 0);
+    let mut writer = buffer.get_writer();
+    let mut reader = buffer.get_reader();
+    writer.write(1);
+
+    // The guard's borrow of the reader is what needs asserting here.
+    assert!(catch_unwind(AssertUnwindSafe(|| {
+        let guard = reader.read();
+        panic!("holding {}", *guard);
+    }))
+    .is_err());
+
+    assert!(writer.can_publish());
+    writer.write(2);
+    assert_eq!(*reader.read(), 2);
+}
+
+#[test]
+fn shared_writer_panicking_mid_write_releases_the_lock() {
+    let buffer = TripleBuffer::new(|| Bomb(false));
+    let mut reader = buffer.get_reader();
+    let mut writer = buffer.get_writer();
+    writer.input_buffer().0 = true;
+    let lock = writer.into_shared();
+
+    // Overwriting the armed input frame panics inside the locked write.
+    let shared = lock.writer();
+    assert!(catch_unwind(AssertUnwindSafe(|| shared.write(Bomb(false)))).is_err());
+
+    assert!(lock.writer().try_write(Bomb(false)).is_ok());
+    assert!(reader.update());
+}
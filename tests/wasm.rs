@@ -0,0 +1,99 @@
+//! A web worker publishes into a static buffer, in linear memory shared
+//! through a `SharedArrayBuffer`, and the browser's main thread reads it by
+//! polling between turns of its event loop. Shared memory needs nightly and
+//! `wasm-bindgen-test-runner`:
+//!
+//! ```text
+//! RUSTFLAGS="-C target-feature=+atomics,+bulk-memory -C link-arg=--shared-memory \
+//!     -C link-arg=--import-memory -C link-arg=--max-memory=1073741824 \
+//!     -C link-arg=--export=__wasm_init_tls -C link-arg=--export=__tls_size \
+//!     -C link-arg=--export=__tls_align -C link-arg=--export=__tls_base" \
+//! CARGO_TARGET_WASM32_UNKNOWN_UNKNOWN_RUNNER=wasm-bindgen-test-runner \
+//! cargo +nightly test -Z build-std=std,panic_abort --target wasm32-unknown-unknown \
+//!     --features std --test wasm
+//! ```
+
+#![cfg(target_arch = "wasm32")]
+
+use js_sys::{Array, Promise};
+use tri_buffer::TripleBuffer;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
+use web_sys::{Blob, BlobPropertyBag, Url, Worker, WorkerOptions, WorkerType};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+const FRAMES: u32 = 1000;
+
+static BUFFER: TripleBuffer<[u32; 4]> = TripleBuffer::new_const([0; 4], [0; 4], [0; 4]);
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(thread_local_v2, js_namespace = ["import", "meta"], js_name = url)]
+    static MODULE_URL: String;
+}
+
+/// The worker's side: publishes frames 1 to `FRAMES`.
+#[wasm_bindgen]
+pub fn tri_buffer_publish_frames() {
+    let mut writer = BUFFER.get_writer();
+    for i in 1..=FRAMES {
+        writer.write([i; 4]);
+    }
+}
+
+// Instantiates this module again over the same memory, then publishes.
+const WORKER: &str = "
+onmessage = async ({ data: [url, module, memory] }) => {
+    const glue = await import(url);
+    await glue.default({ module_or_path: module, memory });
+    glue.tri_buffer_publish_frames();
+};
+";
+
+fn spawn_worker() -> Result<Worker, JsValue> {
+    let script = BlobPropertyBag::new();
+    script.set_type("text/javascript");
+    let blob = Blob::new_with_str_sequence_and_options(&Array::of1(&WORKER.into()), &script)?;
+    let options = WorkerOptions::new();
+    options.set_type(WorkerType::Module);
+    let worker = Worker::new_with_options(&Url::create_object_url_with_blob(&blob)?, &options)?;
+    let url = MODULE_URL.with(|url| JsValue::from(url.as_str()));
+    worker.post_message(&Array::of3(
+        &url,
+        &wasm_bindgen::module(),
+        &wasm_bindgen::memory(),
+    ))?;
+    Ok(worker)
+}
+
+/// Hands the main thread back to its event loop for a turn.
+async fn next_turn() {
+    let turn = Promise::new(&mut |resolve, _| {
+        web_sys::window()
+            .unwrap()
+            .set_timeout_with_callback(&resolve)
+            .unwrap();
+    });
+    JsFuture::from(turn).await.unwrap();
+}
+
+#[wasm_bindgen_test]
+async fn main_thread_reads_frames_a_worker_publishes() {
+    let mut reader = BUFFER.get_reader();
+    assert_eq!(*reader.read(), [0; 4]);
+    let _worker = spawn_worker().unwrap();
+
+    let mut previous = 0;
+    while previous != FRAMES {
+        // Never `read_blocking`: the main thread can't wait.
+        if reader.update() {
+            let frame = *reader.output_buffer();
+            assert!(frame.iter().all(|&word| word == frame[0]), "torn frame");
+            assert!(frame[0] > previous, "frame went back in time");
+            previous = frame[0];
+        }
+        next_turn().await;
+    }
+}